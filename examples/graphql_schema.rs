@@ -0,0 +1,5 @@
+//! Print the GraphQL schema SDL, for checking in as `schema.graphql`.
+
+fn main() {
+    print!("{}", djv::graphql::sdl());
+}
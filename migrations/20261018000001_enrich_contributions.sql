@@ -0,0 +1,20 @@
+-- Size and label metadata for each contribution
+ALTER TABLE contributions
+    ADD COLUMN additions INTEGER,
+    ADD COLUMN deletions INTEGER,
+    ADD COLUMN changed_files INTEGER,
+    ADD COLUMN labels TEXT[] NOT NULL DEFAULT '{}';
+
+-- Upstream repositories that contributions were made to
+CREATE TABLE upstream_repos (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    forge TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    name TEXT NOT NULL,
+    url TEXT NOT NULL,
+    description TEXT,
+    language TEXT,
+    stars INTEGER NOT NULL DEFAULT 0,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (forge, owner, name)
+);
@@ -0,0 +1,14 @@
+-- Release history for crates, npm packages and repositories
+CREATE TABLE releases (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    artifact_kind TEXT NOT NULL,
+    artifact TEXT NOT NULL,
+    version TEXT NOT NULL,
+    released_at TIMESTAMPTZ,
+    yanked BOOLEAN NOT NULL DEFAULT false,
+    notes_url TEXT,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (artifact_kind, artifact, version)
+);
+
+CREATE INDEX idx_releases_released_at ON releases(released_at DESC) WHERE released_at IS NOT NULL;
@@ -0,0 +1,75 @@
+-- Record when each project was first synced so feeds can announce new ones.
+-- Existing rows are backdated to their last sync, the best information we have.
+ALTER TABLE repositories ADD COLUMN first_seen_at TIMESTAMPTZ;
+UPDATE repositories SET first_seen_at = synced_at;
+ALTER TABLE repositories ALTER COLUMN first_seen_at SET NOT NULL, ALTER COLUMN first_seen_at SET DEFAULT now();
+
+ALTER TABLE crates ADD COLUMN first_seen_at TIMESTAMPTZ;
+UPDATE crates SET first_seen_at = synced_at;
+ALTER TABLE crates ALTER COLUMN first_seen_at SET NOT NULL, ALTER COLUMN first_seen_at SET DEFAULT now();
+
+ALTER TABLE npm_packages ADD COLUMN first_seen_at TIMESTAMPTZ;
+UPDATE npm_packages SET first_seen_at = synced_at;
+ALTER TABLE npm_packages ALTER COLUMN first_seen_at SET NOT NULL, ALTER COLUMN first_seen_at SET DEFAULT now();
+
+DROP VIEW IF EXISTS projects;
+
+CREATE VIEW projects AS
+-- Crates (preferred over their repos)
+SELECT
+    c.id,
+    'crate'::TEXT AS kind,
+    c.name,
+    c.description,
+    c.crates_io_url AS url,
+    'rust'::TEXT AS language,
+    c.keywords AS topics,
+    c.downloads AS popularity,
+    c.version,
+    r.commit_count,
+    r.updated_at,
+    c.synced_at,
+    c.first_seen_at
+FROM crates c
+LEFT JOIN repositories r ON c.repository_id = r.id
+
+UNION ALL
+
+-- NPM packages (preferred over their repos)
+SELECT
+    n.id,
+    'npm'::TEXT AS kind,
+    n.name,
+    n.description,
+    n.npm_url AS url,
+    'typescript'::TEXT AS language,
+    n.keywords AS topics,
+    n.downloads_weekly AS popularity,
+    n.version,
+    r.commit_count,
+    r.updated_at,
+    n.synced_at,
+    n.first_seen_at
+FROM npm_packages n
+LEFT JOIN repositories r ON n.repository_id = r.id
+
+UNION ALL
+
+-- Repositories not represented by crates/packages
+SELECT
+    r.id,
+    'repo'::TEXT AS kind,
+    r.name,
+    r.description,
+    r.url,
+    r.language,
+    r.topics,
+    r.stars AS popularity,
+    NULL::TEXT AS version,
+    r.commit_count,
+    r.updated_at,
+    r.synced_at,
+    r.first_seen_at
+FROM repositories r
+WHERE NOT EXISTS (SELECT 1 FROM crates WHERE repository_id = r.id)
+  AND NOT EXISTS (SELECT 1 FROM npm_packages WHERE repository_id = r.id);
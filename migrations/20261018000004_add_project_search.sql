@@ -0,0 +1,90 @@
+-- Full-text search over projects. Each source table carries a stored tsvector weighted
+-- name > topics/keywords > description, indexed with GIN, and the projects view exposes it
+-- as `search` so filters on the view can use the per-table indexes.
+
+-- array_to_string is only STABLE, which generated columns reject; for TEXT[] it is immutable
+-- in practice, so wrap the whole vector in an IMMUTABLE function.
+CREATE FUNCTION project_search_vector(name TEXT, description TEXT, tags TEXT[])
+RETURNS tsvector
+LANGUAGE SQL IMMUTABLE PARALLEL SAFE
+AS $$
+    SELECT setweight(to_tsvector('english', COALESCE(name, '')), 'A')
+        || setweight(to_tsvector('english', array_to_string(COALESCE(tags, '{}'), ' ')), 'B')
+        || setweight(to_tsvector('english', COALESCE(description, '')), 'C')
+$$;
+
+ALTER TABLE repositories ADD COLUMN search tsvector
+    GENERATED ALWAYS AS (project_search_vector(name, description, topics)) STORED;
+ALTER TABLE crates ADD COLUMN search tsvector
+    GENERATED ALWAYS AS (project_search_vector(name, description, keywords || categories)) STORED;
+ALTER TABLE npm_packages ADD COLUMN search tsvector
+    GENERATED ALWAYS AS (project_search_vector(name, description, keywords)) STORED;
+
+CREATE INDEX idx_repositories_search ON repositories USING GIN(search);
+CREATE INDEX idx_crates_search ON crates USING GIN(search);
+CREATE INDEX idx_npm_packages_search ON npm_packages USING GIN(search);
+
+DROP VIEW IF EXISTS projects;
+
+CREATE VIEW projects AS
+-- Crates (preferred over their repos)
+SELECT
+    c.id,
+    'crate'::TEXT AS kind,
+    c.name,
+    c.description,
+    c.crates_io_url AS url,
+    'rust'::TEXT AS language,
+    c.keywords AS topics,
+    c.downloads AS popularity,
+    c.version,
+    r.commit_count,
+    r.updated_at,
+    c.synced_at,
+    c.first_seen_at,
+    c.search
+FROM crates c
+LEFT JOIN repositories r ON c.repository_id = r.id
+
+UNION ALL
+
+-- NPM packages (preferred over their repos)
+SELECT
+    n.id,
+    'npm'::TEXT AS kind,
+    n.name,
+    n.description,
+    n.npm_url AS url,
+    'typescript'::TEXT AS language,
+    n.keywords AS topics,
+    n.downloads_weekly AS popularity,
+    n.version,
+    r.commit_count,
+    r.updated_at,
+    n.synced_at,
+    n.first_seen_at,
+    n.search
+FROM npm_packages n
+LEFT JOIN repositories r ON n.repository_id = r.id
+
+UNION ALL
+
+-- Repositories not represented by crates/packages
+SELECT
+    r.id,
+    'repo'::TEXT AS kind,
+    r.name,
+    r.description,
+    r.url,
+    r.language,
+    r.topics,
+    r.stars AS popularity,
+    NULL::TEXT AS version,
+    r.commit_count,
+    r.updated_at,
+    r.synced_at,
+    r.first_seen_at,
+    r.search
+FROM repositories r
+WHERE NOT EXISTS (SELECT 1 FROM crates WHERE repository_id = r.id)
+  AND NOT EXISTS (SELECT 1 FROM npm_packages WHERE repository_id = r.id);
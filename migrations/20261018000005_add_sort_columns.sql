@@ -0,0 +1,114 @@
+-- Richer sort keys for projects: real creation dates, stars and downloads from the linked
+-- repository or registry, the latest non-yanked release, and a composite popularity score.
+-- npm's search API carries no creation date, so packages fall back to their first release.
+ALTER TABLE repositories ADD COLUMN created_at TIMESTAMPTZ;
+ALTER TABLE crates ADD COLUMN created_at TIMESTAMPTZ;
+
+CREATE INDEX idx_releases_artifact ON releases(artifact_kind, artifact);
+
+-- Log-scaled so a handful of stars still counts next to thousands of downloads; stars weigh
+-- double because they are a deliberate signal rather than CI traffic.
+CREATE FUNCTION project_score(stars INTEGER, downloads INTEGER)
+RETURNS DOUBLE PRECISION
+LANGUAGE SQL IMMUTABLE PARALLEL SAFE
+AS $$
+    SELECT 2 * LN(1 + GREATEST(COALESCE(stars, 0), 0))
+        + LN(1 + GREATEST(COALESCE(downloads, 0), 0))
+$$;
+
+DROP VIEW IF EXISTS projects;
+
+CREATE VIEW projects AS
+-- Crates (preferred over their repos)
+SELECT
+    c.id,
+    'crate'::TEXT AS kind,
+    c.name,
+    c.description,
+    c.crates_io_url AS url,
+    'rust'::TEXT AS language,
+    c.keywords AS topics,
+    c.downloads AS popularity,
+    c.version,
+    r.commit_count,
+    r.updated_at,
+    c.synced_at,
+    c.first_seen_at,
+    c.search,
+    COALESCE(c.created_at, rel.first_released_at) AS created_at,
+    rel.latest_released_at,
+    r.stars,
+    c.downloads,
+    project_score(r.stars, c.downloads) AS score
+FROM crates c
+LEFT JOIN repositories r ON c.repository_id = r.id
+LEFT JOIN LATERAL (
+    SELECT MIN(released_at) AS first_released_at,
+           MAX(released_at) FILTER (WHERE NOT yanked) AS latest_released_at
+    FROM releases
+    WHERE artifact_kind = 'crate' AND artifact = c.name
+) rel ON true
+
+UNION ALL
+
+-- NPM packages (preferred over their repos)
+SELECT
+    n.id,
+    'npm'::TEXT AS kind,
+    n.name,
+    n.description,
+    n.npm_url AS url,
+    'typescript'::TEXT AS language,
+    n.keywords AS topics,
+    n.downloads_weekly AS popularity,
+    n.version,
+    r.commit_count,
+    r.updated_at,
+    n.synced_at,
+    n.first_seen_at,
+    n.search,
+    rel.first_released_at AS created_at,
+    rel.latest_released_at,
+    r.stars,
+    n.downloads_weekly AS downloads,
+    project_score(r.stars, n.downloads_weekly) AS score
+FROM npm_packages n
+LEFT JOIN repositories r ON n.repository_id = r.id
+LEFT JOIN LATERAL (
+    SELECT MIN(released_at) AS first_released_at,
+           MAX(released_at) FILTER (WHERE NOT yanked) AS latest_released_at
+    FROM releases
+    WHERE artifact_kind = 'npm' AND artifact = n.name
+) rel ON true
+
+UNION ALL
+
+-- Repositories not represented by crates/packages
+SELECT
+    r.id,
+    'repo'::TEXT AS kind,
+    r.name,
+    r.description,
+    r.url,
+    r.language,
+    r.topics,
+    r.stars AS popularity,
+    NULL::TEXT AS version,
+    r.commit_count,
+    r.updated_at,
+    r.synced_at,
+    r.first_seen_at,
+    r.search,
+    r.created_at,
+    rel.latest_released_at,
+    r.stars,
+    NULL::INTEGER AS downloads,
+    project_score(r.stars, NULL) AS score
+FROM repositories r
+LEFT JOIN LATERAL (
+    SELECT MAX(released_at) FILTER (WHERE NOT yanked) AS latest_released_at
+    FROM releases
+    WHERE artifact_kind = 'repo' AND artifact = r.forge_id
+) rel ON true
+WHERE NOT EXISTS (SELECT 1 FROM crates WHERE repository_id = r.id)
+  AND NOT EXISTS (SELECT 1 FROM npm_packages WHERE repository_id = r.id);
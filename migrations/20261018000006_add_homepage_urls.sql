@@ -0,0 +1,4 @@
+-- Project homepages, as declared on the forge or registry, for project detail pages
+ALTER TABLE repositories ADD COLUMN homepage_url TEXT;
+ALTER TABLE crates ADD COLUMN homepage_url TEXT;
+ALTER TABLE npm_packages ADD COLUMN homepage_url TEXT;
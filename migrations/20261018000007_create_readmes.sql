@@ -0,0 +1,8 @@
+-- Rendered READMEs for repositories. The hash covers the fetched Markdown and how it was
+-- rendered, so sync can skip READMEs that haven't changed.
+CREATE TABLE readmes (
+    repository_id UUID PRIMARY KEY REFERENCES repositories(id) ON DELETE CASCADE,
+    content_hash TEXT NOT NULL,
+    html TEXT NOT NULL,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,193 @@
+-- One project per repository. Crates and npm packages built from a repository are grouped
+-- with it, a workspace's crates included, instead of each being listed on its own while the
+-- repository disappears; an artifact without a repository is a project by itself.
+-- `project_artifacts` lists every artifact with the project it belongs to, and `projects`
+-- combines them.
+
+-- `||` for tsvectors as an aggregate, so a project is found through any of its artifacts.
+CREATE AGGREGATE tsvector_agg(tsvector) (
+    SFUNC = tsvector_concat,
+    STYPE = tsvector,
+    INITCOND = ''
+);
+
+DROP VIEW IF EXISTS projects;
+
+-- `project_id` is the repository's id, or the artifact's own when it has no repository.
+CREATE VIEW project_artifacts AS
+SELECT
+    COALESCE(c.repository_id, c.id) AS project_id,
+    'crate'::TEXT AS kind,
+    c.id,
+    c.name,
+    c.name AS release_artifact,
+    c.description,
+    c.crates_io_url AS url,
+    'rust'::TEXT AS language,
+    c.keywords AS topics,
+    c.version,
+    c.downloads,
+    c.synced_at,
+    c.first_seen_at,
+    c.search,
+    COALESCE(c.created_at, rel.first_released_at) AS created_at,
+    rel.latest_released_at
+FROM crates c
+LEFT JOIN LATERAL (
+    SELECT MIN(released_at) AS first_released_at,
+           MAX(released_at) FILTER (WHERE NOT yanked) AS latest_released_at
+    FROM releases
+    WHERE artifact_kind = 'crate' AND artifact = c.name
+) rel ON true
+
+UNION ALL
+
+SELECT
+    COALESCE(n.repository_id, n.id) AS project_id,
+    'npm'::TEXT AS kind,
+    n.id,
+    n.name,
+    n.name AS release_artifact,
+    n.description,
+    n.npm_url AS url,
+    'typescript'::TEXT AS language,
+    n.keywords AS topics,
+    n.version,
+    n.downloads_weekly AS downloads,
+    n.synced_at,
+    n.first_seen_at,
+    n.search,
+    rel.first_released_at AS created_at,
+    rel.latest_released_at
+FROM npm_packages n
+LEFT JOIN LATERAL (
+    SELECT MIN(released_at) AS first_released_at,
+           MAX(released_at) FILTER (WHERE NOT yanked) AS latest_released_at
+    FROM releases
+    WHERE artifact_kind = 'npm' AND artifact = n.name
+) rel ON true
+
+UNION ALL
+
+SELECT
+    r.id AS project_id,
+    'repo'::TEXT AS kind,
+    r.id,
+    r.name,
+    r.forge_id AS release_artifact,
+    r.description,
+    r.url,
+    r.language,
+    r.topics,
+    NULL::TEXT AS version,
+    NULL::INTEGER AS downloads,
+    r.synced_at,
+    r.first_seen_at,
+    r.search,
+    r.created_at,
+    rel.latest_released_at
+FROM repositories r
+LEFT JOIN LATERAL (
+    SELECT MAX(released_at) FILTER (WHERE NOT yanked) AS latest_released_at
+    FROM releases
+    WHERE artifact_kind = 'repo' AND artifact = r.forge_id
+) rel ON true;
+
+-- Each project is named after one primary artifact: the package named like its repository
+-- (ignoring any npm scope), else the repository itself if it publishes several packages,
+-- else its most downloaded package. `artifacts` lists the primary first.
+CREATE VIEW projects AS
+WITH counted AS (
+    SELECT
+        a.*,
+        r.name AS repository_name,
+        COUNT(*) FILTER (WHERE a.kind <> 'repo') OVER (PARTITION BY a.project_id) AS packages
+    FROM project_artifacts a
+    LEFT JOIN repositories r ON r.id = a.project_id
+),
+artifacts AS (
+    SELECT
+        counted.*,
+        ROW_NUMBER() OVER (
+            PARTITION BY project_id
+            ORDER BY
+                CASE
+                    WHEN kind <> 'repo'
+                         AND LOWER(regexp_replace(name, '^@[^/]+/', '')) = LOWER(repository_name)
+                        THEN 0
+                    WHEN kind = 'repo' AND packages > 1 THEN 1
+                    WHEN kind <> 'repo' THEN 2
+                    ELSE 3
+                END,
+                downloads DESC NULLS LAST,
+                name,
+                id
+        ) AS rank
+    FROM counted
+),
+grouped AS (
+    SELECT
+        project_id,
+        array_agg(DISTINCT kind ORDER BY kind) AS kinds,
+        SUM(downloads)::INTEGER AS downloads,
+        SUM(downloads) FILTER (WHERE kind = 'crate')::INTEGER AS crate_downloads,
+        SUM(downloads) FILTER (WHERE kind = 'npm')::INTEGER AS npm_downloads,
+        MIN(created_at) AS created_at,
+        MAX(latest_released_at) AS latest_released_at,
+        MAX(synced_at) AS synced_at,
+        MIN(first_seen_at) AS first_seen_at,
+        tsvector_agg(search) AS search,
+        jsonb_agg(
+            jsonb_build_object(
+                'kind', kind,
+                'name', name,
+                'url', url,
+                'version', version,
+                'downloads', downloads
+            )
+            ORDER BY rank
+        ) AS artifacts
+    FROM artifacts
+    GROUP BY project_id
+),
+-- Every artifact's topics, deduplicated, in the primary artifact's order first
+topics AS (
+    SELECT project_id, array_agg(topic ORDER BY position) AS topics
+    FROM (
+        SELECT a.project_id, t.topic, MIN(a.rank * 10000 + t.position) AS position
+        FROM artifacts a, unnest(a.topics) WITH ORDINALITY AS t(topic, position)
+        GROUP BY a.project_id, t.topic
+    ) ranked_topics
+    GROUP BY project_id
+)
+SELECT
+    p.project_id AS id,
+    p.kind,
+    p.name,
+    COALESCE(NULLIF(p.description, ''), r.description) AS description,
+    p.url,
+    COALESCE(p.language, r.language) AS language,
+    COALESCE(t.topics, '{}') AS topics,
+    CASE p.kind
+        WHEN 'crate' THEN g.crate_downloads
+        WHEN 'npm' THEN g.npm_downloads
+        ELSE r.stars
+    END AS popularity,
+    p.version,
+    r.commit_count,
+    r.updated_at,
+    g.synced_at,
+    g.first_seen_at,
+    g.search,
+    g.created_at,
+    g.latest_released_at,
+    r.stars,
+    g.downloads,
+    project_score(r.stars, g.downloads) AS score,
+    g.kinds,
+    g.artifacts
+FROM artifacts p
+JOIN grouped g ON g.project_id = p.project_id
+LEFT JOIN topics t ON t.project_id = p.project_id
+LEFT JOIN repositories r ON r.id = p.project_id
+WHERE p.rank = 1;
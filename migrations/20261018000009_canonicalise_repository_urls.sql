@@ -0,0 +1,26 @@
+-- Artifacts are linked to repositories by canonical URL (see `CanonicalUrl`) rather than by
+-- the exact string each registry reports. Crates and npm packages keep the URL they declare,
+-- so one synced before its repository can be linked once the repository appears.
+ALTER TABLE repositories ADD COLUMN canonical_url TEXT;
+ALTER TABLE crates ADD COLUMN repository_url TEXT;
+ALTER TABLE npm_packages ADD COLUMN repository_url TEXT;
+
+-- Forges report clean `https://host/owner/name` URLs, so only case, trailing slashes and a
+-- `.git` suffix need stripping here; the next sync rewrites each row through `CanonicalUrl`.
+UPDATE repositories
+SET canonical_url = lower(regexp_replace(
+    regexp_replace(url, '^(git\+)?[a-z]+://(www\.)?', 'https://', 'i'),
+    '(\.git)?/*$', ''
+));
+
+UPDATE crates c SET repository_url = r.canonical_url
+FROM repositories r WHERE r.id = c.repository_id;
+
+UPDATE npm_packages n SET repository_url = r.canonical_url
+FROM repositories r WHERE r.id = n.repository_id;
+
+CREATE INDEX idx_repositories_canonical_url ON repositories(canonical_url);
+CREATE INDEX idx_crates_unlinked ON crates(repository_url)
+    WHERE repository_id IS NULL AND repository_url IS NOT NULL;
+CREATE INDEX idx_npm_packages_unlinked ON npm_packages(repository_url)
+    WHERE repository_id IS NULL AND repository_url IS NOT NULL;
@@ -0,0 +1,125 @@
+-- `projects` was a plain view, so every page load re-ran the grouping of three artifact
+-- tables, their release lookups and the topic `unnest`. Its rows only change when a sync
+-- runs, so it's now materialised, and refreshed at the end of each sync.
+DROP VIEW projects;
+
+-- Each project is named after one primary artifact: the package named like its repository
+-- (ignoring any npm scope), else the repository itself if it publishes several packages,
+-- else its most downloaded package. `artifacts` lists the primary first.
+CREATE MATERIALIZED VIEW projects AS
+WITH counted AS (
+    SELECT
+        a.*,
+        r.name AS repository_name,
+        COUNT(*) FILTER (WHERE a.kind <> 'repo') OVER (PARTITION BY a.project_id) AS packages
+    FROM project_artifacts a
+    LEFT JOIN repositories r ON r.id = a.project_id
+),
+artifacts AS (
+    SELECT
+        counted.*,
+        ROW_NUMBER() OVER (
+            PARTITION BY project_id
+            ORDER BY
+                CASE
+                    WHEN kind <> 'repo'
+                         AND LOWER(regexp_replace(name, '^@[^/]+/', '')) = LOWER(repository_name)
+                        THEN 0
+                    WHEN kind = 'repo' AND packages > 1 THEN 1
+                    WHEN kind <> 'repo' THEN 2
+                    ELSE 3
+                END,
+                downloads DESC NULLS LAST,
+                name,
+                id
+        ) AS rank
+    FROM counted
+),
+grouped AS (
+    SELECT
+        project_id,
+        array_agg(DISTINCT kind ORDER BY kind) AS kinds,
+        SUM(downloads)::INTEGER AS downloads,
+        SUM(downloads) FILTER (WHERE kind = 'crate')::INTEGER AS crate_downloads,
+        SUM(downloads) FILTER (WHERE kind = 'npm')::INTEGER AS npm_downloads,
+        MIN(created_at) AS created_at,
+        MAX(latest_released_at) AS latest_released_at,
+        MAX(synced_at) AS synced_at,
+        MIN(first_seen_at) AS first_seen_at,
+        tsvector_agg(search) AS search,
+        jsonb_agg(
+            jsonb_build_object(
+                'kind', kind,
+                'name', name,
+                'url', url,
+                'version', version,
+                'downloads', downloads,
+                'workspace_path', workspace_path
+            )
+            ORDER BY rank
+        ) AS artifacts
+    FROM artifacts
+    GROUP BY project_id
+),
+-- Every artifact's topics, deduplicated, in the primary artifact's order first
+topics AS (
+    SELECT project_id, array_agg(topic ORDER BY position) AS topics
+    FROM (
+        SELECT a.project_id, t.topic, MIN(a.rank * 10000 + t.position) AS position
+        FROM artifacts a, unnest(a.topics) WITH ORDINALITY AS t(topic, position)
+        GROUP BY a.project_id, t.topic
+    ) ranked_topics
+    GROUP BY project_id
+)
+SELECT
+    p.project_id AS id,
+    p.kind,
+    p.name,
+    COALESCE(NULLIF(p.description, ''), r.description) AS description,
+    p.url,
+    COALESCE(p.language, r.language) AS language,
+    COALESCE(t.topics, '{}') AS topics,
+    CASE p.kind
+        WHEN 'crate' THEN g.crate_downloads
+        WHEN 'npm' THEN g.npm_downloads
+        ELSE r.stars
+    END AS popularity,
+    p.version,
+    r.commit_count,
+    r.updated_at,
+    g.synced_at,
+    g.first_seen_at,
+    g.search,
+    g.created_at,
+    g.latest_released_at,
+    r.stars,
+    g.downloads,
+    project_score(r.stars, g.downloads) AS score,
+    g.kinds,
+    g.artifacts
+FROM artifacts p
+JOIN grouped g ON g.project_id = p.project_id
+LEFT JOIN topics t ON t.project_id = p.project_id
+LEFT JOIN repositories r ON r.id = p.project_id
+WHERE p.rank = 1;
+
+-- `REFRESH MATERIALIZED VIEW CONCURRENTLY` needs a unique index to match rows on, and lets
+-- requests keep reading the old rows while it runs.
+CREATE UNIQUE INDEX idx_projects_id ON projects(id);
+
+-- Filters
+CREATE INDEX idx_projects_kind ON projects(kind);
+CREATE INDEX idx_projects_kinds ON projects USING GIN(kinds);
+CREATE INDEX idx_projects_language ON projects(LOWER(language));
+CREATE INDEX idx_projects_topics ON projects USING GIN(topics);
+CREATE INDEX idx_projects_search ON projects USING GIN(search);
+
+-- Sorts
+CREATE INDEX idx_projects_name ON projects(name, id);
+CREATE INDEX idx_projects_score ON projects(score, id);
+CREATE INDEX idx_projects_stars ON projects(stars, id);
+CREATE INDEX idx_projects_downloads ON projects(downloads, id);
+CREATE INDEX idx_projects_updated_at ON projects(updated_at, id);
+CREATE INDEX idx_projects_created_at ON projects(created_at, id);
+CREATE INDEX idx_projects_latest_released_at ON projects(latest_released_at, id);
+CREATE INDEX idx_projects_first_seen_at ON projects(first_seen_at, name);
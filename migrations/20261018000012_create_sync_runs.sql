@@ -0,0 +1,10 @@
+-- One row per finished sync run, successful or not. The latest `finished_at` identifies the
+-- data every page was rendered from, so it's what HTTP validators are derived from.
+CREATE TABLE sync_runs (
+    id BIGSERIAL PRIMARY KEY,
+    finished_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    -- Why the run stopped early, or NULL if every source synced
+    error TEXT
+);
+
+CREATE INDEX idx_sync_runs_finished_at ON sync_runs(finished_at DESC);
@@ -0,0 +1,25 @@
+-- Units of sync work: one job per source, each enqueuing a job per item it found (a README to
+-- fetch, a package's versions), and a `finish` job that refreshes `projects` once the rest
+-- are done. Any instance's workers claim due jobs with `FOR UPDATE SKIP LOCKED`.
+CREATE TABLE sync_jobs (
+    id BIGSERIAL PRIMARY KEY,
+    -- Mirrors the `kind` tag in `payload`, so the table reads without unpacking it
+    kind TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    -- `queued` until claimed, `running` while a worker holds it, then `done`, or `dead` once
+    -- its attempts run out
+    status TEXT NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'done', 'dead')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL,
+    -- When a queued job is next due; pushed back after each failed attempt
+    run_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    -- When a running job's worker is presumed dead and the job may be claimed again
+    locked_until TIMESTAMPTZ,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_sync_jobs_due ON sync_jobs(run_at) WHERE status IN ('queued', 'running');
+CREATE INDEX idx_sync_jobs_finished_at ON sync_jobs(finished_at) WHERE status = 'done';
@@ -0,0 +1,3 @@
+-- The repository's `pushed_at` when its workspace manifests were last read, so a sync can skip
+-- repositories nothing has been pushed to since
+ALTER TABLE repositories ADD COLUMN workspace_pushed_at TIMESTAMPTZ;
@@ -0,0 +1,140 @@
+-- The SQLite schema, for single-box deployments. It mirrors where the `PostgreSQL`
+-- migrations ended up rather than replaying them, with these differences:
+--
+-- * ids are UUID blobs generated by the application
+-- * timestamps are RFC 3339 text in UTC, so they sort as text
+-- * `TEXT[]` columns hold JSON arrays, queried with `json_each`
+-- * full-text search lives in the `project_search` FTS5 table, filled on refresh
+CREATE TABLE repositories (
+    id BLOB PRIMARY KEY,
+    forge TEXT NOT NULL,
+    forge_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    url TEXT NOT NULL,
+    canonical_url TEXT,
+    homepage_url TEXT,
+    language TEXT,
+    stars INTEGER DEFAULT 0,
+    topics TEXT NOT NULL DEFAULT '[]',
+    commit_count INTEGER,
+    created_at TEXT,
+    updated_at TEXT,
+    synced_at TEXT NOT NULL,
+    first_seen_at TEXT NOT NULL,
+    UNIQUE (forge, forge_id)
+);
+
+CREATE INDEX idx_repositories_forge ON repositories(forge);
+CREATE INDEX idx_repositories_canonical_url ON repositories(canonical_url);
+
+CREATE TABLE crates (
+    id BLOB PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    repository_id BLOB REFERENCES repositories(id) ON DELETE SET NULL,
+    repository_url TEXT,
+    crates_io_url TEXT NOT NULL,
+    documentation_url TEXT,
+    homepage_url TEXT,
+    downloads INTEGER DEFAULT 0,
+    version TEXT,
+    keywords TEXT NOT NULL DEFAULT '[]',
+    categories TEXT NOT NULL DEFAULT '[]',
+    created_at TEXT,
+    synced_at TEXT NOT NULL,
+    first_seen_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_crates_repository_id ON crates(repository_id);
+CREATE INDEX idx_crates_unlinked ON crates(repository_url)
+    WHERE repository_id IS NULL AND repository_url IS NOT NULL;
+
+CREATE TABLE npm_packages (
+    id BLOB PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    scope TEXT,
+    description TEXT,
+    repository_id BLOB REFERENCES repositories(id) ON DELETE SET NULL,
+    repository_url TEXT,
+    npm_url TEXT NOT NULL,
+    homepage_url TEXT,
+    downloads_weekly INTEGER DEFAULT 0,
+    version TEXT,
+    keywords TEXT NOT NULL DEFAULT '[]',
+    synced_at TEXT NOT NULL,
+    first_seen_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_npm_packages_repository_id ON npm_packages(repository_id);
+CREATE INDEX idx_npm_packages_unlinked ON npm_packages(repository_url)
+    WHERE repository_id IS NULL AND repository_url IS NOT NULL;
+
+CREATE TABLE workspace_members (
+    repository_id BLOB NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    crate_name TEXT NOT NULL,
+    path TEXT NOT NULL,
+    PRIMARY KEY (repository_id, crate_name)
+);
+
+CREATE TABLE readmes (
+    repository_id BLOB PRIMARY KEY REFERENCES repositories(id) ON DELETE CASCADE,
+    content_hash TEXT NOT NULL,
+    html TEXT NOT NULL,
+    synced_at TEXT NOT NULL
+);
+
+CREATE TABLE releases (
+    id BLOB PRIMARY KEY,
+    artifact_kind TEXT NOT NULL,
+    artifact TEXT NOT NULL,
+    version TEXT NOT NULL,
+    released_at TEXT,
+    yanked BOOLEAN NOT NULL DEFAULT FALSE,
+    notes_url TEXT,
+    synced_at TEXT NOT NULL,
+    UNIQUE (artifact_kind, artifact, version)
+);
+
+CREATE INDEX idx_releases_released_at ON releases(released_at DESC) WHERE released_at IS NOT NULL;
+
+CREATE TABLE contributions (
+    id BLOB PRIMARY KEY,
+    forge TEXT NOT NULL,
+    repo_owner TEXT NOT NULL,
+    repo_name TEXT NOT NULL,
+    repo_url TEXT NOT NULL,
+    contribution_type TEXT NOT NULL,
+    title TEXT,
+    url TEXT NOT NULL,
+    merged_at TEXT,
+    additions INTEGER,
+    deletions INTEGER,
+    changed_files INTEGER,
+    labels TEXT NOT NULL DEFAULT '[]',
+    synced_at TEXT NOT NULL,
+    UNIQUE (forge, repo_owner, repo_name, url)
+);
+
+CREATE INDEX idx_contributions_merged_at ON contributions(merged_at DESC) WHERE merged_at IS NOT NULL;
+
+CREATE TABLE upstream_repos (
+    id BLOB PRIMARY KEY,
+    forge TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    name TEXT NOT NULL,
+    url TEXT NOT NULL,
+    description TEXT,
+    language TEXT,
+    stars INTEGER NOT NULL DEFAULT 0,
+    synced_at TEXT NOT NULL,
+    UNIQUE (forge, owner, name)
+);
+
+CREATE TABLE sync_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    finished_at TEXT NOT NULL,
+    error TEXT
+);
+
+CREATE INDEX idx_sync_runs_finished_at ON sync_runs(finished_at DESC);
@@ -0,0 +1,230 @@
+-- Projects, grouped from the artifact tables as the `PostgreSQL` `projects` view does.
+-- `project_rows` is the grouping query itself, and `projects` a table that
+-- `refresh_projects` refills from it once a sync run has written everything, together with
+-- `project_search`. SQLite has no `LN`, so the refresh computes `score` afterwards.
+
+-- `project_id` is the repository's id, or the artifact's own when it has no repository.
+-- `search_tags` are the words weighted between name and description in search.
+CREATE VIEW project_artifacts AS
+SELECT
+    COALESCE(c.repository_id, c.id) AS project_id,
+    'crate' AS kind,
+    c.id,
+    c.name,
+    c.name AS release_artifact,
+    c.description,
+    c.crates_io_url AS url,
+    'rust' AS language,
+    c.keywords AS topics,
+    (
+        SELECT group_concat(value, ' ')
+        FROM (SELECT value FROM json_each(c.keywords) UNION ALL SELECT value FROM json_each(c.categories))
+    ) AS search_tags,
+    c.version,
+    c.downloads,
+    c.synced_at,
+    c.first_seen_at,
+    COALESCE(c.created_at, (
+        SELECT MIN(released_at) FROM releases WHERE artifact_kind = 'crate' AND artifact = c.name
+    )) AS created_at,
+    (
+        SELECT MAX(released_at) FROM releases
+        WHERE artifact_kind = 'crate' AND artifact = c.name AND NOT yanked
+    ) AS latest_released_at,
+    w.path AS workspace_path
+FROM crates c
+LEFT JOIN workspace_members w
+    ON w.repository_id = c.repository_id AND w.crate_name = c.name
+
+UNION ALL
+
+SELECT
+    COALESCE(n.repository_id, n.id) AS project_id,
+    'npm' AS kind,
+    n.id,
+    n.name,
+    n.name AS release_artifact,
+    n.description,
+    n.npm_url AS url,
+    'typescript' AS language,
+    n.keywords AS topics,
+    (SELECT group_concat(value, ' ') FROM json_each(n.keywords)) AS search_tags,
+    n.version,
+    n.downloads_weekly AS downloads,
+    n.synced_at,
+    n.first_seen_at,
+    (
+        SELECT MIN(released_at) FROM releases WHERE artifact_kind = 'npm' AND artifact = n.name
+    ) AS created_at,
+    (
+        SELECT MAX(released_at) FROM releases
+        WHERE artifact_kind = 'npm' AND artifact = n.name AND NOT yanked
+    ) AS latest_released_at,
+    NULL AS workspace_path
+FROM npm_packages n
+
+UNION ALL
+
+SELECT
+    r.id AS project_id,
+    'repo' AS kind,
+    r.id,
+    r.name,
+    r.forge_id AS release_artifact,
+    r.description,
+    r.url,
+    r.language,
+    r.topics,
+    (SELECT group_concat(value, ' ') FROM json_each(r.topics)) AS search_tags,
+    NULL AS version,
+    NULL AS downloads,
+    r.synced_at,
+    r.first_seen_at,
+    r.created_at,
+    (
+        SELECT MAX(released_at) FROM releases
+        WHERE artifact_kind = 'repo' AND artifact = r.forge_id AND NOT yanked
+    ) AS latest_released_at,
+    NULL AS workspace_path
+FROM repositories r;
+
+-- Each project is named after one primary artifact: the package named like its repository
+-- (ignoring any npm scope), else the repository itself if it publishes several packages,
+-- else its most downloaded package. `artifacts` lists the primary first.
+CREATE VIEW project_rows AS
+WITH counted AS (
+    SELECT
+        a.*,
+        r.name AS repository_name,
+        COUNT(*) FILTER (WHERE a.kind <> 'repo') OVER (PARTITION BY a.project_id) AS packages
+    FROM project_artifacts a
+    LEFT JOIN repositories r ON r.id = a.project_id
+),
+artifacts AS (
+    SELECT
+        counted.*,
+        ROW_NUMBER() OVER (
+            PARTITION BY project_id
+            ORDER BY
+                CASE
+                    WHEN kind <> 'repo'
+                         AND LOWER(CASE WHEN name LIKE '@%/%' THEN substr(name, instr(name, '/') + 1) ELSE name END)
+                             = LOWER(repository_name)
+                        THEN 0
+                    WHEN kind = 'repo' AND packages > 1 THEN 1
+                    WHEN kind <> 'repo' THEN 2
+                    ELSE 3
+                END,
+                downloads DESC NULLS LAST,
+                name,
+                id
+        ) AS rank
+    FROM counted
+),
+grouped AS (
+    SELECT
+        project_id,
+        SUM(downloads) AS downloads,
+        SUM(downloads) FILTER (WHERE kind = 'crate') AS crate_downloads,
+        SUM(downloads) FILTER (WHERE kind = 'npm') AS npm_downloads,
+        MIN(created_at) AS created_at,
+        MAX(latest_released_at) AS latest_released_at,
+        MAX(synced_at) AS synced_at,
+        MIN(first_seen_at) AS first_seen_at,
+        json_group_array(
+            json_object(
+                'kind', kind,
+                'name', name,
+                'url', url,
+                'version', version,
+                'downloads', downloads,
+                'workspace_path', workspace_path
+            )
+            ORDER BY rank
+        ) AS artifacts
+    FROM artifacts
+    GROUP BY project_id
+),
+kinds AS (
+    SELECT project_id, json_group_array(kind ORDER BY kind) AS kinds
+    FROM (SELECT DISTINCT project_id, kind FROM artifacts)
+    GROUP BY project_id
+),
+-- Every artifact's topics, deduplicated, in the primary artifact's order first
+topics AS (
+    SELECT project_id, json_group_array(topic ORDER BY position) AS topics
+    FROM (
+        SELECT a.project_id, t.value AS topic, MIN(a.rank * 10000 + t.key) AS position
+        FROM artifacts a, json_each(a.topics) t
+        GROUP BY a.project_id, t.value
+    )
+    GROUP BY project_id
+)
+SELECT
+    p.project_id AS id,
+    p.kind,
+    p.name,
+    COALESCE(NULLIF(p.description, ''), r.description) AS description,
+    p.url,
+    COALESCE(p.language, r.language) AS language,
+    COALESCE(t.topics, '[]') AS topics,
+    CASE p.kind
+        WHEN 'crate' THEN g.crate_downloads
+        WHEN 'npm' THEN g.npm_downloads
+        ELSE r.stars
+    END AS popularity,
+    p.version,
+    r.commit_count,
+    r.updated_at,
+    g.synced_at,
+    g.first_seen_at,
+    g.created_at,
+    g.latest_released_at,
+    r.stars,
+    g.downloads,
+    k.kinds,
+    g.artifacts
+FROM artifacts p
+JOIN grouped g ON g.project_id = p.project_id
+JOIN kinds k ON k.project_id = p.project_id
+LEFT JOIN topics t ON t.project_id = p.project_id
+LEFT JOIN repositories r ON r.id = p.project_id
+WHERE p.rank = 1;
+
+CREATE TABLE projects (
+    id BLOB PRIMARY KEY,
+    kind TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    url TEXT NOT NULL,
+    language TEXT,
+    topics TEXT NOT NULL,
+    popularity INTEGER,
+    version TEXT,
+    commit_count INTEGER,
+    updated_at TEXT,
+    synced_at TEXT NOT NULL,
+    first_seen_at TEXT NOT NULL,
+    created_at TEXT,
+    latest_released_at TEXT,
+    stars INTEGER,
+    downloads INTEGER,
+    -- `2 * ln(1 + stars) + ln(1 + downloads)`, as `project_score` computes it
+    score REAL NOT NULL DEFAULT 0,
+    kinds TEXT NOT NULL,
+    artifacts TEXT NOT NULL
+);
+
+CREATE INDEX idx_projects_language ON projects(LOWER(language));
+CREATE INDEX idx_projects_name ON projects(name, id);
+CREATE INDEX idx_projects_score ON projects(score, id);
+
+-- Every artifact's names, tags and descriptions, weighted in that order when ranking.
+-- The Porter stemmer stands in for `PostgreSQL`'s English configuration.
+CREATE VIRTUAL TABLE project_search USING fts5(
+    id UNINDEXED,
+    name,
+    tags,
+    description,
+    tokenize = 'porter unicode61'
+);
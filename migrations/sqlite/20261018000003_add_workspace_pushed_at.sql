@@ -0,0 +1,2 @@
+-- The repository's `pushed_at` when its workspace manifests were last read, as in `PostgreSQL`
+ALTER TABLE repositories ADD COLUMN workspace_pushed_at TEXT;
@@ -0,0 +1,96 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+
+/// Errors returned by the public API, each rendered as a typed JSON body:
+///
+/// ```json
+/// {"error": {"code": "invalid_parameter", "message": "...", "parameter": "limit"}}
+/// ```
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("invalid value for `{parameter}`: {message}")]
+    InvalidParameter {
+        parameter: &'static str,
+        message: String,
+    },
+
+    #[error("invalid query string: {0}")]
+    InvalidQuery(String),
+
+    #[error("database not available")]
+    Unavailable,
+
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl ApiError {
+    pub(super) fn invalid(parameter: &'static str, message: impl Into<String>) -> Self {
+        Self::InvalidParameter {
+            parameter,
+            message: message.into(),
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            ApiError::InvalidParameter { .. } | ApiError::InvalidQuery(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            ApiError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            ApiError::InvalidParameter { .. } => "invalid_parameter",
+            ApiError::InvalidQuery(_) => "invalid_query",
+            ApiError::Unavailable => "unavailable",
+            ApiError::Database(_) => "internal",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody<'a> {
+    error: ErrorDetail<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorDetail<'a> {
+    code: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parameter: Option<&'a str>,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let message = match self {
+            // Internal details stay in the logs rather than the response body.
+            ApiError::Database(ref e) => {
+                tracing::error!(error = %e, "api request failed");
+                "internal server error".to_owned()
+            }
+            ApiError::InvalidParameter { ref message, .. } => message.clone(),
+            _ => self.to_string(),
+        };
+        let parameter = match self {
+            ApiError::InvalidParameter { parameter, .. } => Some(parameter),
+            _ => None,
+        };
+
+        let body = ErrorBody {
+            error: ErrorDetail {
+                code: self.code(),
+                message,
+                parameter,
+            },
+        };
+
+        (self.status(), Json(body)).into_response()
+    }
+}
@@ -0,0 +1,502 @@
+//! Versioned public REST API, mounted at `/api/v1`.
+//!
+//! Unlike the Leptos server functions, these routes are a stable contract for other tools:
+//! query parameters are validated strictly, page sizes are capped on the server, and every
+//! error comes back as a typed JSON body (see [`ApiError`]).
+
+mod error;
+mod openapi;
+mod types;
+
+use axum::{
+    extract::{rejection::QueryRejection, Extension, Query, RawQuery},
+    routing::get,
+    Json, Router,
+};
+use serde::Deserialize;
+
+use crate::db::{
+    ContributionCursor, ContributionFilters, ProjectCursor, ProjectFilters, ProjectKind,
+    SortDirection, SortOrder, TopicMatch, MAX_PROJECT_LIMIT,
+};
+use crate::state::AppState;
+use crate::store::Store;
+
+pub use error::ApiError;
+
+const DEFAULT_LIMIT: i32 = 25;
+
+/// Routes for `/api/v1`, ready to be nested by the caller.
+pub fn router<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/projects", get(list_projects))
+        .route("/topics", get(list_topics))
+        .route("/contributions", get(list_contributions))
+        .route("/sync/status", get(sync_status))
+        .route("/openapi.json", get(openapi_document))
+}
+
+fn store(state: &AppState) -> Result<&dyn Store, ApiError> {
+    state.store.as_deref().ok_or(ApiError::Unavailable)
+}
+
+fn query<T>(q: Result<Query<T>, QueryRejection>) -> Result<T, ApiError> {
+    q.map(|Query(q)| q)
+        .map_err(|e| ApiError::InvalidQuery(e.body_text()))
+}
+
+fn parse_limit(limit: Option<&str>) -> Result<i32, ApiError> {
+    let Some(limit) = limit else {
+        return Ok(DEFAULT_LIMIT);
+    };
+    let limit = limit
+        .parse::<i32>()
+        .map_err(|_| ApiError::invalid("limit", "must be an integer"))?;
+    if (1..=MAX_PROJECT_LIMIT).contains(&limit) {
+        Ok(limit)
+    } else {
+        Err(ApiError::invalid(
+            "limit",
+            format!("must be between 1 and {MAX_PROJECT_LIMIT}"),
+        ))
+    }
+}
+
+fn parse_optional<T: std::str::FromStr>(
+    parameter: &'static str,
+    value: Option<&str>,
+) -> Result<Option<T>, ApiError>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .map(|v| {
+            v.parse::<T>()
+                .map_err(|e| ApiError::invalid(parameter, e.to_string()))
+        })
+        .transpose()
+}
+
+fn parse_choice(
+    parameter: &'static str,
+    value: Option<String>,
+    allowed: &[&str],
+) -> Result<Option<String>, ApiError> {
+    match value {
+        Some(v) if !allowed.contains(&v.as_str()) => Err(ApiError::invalid(
+            parameter,
+            format!("must be one of: {}", allowed.join(", ")),
+        )),
+        v => Ok(v),
+    }
+}
+
+/// Query string for `/projects`. `kind`, `language` and `topic` may repeat, which `Query`
+/// cannot collect, so this is parsed by hand with the same strictness: unknown or repeated
+/// single-valued parameters are rejected.
+#[derive(Debug, Default)]
+struct ProjectsQuery {
+    kind: Vec<String>,
+    language: Vec<String>,
+    /// Required topics, or excluded ones when prefixed with `-`
+    topic: Vec<String>,
+    topic_match: Option<String>,
+    q: Option<String>,
+    sort: Option<String>,
+    dir: Option<String>,
+    limit: Option<String>,
+    cursor: Option<String>,
+}
+
+impl ProjectsQuery {
+    fn parse(raw: &str) -> Result<Self, ApiError> {
+        let mut query = Self::default();
+        for (key, value) in url::form_urlencoded::parse(raw.as_bytes()) {
+            let value = value.into_owned();
+            let single = match key.as_ref() {
+                "kind" => {
+                    query.kind.push(value);
+                    continue;
+                }
+                "language" => {
+                    query.language.push(value);
+                    continue;
+                }
+                "topic" => {
+                    query.topic.push(value);
+                    continue;
+                }
+                "match" => &mut query.topic_match,
+                "q" => &mut query.q,
+                "sort" => &mut query.sort,
+                "dir" => &mut query.dir,
+                "limit" => &mut query.limit,
+                "cursor" => &mut query.cursor,
+                other => {
+                    return Err(ApiError::InvalidQuery(format!("unknown field `{other}`")));
+                }
+            };
+            if single.replace(value).is_some() {
+                return Err(ApiError::InvalidQuery(format!("duplicate field `{key}`")));
+            }
+        }
+        Ok(query)
+    }
+}
+
+impl TryFrom<ProjectsQuery> for ProjectFilters {
+    type Error = ApiError;
+
+    fn try_from(q: ProjectsQuery) -> Result<Self, Self::Error> {
+        let filters = ProjectFilters {
+            kinds: q
+                .kind
+                .iter()
+                .map(|k| {
+                    k.parse::<ProjectKind>()
+                        .map_err(|e| ApiError::invalid("kind", e))
+                })
+                .collect::<Result<_, _>>()?,
+            languages: q.language,
+            topic_match: parse_optional::<TopicMatch>("match", q.topic_match.as_deref())?
+                .unwrap_or_default(),
+            q: q.q,
+            sort: parse_optional::<SortOrder>("sort", q.sort.as_deref())?,
+            direction: parse_optional::<SortDirection>("dir", q.dir.as_deref())?,
+            limit: Some(parse_limit(q.limit.as_deref())?),
+            cursor: parse_optional::<ProjectCursor>("cursor", q.cursor.as_deref())?,
+            ..ProjectFilters::default()
+        }
+        .with_topic_terms(q.topic);
+
+        // A cursor from another sort order would silently restart the list.
+        if filters.cursor.as_ref().is_some_and(|c| {
+            c.sort != filters.effective_sort() || c.direction != filters.effective_direction()
+        }) {
+            return Err(ApiError::invalid(
+                "cursor",
+                "cursor belongs to a different sort order",
+            ));
+        }
+        Ok(filters)
+    }
+}
+
+async fn list_projects(
+    Extension(state): Extension<AppState>,
+    RawQuery(raw): RawQuery,
+) -> Result<Json<types::Page<types::Project>>, ApiError> {
+    let filters =
+        ProjectFilters::try_from(ProjectsQuery::parse(raw.as_deref().unwrap_or_default())?)?;
+    let page = store(&state)?.get_projects(&filters).await?;
+
+    Ok(Json(types::Page {
+        items: page.projects.into_iter().map(Into::into).collect(),
+        next_cursor: page.next_cursor.map(|c| c.to_string()),
+    }))
+}
+
+async fn list_topics(
+    Extension(state): Extension<AppState>,
+) -> Result<Json<types::List<String>>, ApiError> {
+    Ok(Json(types::List {
+        items: store(&state)?.get_distinct_topics().await?,
+    }))
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ContributionsQuery {
+    forge: Option<String>,
+    owner: Option<String>,
+    year: Option<String>,
+    #[serde(rename = "type")]
+    contribution_type: Option<String>,
+    limit: Option<String>,
+    cursor: Option<String>,
+}
+
+impl TryFrom<ContributionsQuery> for (ContributionFilters, i32) {
+    type Error = ApiError;
+
+    fn try_from(q: ContributionsQuery) -> Result<Self, Self::Error> {
+        let limit = parse_limit(q.limit.as_deref())?;
+        let filters = ContributionFilters {
+            forge: parse_choice("forge", q.forge, &["github", "gitlab"])?,
+            owner: q.owner,
+            year: parse_optional::<i32>("year", q.year.as_deref())?,
+            contribution_type: parse_choice("type", q.contribution_type, &["pr", "mr"])?,
+            cursor: parse_optional::<ContributionCursor>("cursor", q.cursor.as_deref())?,
+        };
+        Ok((filters, limit))
+    }
+}
+
+async fn list_contributions(
+    Extension(state): Extension<AppState>,
+    q: Result<Query<ContributionsQuery>, QueryRejection>,
+) -> Result<Json<types::Page<types::ContributionItem>>, ApiError> {
+    let (filters, limit) = <(ContributionFilters, i32)>::try_from(query(q)?)?;
+    let page = store(&state)?
+        .get_contribution_page(&filters, i64::from(limit))
+        .await?;
+
+    Ok(Json(types::Page {
+        items: page.contributions.into_iter().map(Into::into).collect(),
+        next_cursor: page.next_cursor.map(|c| c.to_string()),
+    }))
+}
+
+async fn sync_status(
+    Extension(state): Extension<AppState>,
+) -> Result<Json<types::SyncStatus>, ApiError> {
+    let sources = store(&state)?.get_sync_status().await?;
+
+    Ok(Json(types::SyncStatus {
+        last_synced_at: sources.iter().filter_map(|s| s.last_synced_at).max(),
+        sources: sources.into_iter().map(Into::into).collect(),
+    }))
+}
+
+async fn openapi_document() -> Json<serde_json::Value> {
+    Json(openapi::document())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::CursorKey;
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    fn app(store: Option<crate::store::SharedStore>) -> Router {
+        Router::new()
+            .nest("/api/v1", router())
+            .layer(Extension(AppState {
+                store,
+                cache: crate::cache::ResponseCache::default(),
+                snapshots: crate::snapshot::SnapshotStore::default(),
+            }))
+    }
+
+    async fn get_json(uri: &str) -> (StatusCode, serde_json::Value) {
+        let store = std::sync::Arc::new(crate::store::MemoryStore::default());
+        request_json(app(Some(store)), uri).await
+    }
+
+    async fn request_json(app: Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn projects_request_defaults() {
+        let filters = ProjectFilters::try_from(ProjectsQuery::default()).unwrap();
+        assert_eq!(filters.limit, Some(DEFAULT_LIMIT));
+        assert_eq!(filters.cursor, None);
+    }
+
+    #[test]
+    fn projects_request_parses_filters_and_cursor() {
+        let cursor = ProjectCursor {
+            sort: SortOrder::Name,
+            direction: SortDirection::Desc,
+            key: CursorKey::Text("tokio".to_string()),
+            id: uuid::Uuid::new_v4(),
+        };
+        let filters = ProjectFilters::try_from(ProjectsQuery {
+            kind: vec!["crate".to_string()],
+            q: Some("async runtime".to_string()),
+            sort: Some("name".to_string()),
+            dir: Some("desc".to_string()),
+            limit: Some("10".to_string()),
+            cursor: Some(cursor.to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(filters.kinds, [ProjectKind::Crate]);
+        assert_eq!(filters.q.as_deref(), Some("async runtime"));
+        assert_eq!(filters.sort, Some(SortOrder::Name));
+        assert_eq!(filters.direction, Some(SortDirection::Desc));
+        assert_eq!(filters.limit, Some(10));
+        assert_eq!(filters.cursor, Some(cursor));
+    }
+
+    #[test]
+    fn projects_query_collects_repeated_parameters() {
+        let query = ProjectsQuery::parse(
+            "language=rust&language=nix&topic=opentelemetry&topic=tracing&topic=-archived\
+             &match=any&q=open%20telemetry",
+        )
+        .unwrap();
+        assert_eq!(query.language, ["rust", "nix"]);
+        assert_eq!(query.q.as_deref(), Some("open telemetry"));
+
+        let filters = ProjectFilters::try_from(query).unwrap();
+        assert_eq!(filters.languages, ["rust", "nix"]);
+        assert_eq!(filters.topics, ["opentelemetry", "tracing"]);
+        assert_eq!(filters.exclude_topics, ["archived"]);
+        assert_eq!(filters.topic_match, TopicMatch::Any);
+    }
+
+    #[test]
+    fn projects_query_rejects_unknown_and_duplicate_parameters() {
+        for raw in ["colour=red", "sort=name&sort=updated"] {
+            let err = ProjectsQuery::parse(raw).unwrap_err();
+            assert!(matches!(err, ApiError::InvalidQuery(_)), "{raw}");
+        }
+    }
+
+    #[test]
+    fn rejects_limit_outside_cap() {
+        for limit in ["0", "101", "-5", "ten"] {
+            let err = ProjectFilters::try_from(ProjectsQuery {
+                limit: Some(limit.to_string()),
+                ..Default::default()
+            })
+            .unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    ApiError::InvalidParameter {
+                        parameter: "limit",
+                        ..
+                    }
+                ),
+                "{limit}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_kind_and_bad_cursor() {
+        let err = ProjectFilters::try_from(ProjectsQuery {
+            kind: vec!["crate".to_string(), "gem".to_string()],
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ApiError::InvalidParameter {
+                parameter: "kind",
+                ..
+            }
+        ));
+
+        let popularity = ProjectCursor {
+            sort: SortOrder::Popularity,
+            direction: SortDirection::Desc,
+            key: CursorKey::Numbers(vec![3.0]),
+            id: uuid::Uuid::new_v4(),
+        };
+        for (cursor, q, dir) in [
+            ("20".to_string(), None, None),
+            (popularity.to_string(), Some("tracing".to_string()), None),
+            (popularity.to_string(), None, Some("asc".to_string())),
+        ] {
+            let err = ProjectFilters::try_from(ProjectsQuery {
+                cursor: Some(cursor.clone()),
+                q,
+                dir,
+                ..Default::default()
+            })
+            .unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    ApiError::InvalidParameter {
+                        parameter: "cursor",
+                        ..
+                    }
+                ),
+                "{cursor}"
+            );
+        }
+    }
+
+    #[test]
+    fn contributions_query_validates_choices() {
+        let err = <(ContributionFilters, i32)>::try_from(ContributionsQuery {
+            forge: Some("bitbucket".to_string()),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ApiError::InvalidParameter {
+                parameter: "forge",
+                ..
+            }
+        ));
+
+        let (filters, limit) = <(ContributionFilters, i32)>::try_from(ContributionsQuery {
+            year: Some("2024".to_string()),
+            contribution_type: Some("mr".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(filters.year, Some(2024));
+        assert_eq!(filters.contribution_type.as_deref(), Some("mr"));
+        assert_eq!(limit, DEFAULT_LIMIT);
+    }
+
+    #[tokio::test]
+    async fn invalid_parameter_returns_typed_error() {
+        let (status, body) = get_json("/api/v1/projects?limit=500").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "invalid_parameter");
+        assert_eq!(body["error"]["parameter"], "limit");
+    }
+
+    #[tokio::test]
+    async fn unknown_parameter_is_rejected() {
+        let (status, body) = get_json("/api/v1/contributions?colour=red").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "invalid_query");
+    }
+
+    #[tokio::test]
+    async fn reads_the_store() {
+        for path in ["projects", "topics", "contributions", "sync/status"] {
+            let (status, _) = get_json(&format!("/api/v1/{path}")).await;
+            assert_eq!(status, StatusCode::OK, "{path}");
+        }
+        let (_, body) = get_json("/api/v1/sync/status").await;
+        assert_eq!(body["sources"][0]["source"], "contributions");
+    }
+
+    #[tokio::test]
+    async fn missing_store_is_unavailable() {
+        for path in ["projects", "topics", "contributions", "sync/status"] {
+            let (status, body) = request_json(app(None), &format!("/api/v1/{path}")).await;
+            assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{path}");
+            assert_eq!(body["error"]["code"], "unavailable", "{path}");
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_openapi_document() {
+        let (status, body) = get_json("/api/v1/openapi.json").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["openapi"].as_str().unwrap().starts_with("3."));
+        for path in [
+            "/projects",
+            "/topics",
+            "/contributions",
+            "/sync/status",
+            "/openapi.json",
+        ] {
+            assert!(body["paths"][path].is_object(), "{path} is documented");
+        }
+    }
+}
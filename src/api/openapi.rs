@@ -0,0 +1,250 @@
+//! `OpenAPI` 3.1 description of `/api/v1`, served at `/api/v1/openapi.json`.
+
+use serde_json::{json, Value};
+
+use crate::db::MAX_PROJECT_LIMIT;
+
+use super::DEFAULT_LIMIT;
+
+fn query_param(name: &str, description: &str, schema: &Value) -> Value {
+    json!({
+        "name": name,
+        "in": "query",
+        "required": false,
+        "description": description,
+        "schema": schema,
+    })
+}
+
+fn limit_param() -> Value {
+    query_param(
+        "limit",
+        "Page size",
+        &json!({
+            "type": "integer",
+            "minimum": 1,
+            "maximum": MAX_PROJECT_LIMIT,
+            "default": DEFAULT_LIMIT,
+        }),
+    )
+}
+
+fn cursor_param() -> Value {
+    query_param(
+        "cursor",
+        "Opaque cursor taken from `next_cursor` of the previous page",
+        &json!({ "type": "string" }),
+    )
+}
+
+fn json_response(description: &str, schema_ref: &str) -> Value {
+    json!({
+        "description": description,
+        "content": {
+            "application/json": { "schema": { "$ref": schema_ref } }
+        }
+    })
+}
+
+fn error_responses() -> Value {
+    json!({
+        "400": json_response("Invalid query parameter", "#/components/schemas/Error"),
+        "503": json_response("Database not available", "#/components/schemas/Error"),
+    })
+}
+
+fn page_of(item_ref: &str) -> Value {
+    json!({
+        "type": "object",
+        "required": ["items", "next_cursor"],
+        "properties": {
+            "items": { "type": "array", "items": { "$ref": item_ref } },
+            "next_cursor": { "type": ["string", "null"] }
+        }
+    })
+}
+
+fn with_errors(mut responses: Value) -> Value {
+    if let (Some(responses), Value::Object(errors)) = (responses.as_object_mut(), error_responses())
+    {
+        responses.extend(errors);
+    }
+    responses
+}
+
+#[allow(clippy::too_many_lines)]
+pub(super) fn document() -> Value {
+    let string = json!({ "type": "string" });
+    let nullable_string = json!({ "type": ["string", "null"] });
+    let nullable_int = json!({ "type": ["integer", "null"] });
+    let nullable_time = json!({ "type": ["string", "null"], "format": "date-time" });
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "djv API",
+            "version": "1.0.0",
+            "description": "Projects, topics, contributions and sync status."
+        },
+        "servers": [{ "url": "/api/v1" }],
+        "paths": {
+            "/projects": {
+                "get": {
+                    "operationId": "listProjects",
+                    "summary": "List projects",
+                    "parameters": [
+                        query_param("kind", "Projects with an artifact of this kind; repeat to match any of several", &json!({ "type": "array", "items": { "type": "string", "enum": ["crate", "npm", "repo"] } })),
+                        query_param("language", "Primary language (case-insensitive); repeat to match any of several", &json!({ "type": "array", "items": string })),
+                        query_param("topic", "Topic or keyword; repeat to require several, prefix with `-` to exclude", &json!({ "type": "array", "items": string })),
+                        query_param("match", "Whether a project needs `all` of the topics or `any` of them", &json!({ "type": "string", "enum": ["all", "any"], "default": "all" })),
+                        query_param("q", "Full-text search over name, description, topics and keywords", &string),
+                        query_param("sort", "Sort order; defaults to `relevance` when `q` is given, otherwise `popularity`, a score combining stars and downloads", &json!({ "type": "string", "enum": ["popularity", "name", "updated", "created", "released", "stars", "downloads", "relevance"] })),
+                        query_param("dir", "Sort direction; defaults to `asc` for `name` and `desc` otherwise", &json!({ "type": "string", "enum": ["asc", "desc"] })),
+                        limit_param(),
+                        cursor_param(),
+                    ],
+                    "responses": with_errors(json!({
+                        "200": json_response("A page of projects", "#/components/schemas/ProjectPage"),
+                    }))
+                }
+            },
+            "/topics": {
+                "get": {
+                    "operationId": "listTopics",
+                    "summary": "List every distinct topic",
+                    "responses": with_errors(json!({
+                        "200": json_response("All topics, alphabetically", "#/components/schemas/TopicList"),
+                    }))
+                }
+            },
+            "/contributions": {
+                "get": {
+                    "operationId": "listContributions",
+                    "summary": "List upstream contributions, newest first",
+                    "parameters": [
+                        query_param("forge", "Forge", &json!({ "type": "string", "enum": ["github", "gitlab"] })),
+                        query_param("owner", "Upstream owner (case-insensitive)", &string),
+                        query_param("year", "Calendar year merged", &json!({ "type": "integer" })),
+                        query_param("type", "Pull or merge request", &json!({ "type": "string", "enum": ["pr", "mr"] })),
+                        limit_param(),
+                        cursor_param(),
+                    ],
+                    "responses": with_errors(json!({
+                        "200": json_response("A page of contributions", "#/components/schemas/ContributionPage"),
+                    }))
+                }
+            },
+            "/sync/status": {
+                "get": {
+                    "operationId": "getSyncStatus",
+                    "summary": "Freshness of each sync source",
+                    "responses": with_errors(json!({
+                        "200": json_response("Sync status", "#/components/schemas/SyncStatus"),
+                    }))
+                }
+            },
+            "/openapi.json": {
+                "get": {
+                    "operationId": "getOpenApi",
+                    "summary": "This document",
+                    "responses": {
+                        "200": { "description": "OpenAPI document", "content": { "application/json": {} } }
+                    }
+                }
+            }
+        },
+        "components": {
+            "schemas": {
+                "Project": {
+                    "type": "object",
+                    "required": ["id", "kind", "name", "url", "topics", "popularity", "artifacts"],
+                    "properties": {
+                        "id": { "type": "string", "format": "uuid" },
+                        "kind": { "type": "string", "enum": ["crate", "npm", "repo"], "description": "Kind of the primary artifact" },
+                        "name": string,
+                        "description": nullable_string,
+                        "url": string,
+                        "language": nullable_string,
+                        "topics": { "type": "array", "items": string },
+                        "popularity": { "type": "integer" },
+                        "version": nullable_string,
+                        "commit_count": nullable_int,
+                        "updated_at": nullable_time,
+                        "artifacts": { "type": "array", "items": { "$ref": "#/components/schemas/Artifact" } }
+                    }
+                },
+                "Artifact": {
+                    "type": "object",
+                    "required": ["kind", "name", "url"],
+                    "properties": {
+                        "kind": { "type": "string", "enum": ["crate", "npm", "repo"] },
+                        "name": string,
+                        "url": string,
+                        "version": nullable_string,
+                        "downloads": { "type": ["integer", "null"], "description": "All-time for crates, weekly for npm packages" },
+                        "workspace_path": { "type": ["string", "null"], "description": "Directory of a crate within its repository's Cargo workspace, if it is a member" }
+                    }
+                },
+                "Contribution": {
+                    "type": "object",
+                    "required": ["id", "forge", "repo_owner", "repo_name", "repo_url", "type", "url", "labels"],
+                    "properties": {
+                        "id": { "type": "string", "format": "uuid" },
+                        "forge": string,
+                        "repo_owner": string,
+                        "repo_name": string,
+                        "repo_url": string,
+                        "type": { "type": "string", "enum": ["pr", "mr"] },
+                        "title": nullable_string,
+                        "url": string,
+                        "merged_at": nullable_time,
+                        "additions": nullable_int,
+                        "deletions": nullable_int,
+                        "labels": { "type": "array", "items": string }
+                    }
+                },
+                "ProjectPage": page_of("#/components/schemas/Project"),
+                "ContributionPage": page_of("#/components/schemas/Contribution"),
+                "TopicList": {
+                    "type": "object",
+                    "required": ["items"],
+                    "properties": { "items": { "type": "array", "items": string } }
+                },
+                "SyncStatus": {
+                    "type": "object",
+                    "required": ["sources"],
+                    "properties": {
+                        "last_synced_at": nullable_time,
+                        "sources": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "required": ["source", "items"],
+                                "properties": {
+                                    "source": string,
+                                    "items": { "type": "integer" },
+                                    "last_synced_at": nullable_time
+                                }
+                            }
+                        }
+                    }
+                },
+                "Error": {
+                    "type": "object",
+                    "required": ["error"],
+                    "properties": {
+                        "error": {
+                            "type": "object",
+                            "required": ["code", "message"],
+                            "properties": {
+                                "code": { "type": "string", "enum": ["invalid_parameter", "invalid_query", "unavailable", "internal"] },
+                                "message": string,
+                                "parameter": string
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,137 @@
+//! Wire types for `/api/v1`. These are kept separate from the database models so the schema
+//! can evolve without changing the public contract.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::db::{Contribution, ProjectArtifact, ProjectView, SourceSyncStatus};
+
+/// One page of results and the cursor for the next page, if any
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct List<T> {
+    pub items: Vec<T>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Project {
+    pub id: String,
+    pub kind: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub url: String,
+    pub language: Option<String>,
+    pub topics: Vec<String>,
+    pub popularity: i32,
+    pub version: Option<String>,
+    pub commit_count: Option<i32>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub artifacts: Vec<Artifact>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Artifact {
+    pub kind: String,
+    pub name: String,
+    pub url: String,
+    pub version: Option<String>,
+    pub downloads: Option<i32>,
+    pub workspace_path: Option<String>,
+}
+
+impl From<ProjectArtifact> for Artifact {
+    fn from(a: ProjectArtifact) -> Self {
+        Self {
+            kind: a.kind.to_string(),
+            name: a.name,
+            url: a.url,
+            version: a.version,
+            downloads: a.downloads,
+            workspace_path: a.workspace_path,
+        }
+    }
+}
+
+impl From<ProjectView> for Project {
+    fn from(p: ProjectView) -> Self {
+        Self {
+            id: p.id.to_string(),
+            kind: p.kind.to_string(),
+            name: p.name,
+            description: p.description,
+            url: p.url,
+            language: p.language,
+            topics: p.topics,
+            popularity: p.popularity,
+            version: p.version,
+            commit_count: p.commit_count,
+            updated_at: p.updated_at,
+            artifacts: p.artifacts.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContributionItem {
+    pub id: String,
+    pub forge: String,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub repo_url: String,
+    #[serde(rename = "type")]
+    pub contribution_type: String,
+    pub title: Option<String>,
+    pub url: String,
+    pub merged_at: Option<DateTime<Utc>>,
+    pub additions: Option<i32>,
+    pub deletions: Option<i32>,
+    pub labels: Vec<String>,
+}
+
+impl From<Contribution> for ContributionItem {
+    fn from(c: Contribution) -> Self {
+        Self {
+            id: c.id.to_string(),
+            forge: c.forge,
+            repo_owner: c.repo_owner,
+            repo_name: c.repo_name,
+            repo_url: c.repo_url,
+            contribution_type: c.contribution_type,
+            title: c.title,
+            url: c.url,
+            merged_at: c.merged_at,
+            additions: c.additions,
+            deletions: c.deletions,
+            labels: c.labels,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncSource {
+    pub source: String,
+    pub items: i64,
+    pub last_synced_at: Option<DateTime<Utc>>,
+}
+
+impl From<SourceSyncStatus> for SyncSource {
+    fn from(s: SourceSyncStatus) -> Self {
+        Self {
+            source: s.source,
+            items: s.items,
+            last_synced_at: s.last_synced_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncStatus {
+    /// Most recent write across every source
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub sources: Vec<SyncSource>,
+}
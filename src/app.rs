@@ -1,40 +1,71 @@
 use leptos::prelude::*;
-use leptos_meta::{provide_meta_context, Html, MetaTags, Script, Stylesheet, Title};
+use leptos_meta::{provide_meta_context, Html, Meta, MetaTags, Script, Stylesheet, Title};
 use leptos_router::{
     components::{Route, Router, Routes},
-    hooks::use_query_map,
+    hooks::{use_params_map, use_query_map},
     params::ParamsMap,
-    NavigateOptions, StaticSegment,
+    NavigateOptions, ParamSegment, SsrMode, StaticSegment, WildcardSegment,
 };
-use server_fn::codec::Json;
+use server_fn::codec::GetUrl;
 
-const FILTER_KEYS: [&str; 4] = ["kind", "language", "topic", "sort"];
+const FILTER_KEYS: [&str; 7] = ["kind", "language", "topic", "match", "q", "sort", "dir"];
+const CONTRIBUTION_FILTER_KEYS: [&str; 4] = ["forge", "owner", "year", "type"];
+#[cfg(feature = "ssr")]
+const CONTRIBUTIONS_PAGE_SIZE: i64 = 25;
+#[cfg(feature = "ssr")]
+const PROJECTS_PAGE_SIZE: i32 = 24;
 
 fn query_to_filters(q: &ParamsMap) -> ProjectFilters {
     ProjectFilters {
-        kind: q.get("kind"),
-        language: q.get("language"),
-        topic: q.get("topic"),
+        kind: q.get_all("kind").unwrap_or_default(),
+        language: q.get_all("language").unwrap_or_default(),
+        topic: q.get_all("topic").unwrap_or_default(),
+        topic_match: q.get("match"),
+        q: q.get("q"),
         sort: q.get("sort"),
+        dir: q.get("dir"),
         limit: None,
+        cursor: q.get("cursor"),
     }
 }
 
-fn filter_url(current: &ParamsMap, name: &str, value: Option<String>, base: &str) -> String {
+fn query_to_contribution_filters(q: &ParamsMap) -> ContributionFilters {
+    ContributionFilters {
+        forge: q.get("forge"),
+        owner: q.get("owner"),
+        year: q.get("year"),
+        contribution_type: q.get("type"),
+        cursor: q.get("cursor"),
+    }
+}
+
+/// Build a URL that keeps every current filter in `keys` except `name`, which is replaced by
+/// `values` (none removes it, several repeat the parameter). Anything not listed in `keys`
+/// (such as a page cursor) is dropped.
+fn filter_url(
+    current: &ParamsMap,
+    keys: &[&str],
+    name: &str,
+    values: Vec<String>,
+    base: &str,
+) -> String {
     let mut params: Vec<(String, String)> = Vec::new();
-    for key in FILTER_KEYS {
-        if key != name {
-            if let Some(v) = current.get(key) {
-                params.push((key.to_owned(), v));
+    for key in keys {
+        if *key != name {
+            for v in current.get_all(key).unwrap_or_default() {
+                params.push(((*key).to_owned(), v));
             }
         }
     }
-    if let Some(v) = value {
+    for v in values {
         params.push((name.to_owned(), v));
     }
     let query_string = params
         .into_iter()
-        .map(|(k, v)| format!("{k}={v}"))
+        .map(|(k, v)| {
+            let v: String = url::form_urlencoded::byte_serialize(v.as_bytes()).collect();
+            format!("{k}={v}")
+        })
         .collect::<Vec<_>>()
         .join("&");
     if query_string.is_empty() {
@@ -45,8 +76,10 @@ fn filter_url(current: &ParamsMap, name: &str, value: Option<String>, base: &str
 }
 
 use crate::components::{
-    ContributionData, ContributionsSection, FilterBar, Masthead, ProjectData, ProjectGrid,
-    ProjectGridEmpty, ProjectsPlaceholder,
+    ContributionData, ContributionFilterBar, ContributionGroups, ContributionStats,
+    ContributionStatsData, ContributionsSection, DataNotice, FilterBar, Masthead, ProjectDetail,
+    ProjectDetailData, ProjectFacetsData, ProjectGrid, ProjectGridEmpty, ProjectPageData,
+    ProjectsPlaceholder, ReleaseData, ReleasesSection,
 };
 
 #[must_use]
@@ -59,6 +92,8 @@ pub fn shell(options: LeptosOptions) -> impl IntoView {
                 <meta name="viewport" content="width=device-width, initial-scale=1"/>
                 <meta name="color-scheme" content="light dark"/>
                 <link rel="icon" type="image/svg+xml" href="/favicon.svg"/>
+                <link rel="alternate" type="application/atom+xml" title="Daniel Verrall" href="/feed.atom"/>
+                <link rel="alternate" type="application/feed+json" title="Daniel Verrall" href="/feed.json"/>
                 <link rel="preconnect" href="https://fonts.googleapis.com"/>
                 <link rel="preconnect" href="https://fonts.gstatic.com" crossorigin/>
                 <link href="https://fonts.googleapis.com/css2?family=Inter:wght@300;400;500&family=JetBrains+Mono:wght@400&family=Libre+Baskerville:wght@400;700&display=swap" rel="stylesheet"/>
@@ -155,6 +190,13 @@ pub fn app() -> impl IntoView {
                 <Routes fallback=|| "Page not found.".into_view()>
                     <Route path=StaticSegment("") view=HomePage/>
                     <Route path=StaticSegment("projects") view=ProjectsPage/>
+                    <Route
+                        path=(StaticSegment("projects"), ParamSegment("kind"), WildcardSegment("name"))
+                        view=ProjectDetailPage
+                        ssr=SsrMode::Async
+                    />
+                    <Route path=StaticSegment("contributions") view=ContributionsPage/>
+                    <Route path=StaticSegment("stats") view=StatsPage/>
                 </Routes>
             </main>
         </Router>
@@ -162,60 +204,256 @@ pub fn app() -> impl IntoView {
 }
 
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
 pub struct ProjectFilters {
-    pub kind: Option<String>,
-    pub language: Option<String>,
-    pub topic: Option<String>,
+    pub kind: Vec<String>,
+    pub language: Vec<String>,
+    /// Required topics, or excluded ones when prefixed with `-`
+    pub topic: Vec<String>,
+    /// `any` to match any of the required topics rather than all of them
+    pub topic_match: Option<String>,
+    pub q: Option<String>,
     pub sort: Option<String>,
+    /// `asc` or `desc`; each sort has its own default
+    pub dir: Option<String>,
     pub limit: Option<i32>,
+    /// Continue after this position, as taken from a previous page's `next_cursor`
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ContributionFilters {
+    pub forge: Option<String>,
+    pub owner: Option<String>,
+    pub year: Option<String>,
+    pub contribution_type: Option<String>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ContributionPageData {
+    pub contributions: Vec<ContributionData>,
+    pub next_cursor: Option<String>,
+    /// When the data was last known good, if it comes from a snapshot rather than the database
+    #[serde(default)]
+    pub data_as_of: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ContributionFacets {
+    pub years: Vec<i32>,
+    pub owners: Vec<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct InitialPageData {
-    pub topics: Vec<String>,
     pub contributions: Vec<ContributionData>,
+    pub releases: Vec<ReleaseData>,
+}
+
+/// Parse the filters as they appear in the URL. Unknown kinds, sorts, directions and match
+/// modes are ignored rather than rejected, as are malformed cursors, which restart the list.
+#[cfg(feature = "ssr")]
+fn db_filters(filters: ProjectFilters) -> crate::db::ProjectFilters {
+    use crate::db::{
+        ProjectCursor, ProjectFilters as DbFilters, ProjectKind, SortDirection, SortOrder,
+        TopicMatch, MAX_PROJECT_LIMIT,
+    };
+
+    DbFilters {
+        kinds: filters
+            .kind
+            .iter()
+            .filter_map(|k| k.parse::<ProjectKind>().ok())
+            .collect(),
+        languages: filters.language,
+        topic_match: filters
+            .topic_match
+            .as_deref()
+            .and_then(|m| m.parse::<TopicMatch>().ok())
+            .unwrap_or_default(),
+        q: filters.q,
+        sort: filters
+            .sort
+            .as_deref()
+            .and_then(|s| s.parse::<SortOrder>().ok()),
+        direction: filters
+            .dir
+            .as_deref()
+            .and_then(|d| d.parse::<SortDirection>().ok()),
+        limit: Some(
+            filters
+                .limit
+                .map_or(PROJECTS_PAGE_SIZE, |l| l.clamp(1, MAX_PROJECT_LIMIT)),
+        ),
+        cursor: filters
+            .cursor
+            .as_deref()
+            .and_then(|c| c.parse::<ProjectCursor>().ok()),
+        ..DbFilters::default()
+    }
+    .with_topic_terms(filters.topic)
 }
 
-#[server(input = Json)]
+#[server(input = GetUrl)]
 #[cfg_attr(feature = "ssr", tracing::instrument(skip_all, fields(
     filter.kind = ?filters.kind,
     filter.language = ?filters.language,
     filter.topic = ?filters.topic,
+    filter.topic_match = ?filters.topic_match,
+    filter.q = ?filters.q,
     filter.sort = ?filters.sort,
+    filter.dir = ?filters.dir,
+    page.cursor = ?filters.cursor,
 )))]
-pub async fn fetch_projects(filters: ProjectFilters) -> Result<Vec<ProjectData>, ServerFnError> {
+pub async fn fetch_projects(
+    #[server(default)] filters: ProjectFilters,
+) -> Result<ProjectPageData, ServerFnError> {
     #[cfg(feature = "ssr")]
     {
-        use crate::db::{get_projects, ProjectFilters as DbFilters, ProjectKind, SortOrder};
-        use crate::state::AppState;
-        use axum::Extension;
-        use leptos_axum::extract;
+        let db_filters = &db_filters(filters);
+        cached(
+            format!("projects:{}", cache_key(&db_filters.normalised())),
+            |store| async move {
+                let page = store
+                    .get_projects(db_filters)
+                    .await
+                    .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?;
+                Ok(project_page_data(page, None))
+            },
+            |snapshot| project_page_data(snapshot.projects(db_filters), Some(snapshot.as_of())),
+        )
+        .await
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        let _ = filters;
+        unreachable!()
+    }
+}
 
-        let Extension(app_state): Extension<AppState> = extract().await?;
-        let pool = app_state
-            .pool
-            .as_ref()
-            .ok_or_else(|| ServerFnError::new("Database not available"))?;
+#[server(input = GetUrl)]
+pub async fn fetch_project_facets(
+    #[server(default)] filters: ProjectFilters,
+) -> Result<ProjectFacetsData, ServerFnError> {
+    #[cfg(feature = "ssr")]
+    {
+        use crate::components::FacetData;
+        use crate::db::{FacetCount, ProjectFacets};
 
-        let db_filters = DbFilters {
-            kind: filters
-                .kind
-                .as_deref()
-                .and_then(|k| k.parse::<ProjectKind>().ok()),
-            language: filters.language,
-            topic: filters.topic,
-            sort: filters
-                .sort
-                .as_deref()
-                .and_then(|s| s.parse::<SortOrder>().ok()),
-            limit: filters.limit,
-        };
+        fn facet_data(counts: Vec<FacetCount>) -> Vec<FacetData> {
+            counts
+                .into_iter()
+                .map(|c| FacetData {
+                    value: c.value,
+                    count: c.count,
+                })
+                .collect()
+        }
 
-        let projects = get_projects(pool, &db_filters)
-            .await
-            .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?;
+        fn facets_data(facets: ProjectFacets) -> ProjectFacetsData {
+            ProjectFacetsData {
+                kinds: facet_data(facets.kinds),
+                languages: facet_data(facets.languages),
+                topics: facet_data(facets.topics),
+            }
+        }
 
-        Ok(projects
+        let db_filters = &db_filters(filters);
+        cached(
+            format!("project_facets:{}", cache_key(&db_filters.normalised())),
+            |store| async move {
+                let facets = store
+                    .get_project_facets(db_filters)
+                    .await
+                    .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?;
+                Ok(facets_data(facets))
+            },
+            |snapshot| facets_data(snapshot.project_facets(db_filters)),
+        )
+        .await
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        let _ = filters;
+        unreachable!()
+    }
+}
+
+/// Run `fetch` against the store, or answer from the response cache when the same
+/// request (by `key`) has already been served since the last sync.
+///
+/// Without a store, or when `fetch` fails, `from_snapshot` answers from the last-known-good
+/// snapshot instead, if there is one. Those answers aren't cached, here or by anyone else:
+/// the next request tries the store again, and the response is sent `no-store`, which also
+/// keeps the HTTP cache layer from giving it the validator of current data.
+#[cfg(feature = "ssr")]
+async fn cached<T, F, Fut>(
+    key: String,
+    fetch: F,
+    from_snapshot: impl FnOnce(&crate::snapshot::Snapshot) -> T,
+) -> Result<T, ServerFnError>
+where
+    T: Clone + Send + Sync + 'static,
+    F: FnOnce(crate::store::SharedStore) -> Fut,
+    Fut: std::future::Future<Output = Result<T, ServerFnError>>,
+{
+    use crate::state::AppState;
+    use axum::Extension;
+    use leptos_axum::extract;
+
+    let Extension(app_state): Extension<AppState> = extract().await?;
+    let snapshot = app_state.snapshots.current();
+    let from_snapshot = |snapshot: &crate::snapshot::Snapshot| {
+        if let Some(response) = use_context::<leptos_axum::ResponseOptions>() {
+            response.insert_header(
+                axum::http::header::CACHE_CONTROL,
+                axum::http::HeaderValue::from_static("no-store"),
+            );
+        }
+        from_snapshot(snapshot)
+    };
+    let Some(store) = app_state.store.clone() else {
+        return snapshot
+            .map(|s| from_snapshot(&s))
+            .ok_or_else(|| ServerFnError::new("Database not available"));
+    };
+
+    match app_state
+        .cache
+        .get_or_try_insert(key, || fetch(store))
+        .await
+    {
+        Ok(value) => Ok(value),
+        Err(e) => match snapshot {
+            Some(snapshot) => {
+                tracing::warn!(error = %e, "serving snapshot after database error");
+                Ok(from_snapshot(&snapshot))
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Requests' arguments as a cache key. Only called with plain data, which always serialises.
+#[cfg(feature = "ssr")]
+fn cache_key(args: &impl serde::Serialize) -> String {
+    serde_json::to_string(args).unwrap_or_default()
+}
+
+#[cfg(feature = "ssr")]
+fn date(dt: chrono::DateTime<chrono::Utc>) -> String {
+    dt.format("%Y-%m-%d").to_string()
+}
+
+#[cfg(feature = "ssr")]
+fn project_page_data(page: crate::db::ProjectPage, data_as_of: Option<String>) -> ProjectPageData {
+    use crate::components::ProjectData;
+
+    ProjectPageData {
+        projects: page
+            .projects
             .into_iter()
             .map(|p| ProjectData {
                 id: p.id.to_string(),
@@ -227,111 +465,410 @@ pub async fn fetch_projects(filters: ProjectFilters) -> Result<Vec<ProjectData>,
                 popularity: p.popularity,
                 version: p.version,
                 commit_count: p.commit_count,
-                updated_at: p.updated_at.map(|dt| dt.format("%Y-%m-%d").to_string()),
+                updated_at: p.updated_at.map(date),
+                artifacts: p.artifacts.into_iter().map(artifact_data).collect(),
             })
-            .collect())
+            .collect(),
+        next_cursor: page.next_cursor.map(|c| c.to_string()),
+        data_as_of,
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn project_detail_data(
+    detail: crate::db::ProjectDetail,
+    releases: Vec<crate::db::Release>,
+    contributions: Vec<crate::db::Contribution>,
+    data_as_of: Option<String>,
+) -> ProjectDetailData {
+    let project = detail.project;
+    ProjectDetailData {
+        kind: project.kind.to_string(),
+        name: project.name,
+        description: project.description,
+        url: project.url,
+        language: project.language,
+        topics: project.topics,
+        version: project.version,
+        stars: detail.stars,
+        downloads: detail.downloads,
+        commit_count: project.commit_count,
+        created_at: detail.created_at.map(date),
+        updated_at: project.updated_at.map(date),
+        latest_released_at: detail.latest_released_at.map(date),
+        repository_url: detail.repository_url,
+        documentation_url: detail.documentation_url,
+        homepage_url: detail.homepage_url,
+        readme_html: detail.readme_html,
+        artifacts: project.artifacts.into_iter().map(artifact_data).collect(),
+        releases: releases.into_iter().map(release_data).collect(),
+        contributions: contributions.into_iter().map(contribution_data).collect(),
+        data_as_of,
+    }
+}
+
+/// Names and topics of a project, to find contributions related to it
+#[cfg(feature = "ssr")]
+fn related_terms(project: &crate::db::ProjectView) -> Vec<String> {
+    project
+        .artifacts
+        .iter()
+        .map(|a| a.name.clone())
+        .chain(project.topics.iter().cloned())
+        .collect()
+}
+
+#[cfg(feature = "ssr")]
+fn contribution_page_data(
+    page: crate::db::ContributionPage,
+    data_as_of: Option<String>,
+) -> ContributionPageData {
+    ContributionPageData {
+        contributions: page
+            .contributions
+            .into_iter()
+            .map(contribution_data)
+            .collect(),
+        next_cursor: page.next_cursor.map(|c| c.to_string()),
+        data_as_of,
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn stats_data(
+    stats: crate::db::ContributionStats,
+    data_as_of: Option<String>,
+) -> ContributionStatsData {
+    use crate::components::{BarDatum, RepoActivityData};
+    use crate::db::ContributionCount;
+
+    fn bars(counts: Vec<ContributionCount>) -> Vec<BarDatum> {
+        counts
+            .into_iter()
+            .map(|c| BarDatum {
+                label: c.key,
+                count: c.count,
+            })
+            .collect()
+    }
+
+    ContributionStatsData {
+        by_owner: bars(stats.by_owner),
+        by_year: bars(stats.by_year),
+        by_month: bars(stats.by_month),
+        repos: stats
+            .repos
+            .into_iter()
+            .map(|r| RepoActivityData {
+                repo_name: format!("{}/{}", r.owner, r.name),
+                url: r.url,
+                count: r.count,
+                first_merged_at: r.first_merged_at.map(date),
+                latest_merged_at: r.latest_merged_at.map(date),
+            })
+            .collect(),
+        data_as_of,
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn contribution_data(c: crate::db::Contribution) -> ContributionData {
+    ContributionData {
+        repo_name: format!("{}/{}", c.repo_owner, c.repo_name),
+        short_repo_name: c.repo_name,
+        repo_url: c.repo_url,
+        title: c.title.unwrap_or_default(),
+        url: c.url,
+        merged_at: c.merged_at.map(date),
+        additions: c.additions,
+        deletions: c.deletions,
+        upstream_stars: c.upstream_stars,
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn artifact_data(a: crate::db::ProjectArtifact) -> crate::components::ArtifactData {
+    crate::components::ArtifactData {
+        kind: a.kind.to_string(),
+        name: a.name,
+        url: a.url,
+        version: a.version,
+        downloads: a.downloads,
+        workspace_path: a.workspace_path,
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn release_data(r: crate::db::Release) -> ReleaseData {
+    ReleaseData {
+        kind: r.artifact_kind,
+        artifact: r.artifact,
+        version: r.version,
+        released_at: r.released_at.map(date),
+        notes_url: r.notes_url,
+    }
+}
+
+/// Releases shown on a project page
+#[cfg(feature = "ssr")]
+const PROJECT_RELEASES: i64 = 10;
+/// Related contributions shown on a project page
+#[cfg(feature = "ssr")]
+const PROJECT_CONTRIBUTIONS: i64 = 10;
+
+/// One project's page, or `None` when there is no such project. The page answers that with a
+/// 404; the server function itself answers 200, as its client treats any error status as a
+/// failure rather than as `None`.
+#[server(FetchProjectDetail, input = GetUrl)]
+#[cfg_attr(feature = "ssr", tracing::instrument(skip_all, fields(
+    project.kind = %kind,
+    project.name = %name,
+)))]
+pub async fn fetch_project_detail(
+    kind: String,
+    name: String,
+) -> Result<Option<ProjectDetailData>, ServerFnError> {
+    #[cfg(feature = "ssr")]
+    {
+        use crate::db::ProjectKind;
+
+        let Ok(kind) = kind.parse::<ProjectKind>() else {
+            return Ok(None);
+        };
+        let name = &name;
+        cached(
+            format!("project_detail:{kind}/{name}"),
+            |store| async move {
+                let Some(detail) = store
+                    .get_project_detail(kind, name)
+                    .await
+                    .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?
+                else {
+                    return Ok(None);
+                };
+
+                let terms = related_terms(&detail.project);
+                let (releases, contributions) = tokio::join!(
+                    store.get_project_releases(detail.project.id, PROJECT_RELEASES),
+                    store.get_related_contributions(&terms, PROJECT_CONTRIBUTIONS),
+                );
+                let releases =
+                    releases.map_err(|e| ServerFnError::new(format!("Database error: {e}")))?;
+                let contributions = contributions
+                    .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?;
+
+                Ok(Some(project_detail_data(
+                    detail,
+                    releases,
+                    contributions,
+                    None,
+                )))
+            },
+            // A snapshot has no releases, READMEs or registry metadata, so the page shows
+            // what the project listing knows.
+            |snapshot| {
+                let detail = snapshot.project_detail(kind, name)?;
+                let contributions = snapshot
+                    .related_contributions(&related_terms(&detail.project), PROJECT_CONTRIBUTIONS);
+                Some(project_detail_data(
+                    detail,
+                    Vec::new(),
+                    contributions,
+                    Some(snapshot.as_of()),
+                ))
+            },
+        )
+        .await
     }
     #[cfg(not(feature = "ssr"))]
     {
-        let _ = filters;
+        let _ = (kind, name);
         unreachable!()
     }
 }
 
-#[server(FetchTopics)]
-pub async fn fetch_topics() -> Result<Vec<String>, ServerFnError> {
+#[server(FetchContributions, input = GetUrl)]
+pub async fn fetch_contributions(
+    sort: Option<String>,
+) -> Result<Vec<ContributionData>, ServerFnError> {
     #[cfg(feature = "ssr")]
     {
-        use crate::db::get_distinct_topics;
-        use crate::state::AppState;
-        use axum::Extension;
-        use leptos_axum::extract;
+        use crate::db::ContributionSort;
+
+        let sort = sort
+            .as_deref()
+            .and_then(|s| s.parse::<ContributionSort>().ok())
+            .unwrap_or_default();
+        cached(
+            format!("contributions:{}", sort.as_str()),
+            |store| async move {
+                let contributions = store
+                    .get_contributions(10, 2, sort)
+                    .await
+                    .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?;
+
+                Ok(contributions.into_iter().map(contribution_data).collect())
+            },
+            |snapshot| {
+                snapshot
+                    .contributions(10, 2, sort)
+                    .into_iter()
+                    .map(contribution_data)
+                    .collect()
+            },
+        )
+        .await
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        let _ = sort;
+        unreachable!()
+    }
+}
 
-        let Extension(app_state): Extension<AppState> = extract().await?;
-        let pool = app_state
-            .pool
-            .as_ref()
-            .ok_or_else(|| ServerFnError::new("Database not available"))?;
+#[server(input = GetUrl)]
+#[cfg_attr(feature = "ssr", tracing::instrument(skip_all, fields(
+    filter.forge = ?filters.forge,
+    filter.owner = ?filters.owner,
+    filter.year = ?filters.year,
+    filter.contribution_type = ?filters.contribution_type,
+)))]
+pub async fn fetch_contribution_page(
+    #[server(default)] filters: ContributionFilters,
+) -> Result<ContributionPageData, ServerFnError> {
+    #[cfg(feature = "ssr")]
+    {
+        use crate::db::{ContributionCursor, ContributionFilters as DbFilters};
+
+        let db_filters = DbFilters {
+            forge: filters.forge,
+            owner: filters.owner,
+            year: filters.year.as_deref().and_then(|y| y.parse().ok()),
+            contribution_type: filters.contribution_type,
+            cursor: filters
+                .cursor
+                .as_deref()
+                .and_then(|c| c.parse::<ContributionCursor>().ok()),
+        };
+        let db_filters = &db_filters;
+
+        cached(
+            format!("contribution_page:{}", cache_key(db_filters)),
+            |store| async move {
+                let page = store
+                    .get_contribution_page(db_filters, CONTRIBUTIONS_PAGE_SIZE)
+                    .await
+                    .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?;
+                Ok(contribution_page_data(page, None))
+            },
+            |snapshot| {
+                contribution_page_data(
+                    snapshot.contribution_page(db_filters, CONTRIBUTIONS_PAGE_SIZE),
+                    Some(snapshot.as_of()),
+                )
+            },
+        )
+        .await
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        let _ = filters;
+        unreachable!()
+    }
+}
 
-        let topics = get_distinct_topics(pool)
-            .await
-            .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?;
+#[server(FetchContributionFacets, input = GetUrl)]
+pub async fn fetch_contribution_facets() -> Result<ContributionFacets, ServerFnError> {
+    #[cfg(feature = "ssr")]
+    {
+        cached(
+            "contribution_facets".to_owned(),
+            |store| async move {
+                let (years, owners) = tokio::join!(
+                    store.get_contribution_years(),
+                    store.get_contribution_owners()
+                );
 
-        Ok(topics)
+                Ok(ContributionFacets {
+                    years: years.map_err(|e| ServerFnError::new(format!("Database error: {e}")))?,
+                    owners: owners
+                        .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?,
+                })
+            },
+            |snapshot| ContributionFacets {
+                years: snapshot.contribution_years(),
+                owners: snapshot.contribution_owners(),
+            },
+        )
+        .await
     }
     #[cfg(not(feature = "ssr"))]
     unreachable!()
 }
 
-#[server(FetchContributions)]
-pub async fn fetch_contributions() -> Result<Vec<ContributionData>, ServerFnError> {
+#[server(FetchContributionStats, input = GetUrl)]
+pub async fn fetch_contribution_stats() -> Result<ContributionStatsData, ServerFnError> {
     #[cfg(feature = "ssr")]
     {
-        use crate::db::get_contributions;
-        use crate::state::AppState;
-        use axum::Extension;
-        use leptos_axum::extract;
-
-        let Extension(app_state): Extension<AppState> = extract().await?;
-        let pool = app_state
-            .pool
-            .as_ref()
-            .ok_or_else(|| ServerFnError::new("Database not available"))?;
-
-        let contributions = get_contributions(pool, 10, 2)
-            .await
-            .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?;
-
-        Ok(contributions
-            .into_iter()
-            .map(|c| ContributionData {
-                repo_name: format!("{}/{}", c.repo_owner, c.repo_name),
-                title: c.title.unwrap_or_default(),
-                url: c.url,
-                merged_at: c.merged_at.map(|dt| dt.format("%Y-%m-%d").to_string()),
-            })
-            .collect())
+        cached(
+            "contribution_stats".to_owned(),
+            |store| async move {
+                let stats = store
+                    .get_contribution_stats()
+                    .await
+                    .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?;
+                Ok(stats_data(stats, None))
+            },
+            |snapshot| stats_data(snapshot.contribution_stats(), Some(snapshot.as_of())),
+        )
+        .await
     }
     #[cfg(not(feature = "ssr"))]
     unreachable!()
 }
 
-#[server(FetchInitialPageData)]
+#[server(FetchInitialPageData, input = GetUrl)]
 pub async fn fetch_initial_page_data() -> Result<InitialPageData, ServerFnError> {
     #[cfg(feature = "ssr")]
     {
-        use crate::db::{get_contributions, get_distinct_topics};
-        use crate::state::AppState;
-        use axum::Extension;
-        use leptos_axum::extract;
+        use crate::db::ContributionSort;
 
-        let Extension(app_state): Extension<AppState> = extract().await?;
-        let pool = app_state
-            .pool
-            .as_ref()
-            .ok_or_else(|| ServerFnError::new("Database not available"))?;
+        cached(
+            "initial_page".to_owned(),
+            |store| async move {
+                let (contributions_result, releases_result) = tokio::join!(
+                    store.get_contributions(10, 2, ContributionSort::Recent),
+                    store.get_recent_releases(None, 8)
+                );
 
-        let (topics_result, contributions_result) =
-            tokio::join!(get_distinct_topics(pool), get_contributions(pool, 10, 2));
+                let contributions = contributions_result
+                    .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?
+                    .into_iter()
+                    .map(contribution_data)
+                    .collect();
 
-        let topics =
-            topics_result.map_err(|e| ServerFnError::new(format!("Database error: {e}")))?;
+                let releases = releases_result
+                    .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?
+                    .into_iter()
+                    .map(release_data)
+                    .collect();
 
-        let contributions = contributions_result
-            .map_err(|e| ServerFnError::new(format!("Database error: {e}")))?
-            .into_iter()
-            .map(|c| ContributionData {
-                repo_name: format!("{}/{}", c.repo_owner, c.repo_name),
-                title: c.title.unwrap_or_default(),
-                url: c.url,
-                merged_at: c.merged_at.map(|dt| dt.format("%Y-%m-%d").to_string()),
-            })
-            .collect();
-
-        Ok(InitialPageData {
-            topics,
-            contributions,
-        })
+                Ok(InitialPageData {
+                    contributions,
+                    releases,
+                })
+            },
+            // Releases aren't kept in a snapshot.
+            |snapshot| InitialPageData {
+                contributions: snapshot
+                    .contributions(10, 2, ContributionSort::Recent)
+                    .into_iter()
+                    .map(contribution_data)
+                    .collect(),
+                releases: Vec::new(),
+            },
+        )
+        .await
     }
     #[cfg(not(feature = "ssr"))]
     unreachable!()
@@ -347,64 +884,94 @@ fn HomePage() -> impl IntoView {
         move || filters.get(),
         |f| async move { fetch_projects(f).await },
     );
+    let facets = Resource::new(
+        move || filters.get(),
+        |f| async move { fetch_project_facets(f).await },
+    );
     let initial_data = Resource::new(|| (), |()| async move { fetch_initial_page_data().await });
 
     let navigate = leptos_router::hooks::use_navigate();
-    let on_filter_change = Callback::new(move |(name, value): (String, Option<String>)| {
-        let url = filter_url(&query.get(), &name, value, "/");
+    let on_filter_change = Callback::new(move |(name, values): (String, Vec<String>)| {
+        let url = filter_url(&query.get(), &FILTER_KEYS, &name, values, "/");
         navigate(&url, NavigateOptions::default());
     });
+    let page_href = Callback::new(move |cursor: String| {
+        filter_url(
+            &query.get_untracked(),
+            &FILTER_KEYS,
+            "cursor",
+            vec![cursor],
+            "/",
+        )
+    });
     let current_filters = filters;
     let (filter_open, set_filter_open) = signal(false);
+    let refocus_search = RwSignal::new(false);
 
     view! {
         <div class="shell">
             <Masthead />
             <main class="main">
                 <div class="main__filter-row">
-                    <Suspense fallback=move || {
+                    <Transition fallback=move || {
                         let f = current_filters.get_untracked();
                         view! {
                             <FilterBar
                                 kind_filter=f.kind.clone()
                                 language_filter=f.language.clone()
                                 topic_filter=f.topic.clone()
+                                topic_match=f.topic_match.clone()
+                                search_filter=f.q.clone()
                                 sort_filter=f.sort.clone()
-                                topics=vec![]
+                                dir_filter=f.dir.clone()
                                 on_filter_change=on_filter_change
                                 is_expanded=filter_open
                                 set_expanded=set_filter_open
+                                refocus_search=refocus_search
                             />
                         }
                     }>
                         {move || {
                             let f = current_filters.get();
-                            let available_topics = initial_data.get()
+                            let available = facets.get()
                                 .and_then(Result::ok)
-                                .map(|d| d.topics)
                                 .unwrap_or_default();
                             view! {
                                 <FilterBar
                                     kind_filter=f.kind.clone()
                                     language_filter=f.language.clone()
                                     topic_filter=f.topic.clone()
+                                    topic_match=f.topic_match.clone()
+                                    search_filter=f.q.clone()
                                     sort_filter=f.sort.clone()
-                                    topics=available_topics
+                                    dir_filter=f.dir.clone()
+                                    facets=available
                                     on_filter_change=on_filter_change
                                     is_expanded=filter_open
                                     set_expanded=set_filter_open
+                                    refocus_search=refocus_search
                                 />
                             }
                         }}
-                    </Suspense>
+                    </Transition>
                 </div>
 
                 <Suspense fallback=move || view! { <ProjectsPlaceholder /> }>
                     {move || {
                         projects.get().map(|result| {
                             match result {
-                                Ok(data) if !data.is_empty() => {
-                                    view! { <ProjectGrid projects=data /> }.into_any()
+                                Ok(data) if !data.projects.is_empty() => {
+                                    let start_href = current_filters.get().cursor.is_some().then(|| {
+                                        filter_url(&query.get(), &FILTER_KEYS, "cursor", vec![], "/")
+                                    });
+                                    view! {
+                                        <ProjectGrid
+                                            page=data
+                                            filters=current_filters.get()
+                                            page_href=page_href
+                                            start_href=start_href
+                                        />
+                                    }.into_any()
                                 }
                                 Ok(_) => view! { <ProjectGridEmpty /> }.into_any(),
                                 Err(_) => view! { <ProjectsPlaceholder /> }.into_any(),
@@ -418,6 +985,7 @@ fn HomePage() -> impl IntoView {
                         initial_data.get().map(|result| match result {
                             Ok(data) => view! {
                                 <ContributionsSection contributions=data.contributions />
+                                <ReleasesSection releases=data.releases />
                             }.into_any(),
                             Err(_) => view! {
                                 <ContributionsSection contributions=vec![] />
@@ -440,39 +1008,55 @@ fn ProjectsPage() -> impl IntoView {
         move || filters.get(),
         |f| async move { fetch_projects(f).await },
     );
-    let topics = Resource::new(|| (), |()| async move { fetch_topics().await });
+    let facets = Resource::new(
+        move || filters.get(),
+        |f| async move { fetch_project_facets(f).await },
+    );
 
     let navigate = leptos_router::hooks::use_navigate();
-    let on_filter_change = Callback::new(move |(name, value): (String, Option<String>)| {
-        let url = filter_url(&query.get(), &name, value, "/projects");
+    let on_filter_change = Callback::new(move |(name, values): (String, Vec<String>)| {
+        let url = filter_url(&query.get(), &FILTER_KEYS, &name, values, "/projects");
         navigate(&url, NavigateOptions::default());
     });
+    let page_href = Callback::new(move |cursor: String| {
+        filter_url(
+            &query.get_untracked(),
+            &FILTER_KEYS,
+            "cursor",
+            vec![cursor],
+            "/projects",
+        )
+    });
     let current_filters = filters;
     let (filter_open, set_filter_open) = signal(false);
+    let refocus_search = RwSignal::new(false);
 
     view! {
         <div class="shell">
             <Masthead />
             <main class="main">
                 <div class="main__filter-row">
-                    <Suspense fallback=move || {
+                    <Transition fallback=move || {
                         let f = current_filters.get_untracked();
                         view! {
                             <FilterBar
                                 kind_filter=f.kind.clone()
                                 language_filter=f.language.clone()
                                 topic_filter=f.topic.clone()
+                                topic_match=f.topic_match.clone()
+                                search_filter=f.q.clone()
                                 sort_filter=f.sort.clone()
-                                topics=vec![]
+                                dir_filter=f.dir.clone()
                                 on_filter_change=on_filter_change
                                 is_expanded=filter_open
                                 set_expanded=set_filter_open
+                                refocus_search=refocus_search
                             />
                         }
                     }>
                         {move || {
                             let f = current_filters.get();
-                            let available_topics = topics.get()
+                            let available = facets.get()
                                 .and_then(Result::ok)
                                 .unwrap_or_default();
                             view! {
@@ -480,23 +1064,37 @@ fn ProjectsPage() -> impl IntoView {
                                     kind_filter=f.kind.clone()
                                     language_filter=f.language.clone()
                                     topic_filter=f.topic.clone()
+                                    topic_match=f.topic_match.clone()
+                                    search_filter=f.q.clone()
                                     sort_filter=f.sort.clone()
-                                    topics=available_topics
+                                    dir_filter=f.dir.clone()
+                                    facets=available
                                     on_filter_change=on_filter_change
                                     is_expanded=filter_open
                                     set_expanded=set_filter_open
+                                    refocus_search=refocus_search
                                 />
                             }
                         }}
-                    </Suspense>
+                    </Transition>
                 </div>
 
                 <Suspense fallback=move || view! { <ProjectsPlaceholder /> }>
                     {move || {
                         projects.get().map(|result| {
                             match result {
-                                Ok(data) if !data.is_empty() => {
-                                    view! { <ProjectGrid projects=data /> }.into_any()
+                                Ok(data) if !data.projects.is_empty() => {
+                                    let start_href = current_filters.get().cursor.is_some().then(|| {
+                                        filter_url(&query.get(), &FILTER_KEYS, "cursor", vec![], "/projects")
+                                    });
+                                    view! {
+                                        <ProjectGrid
+                                            page=data
+                                            filters=current_filters.get()
+                                            page_href=page_href
+                                            start_href=start_href
+                                        />
+                                    }.into_any()
                                 }
                                 Ok(_) => view! { <ProjectGridEmpty /> }.into_any(),
                                 Err(_) => view! { <ProjectsPlaceholder /> }.into_any(),
@@ -508,3 +1106,233 @@ fn ProjectsPage() -> impl IntoView {
         </div>
     }
 }
+
+#[component]
+fn ProjectDetailPage() -> impl IntoView {
+    let params = use_params_map();
+    let detail = Resource::new(
+        move || {
+            params.with(|p| {
+                (
+                    p.get("kind").unwrap_or_default(),
+                    p.get("name").unwrap_or_default(),
+                )
+            })
+        },
+        |(kind, name)| async move { fetch_project_detail(kind, name).await },
+    );
+
+    view! {
+        <div class="shell">
+            <Masthead />
+            <main class="main">
+                <Suspense fallback=|| ()>
+                    {move || {
+                        detail.get().map(|result| match result {
+                            Ok(Some(project)) => {
+                                let title = format!("{} · Daniel Verrall", project.name);
+                                let description = project
+                                    .description
+                                    .clone()
+                                    .filter(|d| !d.is_empty())
+                                    .unwrap_or_else(|| format!("{} by Daniel Verrall", project.name));
+                                view! {
+                                    <Title text=title.clone()/>
+                                    <Meta name="description" content=description.clone()/>
+                                    <Meta property="og:title" content=title/>
+                                    <Meta property="og:description" content=description/>
+                                    <ProjectDetail project=project />
+                                }.into_any()
+                            }
+                            Ok(None) => {
+                                // Only the server-rendered page is a 404; see
+                                // `fetch_project_detail`.
+                                #[cfg(feature = "ssr")]
+                                if let Some(response) = use_context::<leptos_axum::ResponseOptions>() {
+                                    response.set_status(axum::http::StatusCode::NOT_FOUND);
+                                }
+                                view! {
+                                    <Title text="Project not found · Daniel Verrall"/>
+                                    <p class="contrib-empty">"There is no such project."</p>
+                                }.into_any()
+                            }
+                            Err(_) => view! {
+                                <p class="contrib-empty">"This project is unavailable right now."</p>
+                            }.into_any(),
+                        })
+                    }}
+                </Suspense>
+            </main>
+        </div>
+    }
+}
+
+#[component]
+fn ContributionsPage() -> impl IntoView {
+    let query = use_query_map();
+
+    let filters = Memo::new(move |_| query_to_contribution_filters(&query.get()));
+
+    let page = Resource::new(
+        move || filters.get(),
+        |f| async move { fetch_contribution_page(f).await },
+    );
+    let facets = Resource::new(|| (), |()| async move { fetch_contribution_facets().await });
+
+    let navigate = leptos_router::hooks::use_navigate();
+    let on_filter_change = Callback::new(move |(name, values): (String, Vec<String>)| {
+        let url = filter_url(
+            &query.get(),
+            &CONTRIBUTION_FILTER_KEYS,
+            &name,
+            values,
+            "/contributions",
+        );
+        navigate(&url, NavigateOptions::default());
+    });
+    let current_filters = filters;
+    let (filter_open, set_filter_open) = signal(false);
+
+    view! {
+        <Title text="Contributions · Daniel Verrall"/>
+        <div class="shell">
+            <Masthead />
+            <main class="main">
+                <div class="main__filter-row">
+                    <Suspense fallback=move || {
+                        let f = current_filters.get_untracked();
+                        view! {
+                            <ContributionFilterBar
+                                forge_filter=f.forge.clone()
+                                owner_filter=f.owner.clone()
+                                year_filter=f.year.clone()
+                                type_filter=f.contribution_type.clone()
+                                on_filter_change=on_filter_change
+                                is_expanded=filter_open
+                                set_expanded=set_filter_open
+                            />
+                        }
+                    }>
+                        {move || {
+                            let f = current_filters.get();
+                            let available = facets.get()
+                                .and_then(Result::ok)
+                                .unwrap_or_default();
+                            view! {
+                                <ContributionFilterBar
+                                    forge_filter=f.forge.clone()
+                                    owner_filter=f.owner.clone()
+                                    year_filter=f.year.clone()
+                                    type_filter=f.contribution_type.clone()
+                                    years=available.years
+                                    owners=available.owners
+                                    on_filter_change=on_filter_change
+                                    is_expanded=filter_open
+                                    set_expanded=set_filter_open
+                                />
+                            }
+                        }}
+                    </Suspense>
+                </div>
+
+                <section class="section">
+                    <header class="section__head">
+                        <div class="section__title-row">
+                            <h2 class="section__title">"contributions"</h2>
+                        </div>
+                        <span class="section__note">"upstream patches to public software"</span>
+                    </header>
+                    <Suspense fallback=|| ()>
+                        {move || {
+                            page.get().map(|result| match result {
+                                Ok(data) => {
+                                    let current = query.get();
+                                    let newer_href = current_filters.get().cursor.is_some().then(|| {
+                                        filter_url(&current, &CONTRIBUTION_FILTER_KEYS, "cursor", vec![], "/contributions")
+                                    });
+                                    let older_href = data.next_cursor.map(|c| {
+                                        filter_url(&current, &CONTRIBUTION_FILTER_KEYS, "cursor", vec![c], "/contributions")
+                                    });
+                                    view! {
+                                        <DataNotice as_of=data.data_as_of />
+                                        <ContributionGroups
+                                            contributions=data.contributions
+                                            newer_href=newer_href
+                                            older_href=older_href
+                                        />
+                                    }.into_any()
+                                }
+                                Err(_) => view! {
+                                    <p class="contrib-empty">"Contributions are unavailable right now."</p>
+                                }.into_any(),
+                            })
+                        }}
+                    </Suspense>
+                    <a class="section__more" href="/stats">"statistics \u{2192}"</a>
+                </section>
+            </main>
+        </div>
+    }
+}
+
+#[component]
+fn StatsPage() -> impl IntoView {
+    let stats = Resource::new(|| (), |()| async move { fetch_contribution_stats().await });
+
+    view! {
+        <Title text="Contribution statistics · Daniel Verrall"/>
+        <div class="shell">
+            <Masthead />
+            <main class="main stats">
+                <Suspense fallback=|| ()>
+                    {move || {
+                        stats.get().map(|result| match result {
+                            Ok(data) => view! { <ContributionStats stats=data /> }.into_any(),
+                            Err(_) => view! {
+                                <p class="contrib-empty">"Statistics are unavailable right now."</p>
+                            }.into_any(),
+                        })
+                    }}
+                </Suspense>
+            </main>
+        </div>
+    }
+}
+
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use axum::{Extension, Router};
+    use leptos::server_fn::ServerFn;
+    use tower::ServiceExt;
+
+    use crate::state::AppState;
+
+    #[tokio::test]
+    async fn missing_project_detail_is_none_rather_than_an_error() {
+        let app = Router::new()
+            .route(
+                "/api/{*fn_name}",
+                axum::routing::get(leptos_axum::handle_server_fns),
+            )
+            .layer(Extension(AppState {
+                store: Some(std::sync::Arc::new(crate::store::MemoryStore::default())),
+                cache: crate::cache::ResponseCache::default(),
+                snapshots: crate::snapshot::SnapshotStore::default(),
+            }));
+
+        for query in ["kind=crate&name=missing", "kind=nonsense&name=missing"] {
+            let uri = format!("{}?{query}", FetchProjectDetail::PATH);
+            let response = app
+                .clone()
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{query}");
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            assert_eq!(&body[..], b"null", "{query}");
+        }
+    }
+}
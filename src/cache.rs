@@ -0,0 +1,311 @@
+//! In-process cache for server function responses.
+//!
+//! Everything the pages show changes only when a sync runs, so responses are kept until the
+//! next sync finishes on any instance (announced over `PostgreSQL` `LISTEN/NOTIFY`), bounded
+//! by entry count and a TTL in case a notification is missed. The same notification has each
+//! instance take its own snapshot, so every one falls back on the latest good data rather
+//! than only the one whose worker finished the run.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+
+use crate::snapshot::SnapshotStore;
+
+/// How long to wait before reconnecting a listener whose connection failed
+const RELISTEN_DELAY: Duration = Duration::from_secs(5);
+
+struct Entry {
+    value: Arc<dyn Any + Send + Sync>,
+    inserted_at: Instant,
+}
+
+struct Entries {
+    map: HashMap<String, Entry>,
+    /// Bumped on every clear, so a response fetched before a sync isn't stored after it
+    generation: u64,
+}
+
+/// Responses keyed by server function and normalised arguments. Cheap to clone; clones share
+/// their entries.
+#[derive(Clone)]
+pub struct ResponseCache {
+    entries: Arc<Mutex<Entries>>,
+    max_entries: usize,
+    ttl: Duration,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new(1000, Duration::from_mins(5))
+    }
+}
+
+impl ResponseCache {
+    #[must_use]
+    pub fn new(max_entries: usize, ttl: Duration) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(Entries {
+                map: HashMap::new(),
+                generation: 0,
+            })),
+            max_entries,
+            ttl,
+        }
+    }
+
+    /// A poisoned lock only means another request panicked mid-update; the map is still
+    /// usable, and a stale entry is bounded by the TTL.
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The cached response for `key`, or `fetch`'s, which is stored unless it failed.
+    ///
+    /// # Errors
+    /// Returns `fetch`'s error; errors are never cached.
+    pub async fn get_or_try_insert<T, E, F, Fut>(&self, key: String, fetch: F) -> Result<T, E>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let generation = {
+            let entries = self.lock();
+            if let Some(value) = entries
+                .map
+                .get(&key)
+                .filter(|e| e.inserted_at.elapsed() < self.ttl)
+                .and_then(|e| e.value.downcast_ref::<T>())
+            {
+                tracing::debug!(key, "response cache hit");
+                return Ok(value.clone());
+            }
+            entries.generation
+        };
+
+        tracing::debug!(key, "response cache miss");
+        let value = fetch().await?;
+        self.insert(key, value.clone(), generation);
+        Ok(value)
+    }
+
+    fn insert<T: Send + Sync + 'static>(&self, key: String, value: T, generation: u64) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let mut entries = self.lock();
+        if entries.generation != generation {
+            return;
+        }
+
+        if entries.map.len() >= self.max_entries && !entries.map.contains_key(&key) {
+            entries
+                .map
+                .retain(|_, e| e.inserted_at.elapsed() < self.ttl);
+            if entries.map.len() >= self.max_entries {
+                if let Some(oldest) = entries
+                    .map
+                    .iter()
+                    .min_by_key(|(_, e)| e.inserted_at)
+                    .map(|(k, _)| k.clone())
+                {
+                    entries.map.remove(&oldest);
+                }
+            }
+        }
+        entries.map.insert(
+            key,
+            Entry {
+                value: Arc::new(value),
+                inserted_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Drop every entry, including responses still being fetched.
+    pub fn clear(&self) {
+        let mut entries = self.lock();
+        entries.map.clear();
+        entries.generation += 1;
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().map.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Clear `cache` and refresh `snapshots` whenever any instance announces a finished sync on
+/// [`crate::db::SYNC_CHANNEL`], reconnecting if the listening connection drops.
+pub fn spawn_invalidation_listener(pool: PgPool, cache: ResponseCache, snapshots: SnapshotStore) {
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = listen(&pool, &cache, &snapshots).await {
+                tracing::warn!(error = %e, "sync listener failed, reconnecting");
+            }
+            tokio::time::sleep(RELISTEN_DELAY).await;
+        }
+    });
+}
+
+async fn listen(
+    pool: &PgPool,
+    cache: &ResponseCache,
+    snapshots: &SnapshotStore,
+) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(crate::db::SYNC_CHANNEL).await?;
+    // A sync may have finished while nobody was listening.
+    refresh(pool, cache, snapshots).await;
+
+    loop {
+        if listener.try_recv().await?.is_some() {
+            tracing::debug!("sync finished, clearing response cache");
+        } else {
+            // The connection dropped and notifications may have been missed; the next
+            // `try_recv` reconnects.
+            tracing::warn!("sync listener connection lost, clearing response cache");
+        }
+        refresh(pool, cache, snapshots).await;
+    }
+}
+
+/// Drop cached responses, and snapshot the database if the latest run succeeded. A failed
+/// run may have left partial data, so the previous snapshot stays.
+async fn refresh(pool: &PgPool, cache: &ResponseCache, snapshots: &SnapshotStore) {
+    cache.clear();
+    match crate::db::get_last_sync_succeeded(pool).await {
+        Ok(Some(true)) => {
+            if let Err(e) = snapshots.save(pool).await {
+                tracing::warn!(error = %e, "failed to save snapshot");
+            }
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!(error = %e, "failed to read last sync run"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+
+    async fn get(cache: &ResponseCache, key: &str, value: u32) -> u32 {
+        cache
+            .get_or_try_insert(key.to_owned(), || async { Ok::<_, Infallible>(value) })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn serves_the_first_response_until_cleared() {
+        let cache = ResponseCache::default();
+
+        assert_eq!(get(&cache, "a", 1).await, 1);
+        assert_eq!(get(&cache, "a", 2).await, 1);
+        assert_eq!(get(&cache, "b", 3).await, 3);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(get(&cache, "a", 4).await, 4);
+    }
+
+    #[tokio::test]
+    async fn does_not_cache_errors() {
+        let cache = ResponseCache::default();
+
+        let failed = cache
+            .get_or_try_insert("a".to_owned(), || async { Err::<u32, _>("down") })
+            .await;
+        assert_eq!(failed, Err("down"));
+        assert_eq!(get(&cache, "a", 1).await, 1);
+    }
+
+    #[tokio::test]
+    async fn evicts_the_oldest_entry_when_full() {
+        let cache = ResponseCache::new(2, Duration::from_secs(60));
+
+        get(&cache, "a", 1).await;
+        get(&cache, "b", 2).await;
+        get(&cache, "c", 3).await;
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(get(&cache, "a", 4).await, 4);
+        assert_eq!(get(&cache, "c", 5).await, 3);
+    }
+
+    #[tokio::test]
+    async fn expires_entries_after_the_ttl() {
+        let cache = ResponseCache::new(10, Duration::ZERO);
+
+        assert_eq!(get(&cache, "a", 1).await, 1);
+        assert_eq!(get(&cache, "a", 2).await, 2);
+    }
+
+    #[tokio::test]
+    async fn drops_responses_fetched_across_a_clear() {
+        let cache = ResponseCache::default();
+
+        let value = cache
+            .get_or_try_insert("a".to_owned(), || async {
+                cache.clear();
+                Ok::<_, Infallible>(1)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(value, 1);
+        assert!(cache.is_empty());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn followers_take_a_snapshot_when_any_instance_finishes_a_run(pool: PgPool) {
+        use crate::store::Store;
+
+        let snapshots = SnapshotStore::default();
+        spawn_invalidation_listener(pool.clone(), ResponseCache::default(), snapshots.clone());
+
+        // Another instance syncs a repository and finishes the run.
+        let url = "https://github.com/someone/tracer";
+        pool.upsert_repository(&crate::db::NewRepository {
+            forge: "github",
+            forge_id: "someone/tracer",
+            name: "tracer",
+            description: None,
+            url,
+            homepage_url: None,
+            language: None,
+            stars: 1,
+            topics: &[],
+            created_at: None,
+            updated_at: None,
+        })
+        .await
+        .unwrap();
+        pool.refresh_projects().await.unwrap();
+        pool.record_sync_run(None).await.unwrap();
+
+        let mut names = Vec::new();
+        for _ in 0..50 {
+            if let Some(snapshot) = snapshots.current() {
+                names = snapshot.projects.iter().map(|p| p.name.clone()).collect();
+                if !names.is_empty() {
+                    break;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(names, ["tracer"]);
+    }
+}
@@ -0,0 +1,92 @@
+use leptos::prelude::*;
+
+use super::filter_bar::{render_group, FilterOption, FilterPanel};
+
+fn choices(
+    f: Option<&str>,
+    values: impl IntoIterator<Item = (String, String)>,
+) -> Vec<FilterOption> {
+    std::iter::once(FilterOption::single(None, "any".to_owned(), f.is_none()))
+        .chain(values.into_iter().map(|(value, label)| {
+            let active = f == Some(value.as_str());
+            FilterOption::single(Some(value), label, active)
+        }))
+        .collect()
+}
+
+fn forges_for(f: Option<&str>) -> Vec<FilterOption> {
+    choices(
+        f,
+        [
+            ("github".to_owned(), "github".to_owned()),
+            ("gitlab".to_owned(), "gitlab".to_owned()),
+        ],
+    )
+}
+
+fn types_for(f: Option<&str>) -> Vec<FilterOption> {
+    choices(
+        f,
+        [
+            ("pr".to_owned(), "pull requests".to_owned()),
+            ("mr".to_owned(), "merge requests".to_owned()),
+        ],
+    )
+}
+
+#[component]
+pub fn ContributionFilterBar(
+    forge_filter: Option<String>,
+    owner_filter: Option<String>,
+    year_filter: Option<String>,
+    type_filter: Option<String>,
+    #[prop(optional)] years: Vec<i32>,
+    #[prop(optional)] owners: Vec<String>,
+    #[prop(into)] on_filter_change: Callback<(String, Vec<String>)>,
+    is_expanded: ReadSignal<bool>,
+    set_expanded: WriteSignal<bool>,
+) -> impl IntoView {
+    struct Selected {
+        forge: Option<String>,
+        owner: Option<String>,
+        year: Option<String>,
+        contribution_type: Option<String>,
+    }
+    let selected = Selected {
+        forge: forge_filter,
+        owner: owner_filter,
+        year: year_filter,
+        contribution_type: type_filter,
+    };
+
+    let has_active_filters = selected.forge.is_some()
+        || selected.owner.is_some()
+        || selected.year.is_some()
+        || selected.contribution_type.is_some();
+
+    let forges = forges_for(selected.forge.as_deref());
+    let types = types_for(selected.contribution_type.as_deref());
+    let year_options = choices(
+        selected.year.as_deref(),
+        years.into_iter().map(|y| (y.to_string(), y.to_string())),
+    );
+    let owner_options = choices(
+        selected.owner.as_deref(),
+        owners.into_iter().map(|o| (o.clone(), o)),
+    );
+    let show_years = year_options.len() > 1;
+    let show_owners = owner_options.len() > 1;
+
+    view! {
+        <FilterPanel
+            has_active_filters=has_active_filters
+            is_expanded=is_expanded
+            set_expanded=set_expanded
+        >
+            {render_group("forge", forges, on_filter_change)}
+            {render_group("type", types, on_filter_change)}
+            {show_years.then(|| render_group("year", year_options, on_filter_change))}
+            {show_owners.then(|| render_group("owner", owner_options, on_filter_change))}
+        </FilterPanel>
+    }
+}
@@ -0,0 +1,216 @@
+use leptos::prelude::*;
+
+use super::DataNotice;
+
+/// One bar in a chart: a label and the number of contributions behind it.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BarDatum {
+    pub label: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RepoActivityData {
+    pub repo_name: String,
+    pub url: String,
+    pub count: i64,
+    pub first_merged_at: Option<String>,
+    pub latest_merged_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ContributionStatsData {
+    pub by_owner: Vec<BarDatum>,
+    pub by_year: Vec<BarDatum>,
+    /// Keyed `YYYY-MM`, oldest first, with no gaps between the first and last month
+    pub by_month: Vec<BarDatum>,
+    pub repos: Vec<RepoActivityData>,
+    /// When the data was last known good, if it comes from a snapshot rather than the database
+    #[serde(default)]
+    pub data_as_of: Option<String>,
+}
+
+const BAR_CHART_WIDTH: i64 = 600;
+const BAR_LABEL_WIDTH: i64 = 170;
+const BAR_MAX_WIDTH: i64 = 370;
+const BAR_ROW_HEIGHT: i64 = 26;
+const BAR_THICKNESS: i64 = 16;
+
+const COLUMN_CHART_HEIGHT: i64 = 180;
+const COLUMN_MAX_HEIGHT: i64 = 150;
+const COLUMN_SLOT_WIDTH: i64 = 14;
+const COLUMN_GAP: i64 = 3;
+
+/// Scale `count` against `max` to a length of at most `span`, keeping non-zero counts visible.
+fn scaled(count: i64, max: i64, span: i64) -> i64 {
+    if max <= 0 || count <= 0 {
+        0
+    } else {
+        (count * span / max).max(2)
+    }
+}
+
+/// Horizontal bar chart, one row per datum, rendered as static SVG.
+#[component]
+pub fn BarChart(#[prop(into)] label: String, bars: Vec<BarDatum>) -> impl IntoView {
+    let max = bars.iter().map(|b| b.count).max().unwrap_or(0);
+    let rows = i64::try_from(bars.len()).unwrap_or(0);
+    let height = rows * BAR_ROW_HEIGHT;
+    let view_box = format!("0 0 {BAR_CHART_WIDTH} {height}");
+
+    let rows = bars
+        .into_iter()
+        .zip(0_i64..)
+        .map(|(bar, i)| {
+            let y = i * BAR_ROW_HEIGHT;
+            let width = scaled(bar.count, max, BAR_MAX_WIDTH);
+            let text_y = y + BAR_ROW_HEIGHT / 2;
+            view! {
+                <g class="chart__row">
+                    <title>{format!("{}: {}", bar.label, bar.count)}</title>
+                    <text class="chart__label" x=BAR_LABEL_WIDTH - 10 y=text_y text-anchor="end" dominant-baseline="middle">
+                        {bar.label}
+                    </text>
+                    <rect
+                        class="chart__bar"
+                        x=BAR_LABEL_WIDTH
+                        y=y + (BAR_ROW_HEIGHT - BAR_THICKNESS) / 2
+                        width=width
+                        height=BAR_THICKNESS
+                    />
+                    <text class="chart__value" x=BAR_LABEL_WIDTH + width + 6 y=text_y dominant-baseline="middle">
+                        {bar.count}
+                    </text>
+                </g>
+            }
+        })
+        .collect::<Vec<_>>();
+
+    view! {
+        <svg class="chart" viewBox=view_box role="img" aria-label=label>
+            {rows}
+        </svg>
+    }
+}
+
+/// Vertical column chart for a time series, labelling only the first and last columns.
+#[component]
+pub fn ColumnChart(#[prop(into)] label: String, bars: Vec<BarDatum>) -> impl IntoView {
+    let max = bars.iter().map(|b| b.count).max().unwrap_or(0);
+    let columns = i64::try_from(bars.len()).unwrap_or(0);
+    let width = (columns * COLUMN_SLOT_WIDTH).max(COLUMN_SLOT_WIDTH);
+    let view_box = format!("0 0 {width} {COLUMN_CHART_HEIGHT}");
+    let first = bars.first().map(|b| b.label.clone());
+    let last = (bars.len() > 1)
+        .then(|| bars.last().map(|b| b.label.clone()))
+        .flatten();
+
+    let columns = bars
+        .into_iter()
+        .zip(0_i64..)
+        .map(|(bar, i)| {
+            let height = scaled(bar.count, max, COLUMN_MAX_HEIGHT);
+            view! {
+                <rect
+                    class="chart__bar"
+                    x=i * COLUMN_SLOT_WIDTH
+                    y=COLUMN_MAX_HEIGHT - height
+                    width=COLUMN_SLOT_WIDTH - COLUMN_GAP
+                    height=height
+                >
+                    <title>{format!("{}: {}", bar.label, bar.count)}</title>
+                </rect>
+            }
+        })
+        .collect::<Vec<_>>();
+
+    view! {
+        <svg class="chart chart--columns" viewBox=view_box preserveAspectRatio="none" role="img" aria-label=label>
+            <line class="chart__axis" x1="0" y1=COLUMN_MAX_HEIGHT x2=width y2=COLUMN_MAX_HEIGHT />
+            {columns}
+        </svg>
+        <div class="chart__range">
+            <span>{first}</span>
+            <span>{last}</span>
+        </div>
+    }
+}
+
+#[component]
+fn StatsBlock(#[prop(into)] title: String, children: Children) -> impl IntoView {
+    view! {
+        <section class="section stats__block">
+            <header class="section__head">
+                <div class="section__title-row">
+                    <h2 class="section__title">{title}</h2>
+                </div>
+            </header>
+            {children()}
+        </section>
+    }
+}
+
+fn repo_activity_row(repo: RepoActivityData) -> impl IntoView {
+    let span = match (repo.first_merged_at, repo.latest_merged_at) {
+        (Some(first), Some(latest)) if first == latest => first,
+        (Some(first), Some(latest)) => format!("{first} \u{2013} {latest}"),
+        _ => "unmerged".to_owned(),
+    };
+    view! {
+        <tr>
+            <td class="stats-table__repo">
+                <a href=repo.url target="_blank" rel="noopener noreferrer">{repo.repo_name}</a>
+            </td>
+            <td class="stats-table__count">{repo.count}</td>
+            <td class="stats-table__span">{span}</td>
+        </tr>
+    }
+}
+
+/// Contribution statistics: totals, per-owner, per-year and per-month charts, and a table of
+/// every upstream repository touched.
+#[component]
+pub fn ContributionStats(stats: ContributionStatsData) -> impl IntoView {
+    if stats.repos.is_empty() {
+        return view! {
+            <p class="contrib-empty">"No contributions yet."</p>
+        }
+        .into_any();
+    }
+
+    let total: i64 = stats.repos.iter().map(|r| r.count).sum();
+    let summary = format!(
+        "{total} contributions to {} repositories across {} owners",
+        stats.repos.len(),
+        stats.by_owner.len()
+    );
+
+    view! {
+        <p class="stats__summary">{summary}</p>
+        <DataNotice as_of=stats.data_as_of />
+        <StatsBlock title="by owner">
+            <BarChart label="Contributions per upstream owner" bars=stats.by_owner />
+        </StatsBlock>
+        <StatsBlock title="by year">
+            <BarChart label="Contributions per year" bars=stats.by_year />
+        </StatsBlock>
+        <StatsBlock title="by month">
+            <ColumnChart label="Contributions per month" bars=stats.by_month />
+        </StatsBlock>
+        <StatsBlock title="repositories">
+            <table class="stats-table">
+                <thead>
+                    <tr>
+                        <th>"repository"</th>
+                        <th class="stats-table__count">"patches"</th>
+                        <th>"first \u{2013} latest"</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {stats.repos.into_iter().map(repo_activity_row).collect::<Vec<_>>()}
+                </tbody>
+            </table>
+        </StatsBlock>
+    }
+    .into_any()
+}
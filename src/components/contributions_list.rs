@@ -1,11 +1,62 @@
 use leptos::prelude::*;
 
+use super::project_card::format_number;
+
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ContributionData {
     pub repo_name: String,
+    pub short_repo_name: String,
+    pub repo_url: String,
     pub title: String,
     pub url: String,
     pub merged_at: Option<String>,
+    pub additions: Option<i32>,
+    pub deletions: Option<i32>,
+    pub upstream_stars: Option<i32>,
+}
+
+fn size_summary(additions: Option<i32>, deletions: Option<i32>) -> Option<String> {
+    match (additions, deletions) {
+        (Some(a), Some(d)) => Some(format!("+{a}/\u{2212}{d}")),
+        _ => None,
+    }
+}
+
+/// Summarise a contribution's size and upstream reach, e.g. "+120/−30 in tokio (25.0k★)".
+fn impact_summary(c: &ContributionData) -> Option<String> {
+    let size = size_summary(c.additions, c.deletions);
+    let stars = c
+        .upstream_stars
+        .filter(|s| *s > 0)
+        .map(|s| format!("({}\u{2605})", format_number(s)));
+
+    match (size, stars) {
+        (Some(size), Some(stars)) => Some(format!("{size} in {} {stars}", c.short_repo_name)),
+        (Some(size), None) => Some(format!("{size} in {}", c.short_repo_name)),
+        (None, Some(stars)) => Some(format!("{} {stars}", c.short_repo_name)),
+        (None, None) => None,
+    }
+}
+
+pub(super) fn contribution_row(c: ContributionData) -> impl IntoView {
+    let summary = impact_summary(&c);
+    view! {
+        <li class="contrib-row">
+            <a href=c.url target="_blank" rel="noopener noreferrer">
+                <span class="contrib-row__repo">{c.repo_name}</span>
+                <span class="contrib-row__title">{c.title}</span>
+                {summary.map(|s| view! {
+                    <span class="contrib-row__meta">{s}</span>
+                })}
+                <span class="contrib-row__arrow" aria-hidden="true">
+                    <svg width="12" height="12" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="1.8" stroke-linecap="round" stroke-linejoin="round">
+                        <line x1="7" y1="17" x2="17" y2="7" />
+                        <polyline points="7 7 17 7 17 17" />
+                    </svg>
+                </span>
+            </a>
+        </li>
+    }
 }
 
 #[component]
@@ -38,27 +89,82 @@ pub fn ContributionsSection(contributions: Vec<ContributionData>) -> impl IntoVi
                 <span class="section__note">"upstream patches to public software"</span>
             </header>
             <ul class="contrib-list">
-                {contributions
-                    .into_iter()
-                    .map(|c| {
-                        view! {
-                            <li class="contrib-row">
-                                <a href=c.url target="_blank" rel="noopener noreferrer">
-                                    <span class="contrib-row__repo">{c.repo_name}</span>
-                                    <span class="contrib-row__title">{c.title}</span>
-                                    <span class="contrib-row__arrow" aria-hidden="true">
-                                        <svg width="12" height="12" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="1.8" stroke-linecap="round" stroke-linejoin="round">
-                                            <line x1="7" y1="17" x2="17" y2="7" />
-                                            <polyline points="7 7 17 7 17 17" />
-                                        </svg>
-                                    </span>
-                                </a>
-                            </li>
-                        }
-                    })
-                    .collect::<Vec<_>>()}
+                {contributions.into_iter().map(contribution_row).collect::<Vec<_>>()}
             </ul>
+            <a class="section__more" href="/contributions">"all contributions \u{2192}"</a>
         </section>
     }
     .into_any()
 }
+
+/// Group contributions by upstream repository, keeping the order in which each repository
+/// first appears.
+fn group_by_repo(contributions: Vec<ContributionData>) -> Vec<Vec<ContributionData>> {
+    let mut groups: Vec<Vec<ContributionData>> = Vec::new();
+    for c in contributions {
+        match groups.iter_mut().find(|g| g[0].repo_url == c.repo_url) {
+            Some(group) => group.push(c),
+            None => groups.push(vec![c]),
+        }
+    }
+    groups
+}
+
+fn contribution_group(mut group: Vec<ContributionData>) -> AnyView {
+    if group.len() == 1 {
+        return contribution_row(group.remove(0)).into_any();
+    }
+
+    let repo_name = group[0].repo_name.clone();
+    let count = format!("{} patches", group.len());
+    let additions = group.iter().map(|c| c.additions).sum::<Option<i32>>();
+    let deletions = group.iter().map(|c| c.deletions).sum::<Option<i32>>();
+    let size = size_summary(additions, deletions);
+
+    view! {
+        <li class="contrib-group">
+            <details>
+                <summary class="contrib-group__summary">
+                    <span class="contrib-row__repo">{repo_name}</span>
+                    <span class="contrib-group__count">{count}</span>
+                    {size.map(|s| view! { <span class="contrib-row__meta">{s}</span> })}
+                </summary>
+                <ul class="contrib-group__list">
+                    {group.into_iter().map(contribution_row).collect::<Vec<_>>()}
+                </ul>
+            </details>
+        </li>
+    }
+    .into_any()
+}
+
+/// Paged contributions list for the `/contributions` page, with repeated patches to the same
+/// upstream repository collapsed into one expandable row.
+#[component]
+pub fn ContributionGroups(
+    contributions: Vec<ContributionData>,
+    newer_href: Option<String>,
+    older_href: Option<String>,
+) -> impl IntoView {
+    if contributions.is_empty() {
+        return view! {
+            <p class="contrib-empty">"No contributions match these filters."</p>
+        }
+        .into_any();
+    }
+
+    let show_pager = newer_href.is_some() || older_href.is_some();
+
+    view! {
+        <ul class="contrib-list">
+            {group_by_repo(contributions).into_iter().map(contribution_group).collect::<Vec<_>>()}
+        </ul>
+        {show_pager.then(|| view! {
+            <nav class="pager" aria-label="contribution pages">
+                {newer_href.map(|href| view! { <a class="pager__link" href=href>"\u{2190} newest"</a> })}
+                {older_href.map(|href| view! { <a class="pager__link pager__link--next" href=href>"older \u{2192}"</a> })}
+            </nav>
+        })}
+    }
+    .into_any()
+}
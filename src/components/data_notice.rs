@@ -0,0 +1,12 @@
+use leptos::prelude::*;
+
+/// Says when the data was last known good, if it's being served from a snapshot because the
+/// database is unavailable. Renders nothing otherwise.
+#[component]
+pub fn DataNotice(as_of: Option<String>) -> impl IntoView {
+    as_of.map(|as_of| {
+        view! {
+            <p class="data-notice">"data as of "{as_of}</p>
+        }
+    })
+}
@@ -1,95 +1,300 @@
+use std::time::Duration;
+
+use leptos::html::Input;
 use leptos::prelude::*;
 
+/// How long typing must pause before a search navigates.
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// How many projects carry one value of a filter group
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FacetData {
+    pub value: String,
+    pub count: i64,
+}
+
+/// The values each filter group offers, counted against the current filters
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ProjectFacetsData {
+    pub kinds: Vec<FacetData>,
+    pub languages: Vec<FacetData>,
+    pub topics: Vec<FacetData>,
+}
+
+/// One button in a filter group. Clicking it sets the group's parameter to `values`.
 #[derive(Debug, Clone, PartialEq)]
 pub struct FilterOption {
-    pub value: String,
+    pub values: Vec<String>,
     pub label: String,
     pub active: bool,
+    /// Shown as an exclusion rather than a selection
+    pub excluded: bool,
+    /// Projects matching this option, where known
+    pub count: Option<i64>,
 }
 
-fn kinds_for(f: Option<&str>) -> Vec<FilterOption> {
-    vec![
-        FilterOption {
-            value: String::new(),
-            label: "all".to_owned(),
-            active: f.is_none(),
-        },
-        FilterOption {
-            value: "crate".to_owned(),
-            label: "crates".to_owned(),
-            active: f == Some("crate"),
-        },
-        FilterOption {
-            value: "repo".to_owned(),
-            label: "repos".to_owned(),
-            active: f == Some("repo"),
-        },
-    ]
+impl FilterOption {
+    /// An option selecting a single value, or clearing the filter when `value` is `None`.
+    pub(super) fn single(value: Option<String>, label: String, active: bool) -> Self {
+        Self {
+            values: value.into_iter().collect(),
+            label,
+            active,
+            excluded: false,
+            count: None,
+        }
+    }
+
+    /// Selecting it would leave nothing to show, and it isn't selected already.
+    fn is_empty(&self) -> bool {
+        self.count == Some(0) && !self.active && !self.excluded
+    }
 }
 
-fn languages_for(f: Option<&str>) -> Vec<FilterOption> {
-    vec![
-        FilterOption {
-            value: String::new(),
-            label: "any".to_owned(),
-            active: f.is_none(),
-        },
-        FilterOption {
-            value: "Rust".to_owned(),
-            label: "rust".to_owned(),
-            active: f == Some("Rust"),
-        },
-        FilterOption {
-            value: "TypeScript".to_owned(),
-            label: "typescript".to_owned(),
-            active: f == Some("TypeScript"),
-        },
-        FilterOption {
-            value: "Nix".to_owned(),
-            label: "nix".to_owned(),
-            active: f == Some("Nix"),
-        },
-    ]
+/// `selected` with `value` added, or removed if it is already there.
+fn toggled(selected: &[String], value: &str) -> Vec<String> {
+    if selected.iter().any(|s| s == value) {
+        selected.iter().filter(|s| *s != value).cloned().collect()
+    } else {
+        selected.iter().cloned().chain([value.to_owned()]).collect()
+    }
+}
+
+/// `facets` plus a zero count for any selected value they lack, so a selection can always be
+/// seen and cleared.
+fn with_selected<'a>(
+    facets: &[FacetData],
+    selected: impl IntoIterator<Item = &'a str>,
+) -> Vec<FacetData> {
+    let mut all = facets.to_vec();
+    for value in selected {
+        if !all.iter().any(|f| f.value.eq_ignore_ascii_case(value)) {
+            all.push(FacetData {
+                value: value.to_owned(),
+                count: 0,
+            });
+        }
+    }
+    all
+}
+
+/// A leading "clear" option followed by one toggle per facet value, so several can be
+/// selected. Values compare case-insensitively.
+fn multi_select(
+    selected: &[String],
+    clear_label: &str,
+    facets: &[FacetData],
+    label: impl Fn(&str) -> String,
+) -> Vec<FilterOption> {
+    std::iter::once(FilterOption::single(
+        None,
+        clear_label.to_owned(),
+        selected.is_empty(),
+    ))
+    .chain(
+        with_selected(facets, selected.iter().map(String::as_str))
+            .into_iter()
+            .map(|f| {
+                let active = selected.iter().any(|s| s.eq_ignore_ascii_case(&f.value));
+                let values = if active {
+                    selected
+                        .iter()
+                        .filter(|s| !s.eq_ignore_ascii_case(&f.value))
+                        .cloned()
+                        .collect()
+                } else {
+                    selected.iter().cloned().chain([f.value.clone()]).collect()
+                };
+                FilterOption {
+                    values,
+                    label: label(&f.value),
+                    active,
+                    excluded: false,
+                    count: Some(f.count),
+                }
+            }),
+    )
+    .collect()
+}
+
+fn kind_label(kind: &str) -> String {
+    match kind {
+        "crate" => "crates".to_owned(),
+        "repo" => "repos".to_owned(),
+        other => other.to_owned(),
+    }
+}
+
+fn kinds_for(selected: &[String], facets: &[FacetData]) -> Vec<FilterOption> {
+    multi_select(selected, "all", facets, kind_label)
+}
+
+fn languages_for(selected: &[String], facets: &[FacetData]) -> Vec<FilterOption> {
+    multi_select(selected, "any", facets, str::to_lowercase)
 }
 
-fn topics_for(f: Option<&str>, topics: Vec<String>) -> Vec<FilterOption> {
-    std::iter::once(FilterOption {
-        value: String::new(),
-        label: "any".to_owned(),
-        active: f.is_none(),
-    })
-    .chain(topics.into_iter().map(|t| FilterOption {
-        active: f == Some(t.as_str()),
-        value: t.clone(),
-        label: t,
-    }))
+/// Each topic cycles through required, excluded (`-topic`) and unset. Topics no project
+/// would match are left out unless they are already selected.
+fn topics_for(selected: &[String], facets: &[FacetData]) -> Vec<FilterOption> {
+    let selected_topics = selected.iter().map(|t| t.strip_prefix('-').unwrap_or(t));
+    std::iter::once(FilterOption::single(
+        None,
+        "any".to_owned(),
+        selected.is_empty(),
+    ))
+    .chain(
+        with_selected(facets, selected_topics)
+            .into_iter()
+            .map(|f| {
+                let excluded_term = format!("-{}", f.value);
+                let required = selected.contains(&f.value);
+                let excluded = selected.contains(&excluded_term);
+                let values = if required {
+                    toggled(&toggled(selected, &f.value), &excluded_term)
+                } else if excluded {
+                    toggled(selected, &excluded_term)
+                } else {
+                    toggled(selected, &f.value)
+                };
+                FilterOption {
+                    values,
+                    label: f.value,
+                    active: required,
+                    excluded,
+                    count: Some(f.count),
+                }
+            })
+            .filter(|o| !o.is_empty()),
+    )
     .collect()
 }
 
-fn sorts_for(f: Option<&str>) -> Vec<FilterOption> {
+fn topic_match_for(f: Option<&str>) -> Vec<FilterOption> {
     vec![
-        FilterOption {
-            value: "popularity".to_owned(),
-            label: "popular".to_owned(),
-            active: f != Some("name") && f != Some("updated"),
-        },
-        FilterOption {
-            value: "name".to_owned(),
-            label: "name".to_owned(),
-            active: f == Some("name"),
-        },
-        FilterOption {
-            value: "updated".to_owned(),
-            label: "recent".to_owned(),
-            active: f == Some("updated"),
-        },
+        FilterOption::single(None, "all".to_owned(), f != Some("any")),
+        FilterOption::single(Some("any".to_owned()), "any".to_owned(), f == Some("any")),
     ]
 }
 
-fn render_group(
+/// Sort values and their labels, in the order they are offered
+const SORTS: [(&str, &str); 7] = [
+    ("popularity", "popular"),
+    ("name", "name"),
+    ("updated", "updated"),
+    ("created", "created"),
+    ("released", "released"),
+    ("stars", "stars"),
+    ("downloads", "downloads"),
+];
+
+/// The sort in effect: `f` if it is one we offer, otherwise relevance while searching and
+/// popularity when not.
+fn effective_sort(f: Option<&str>, searching: bool) -> &str {
+    match f {
+        Some("relevance") if searching => "relevance",
+        Some(sort) if SORTS.iter().any(|(value, _)| *value == sort) => sort,
+        _ if searching => "relevance",
+        _ => "popularity",
+    }
+}
+
+/// Sort options. While searching, relevance is offered and is the default.
+fn sorts_for(f: Option<&str>, searching: bool) -> Vec<FilterOption> {
+    let current = effective_sort(f, searching);
+    let relevance = searching.then_some(("relevance", "relevance"));
+    relevance
+        .into_iter()
+        .chain(SORTS)
+        .map(|(value, label)| {
+            FilterOption::single(Some(value.to_owned()), label.to_owned(), value == current)
+        })
+        .collect()
+}
+
+/// Direction options for the current sort. Its own direction (A to Z for names, highest or
+/// newest first otherwise) clears the parameter.
+fn directions_for(sort: Option<&str>, dir: Option<&str>, searching: bool) -> Vec<FilterOption> {
+    let default = if effective_sort(sort, searching) == "name" {
+        "asc"
+    } else {
+        "desc"
+    };
+    let current = match dir {
+        Some(d @ ("asc" | "desc")) => d,
+        _ => default,
+    };
+    [("desc", "desc ↓"), ("asc", "asc ↑")]
+        .into_iter()
+        .map(|(value, label)| {
+            FilterOption::single(
+                (value != default).then(|| value.to_owned()),
+                label.to_owned(),
+                value == current,
+            )
+        })
+        .collect()
+}
+
+/// Search box that reports `q` through `on_filter_change` once typing pauses. The filter bar is
+/// rebuilt on every navigation, so `refocus` carries focus over to the new input.
+fn render_search(
+    value: Option<String>,
+    on_filter_change: Callback<(String, Vec<String>)>,
+    refocus: RwSignal<bool>,
+) -> impl IntoView {
+    let input_ref = NodeRef::<Input>::new();
+    let pending = StoredValue::new(None::<TimeoutHandle>);
+
+    Effect::new(move |_| {
+        if let Some(input) = input_ref.get() {
+            if refocus.get_untracked() {
+                refocus.set(false);
+                let _ = input.focus();
+                let end = u32::try_from(input.value().encode_utf16().count()).unwrap_or(0);
+                let _ = input.set_selection_range(end, end);
+            }
+        }
+    });
+
+    let on_input = move |ev| {
+        let text = event_target_value(&ev);
+        if let Some(handle) = pending.get_value() {
+            handle.clear();
+        }
+        let handle = set_timeout_with_handle(
+            move || {
+                let q = text.trim();
+                refocus.set(true);
+                let values = if q.is_empty() {
+                    vec![]
+                } else {
+                    vec![q.to_owned()]
+                };
+                on_filter_change.run(("q".to_owned(), values));
+            },
+            SEARCH_DEBOUNCE,
+        )
+        .ok();
+        pending.set_value(handle);
+    };
+
+    view! {
+        <input
+            node_ref=input_ref
+            class="filter-bar__search"
+            type="search"
+            placeholder="search"
+            aria-label="Search projects"
+            value=value.unwrap_or_default()
+            on:input=on_input
+        />
+    }
+}
+
+pub(super) fn render_group(
     name: &'static str,
     options: Vec<FilterOption>,
-    on_filter_change: Callback<(String, Option<String>)>,
+    on_filter_change: Callback<(String, Vec<String>)>,
 ) -> impl IntoView {
     view! {
         <div class="filter-group">
@@ -99,8 +304,11 @@ fn render_group(
                     .into_iter()
                     .map(|opt| {
                         let filter_name = name.to_owned();
-                        let value = opt.value.clone();
-                        let class = if opt.active {
+                        let disabled = opt.is_empty();
+                        let values = opt.values;
+                        let class = if opt.excluded {
+                            "filter-btn filter-btn--excluded"
+                        } else if opt.active {
                             "filter-btn filter-btn--active"
                         } else {
                             "filter-btn"
@@ -108,12 +316,13 @@ fn render_group(
                         view! {
                             <button
                                 class=class
+                                disabled=disabled
                                 on:click=move |_| {
-                                    let val = if value.is_empty() { None } else { Some(value.clone()) };
-                                    on_filter_change.run((filter_name.clone(), val));
+                                    on_filter_change.run((filter_name.clone(), values.clone()));
                                 }
                             >
                                 {opt.label}
+                                {opt.count.map(|n| view! { <span class="filter-btn__count">{n}</span> })}
                             </button>
                         }
                     })
@@ -125,39 +334,89 @@ fn render_group(
 
 #[component]
 pub fn FilterBar(
-    kind_filter: Option<String>,
-    language_filter: Option<String>,
-    topic_filter: Option<String>,
+    kind_filter: Vec<String>,
+    language_filter: Vec<String>,
+    topic_filter: Vec<String>,
+    topic_match: Option<String>,
+    search_filter: Option<String>,
     sort_filter: Option<String>,
-    #[prop(optional)] topics: Vec<String>,
-    #[prop(into)] on_filter_change: Callback<(String, Option<String>)>,
+    dir_filter: Option<String>,
+    #[prop(optional)] facets: ProjectFacetsData,
+    #[prop(into)] on_filter_change: Callback<(String, Vec<String>)>,
     is_expanded: ReadSignal<bool>,
     set_expanded: WriteSignal<bool>,
+    refocus_search: RwSignal<bool>,
 ) -> impl IntoView {
     struct Selected {
-        kind: Option<String>,
-        language: Option<String>,
-        topic: Option<String>,
+        kind: Vec<String>,
+        language: Vec<String>,
+        topic: Vec<String>,
+        topic_match: Option<String>,
+        q: Option<String>,
         sort: Option<String>,
+        dir: Option<String>,
     }
     let selected = Selected {
         kind: kind_filter,
         language: language_filter,
         topic: topic_filter,
+        topic_match,
+        q: search_filter,
         sort: sort_filter,
+        dir: dir_filter,
     };
+    let ProjectFacetsData {
+        kinds,
+        languages,
+        topics,
+    } = facets;
+    let searching = selected.q.is_some();
 
-    let has_active_filters = selected.kind.is_some()
-        || selected.language.is_some()
-        || selected.topic.is_some()
-        || selected.sort.as_deref().is_some_and(|s| s != "popularity");
+    let has_active_filters = !selected.kind.is_empty()
+        || !selected.language.is_empty()
+        || !selected.topic.is_empty()
+        || selected.sort.as_deref().is_some_and(|s| s != "popularity")
+        || selected.dir.is_some();
 
-    let kinds = kinds_for(selected.kind.as_deref());
-    let languages = languages_for(selected.language.as_deref());
-    let sorts = sorts_for(selected.sort.as_deref());
-    let topic_options = topics_for(selected.topic.as_deref(), topics);
+    let kinds = kinds_for(&selected.kind, &kinds);
+    let languages = languages_for(&selected.language, &languages);
+    let sorts = sorts_for(selected.sort.as_deref(), searching);
+    let directions = directions_for(selected.sort.as_deref(), selected.dir.as_deref(), searching);
+    let required_topics = selected
+        .topic
+        .iter()
+        .filter(|t| !t.starts_with('-'))
+        .count();
+    let topic_options = topics_for(&selected.topic, &topics);
     let show_topics = topic_options.len() > 1;
+    let show_topic_match = show_topics && required_topics > 1;
+    let topic_match_options = topic_match_for(selected.topic_match.as_deref());
+
+    view! {
+        {render_search(selected.q, on_filter_change, refocus_search)}
+        <FilterPanel
+            has_active_filters=has_active_filters
+            is_expanded=is_expanded
+            set_expanded=set_expanded
+        >
+            {render_group("kind", kinds, on_filter_change)}
+            {render_group("language", languages, on_filter_change)}
+            {render_group("sort", sorts, on_filter_change)}
+            {render_group("dir", directions, on_filter_change)}
+            {show_topics.then(|| render_group("topic", topic_options, on_filter_change))}
+            {show_topic_match.then(|| render_group("match", topic_match_options, on_filter_change))}
+        </FilterPanel>
+    }
+}
 
+/// The collapsible "filters" toggle and panel shared by every filter bar.
+#[component]
+pub(super) fn FilterPanel(
+    has_active_filters: bool,
+    is_expanded: ReadSignal<bool>,
+    set_expanded: WriteSignal<bool>,
+    children: Children,
+) -> impl IntoView {
     view! {
         <div class="filter-bar">
             <button
@@ -188,10 +447,7 @@ pub fn FilterBar(
                 }
             >
                 <div class="filter-bar__panel-inner">
-                    {render_group("kind", kinds, on_filter_change)}
-                    {render_group("language", languages, on_filter_change)}
-                    {render_group("sort", sorts, on_filter_change)}
-                    {show_topics.then(|| render_group("topic", topic_options, on_filter_change))}
+                    {children()}
                 </div>
             </div>
         </div>
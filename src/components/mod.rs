@@ -1,17 +1,29 @@
+mod contribution_filter_bar;
+mod contribution_stats;
 mod contributions_list;
+mod data_notice;
 mod filter_bar;
 mod header;
 mod language_icon;
 mod project_card;
+mod project_detail;
 mod project_grid;
 mod projects_placeholder;
+mod releases_list;
 mod theme_toggle;
 
-pub use contributions_list::{ContributionData, ContributionsSection};
-pub use filter_bar::FilterBar;
+pub use contribution_filter_bar::ContributionFilterBar;
+pub use contribution_stats::{
+    BarDatum, ContributionStats, ContributionStatsData, RepoActivityData,
+};
+pub use contributions_list::{ContributionData, ContributionGroups, ContributionsSection};
+pub use data_notice::DataNotice;
+pub use filter_bar::{FacetData, FilterBar, ProjectFacetsData};
 pub use header::Masthead;
 pub use language_icon::LanguageIcon;
-pub use project_card::ProjectCard;
-pub use project_grid::{ProjectData, ProjectGrid, ProjectGridEmpty};
+pub use project_card::{ArtifactData, ProjectCard};
+pub use project_detail::{project_href, ProjectDetail, ProjectDetailData};
+pub use project_grid::{ProjectData, ProjectGrid, ProjectGridEmpty, ProjectPageData};
 pub use projects_placeholder::ProjectsPlaceholder;
+pub use releases_list::{ReleaseData, ReleasesSection};
 pub use theme_toggle::ThemeToggle;
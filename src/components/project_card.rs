@@ -1,6 +1,33 @@
 use leptos::prelude::*;
 
-fn format_number(n: i32) -> String {
+use super::project_href;
+
+/// Badges shown on a card before the rest are summarised as "+N"
+const MAX_BADGES: usize = 4;
+
+/// One crate, npm package or repository making up a project
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ArtifactData {
+    pub kind: String,
+    pub name: String,
+    pub url: String,
+    pub version: Option<String>,
+    /// All-time for crates, weekly for npm packages
+    pub downloads: Option<i32>,
+    /// Directory of a crate within its repository's Cargo workspace, if it is a member
+    pub workspace_path: Option<String>,
+}
+
+/// Registry an artifact is published to, if it is a package.
+pub(crate) fn registry_label(kind: &str) -> Option<&'static str> {
+    match kind {
+        "crate" => Some("crates.io"),
+        "npm" => Some("npm"),
+        _ => None,
+    }
+}
+
+pub(crate) fn format_number(n: i32) -> String {
     if n >= 1_000_000 {
         format!("{:.1}m", f64::from(n) / 1_000_000.0)
     } else if n >= 1_000 {
@@ -19,6 +46,46 @@ fn lang_dot_class(language: Option<&str>) -> Option<&'static str> {
     }
 }
 
+/// A registry badge per package; packages named like the project only need their registry.
+fn artifact_badges(project: &str, artifacts: Vec<ArtifactData>) -> Option<impl IntoView> {
+    let badges: Vec<(String, String, String)> = artifacts
+        .into_iter()
+        .filter_map(|a| {
+            let registry = registry_label(&a.kind)?;
+            let text = if a.name == project {
+                registry.to_owned()
+            } else {
+                format!("{registry} {}", a.name)
+            };
+            let title = match &a.version {
+                Some(v) => format!("{} v{v} on {registry}", a.name),
+                None => format!("{} on {registry}", a.name),
+            };
+            Some((
+                format!("project-row__badge project-row__badge--{}", a.kind),
+                text,
+                title,
+            ))
+        })
+        .collect();
+    let hidden = badges.len().saturating_sub(MAX_BADGES);
+
+    (!badges.is_empty()).then(|| {
+        view! {
+            <ul class="project-row__badges">
+                {badges
+                    .into_iter()
+                    .take(MAX_BADGES)
+                    .map(|(class, text, title)| view! { <li class=class title=title>{text}</li> })
+                    .collect::<Vec<_>>()}
+                {(hidden > 0).then(|| view! {
+                    <li class="project-row__badge">{format!("+{hidden}")}</li>
+                })}
+            </ul>
+        }
+    })
+}
+
 #[component]
 pub fn ProjectCard(
     name: String,
@@ -29,6 +96,9 @@ pub fn ProjectCard(
     popularity: i32,
     version: Option<String>,
     updated_at: Option<String>,
+    /// The project's artifacts; each package gets a registry badge
+    #[prop(optional)]
+    artifacts: Vec<ArtifactData>,
 ) -> impl IntoView {
     struct Card {
         name: String,
@@ -49,6 +119,12 @@ pub fn ProjectCard(
         updated_at,
     };
 
+    // Our own page when we know where it lives, otherwise straight upstream.
+    let href = card
+        .kind
+        .as_deref()
+        .map_or_else(|| card.url.clone(), |k| project_href(k, &card.name));
+    let external_label = format!("{} upstream", card.name);
     let dot_class = lang_dot_class(card.language.as_deref());
     let language_title = card.language;
 
@@ -73,9 +149,11 @@ pub fn ProjectCard(
         view! { <p class="project-row__desc">{d}</p> }
     });
 
+    let badges_el = artifact_badges(&card.name, artifacts);
+
     view! {
         <li class="project-row">
-            <a href=card.url target="_blank" rel="noopener noreferrer">
+            <a class="project-row__link" href=href>
                 <div>
                     <div class="project-row__head">
                         <h3 class="project-row__name">{card.name}</h3>
@@ -84,6 +162,7 @@ pub fn ProjectCard(
                         })}
                     </div>
                     {description_el}
+                    {badges_el}
                 </div>
                 <div class="project-row__stats">
                     {metric.map(|(value, unit)| view! {
@@ -99,6 +178,16 @@ pub fn ProjectCard(
                     })}
                 </div>
             </a>
+            <a
+                class="project-row__external"
+                href=card.url
+                target="_blank"
+                rel="noopener noreferrer"
+                aria-label=external_label.clone()
+                title=external_label
+            >
+                "\u{2197}"
+            </a>
         </li>
     }
 }
@@ -0,0 +1,251 @@
+use leptos::prelude::*;
+
+use super::contributions_list::contribution_row;
+use super::project_card::{format_number, registry_label};
+use super::releases_list::release_row;
+use super::{ArtifactData, ContributionData, DataNotice, ReleaseData};
+
+/// Path of a project's own page. npm scopes keep their slash, which the route's trailing
+/// wildcard segment accepts.
+#[must_use]
+pub fn project_href(kind: &str, name: &str) -> String {
+    format!("/projects/{kind}/{name}")
+}
+
+/// Everything shown on a project's own page
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ProjectDetailData {
+    pub kind: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// The crates.io or npm page, or the repository itself
+    pub url: String,
+    pub language: Option<String>,
+    pub topics: Vec<String>,
+    pub version: Option<String>,
+    pub stars: Option<i32>,
+    pub downloads: Option<i32>,
+    pub commit_count: Option<i32>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub latest_released_at: Option<String>,
+    pub repository_url: Option<String>,
+    pub documentation_url: Option<String>,
+    pub homepage_url: Option<String>,
+    /// Sanitised when it was synced, so it can be embedded as-is
+    pub readme_html: Option<String>,
+    /// Primary artifact first
+    pub artifacts: Vec<ArtifactData>,
+    pub releases: Vec<ReleaseData>,
+    pub contributions: Vec<ContributionData>,
+    /// When the data was last known good, if it comes from a snapshot rather than the database
+    #[serde(default)]
+    pub data_as_of: Option<String>,
+}
+
+impl ProjectDetailData {
+    /// Outbound links, labelled, without repeating a URL.
+    fn links(&self) -> Vec<(&'static str, String)> {
+        let registry = match self.kind.as_str() {
+            "crate" => Some(("crates.io", self.url.clone())),
+            "npm" => Some(("npm", self.url.clone())),
+            _ => None,
+        };
+        let repository = self
+            .repository_url
+            .clone()
+            .or_else(|| (self.kind == "repo").then(|| self.url.clone()));
+
+        let mut links: Vec<(&'static str, String)> = Vec::new();
+        for (label, url) in [
+            repository.map(|u| ("repository", u)),
+            registry,
+            self.documentation_url.clone().map(|u| ("docs", u)),
+            self.homepage_url.clone().map(|u| ("homepage", u)),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if !url.is_empty() && !links.iter().any(|(_, u)| *u == url) {
+                links.push((label, url));
+            }
+        }
+        links
+    }
+
+    /// Label/value pairs for the facts list, skipping anything unknown.
+    fn facts(&self) -> Vec<(&'static str, String)> {
+        let downloads_label = if self.kind == "npm" {
+            "downloads / week"
+        } else {
+            "downloads"
+        };
+        [
+            ("kind", Some(self.kind.clone())),
+            ("version", self.version.as_ref().map(|v| format!("v{v}"))),
+            ("language", self.language.as_ref().map(|l| l.to_lowercase())),
+            ("stars", self.stars.map(format_number)),
+            (downloads_label, self.downloads.map(format_number)),
+            ("commits", self.commit_count.map(format_number)),
+            ("created", self.created_at.clone()),
+            ("updated", self.updated_at.clone()),
+            ("released", self.latest_released_at.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| value.map(|v| (label, v)))
+        .collect()
+    }
+}
+
+#[component]
+pub fn ProjectDetail(project: ProjectDetailData) -> impl IntoView {
+    let links = project.links();
+    let facts = project.facts();
+    let ProjectDetailData {
+        name,
+        description,
+        topics,
+        readme_html,
+        artifacts,
+        releases,
+        contributions,
+        data_as_of,
+        ..
+    } = project;
+
+    let release_count = format!("{:02}", releases.len());
+    let contribution_count = format!("{:02}", contributions.len());
+
+    view! {
+        <article class="project-detail">
+            <header class="project-detail__head">
+                <a class="project-detail__back" href="/projects">"\u{2190} projects"</a>
+                <h1 class="project-detail__name">{name}</h1>
+                {description.filter(|d| !d.is_empty()).map(|d| view! {
+                    <p class="project-detail__desc">{d}</p>
+                })}
+                <DataNotice as_of=data_as_of />
+            </header>
+
+            <ul class="project-detail__links">
+                {links.into_iter().map(|(label, url)| view! {
+                    <li>
+                        <a href=url target="_blank" rel="noopener noreferrer">
+                            {label}" \u{2197}"
+                        </a>
+                    </li>
+                }).collect::<Vec<_>>()}
+            </ul>
+
+            <dl class="project-detail__facts">
+                {facts.into_iter().map(|(label, value)| view! {
+                    <div>
+                        <dt>{label}</dt>
+                        <dd>{value}</dd>
+                    </div>
+                }).collect::<Vec<_>>()}
+            </dl>
+
+            {(!topics.is_empty()).then(|| view! {
+                <ul class="project-detail__topics">
+                    {topics.into_iter().map(|t| {
+                        let href = format!(
+                            "/projects?topic={}",
+                            url::form_urlencoded::byte_serialize(t.as_bytes()).collect::<String>()
+                        );
+                        view! { <li><a href=href>{t}</a></li> }
+                    }).collect::<Vec<_>>()}
+                </ul>
+            })}
+
+            {(artifacts.len() > 1).then(|| artifacts_section(artifacts))}
+
+            {readme_html.map(|html| view! {
+                <section class="section">
+                    <header class="section__head">
+                        <div class="section__title-row">
+                            <h2 class="section__title">"readme"</h2>
+                        </div>
+                    </header>
+                    <div class="readme" inner_html=html></div>
+                </section>
+            })}
+
+            {(!releases.is_empty()).then(|| view! {
+                <section class="section">
+                    <header class="section__head">
+                        <div class="section__title-row">
+                            <h2 class="section__title">"releases"</h2>
+                            <span class="section__count">{release_count}</span>
+                        </div>
+                    </header>
+                    <ul class="release-list">
+                        {releases.into_iter().map(release_row).collect::<Vec<_>>()}
+                    </ul>
+                </section>
+            })}
+
+            {(!contributions.is_empty()).then(|| view! {
+                <section class="section">
+                    <header class="section__head">
+                        <div class="section__title-row">
+                            <h2 class="section__title">"related contributions"</h2>
+                            <span class="section__count">{contribution_count}</span>
+                        </div>
+                        <span class="section__note">"upstream patches to projects with a shared name or topic"</span>
+                    </header>
+                    <ul class="contrib-list">
+                        {contributions.into_iter().map(contribution_row).collect::<Vec<_>>()}
+                    </ul>
+                </section>
+            })}
+        </article>
+    }
+}
+
+fn artifacts_section(artifacts: Vec<ArtifactData>) -> impl IntoView {
+    view! {
+        <section class="section">
+            <header class="section__head">
+                <div class="section__title-row">
+                    <h2 class="section__title">"artifacts"</h2>
+                    <span class="section__count">{format!("{:02}", artifacts.len())}</span>
+                </div>
+                <span class="section__note">"everything published from this project"</span>
+            </header>
+            <ul class="project-detail__artifacts">
+                {artifacts.into_iter().map(artifact_row).collect::<Vec<_>>()}
+            </ul>
+        </section>
+    }
+}
+
+fn artifact_row(artifact: ArtifactData) -> impl IntoView {
+    let place = registry_label(&artifact.kind).unwrap_or("repository");
+    let downloads = artifact.downloads.filter(|d| *d > 0).map(|d| {
+        let unit = if artifact.kind == "npm" {
+            "dl/wk"
+        } else {
+            "dl"
+        };
+        format!("{} {unit}", format_number(d))
+    });
+    let detail: Vec<String> = [
+        artifact.workspace_path.filter(|p| !p.is_empty()),
+        artifact.version.map(|v| format!("v{v}")),
+        downloads,
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    view! {
+        <li>
+            <span class=format!("project-row__badge project-row__badge--{}", artifact.kind)>
+                {place}
+            </span>
+            <a href=artifact.url target="_blank" rel="noopener noreferrer">{artifact.name}</a>
+            <span class="project-detail__artifact-meta">{detail.join("  ·  ")}</span>
+        </li>
+    }
+}
@@ -1,6 +1,9 @@
+use leptos::html::A;
 use leptos::prelude::*;
+use leptos::task::spawn_local;
 
-use super::ProjectCard;
+use super::{ArtifactData, DataNotice, ProjectCard};
+use crate::app::{fetch_projects, ProjectFilters};
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ProjectData {
@@ -14,6 +17,18 @@ pub struct ProjectData {
     pub version: Option<String>,
     pub commit_count: Option<i32>,
     pub updated_at: Option<String>,
+    /// Primary artifact first
+    pub artifacts: Vec<ArtifactData>,
+}
+
+/// One page of projects and the cursor for the next page, if any
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ProjectPageData {
+    pub projects: Vec<ProjectData>,
+    pub next_cursor: Option<String>,
+    /// When the data was last known good, if it comes from a snapshot rather than the database
+    #[serde(default)]
+    pub data_as_of: Option<String>,
 }
 
 struct Group {
@@ -40,62 +55,224 @@ const GROUPS: &[Group] = &[
     },
 ];
 
-#[component]
-pub fn ProjectGrid(projects: Vec<ProjectData>) -> impl IntoView {
-    let sections: Vec<_> = GROUPS
+/// Call `on_visible` whenever `target` scrolls into (or near) view. The observer disconnects
+/// itself once `on_visible` reports there is nothing left to load.
+#[cfg(feature = "hydrate")]
+fn observe_visible(target: &web_sys::Element, on_visible: impl Fn() -> bool + 'static) {
+    use wasm_bindgen::{closure::Closure, JsCast};
+    use web_sys::{IntersectionObserver, IntersectionObserverEntry, IntersectionObserverInit};
+
+    let callback = Closure::<dyn FnMut(js_sys::Array, IntersectionObserver)>::new(
+        move |entries: js_sys::Array, observer: IntersectionObserver| {
+            let visible = entries.iter().any(|e| {
+                e.unchecked_into::<IntersectionObserverEntry>()
+                    .is_intersecting()
+            });
+            if visible && !on_visible() {
+                observer.disconnect();
+            }
+        },
+    )
+    .into_js_value();
+
+    let options = IntersectionObserverInit::new();
+    options.set_root_margin("400px");
+    if let Ok(observer) = IntersectionObserver::new_with_options(callback.unchecked_ref(), &options)
+    {
+        observer.observe(target);
+    }
+}
+
+fn project_card(p: ProjectData) -> impl IntoView {
+    view! {
+        <ProjectCard
+            name=p.name
+            description=p.description
+            url=p.url
+            kind=Some(p.kind)
+            language=p.language
+            popularity=p.popularity
+            version=p.version
+            updated_at=p.updated_at
+            artifacts=p.artifacts
+        />
+    }
+}
+
+/// A project publishing two or more crates from one Cargo workspace gets its usual row and,
+/// below it, the workspace's crates nested in a collapsible list. Other projects are a single
+/// row.
+fn project_entry(mut p: ProjectData) -> AnyView {
+    if p.artifacts
         .iter()
-        .filter_map(|g| {
-            let items: Vec<ProjectData> = projects
+        .filter(|a| a.workspace_path.is_some())
+        .count()
+        < 2
+    {
+        return project_card(p).into_any();
+    }
+
+    // The primary artifact stays with the project's own row, even if it is a member.
+    let (mut own, mut members) = (Vec::new(), Vec::new());
+    for (i, a) in std::mem::take(&mut p.artifacts).into_iter().enumerate() {
+        if i > 0 && a.workspace_path.is_some() {
+            members.push(a);
+        } else {
+            own.push(a);
+        }
+    }
+    let crate_count = members.len() + usize::from(own[0].workspace_path.is_some());
+    p.artifacts = own;
+
+    view! {
+        <li class="project-group">
+            <ul class="project-list">{project_card(p)}</ul>
+            <details>
+                <summary class="project-group__summary">
+                    {format!("workspace  ·  {crate_count} crates")}
+                </summary>
+                <ul class="project-group__list">
+                    {members
+                        .into_iter()
+                        .map(|a| view! {
+                            <ProjectCard
+                                name=a.name
+                                description=None
+                                url=a.url
+                                kind=Some(a.kind)
+                                language=None
+                                popularity=a.downloads.unwrap_or(0)
+                                version=a.version
+                                updated_at=None
+                            />
+                        })
+                        .collect::<Vec<_>>()}
+                </ul>
+            </details>
+        </li>
+    }
+    .into_any()
+}
+
+/// The section for one kind, shown once any loaded project is of that kind.
+fn group_section(g: &'static Group, loaded: RwSignal<Vec<ProjectData>>) -> impl IntoView {
+    let items = Memo::new(move |_| {
+        loaded.with(|projects| {
+            projects
                 .iter()
                 .filter(|p| p.kind == g.kind)
                 .cloned()
-                .collect();
-            if items.is_empty() {
-                None
-            } else {
-                Some((g, items))
+                .collect::<Vec<_>>()
+        })
+    });
+    let shown = Memo::new(move |_| items.with(|i| !i.is_empty()));
+
+    move || {
+        shown.get().then(|| {
+            view! {
+                <section class="section">
+                    <header class="section__head">
+                        <div class="section__title-row">
+                            <h2 class="section__title">{g.title}</h2>
+                            <span class="section__count">
+                                {move || format!("{:02}", items.with(Vec::len))}
+                            </span>
+                        </div>
+                        <span class="section__note">{g.note}</span>
+                    </header>
+                    <ul class="project-list">
+                        <For
+                            each=move || items.get()
+                            key=|p| p.id.clone()
+                            children=project_entry
+                        />
+                    </ul>
+                </section>
             }
         })
-        .collect();
-    drop(projects);
+    }
+}
+
+/// Projects grouped by kind. Further pages are appended in place when the "more" link is
+/// clicked or, once hydrated, scrolls into view; without JS the link opens the next page.
+#[component]
+pub fn ProjectGrid(
+    page: ProjectPageData,
+    /// Filters the page was fetched with, reused for the pages after it
+    filters: ProjectFilters,
+    /// URL of the page after a cursor
+    #[prop(into)]
+    page_href: Callback<String, String>,
+    /// URL of the first page, when this isn't it
+    start_href: Option<String>,
+) -> impl IntoView {
+    let data_as_of = page.data_as_of;
+    let loaded = RwSignal::new(page.projects);
+    let next_cursor = RwSignal::new(page.next_cursor);
+    let loading = RwSignal::new(false);
+    let filters = StoredValue::new(filters);
+    let more_ref = NodeRef::<A>::new();
+
+    // Returns whether there may be more to load. Signals are read with `try_` because the
+    // grid may have been replaced by the time a fetch completes.
+    let load_more = move || {
+        let Some(cursor) = next_cursor.try_get_untracked().flatten() else {
+            return false;
+        };
+        if loading.get_untracked() {
+            return true;
+        }
+        loading.set(true);
+        let filters = ProjectFilters {
+            cursor: Some(cursor),
+            ..filters.get_value()
+        };
+        spawn_local(async move {
+            // On failure the link stays put, so the next click or scroll retries.
+            if let Ok(page) = fetch_projects(filters).await {
+                loaded.try_update(|projects| projects.extend(page.projects));
+                next_cursor.try_set(page.next_cursor);
+            }
+            loading.try_set(false);
+        });
+        true
+    };
+
+    #[cfg(feature = "hydrate")]
+    Effect::new(move |_| {
+        if let Some(link) = more_ref.get() {
+            observe_visible(&link, load_more);
+        }
+    });
+
+    let sections = GROUPS
+        .iter()
+        .map(|g| group_section(g, loaded))
+        .collect::<Vec<_>>();
 
     view! {
-        {sections
-            .into_iter()
-            .map(|(g, items)| {
-                let count = format!("{:02}", items.len());
-                view! {
-                    <section class="section">
-                        <header class="section__head">
-                            <div class="section__title-row">
-                                <h2 class="section__title">{g.title}</h2>
-                                <span class="section__count">{count}</span>
-                            </div>
-                            <span class="section__note">{g.note}</span>
-                        </header>
-                        <ul class="project-list">
-                            {items
-                                .into_iter()
-                                .map(|p| {
-                                    view! {
-                                        <ProjectCard
-                                            name=p.name
-                                            description=p.description
-                                            url=p.url
-                                            kind=Some(p.kind)
-                                            language=p.language
-                                            popularity=p.popularity
-                                            version=p.version
-                                            updated_at=p.updated_at
-                                        />
-                                    }
-                                })
-                                .collect::<Vec<_>>()}
-                        </ul>
-                    </section>
-                }
-            })
-            .collect::<Vec<_>>()}
+        <DataNotice as_of=data_as_of />
+        {sections}
+        <nav class="pager" aria-label="project pages">
+            {start_href.map(|href| view! { <a class="pager__link" href=href>"\u{2190} first page"</a> })}
+            {move || {
+                next_cursor.get().map(|cursor| {
+                    view! {
+                        <a
+                            node_ref=more_ref
+                            class="pager__link pager__link--next"
+                            href=page_href.run(cursor)
+                            on:click=move |ev| {
+                                ev.prevent_default();
+                                load_more();
+                            }
+                        >
+                            {move || if loading.get() { "loading\u{2026}" } else { "more \u{2192}" }}
+                        </a>
+                    }
+                })
+            }}
+        </nav>
     }
 }
 
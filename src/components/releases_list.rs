@@ -0,0 +1,59 @@
+use leptos::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ReleaseData {
+    pub kind: String,
+    pub artifact: String,
+    pub version: String,
+    pub released_at: Option<String>,
+    pub notes_url: Option<String>,
+}
+
+pub(super) fn release_row(r: ReleaseData) -> impl IntoView {
+    let body = view! {
+        <span class="release-row__artifact">{r.artifact}</span>
+        <span class="release-row__version">{r.version}</span>
+        <span class="release-row__kind">{r.kind}</span>
+        <span class="release-row__date">{r.released_at}</span>
+    };
+
+    match r.notes_url {
+        Some(url) => view! {
+            <li class="release-row">
+                <a href=url target="_blank" rel="noopener noreferrer">{body}</a>
+            </li>
+        }
+        .into_any(),
+        None => view! {
+            <li class="release-row">
+                <span class="release-row__inner">{body}</span>
+            </li>
+        }
+        .into_any(),
+    }
+}
+
+#[component]
+pub fn ReleasesSection(releases: Vec<ReleaseData>) -> impl IntoView {
+    if releases.is_empty() {
+        return ().into_any();
+    }
+
+    let count = format!("{:02}", releases.len());
+
+    view! {
+        <section class="section">
+            <header class="section__head">
+                <div class="section__title-row">
+                    <h2 class="section__title">"recent releases"</h2>
+                    <span class="section__count">{count}</span>
+                </div>
+                <span class="section__note">"crates, packages and tagged releases"</span>
+            </header>
+            <ul class="release-list">
+                {releases.into_iter().map(release_row).collect::<Vec<_>>()}
+            </ul>
+        </section>
+    }
+    .into_any()
+}
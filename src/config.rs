@@ -20,6 +20,14 @@ pub struct Config {
     /// Sync configuration
     #[serde(default)]
     pub sync: SyncConfig,
+
+    /// Response cache configuration
+    #[serde(default)]
+    pub cache: CacheConfig,
+
+    /// Last-known-good snapshot configuration
+    #[serde(default)]
+    pub snapshot: SnapshotConfig,
 }
 
 fn default_listen() -> String {
@@ -28,7 +36,7 @@ fn default_listen() -> String {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DatabaseConfig {
-    /// `PostgreSQL` connection URL
+    /// `PostgreSQL` connection URL, or a `sqlite:` one to keep everything in a local file.
     pub url: String,
 }
 
@@ -55,6 +63,53 @@ impl Default for OtelConfig {
     }
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CacheConfig {
+    /// Most responses kept at once (0 disables the cache)
+    #[serde(default = "default_cache_entries")]
+    pub entries: usize,
+
+    /// Seconds a response is kept if no sync clears it first
+    #[serde(default = "default_cache_ttl")]
+    pub ttl: u64,
+}
+
+fn default_cache_entries() -> usize {
+    1000
+}
+
+fn default_cache_ttl() -> u64 {
+    300
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            entries: default_cache_entries(),
+            ttl: default_cache_ttl(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SnapshotConfig {
+    /// File the snapshot served while the database is unavailable is kept in
+    #[serde(default = "default_snapshot_path")]
+    pub path: String,
+}
+
+fn default_snapshot_path() -> String {
+    "snapshot.json".to_string()
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            path: default_snapshot_path(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SyncConfig {
     /// Enable background sync
@@ -65,6 +120,11 @@ pub struct SyncConfig {
     #[serde(default = "default_interval")]
     pub interval_secs: u64,
 
+    /// Workers claiming sync jobs, when the database is `PostgreSQL` (0 leaves the jobs to
+    /// other instances)
+    #[serde(default = "default_workers")]
+    pub workers: usize,
+
     /// GitHub sync configuration
     pub github: Option<GitHubConfig>,
 
@@ -89,11 +149,16 @@ fn default_interval() -> u64 {
     3600
 }
 
+fn default_workers() -> usize {
+    4
+}
+
 impl Default for SyncConfig {
     fn default() -> Self {
         Self {
             enabled: true,
             interval_secs: 3600,
+            workers: default_workers(),
             github: None,
             crates_io: None,
             npm: None,
@@ -154,11 +219,15 @@ impl Config {
     /// - `DJV_OTEL_ENVIRONMENT`
     /// - `DJV_SYNC_ENABLED`
     /// - `DJV_SYNC_INTERVAL_SECS`
+    /// - `DJV_SYNC_WORKERS`
     /// - `DJV_SYNC_GITHUB_USER`
     /// - `DJV_SYNC_GITHUB_TOKEN`
     /// - `DJV_SYNC_CRATES_IO_USER`
     /// - `DJV_SYNC_NPM_USER`
     /// - `DJV_SYNC_CONTRIBUTIONS_USER`
+    /// - `DJV_CACHE_ENTRIES`
+    /// - `DJV_CACHE_TTL`
+    /// - `DJV_SNAPSHOT_PATH`
     ///
     /// # Errors
     /// Returns a boxed [`figment::Error`] if env-var parsing fails (e.g. a malformed value).
@@ -230,6 +299,8 @@ struct ConfigDefaults {
     listen: String,
     otel: OtelConfig,
     sync: SyncConfig,
+    cache: CacheConfig,
+    snapshot: SnapshotConfig,
 }
 
 impl Default for ConfigDefaults {
@@ -238,6 +309,8 @@ impl Default for ConfigDefaults {
             listen: default_listen(),
             otel: OtelConfig::default(),
             sync: SyncConfig::default(),
+            cache: CacheConfig::default(),
+            snapshot: SnapshotConfig::default(),
         }
     }
 }
@@ -252,5 +325,9 @@ mod tests {
         assert_eq!(config.listen, "127.0.0.1:3000");
         assert!(config.sync.enabled);
         assert_eq!(config.sync.interval_secs, 3600);
+        assert_eq!(config.sync.workers, 4);
+        assert_eq!(config.cache.entries, 1000);
+        assert_eq!(config.cache.ttl, 300);
+        assert_eq!(config.snapshot.path, "snapshot.json");
     }
 }
@@ -0,0 +1,165 @@
+use std::fmt;
+
+use url::Url;
+
+/// Hosts whose repositories always live at `/{owner}/{name}`, so anything after that
+/// (`/tree/main`, `/issues`, ...) can be dropped.
+const TWO_SEGMENT_HOSTS: [&str; 3] = ["github.com", "codeberg.org", "bitbucket.org"];
+
+/// A repository URL in the one form repositories, crates and npm packages are matched on:
+/// `https://{host}/{path}`, lowercased, without `www.`, credentials, a `.git` suffix,
+/// trailing slashes, a query or a fragment.
+///
+/// Registries hand back whatever the author typed: `git+https://…/x.git`, `git@host:x/y`,
+/// `github:x/y`, `http://`, mixed case. All of them canonicalise to the URL the forge reports.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CanonicalUrl(String);
+
+impl CanonicalUrl {
+    /// Canonicalise a repository URL, or `None` if it doesn't name a repository on a host.
+    #[must_use]
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        let raw = raw.strip_prefix("git+").unwrap_or(raw);
+        let expanded = expand_shorthand(raw);
+        let url = Url::parse(expanded.as_deref().unwrap_or(raw)).ok()?;
+        if !matches!(url.scheme(), "https" | "http" | "git" | "ssh") {
+            return None;
+        }
+
+        let host = url.host_str()?.to_lowercase();
+        let host = host.strip_prefix("www.").unwrap_or(&host);
+        let mut segments: Vec<&str> = url
+            .path_segments()?
+            .take_while(|s| *s != "-")
+            .filter(|s| !s.is_empty())
+            .collect();
+        if TWO_SEGMENT_HOSTS.contains(&host) {
+            segments.truncate(2);
+        }
+        if let Some(last) = segments.last_mut() {
+            *last = last.strip_suffix(".git").unwrap_or(last);
+        }
+        if segments.len() < 2 || segments.iter().any(|s| s.is_empty()) {
+            return None;
+        }
+
+        let port = match (url.scheme(), url.port()) {
+            ("http" | "https", Some(port)) => format!(":{port}"),
+            _ => String::new(),
+        };
+        Some(Self(
+            format!("https://{host}{port}/{}", segments.join("/")).to_lowercase(),
+        ))
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CanonicalUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<CanonicalUrl> for String {
+    fn from(url: CanonicalUrl) -> Self {
+        url.0
+    }
+}
+
+/// Rewrite npm's `github:x/y` shorthands and scp-style `git@host:x/y` into full URLs.
+fn expand_shorthand(raw: &str) -> Option<String> {
+    for (prefix, host) in [
+        ("github:", "github.com"),
+        ("gitlab:", "gitlab.com"),
+        ("bitbucket:", "bitbucket.org"),
+    ] {
+        if let Some(path) = raw.strip_prefix(prefix) {
+            return Some(format!("https://{host}/{path}"));
+        }
+    }
+    if raw.contains("://") {
+        return None;
+    }
+    let (user_host, path) = raw.split_once(':')?;
+    let host = user_host
+        .rsplit_once('@')
+        .map_or(user_host, |(_, host)| host);
+    Some(format!("ssh://{host}/{}", path.trim_start_matches('/')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(raw: &str) -> Option<String> {
+        CanonicalUrl::parse(raw).map(String::from)
+    }
+
+    #[test]
+    fn registry_spellings_match_the_forge_url() {
+        for raw in [
+            "https://github.com/User/Repo",
+            "https://github.com/user/repo/",
+            "https://github.com/user/repo.git",
+            "http://github.com/user/repo",
+            "git+https://github.com/user/repo.git",
+            "git://github.com/user/repo.git",
+            "git+ssh://git@github.com/user/repo.git",
+            "git@github.com:user/repo.git",
+            "github:user/repo",
+            "https://www.github.com/user/repo",
+            "https://github.com/user/repo/tree/main/crates/core",
+            "https://github.com/user/repo#readme",
+            "  https://github.com/user/repo?tab=readme  ",
+        ] {
+            assert_eq!(
+                canonical(raw).as_deref(),
+                Some("https://github.com/user/repo"),
+                "{raw}"
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_nested_gitlab_groups() {
+        assert_eq!(
+            canonical("https://gitlab.com/Group/Sub/Project.git").as_deref(),
+            Some("https://gitlab.com/group/sub/project")
+        );
+        assert_eq!(
+            canonical("https://gitlab.com/group/sub/project/-/tree/main").as_deref(),
+            Some("https://gitlab.com/group/sub/project")
+        );
+        assert_eq!(
+            canonical("gitlab:group/project").as_deref(),
+            Some("https://gitlab.com/group/project")
+        );
+    }
+
+    #[test]
+    fn keeps_non_default_ports() {
+        assert_eq!(
+            canonical("https://git.example.com:8443/user/repo").as_deref(),
+            Some("https://git.example.com:8443/user/repo")
+        );
+    }
+
+    #[test]
+    fn rejects_urls_that_name_no_repository() {
+        for raw in [
+            "",
+            "not a url",
+            "https://github.com/user",
+            "https://example.com",
+            "mailto:someone@example.com",
+            "file:///home/user/repo",
+        ] {
+            assert_eq!(canonical(raw), None, "{raw}");
+        }
+    }
+}
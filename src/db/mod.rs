@@ -1,3 +1,4 @@
+pub mod canonical_url;
 pub mod models;
 pub mod queries;
 
@@ -8,6 +9,7 @@ use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 use std::time::Duration;
 
+pub use canonical_url::CanonicalUrl;
 pub use models::*;
 pub use queries::*;
 
@@ -61,9 +61,207 @@ pub struct Contribution {
     pub title: Option<String>,
     pub url: String,
     pub merged_at: Option<DateTime<Utc>>,
+    pub additions: Option<i32>,
+    pub deletions: Option<i32>,
+    pub changed_files: Option<i32>,
+    pub labels: Vec<String>,
+    /// Star count of the upstream repository, if its metadata has been synced
+    #[sqlx(default)]
+    pub upstream_stars: Option<i32>,
+    /// Primary language of the upstream repository, if known
+    #[sqlx(default)]
+    pub upstream_language: Option<String>,
     pub synced_at: DateTime<Utc>,
 }
 
+/// An external repository that contributions were made to
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UpstreamRepo {
+    pub id: Uuid,
+    pub forge: String,
+    pub owner: String,
+    pub name: String,
+    pub url: String,
+    pub description: Option<String>,
+    pub language: Option<String>,
+    pub stars: i32,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// A published version of a crate, npm package or repository
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Release {
+    pub id: Uuid,
+    /// `crate`, `npm` or `repo`
+    pub artifact_kind: String,
+    /// Crate or package name, or `owner/name` for repositories
+    pub artifact: String,
+    pub version: String,
+    pub released_at: Option<DateTime<Utc>>,
+    pub yanked: bool,
+    pub notes_url: Option<String>,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// A `sync_jobs` row claimed by a worker
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ClaimedSyncJob {
+    pub id: i64,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    /// Including the one just claimed
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub created_at: DateTime<Utc>,
+    /// When the lease runs out. A reclaim moves it, so it tells this claim from a later one.
+    pub locked_until: DateTime<Utc>,
+}
+
+/// Sort order for contributions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContributionSort {
+    /// Most recently merged first
+    #[default]
+    Recent,
+    /// Largest change to the most popular upstream first
+    Impact,
+}
+
+impl ContributionSort {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContributionSort::Recent => "recent",
+            ContributionSort::Impact => "impact",
+        }
+    }
+}
+
+impl std::str::FromStr for ContributionSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "recent" => Ok(ContributionSort::Recent),
+            "impact" => Ok(ContributionSort::Impact),
+            _ => Err(format!("unknown contribution sort: {s}")),
+        }
+    }
+}
+
+/// Filters for paging through contributions
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ContributionFilters {
+    pub forge: Option<String>,
+    /// Owner of the upstream repository (case-insensitive)
+    pub owner: Option<String>,
+    /// Calendar year the contribution was merged in
+    pub year: Option<i32>,
+    pub contribution_type: Option<String>,
+    /// Resume after this position in the (merged date, id) ordering
+    pub cursor: Option<ContributionCursor>,
+}
+
+/// Keyset position in the contributions list.
+///
+/// Serialised as `<merged_at micros>.<id>` so it can travel in a URL query string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContributionCursor {
+    pub merged_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl std::fmt::Display for ContributionCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.merged_at.timestamp_micros(), self.id)
+    }
+}
+
+impl std::str::FromStr for ContributionCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (micros, id) = s
+            .split_once('.')
+            .ok_or_else(|| format!("malformed cursor: {s}"))?;
+        let merged_at = micros
+            .parse::<i64>()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(|| format!("malformed cursor timestamp: {s}"))?;
+        let id = id
+            .parse::<Uuid>()
+            .map_err(|e| format!("malformed cursor id: {e}"))?;
+
+        Ok(Self { merged_at, id })
+    }
+}
+
+/// One page of contributions, plus the cursor for the next page if there is one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContributionPage {
+    pub contributions: Vec<Contribution>,
+    pub next_cursor: Option<ContributionCursor>,
+}
+
+/// Number of contributions sharing some key (an upstream owner, a year, a month)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContributionCount {
+    pub key: String,
+    pub count: i64,
+}
+
+/// Activity against a single upstream repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoActivity {
+    pub forge: String,
+    pub owner: String,
+    pub name: String,
+    pub url: String,
+    pub count: i64,
+    pub first_merged_at: Option<DateTime<Utc>>,
+    pub latest_merged_at: Option<DateTime<Utc>>,
+}
+
+/// Aggregate statistics over all contributions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContributionStats {
+    /// Most contributed-to owners first
+    pub by_owner: Vec<ContributionCount>,
+    /// Oldest year first, keyed as `YYYY`
+    pub by_year: Vec<ContributionCount>,
+    /// Oldest month first, keyed as `YYYY-MM`, including empty months
+    pub by_month: Vec<ContributionCount>,
+    /// Every distinct repository touched, most recently active first
+    pub repos: Vec<RepoActivity>,
+}
+
+/// Number of projects carrying one value of a facet (a kind, a language, a topic)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: i64,
+}
+
+/// Every distinct kind, language and topic, each counted against the other active filters.
+/// Values no matching project carries are kept with a count of zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectFacets {
+    pub kinds: Vec<FacetCount>,
+    pub languages: Vec<FacetCount>,
+    pub topics: Vec<FacetCount>,
+}
+
+/// Freshness of one sync source, derived from the rows it last wrote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceSyncStatus {
+    /// `github`, `gitlab`, `crates.io`, `npm`, `contributions` or `releases`
+    pub source: String,
+    pub items: i64,
+    pub last_synced_at: Option<DateTime<Utc>>,
+}
+
 /// The kind of project in the unified view
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -96,7 +294,24 @@ impl std::str::FromStr for ProjectKind {
     }
 }
 
-/// A unified project view for UI display
+/// One crate, npm package or repository making up a project
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectArtifact {
+    pub kind: ProjectKind,
+    pub name: String,
+    /// The crates.io or npm page, or the repository itself
+    pub url: String,
+    pub version: Option<String>,
+    /// All-time for crates, weekly for npm packages, none for repositories
+    pub downloads: Option<i32>,
+    /// Directory of a crate within its repository's Cargo workspace, if it is a member
+    #[serde(default)]
+    pub workspace_path: Option<String>,
+}
+
+/// A project: a repository together with the crates and npm packages built from it, or a
+/// crate or package without a repository. Its name, kind and URL are those of its primary
+/// artifact.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectView {
     pub id: Uuid,
@@ -106,51 +321,390 @@ pub struct ProjectView {
     pub url: String,
     pub language: Option<String>,
     pub topics: Vec<String>,
+    /// Downloads summed over artifacts of the primary's kind, or stars for a repository
     pub popularity: i32,
     pub version: Option<String>,
     pub commit_count: Option<i32>,
     pub updated_at: Option<DateTime<Utc>>,
     pub synced_at: DateTime<Utc>,
+    /// When the project first appeared in a sync
+    pub first_seen_at: DateTime<Utc>,
+    /// Primary artifact first
+    pub artifacts: Vec<ProjectArtifact>,
+}
+
+/// Everything shown on a project's own page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectDetail {
+    pub project: ProjectView,
+    pub created_at: Option<DateTime<Utc>>,
+    pub latest_released_at: Option<DateTime<Utc>>,
+    /// Stars on the project's repository
+    pub stars: Option<i32>,
+    /// Summed over the project's crates (all-time) and npm packages (weekly)
+    pub downloads: Option<i32>,
+    pub documentation_url: Option<String>,
+    pub homepage_url: Option<String>,
+    /// The repository the project is built from; the project itself for repositories
+    pub repository_url: Option<String>,
+    pub repository_forge: Option<String>,
+    /// The repository's README, rendered and sanitised at sync time
+    pub readme_html: Option<String>,
 }
 
+/// Largest page of projects a client may ask for
+pub const MAX_PROJECT_LIMIT: i32 = 100;
+
 /// Filters for querying projects
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct ProjectFilters {
-    pub kind: Option<ProjectKind>,
-    pub language: Option<String>,
-    pub topic: Option<String>,
+    /// Projects with an artifact of any of these kinds (empty = every kind)
+    pub kinds: Vec<ProjectKind>,
+    /// Match any of these languages, case-insensitively (empty = every language)
+    pub languages: Vec<String>,
+    /// Topics a project must carry, combined according to `topic_match`
+    pub topics: Vec<String>,
+    pub topic_match: TopicMatch,
+    /// Topics a project must not carry
+    pub exclude_topics: Vec<String>,
+    /// Full-text search over name, description, topics and keywords
+    pub q: Option<String>,
+    /// Defaults to relevance when searching and popularity otherwise
     pub sort: Option<SortOrder>,
+    /// Defaults to the sort's own direction
+    pub direction: Option<SortDirection>,
     /// Maximum number of results to return (None = no limit)
     pub limit: Option<i32>,
+    /// Continue after this position; ignored unless it belongs to the effective sort order
+    /// and direction
+    pub cursor: Option<ProjectCursor>,
+}
+
+impl ProjectFilters {
+    /// The sort order in effect: `sort` if given, otherwise relevance when there is a search
+    /// and popularity when there isn't.
+    #[must_use]
+    pub fn effective_sort(&self) -> SortOrder {
+        let searching = self.q.as_deref().is_some_and(|q| !q.trim().is_empty());
+        match self.sort {
+            Some(sort) => sort,
+            None if searching => SortOrder::Relevance,
+            None => SortOrder::Popularity,
+        }
+    }
+
+    /// The direction in effect: `direction` if given, otherwise the effective sort's default.
+    #[must_use]
+    pub fn effective_direction(&self) -> SortDirection {
+        self.direction
+            .unwrap_or_else(|| self.effective_sort().default_direction())
+    }
+
+    /// The same filters in one canonical form, so requests that select the same page compare
+    /// equal: lists sorted and deduplicated, languages lowercased, a blank search dropped,
+    /// the sort and direction made explicit, and a cursor for another sort discarded.
+    #[must_use]
+    pub fn normalised(&self) -> Self {
+        fn sorted(mut values: Vec<String>) -> Vec<String> {
+            values.sort();
+            values.dedup();
+            values
+        }
+
+        let sort = self.effective_sort();
+        let direction = self.effective_direction();
+        let mut kinds = self.kinds.clone();
+        kinds.sort_by_key(ToString::to_string);
+        kinds.dedup();
+
+        Self {
+            kinds,
+            languages: sorted(self.languages.iter().map(|l| l.to_lowercase()).collect()),
+            topics: sorted(self.topics.clone()),
+            topic_match: self.topic_match,
+            exclude_topics: sorted(self.exclude_topics.clone()),
+            q: self
+                .q
+                .as_deref()
+                .map(str::trim)
+                .filter(|q| !q.is_empty())
+                .map(str::to_owned),
+            sort: Some(sort),
+            direction: Some(direction),
+            limit: self.limit,
+            cursor: self
+                .cursor
+                .clone()
+                .filter(|c| c.sort == sort && c.direction == direction),
+        }
+    }
+
+    /// Split topic terms as written in a URL, where a leading `-` marks an exclusion, into
+    /// `topics` and `exclude_topics`.
+    #[must_use]
+    pub fn with_topic_terms(mut self, terms: impl IntoIterator<Item = String>) -> Self {
+        for term in terms {
+            match term.strip_prefix('-') {
+                Some("") => {}
+                Some(excluded) => self.exclude_topics.push(excluded.to_owned()),
+                None if term.is_empty() => {}
+                None => self.topics.push(term),
+            }
+        }
+        self
+    }
+}
+
+/// How several required topics combine
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TopicMatch {
+    /// Every topic must be present
+    #[default]
+    All,
+    /// At least one topic must be present
+    Any,
+}
+
+impl std::str::FromStr for TopicMatch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "all" => Ok(TopicMatch::All),
+            "any" => Ok(TopicMatch::Any),
+            _ => Err(format!("unknown topic match: {s}")),
+        }
+    }
 }
 
 /// Sort order for projects
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SortOrder {
+    /// Composite score of stars and downloads
     #[default]
     Popularity,
     Name,
+    /// When the repository last changed upstream
     Updated,
+    /// When the project was created upstream
+    Created,
+    /// Date of the latest non-yanked release
+    Released,
+    Stars,
+    Downloads,
+    /// Best full-text match first; popularity when there is no search
+    Relevance,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 8] = [
+        SortOrder::Popularity,
+        SortOrder::Name,
+        SortOrder::Updated,
+        SortOrder::Created,
+        SortOrder::Released,
+        SortOrder::Stars,
+        SortOrder::Downloads,
+        SortOrder::Relevance,
+    ];
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortOrder::Popularity => "popularity",
+            SortOrder::Name => "name",
+            SortOrder::Updated => "updated",
+            SortOrder::Created => "created",
+            SortOrder::Released => "released",
+            SortOrder::Stars => "stars",
+            SortOrder::Downloads => "downloads",
+            SortOrder::Relevance => "relevance",
+        }
+    }
+
+    /// Names read A to Z; everything else is most or newest first.
+    #[must_use]
+    pub fn default_direction(self) -> SortDirection {
+        match self {
+            SortOrder::Name => SortDirection::Asc,
+            _ => SortDirection::Desc,
+        }
+    }
 }
 
 impl std::str::FromStr for SortOrder {
     type Err = String;
 
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        SortOrder::ALL
+            .into_iter()
+            .find(|sort| sort.as_str() == s)
+            .ok_or_else(|| format!("unknown sort order: {s}"))
+    }
+}
+
+/// Direction of a project sort
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortDirection::Asc => "asc",
+            SortDirection::Desc => "desc",
+        }
+    }
+}
+
+impl std::str::FromStr for SortDirection {
+    type Err = String;
+
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "popularity" => Ok(SortOrder::Popularity),
-            "name" => Ok(SortOrder::Name),
-            "updated" => Ok(SortOrder::Updated),
-            _ => Err(format!("unknown sort order: {s}")),
+            "asc" => Ok(SortDirection::Asc),
+            "desc" => Ok(SortDirection::Desc),
+            _ => Err(format!("unknown sort direction: {s}")),
+        }
+    }
+}
+
+/// Sort key of the last project on a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CursorKey {
+    /// Name sorts compare text
+    Text(String),
+    /// Every other sort compares one number, or rank then score for relevance
+    Numbers(Vec<f64>),
+}
+
+/// Keyset position in the project list: the sort key of the last project on a page, with
+/// its id to break ties. A cursor only continues the sort and direction it was taken from.
+///
+/// Serialised as `<sort>.<direction>.<id>.<key>` so it can travel in a URL query string.
+/// Names go last because they may contain dots; numbers travel as the hex bit patterns of
+/// their `f64`s, dot-separated, so they compare exactly against what `PostgreSQL` computes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectCursor {
+    pub sort: SortOrder,
+    pub direction: SortDirection,
+    pub key: CursorKey,
+    pub id: Uuid,
+}
+
+impl std::fmt::Display for ProjectCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.",
+            self.sort.as_str(),
+            self.direction.as_str(),
+            self.id
+        )?;
+        match &self.key {
+            CursorKey::Text(text) => f.write_str(text),
+            CursorKey::Numbers(numbers) => {
+                let bits: Vec<_> = numbers
+                    .iter()
+                    .map(|n| format!("{:x}", n.to_bits()))
+                    .collect();
+                f.write_str(&bits.join("."))
+            }
         }
     }
 }
 
+impl std::str::FromStr for ProjectCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || format!("malformed cursor: {s}");
+        let mut parts = s.splitn(4, '.');
+        let mut next = || parts.next().ok_or_else(malformed);
+
+        let sort = next()?.parse::<SortOrder>()?;
+        let direction = next()?.parse::<SortDirection>()?;
+        let id = next()?
+            .parse::<Uuid>()
+            .map_err(|e| format!("malformed cursor id: {e}"))?;
+        let key = next()?;
+
+        let key = if sort == SortOrder::Name {
+            CursorKey::Text(key.to_owned())
+        } else {
+            let numbers = key
+                .split('.')
+                .map(|bits| {
+                    u64::from_str_radix(bits, 16)
+                        .map(f64::from_bits)
+                        .ok()
+                        .filter(|n| !n.is_nan())
+                        .ok_or_else(malformed)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let expected = if sort == SortOrder::Relevance { 2 } else { 1 };
+            if numbers.len() != expected {
+                return Err(malformed());
+            }
+            CursorKey::Numbers(numbers)
+        };
+
+        Ok(Self {
+            sort,
+            direction,
+            key,
+            id,
+        })
+    }
+}
+
+/// One page of projects, plus the cursor for the next page if there is one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectPage {
+    pub projects: Vec<ProjectView>,
+    pub next_cursor: Option<ProjectCursor>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn normalised_filters_compare_equal() {
+        let a = ProjectFilters {
+            kinds: vec![ProjectKind::Npm, ProjectKind::Crate, ProjectKind::Npm],
+            languages: vec!["Rust".to_owned(), "nix".to_owned()],
+            topics: vec!["web".to_owned(), "cli".to_owned()],
+            q: Some("  ".to_owned()),
+            ..ProjectFilters::default()
+        };
+        let b = ProjectFilters {
+            kinds: vec![ProjectKind::Crate, ProjectKind::Npm],
+            languages: vec!["nix".to_owned(), "rust".to_owned(), "RUST".to_owned()],
+            topics: vec!["cli".to_owned(), "web".to_owned()],
+            sort: Some(SortOrder::Popularity),
+            direction: Some(SortDirection::Desc),
+            ..ProjectFilters::default()
+        };
+
+        assert_eq!(a.normalised(), b.normalised());
+        assert_ne!(
+            a.normalised(),
+            ProjectFilters {
+                sort: Some(SortOrder::Name),
+                ..a.clone()
+            }
+            .normalised()
+        );
+    }
+
     #[test]
     fn project_kind_from_str_valid() {
         assert_eq!("crate".parse::<ProjectKind>().unwrap(), ProjectKind::Crate);
@@ -195,6 +749,13 @@ mod tests {
         );
         assert_eq!("name".parse::<SortOrder>().unwrap(), SortOrder::Name);
         assert_eq!("updated".parse::<SortOrder>().unwrap(), SortOrder::Updated);
+        assert_eq!(
+            "relevance".parse::<SortOrder>().unwrap(),
+            SortOrder::Relevance
+        );
+        for sort in SortOrder::ALL {
+            assert_eq!(sort.as_str().parse::<SortOrder>().unwrap(), sort);
+        }
     }
 
     #[test]
@@ -228,19 +789,154 @@ mod tests {
 
     #[test]
     fn sort_order_serde_roundtrip() {
-        for order in [SortOrder::Popularity, SortOrder::Name, SortOrder::Updated] {
+        for order in SortOrder::ALL {
             let json = serde_json::to_string(&order).unwrap();
             let parsed: SortOrder = serde_json::from_str(&json).unwrap();
             assert_eq!(order, parsed);
         }
     }
 
+    #[test]
+    fn topic_terms_split_exclusions() {
+        let filters = ProjectFilters::default().with_topic_terms(
+            ["tracing", "-archived", "opentelemetry", "-", ""]
+                .into_iter()
+                .map(String::from),
+        );
+        assert_eq!(filters.topics, ["tracing", "opentelemetry"]);
+        assert_eq!(filters.exclude_topics, ["archived"]);
+    }
+
+    #[test]
+    fn topic_match_from_str() {
+        assert_eq!("any".parse::<TopicMatch>().unwrap(), TopicMatch::Any);
+        assert_eq!("ALL".parse::<TopicMatch>().unwrap(), TopicMatch::All);
+        assert_eq!(TopicMatch::default(), TopicMatch::All);
+        assert!("some".parse::<TopicMatch>().is_err());
+    }
+
+    #[test]
+    fn contribution_sort_from_str() {
+        assert_eq!(
+            "recent".parse::<ContributionSort>().unwrap(),
+            ContributionSort::Recent
+        );
+        assert_eq!(
+            "IMPACT".parse::<ContributionSort>().unwrap(),
+            ContributionSort::Impact
+        );
+        assert!("stars".parse::<ContributionSort>().is_err());
+    }
+
+    #[test]
+    fn contribution_sort_roundtrip() {
+        for sort in [ContributionSort::Recent, ContributionSort::Impact] {
+            let parsed: ContributionSort = sort.as_str().parse().unwrap();
+            assert_eq!(sort, parsed);
+        }
+    }
+
+    #[test]
+    fn contribution_cursor_roundtrip() {
+        let cursor = ContributionCursor {
+            merged_at: DateTime::from_timestamp_micros(1_714_563_600_123_456).unwrap(),
+            id: Uuid::new_v4(),
+        };
+        let parsed: ContributionCursor = cursor.to_string().parse().unwrap();
+        assert_eq!(cursor, parsed);
+    }
+
+    #[test]
+    fn project_cursor_round_trip() {
+        let id = Uuid::new_v4();
+        let cursor = |sort, direction, key| ProjectCursor {
+            sort,
+            direction,
+            key,
+            id,
+        };
+        for cursor in [
+            cursor(
+                SortOrder::Name,
+                SortDirection::Asc,
+                CursorKey::Text("tracing.opentelemetry".to_owned()),
+            ),
+            cursor(
+                SortOrder::Stars,
+                SortDirection::Desc,
+                CursorKey::Numbers(vec![-3.0]),
+            ),
+            cursor(
+                SortOrder::Created,
+                SortDirection::Asc,
+                CursorKey::Numbers(vec![f64::INFINITY]),
+            ),
+            cursor(
+                SortOrder::Relevance,
+                SortDirection::Desc,
+                CursorKey::Numbers(vec![0.060_792_7, 9.415_3]),
+            ),
+        ] {
+            let parsed: ProjectCursor = cursor.to_string().parse().unwrap();
+            assert_eq!(cursor, parsed);
+        }
+    }
+
+    #[test]
+    fn project_cursor_rejects_garbage() {
+        let id = Uuid::new_v4();
+        let one = format!("{:x}", 1.0_f64.to_bits());
+        for garbage in [
+            String::new(),
+            "42".to_owned(),
+            format!("stars.desc.{id}"),
+            format!("stars.down.{id}.{one}"),
+            format!("forks.desc.{id}.{one}"),
+            format!("stars.desc.not-a-uuid.{one}"),
+            format!("stars.desc.{id}.many"),
+            format!("stars.desc.{id}.{one}.{one}"),
+            format!("relevance.desc.{id}.{one}"),
+            format!("stars.desc.{id}.7ff8000000000000"),
+        ] {
+            assert!(garbage.parse::<ProjectCursor>().is_err(), "{garbage}");
+        }
+    }
+
+    #[test]
+    fn sort_directions_default_per_sort() {
+        assert_eq!(SortOrder::Name.default_direction(), SortDirection::Asc);
+        assert_eq!(SortOrder::Released.default_direction(), SortDirection::Desc);
+        assert_eq!("ASC".parse::<SortDirection>().unwrap(), SortDirection::Asc);
+        assert!("up".parse::<SortDirection>().is_err());
+
+        let filters = ProjectFilters {
+            sort: Some(SortOrder::Name),
+            ..Default::default()
+        };
+        assert_eq!(filters.effective_direction(), SortDirection::Asc);
+        let filters = ProjectFilters {
+            direction: Some(SortDirection::Desc),
+            ..filters
+        };
+        assert_eq!(filters.effective_direction(), SortDirection::Desc);
+    }
+
+    #[test]
+    fn contribution_cursor_rejects_garbage() {
+        assert!("".parse::<ContributionCursor>().is_err());
+        assert!("123".parse::<ContributionCursor>().is_err());
+        assert!("abc.def".parse::<ContributionCursor>().is_err());
+        assert!("123.not-a-uuid".parse::<ContributionCursor>().is_err());
+    }
+
     #[test]
     fn project_filters_default() {
         let filters = ProjectFilters::default();
-        assert!(filters.kind.is_none());
-        assert!(filters.language.is_none());
-        assert!(filters.topic.is_none());
+        assert!(filters.kinds.is_empty());
+        assert!(filters.languages.is_empty());
+        assert!(filters.topics.is_empty());
+        assert_eq!(filters.topic_match, TopicMatch::All);
+        assert!(filters.exclude_topics.is_empty());
         assert!(filters.sort.is_none());
     }
 }
@@ -1,8 +1,16 @@
 use chrono::{DateTime, Utc};
-use sqlx::PgPool;
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
-use super::models::{Contribution, ProjectFilters, ProjectKind, ProjectView, SortOrder};
+use super::canonical_url::CanonicalUrl;
+use super::models::{
+    ClaimedSyncJob, Contribution, ContributionCount, ContributionCursor, ContributionFilters,
+    ContributionPage, ContributionSort, ContributionStats, Crate, CursorKey, FacetCount,
+    NpmPackage, ProjectArtifact, ProjectCursor, ProjectDetail, ProjectFacets, ProjectFilters,
+    ProjectKind, ProjectPage, ProjectView, Release, RepoActivity, Repository, SortDirection,
+    SortOrder, SourceSyncStatus, TopicMatch,
+};
 
 pub struct NewRepository<'a> {
     pub forge: &'a str,
@@ -10,9 +18,11 @@ pub struct NewRepository<'a> {
     pub name: &'a str,
     pub description: Option<&'a str>,
     pub url: &'a str,
+    pub homepage_url: Option<&'a str>,
     pub language: Option<&'a str>,
     pub stars: i32,
     pub topics: &'a [String],
+    pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
 
@@ -20,12 +30,16 @@ pub struct NewCrate<'a> {
     pub name: &'a str,
     pub description: Option<&'a str>,
     pub repository_id: Option<Uuid>,
+    /// The repository the crate declares, kept so it can be linked once that repository syncs
+    pub repository_url: Option<&'a CanonicalUrl>,
     pub crates_io_url: &'a str,
     pub documentation_url: Option<&'a str>,
+    pub homepage_url: Option<&'a str>,
     pub downloads: i32,
     pub version: Option<&'a str>,
     pub keywords: &'a [String],
     pub categories: &'a [String],
+    pub created_at: Option<DateTime<Utc>>,
 }
 
 pub struct NewNpmPackage<'a> {
@@ -33,12 +47,21 @@ pub struct NewNpmPackage<'a> {
     pub scope: Option<&'a str>,
     pub description: Option<&'a str>,
     pub repository_id: Option<Uuid>,
+    /// The repository the package declares, kept so it can be linked once that repository syncs
+    pub repository_url: Option<&'a CanonicalUrl>,
     pub npm_url: &'a str,
+    pub homepage_url: Option<&'a str>,
     pub downloads_weekly: i32,
     pub version: Option<&'a str>,
     pub keywords: &'a [String],
 }
 
+pub struct NewWorkspaceMember<'a> {
+    pub crate_name: &'a str,
+    /// Directory of the crate within the repository, empty for a root package
+    pub path: &'a str,
+}
+
 pub struct NewContribution<'a> {
     pub forge: &'a str,
     pub repo_owner: &'a str,
@@ -48,23 +71,57 @@ pub struct NewContribution<'a> {
     pub title: Option<&'a str>,
     pub url: &'a str,
     pub merged_at: Option<DateTime<Utc>>,
+    pub additions: Option<i32>,
+    pub deletions: Option<i32>,
+    pub changed_files: Option<i32>,
+    pub labels: &'a [String],
+}
+
+pub struct NewUpstreamRepo<'a> {
+    pub forge: &'a str,
+    pub owner: &'a str,
+    pub name: &'a str,
+    pub url: &'a str,
+    pub description: Option<&'a str>,
+    pub language: Option<&'a str>,
+    pub stars: i32,
+}
+
+pub struct NewRelease<'a> {
+    pub artifact_kind: &'a str,
+    pub artifact: &'a str,
+    pub version: &'a str,
+    pub released_at: Option<DateTime<Utc>>,
+    pub yanked: bool,
+    pub notes_url: Option<&'a str>,
+}
+
+pub struct NewReadme<'a> {
+    pub repository_id: Uuid,
+    pub content_hash: &'a str,
+    /// Rendered and sanitised HTML
+    pub html: &'a str,
 }
 
 /// # Errors
 /// Returns any `PostgreSQL` error from the insert/update.
 pub async fn upsert_repository(pool: &PgPool, r: &NewRepository<'_>) -> Result<Uuid, sqlx::Error> {
+    let canonical_url = CanonicalUrl::parse(r.url);
     let id = sqlx::query_scalar!(
         r#"
-        INSERT INTO repositories (forge, forge_id, name, description, url, language, stars, topics, updated_at, synced_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, now())
+        INSERT INTO repositories (forge, forge_id, name, description, url, language, stars, topics, created_at, updated_at, homepage_url, canonical_url, synced_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, now())
         ON CONFLICT (forge, forge_id) DO UPDATE SET
             name = EXCLUDED.name,
             description = EXCLUDED.description,
             url = EXCLUDED.url,
+            canonical_url = EXCLUDED.canonical_url,
             language = EXCLUDED.language,
             stars = EXCLUDED.stars,
             topics = EXCLUDED.topics,
+            created_at = EXCLUDED.created_at,
             updated_at = EXCLUDED.updated_at,
+            homepage_url = EXCLUDED.homepage_url,
             synced_at = now()
         RETURNING id
         "#,
@@ -76,7 +133,10 @@ pub async fn upsert_repository(pool: &PgPool, r: &NewRepository<'_>) -> Result<U
         r.language,
         r.stars,
         r.topics,
+        r.created_at,
         r.updated_at,
+        r.homepage_url,
+        canonical_url.as_ref().map(CanonicalUrl::as_str),
     )
     .fetch_one(pool)
     .await?;
@@ -86,10 +146,16 @@ pub async fn upsert_repository(pool: &PgPool, r: &NewRepository<'_>) -> Result<U
 
 /// # Errors
 /// Returns any `PostgreSQL` error from the underlying query.
-pub async fn get_repository_by_url(pool: &PgPool, url: &str) -> Result<Option<Uuid>, sqlx::Error> {
-    let id = sqlx::query_scalar!("SELECT id FROM repositories WHERE url = $1", url)
-        .fetch_optional(pool)
-        .await?;
+pub async fn get_repository_by_url(
+    pool: &PgPool,
+    url: &CanonicalUrl,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let id = sqlx::query_scalar!(
+        "SELECT id FROM repositories WHERE canonical_url = $1 LIMIT 1",
+        url.as_str()
+    )
+    .fetch_optional(pool)
+    .await?;
 
     Ok(id)
 }
@@ -115,21 +181,327 @@ pub async fn delete_stale_repositories(
     Ok(result.rows_affected())
 }
 
+/// Repository ids keyed by canonical URL, for those of `urls` that have synced.
+///
 /// # Errors
 /// Returns any `PostgreSQL` error from the underlying query.
 pub async fn get_repositories_by_urls(
     pool: &PgPool,
-    urls: &[String],
+    urls: &[CanonicalUrl],
 ) -> Result<std::collections::HashMap<String, Uuid>, sqlx::Error> {
     if urls.is_empty() {
         return Ok(std::collections::HashMap::new());
     }
 
-    let rows = sqlx::query!("SELECT url, id FROM repositories WHERE url = ANY($1)", urls)
-        .fetch_all(pool)
+    let urls: Vec<&str> = urls.iter().map(CanonicalUrl::as_str).collect();
+    let rows = sqlx::query!(
+        r#"SELECT canonical_url as "canonical_url!", id FROM repositories WHERE canonical_url = ANY($1)"#,
+        &urls as &[&str]
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.canonical_url, r.id)).collect())
+}
+
+/// Channel on which a finished sync run is announced, so every instance drops its cached
+/// responses.
+pub const SYNC_CHANNEL: &str = "djv_sync";
+
+/// Record a finished sync run, with the error that stopped it if any, and announce it on
+/// [`SYNC_CHANNEL`]. The notification is only delivered once the row is committed, so
+/// listeners never see a run that [`get_last_sync_at`] doesn't.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the insert or the notify.
+pub async fn record_sync_run(pool: &PgPool, error: Option<&str>) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        WITH run AS (
+            INSERT INTO sync_runs (error) VALUES ($1) RETURNING id
+        )
+        SELECT pg_notify($2, id::TEXT) FROM run
+        "#,
+        error,
+        SYNC_CHANNEL,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// When the most recent sync run finished, or `None` if none has.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_last_sync_at(pool: &PgPool) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    sqlx::query_scalar!("SELECT MAX(finished_at) FROM sync_runs")
+        .fetch_one(pool)
+        .await
+}
+
+/// Whether the most recent sync run finished without an error, or `None` if none has.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_last_sync_succeeded(pool: &PgPool) -> Result<Option<bool>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT error IS NULL AS "succeeded!" FROM sync_runs ORDER BY finished_at DESC, id DESC LIMIT 1"#
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// A job to add to `sync_jobs`
+pub struct NewSyncJob<'a> {
+    pub kind: &'a str,
+    pub payload: serde_json::Value,
+    pub max_attempts: i32,
+}
+
+/// Queue the jobs that start a sync run, unless the previous run still has jobs queued or
+/// running. Returns whether they were queued.
+///
+/// Runs on `conn` rather than the pool so the leader can queue on the session holding its
+/// lock, which fails rather than queuing once that session is gone.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the insert.
+pub async fn start_sync_run(
+    conn: &mut PgConnection,
+    jobs: &[NewSyncJob<'_>],
+) -> Result<bool, sqlx::Error> {
+    let (kinds, payloads, max_attempts) = sync_job_columns(jobs);
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO sync_jobs (kind, payload, max_attempts)
+        SELECT * FROM UNNEST($1::TEXT[], $2::JSONB[], $3::INTEGER[])
+        WHERE NOT EXISTS (SELECT 1 FROM sync_jobs WHERE status IN ('queued', 'running'))
+        "#,
+        &kinds as &[&str],
+        &payloads,
+        &max_attempts,
+    )
+    .execute(conn)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Queue jobs outside of any run, as [`complete_sync_job`] queues those a job finds.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the insert.
+pub async fn enqueue_sync_jobs(pool: &PgPool, jobs: &[NewSyncJob<'_>]) -> Result<(), sqlx::Error> {
+    let (kinds, payloads, max_attempts) = sync_job_columns(jobs);
+    sqlx::query!(
+        r#"
+        INSERT INTO sync_jobs (kind, payload, max_attempts)
+        SELECT * FROM UNNEST($1::TEXT[], $2::JSONB[], $3::INTEGER[])
+        "#,
+        &kinds as &[&str],
+        &payloads,
+        &max_attempts,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn sync_job_columns<'a>(
+    jobs: &[NewSyncJob<'a>],
+) -> (Vec<&'a str>, Vec<serde_json::Value>, Vec<i32>) {
+    let kinds = jobs.iter().map(|j| j.kind).collect();
+    let payloads = jobs.iter().map(|j| j.payload.clone()).collect();
+    let max_attempts = jobs.iter().map(|j| j.max_attempts).collect();
+    (kinds, payloads, max_attempts)
+}
+
+/// Claim the next due job for `lease`, counting an attempt. A running job whose lease has
+/// expired is due again, as its worker is presumed dead. A `finish` job is only due once no
+/// other job is queued or running.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the update.
+pub async fn claim_sync_job(
+    pool: &PgPool,
+    lease: std::time::Duration,
+) -> Result<Option<ClaimedSyncJob>, sqlx::Error> {
+    sqlx::query_as!(
+        ClaimedSyncJob,
+        r#"
+        UPDATE sync_jobs
+        SET status = 'running',
+            attempts = attempts + 1,
+            locked_until = now() + make_interval(secs => $1)
+        WHERE id = (
+            SELECT id FROM sync_jobs
+            WHERE ((status = 'queued' AND run_at <= now())
+                   OR (status = 'running' AND locked_until < now()))
+              AND (kind <> 'finish' OR NOT EXISTS (
+                  SELECT 1 FROM sync_jobs
+                  WHERE kind <> 'finish' AND status IN ('queued', 'running')
+              ))
+            ORDER BY run_at, id
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, kind, payload, attempts, max_attempts, created_at,
+            locked_until AS "locked_until!"
+        "#,
+        lease.as_secs_f64(),
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Mark a claimed job done and queue the jobs it found, together, so a job that's retried
+/// never queues them twice. Returns `false`, changing nothing, if the claim is no longer
+/// held because the lease ran out and another worker claimed the job.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the update or insert.
+pub async fn complete_sync_job(
+    pool: &PgPool,
+    job: &ClaimedSyncJob,
+    found: &[NewSyncJob<'_>],
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let completed = sqlx::query!(
+        r#"
+        UPDATE sync_jobs
+        SET status = 'done', finished_at = now(), locked_until = NULL, last_error = NULL
+        WHERE id = $1 AND status = 'running' AND locked_until = $2
+        "#,
+        job.id,
+        job.locked_until,
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    if !completed {
+        return Ok(false);
+    }
+
+    let (kinds, payloads, max_attempts) = sync_job_columns(found);
+    sqlx::query!(
+        r#"
+        INSERT INTO sync_jobs (kind, payload, max_attempts)
+        SELECT * FROM UNNEST($1::TEXT[], $2::JSONB[], $3::INTEGER[])
+        "#,
+        &kinds as &[&str],
+        &payloads,
+        &max_attempts,
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Record why a claimed job failed, and queue it to run again after `retry_in`, or mark it
+/// dead if `retry_in` is `None`. Returns `false`, changing nothing, if the claim is no
+/// longer held.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the update.
+pub async fn fail_sync_job(
+    pool: &PgPool,
+    job: &ClaimedSyncJob,
+    error: &str,
+    retry_in: Option<std::time::Duration>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE sync_jobs
+        SET status = CASE WHEN $4::FLOAT8 IS NULL THEN 'dead' ELSE 'queued' END,
+            run_at = now() + make_interval(secs => COALESCE($4, 0)),
+            finished_at = CASE WHEN $4 IS NULL THEN now() END,
+            locked_until = NULL,
+            last_error = $3
+        WHERE id = $1 AND status = 'running' AND locked_until = $2
+        "#,
+        job.id,
+        job.locked_until,
+        error,
+        retry_in.map(|d| d.as_secs_f64()),
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// How many jobs queued since `since` ran out of attempts.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn count_dead_sync_jobs(pool: &PgPool, since: DateTime<Utc>) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM sync_jobs WHERE status = 'dead' AND created_at >= $1"#,
+        since,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Delete jobs, done or dead, that finished before `before`. Returns how many were deleted.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the delete.
+pub async fn delete_finished_sync_jobs(
+    pool: &PgPool,
+    before: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        "DELETE FROM sync_jobs WHERE status IN ('done', 'dead') AND finished_at < $1",
+        before,
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Rebuild the materialised `projects` view from the artifact tables. Readers keep seeing the
+/// previous rows until it finishes.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the refresh.
+pub async fn refresh_projects(pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query!("REFRESH MATERIALIZED VIEW CONCURRENTLY projects")
+        .execute(pool)
         .await?;
+    Ok(())
+}
+
+/// Link crates and npm packages to the repository their declared URL names, for those
+/// synced before it was. Returns how many were linked.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the updates.
+pub async fn relink_artifacts(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let crates = sqlx::query!(
+        r#"
+        UPDATE crates c SET repository_id = r.id
+        FROM repositories r
+        WHERE c.repository_id IS NULL AND r.canonical_url = c.repository_url
+        "#
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    let packages = sqlx::query!(
+        r#"
+        UPDATE npm_packages n SET repository_id = r.id
+        FROM repositories r
+        WHERE n.repository_id IS NULL AND r.canonical_url = n.repository_url
+        "#
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
 
-    Ok(rows.into_iter().map(|r| (r.url, r.id)).collect())
+    Ok(crates + packages)
 }
 
 /// # Errors
@@ -137,17 +509,20 @@ pub async fn get_repositories_by_urls(
 pub async fn upsert_crate(pool: &PgPool, c: &NewCrate<'_>) -> Result<Uuid, sqlx::Error> {
     let id = sqlx::query_scalar!(
         r#"
-        INSERT INTO crates (name, description, repository_id, crates_io_url, documentation_url, downloads, version, keywords, categories, synced_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, now())
+        INSERT INTO crates (name, description, repository_id, crates_io_url, documentation_url, downloads, version, keywords, categories, created_at, homepage_url, repository_url, synced_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, now())
         ON CONFLICT (name) DO UPDATE SET
             description = EXCLUDED.description,
             repository_id = EXCLUDED.repository_id,
+            repository_url = EXCLUDED.repository_url,
             crates_io_url = EXCLUDED.crates_io_url,
             documentation_url = EXCLUDED.documentation_url,
             downloads = EXCLUDED.downloads,
             version = EXCLUDED.version,
             keywords = EXCLUDED.keywords,
             categories = EXCLUDED.categories,
+            created_at = EXCLUDED.created_at,
+            homepage_url = EXCLUDED.homepage_url,
             synced_at = now()
         RETURNING id
         "#,
@@ -160,6 +535,9 @@ pub async fn upsert_crate(pool: &PgPool, c: &NewCrate<'_>) -> Result<Uuid, sqlx:
         c.version,
         c.keywords,
         c.categories,
+        c.created_at,
+        c.homepage_url,
+        c.repository_url.map(CanonicalUrl::as_str),
     )
     .fetch_one(pool)
     .await?;
@@ -172,16 +550,18 @@ pub async fn upsert_crate(pool: &PgPool, c: &NewCrate<'_>) -> Result<Uuid, sqlx:
 pub async fn upsert_npm_package(pool: &PgPool, p: &NewNpmPackage<'_>) -> Result<Uuid, sqlx::Error> {
     let id = sqlx::query_scalar!(
         r#"
-        INSERT INTO npm_packages (name, scope, description, repository_id, npm_url, downloads_weekly, version, keywords, synced_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, now())
+        INSERT INTO npm_packages (name, scope, description, repository_id, npm_url, downloads_weekly, version, keywords, homepage_url, repository_url, synced_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, now())
         ON CONFLICT (name) DO UPDATE SET
             scope = EXCLUDED.scope,
             description = EXCLUDED.description,
             repository_id = EXCLUDED.repository_id,
+            repository_url = EXCLUDED.repository_url,
             npm_url = EXCLUDED.npm_url,
             downloads_weekly = EXCLUDED.downloads_weekly,
             version = EXCLUDED.version,
             keywords = EXCLUDED.keywords,
+            homepage_url = EXCLUDED.homepage_url,
             synced_at = now()
         RETURNING id
         "#,
@@ -193,6 +573,8 @@ pub async fn upsert_npm_package(pool: &PgPool, p: &NewNpmPackage<'_>) -> Result<
         p.downloads_weekly,
         p.version,
         p.keywords,
+        p.homepage_url,
+        p.repository_url.map(CanonicalUrl::as_str),
     )
     .fetch_one(pool)
     .await?;
@@ -208,13 +590,17 @@ pub async fn upsert_contribution(
 ) -> Result<Uuid, sqlx::Error> {
     let id = sqlx::query_scalar!(
         r#"
-        INSERT INTO contributions (forge, repo_owner, repo_name, repo_url, contribution_type, title, url, merged_at, synced_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, now())
+        INSERT INTO contributions (forge, repo_owner, repo_name, repo_url, contribution_type, title, url, merged_at, additions, deletions, changed_files, labels, synced_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, now())
         ON CONFLICT (forge, repo_owner, repo_name, url) DO UPDATE SET
             repo_url = EXCLUDED.repo_url,
             contribution_type = EXCLUDED.contribution_type,
             title = EXCLUDED.title,
             merged_at = EXCLUDED.merged_at,
+            additions = EXCLUDED.additions,
+            deletions = EXCLUDED.deletions,
+            changed_files = EXCLUDED.changed_files,
+            labels = EXCLUDED.labels,
             synced_at = now()
         RETURNING id
         "#,
@@ -226,6 +612,10 @@ pub async fn upsert_contribution(
         c.title,
         c.url,
         c.merged_at,
+        c.additions,
+        c.deletions,
+        c.changed_files,
+        c.labels,
     )
     .fetch_one(pool)
     .await?;
@@ -234,193 +624,990 @@ pub async fn upsert_contribution(
 }
 
 /// # Errors
-/// Returns any `PostgreSQL` error from the underlying query.
-pub async fn get_contributions(
+/// Returns any `PostgreSQL` error from the insert/update.
+pub async fn upsert_upstream_repo(
     pool: &PgPool,
-    limit: i64,
-    max_age_years: i32,
-) -> Result<Vec<Contribution>, sqlx::Error> {
-    let max_age = f64::from(max_age_years);
-    let rows = sqlx::query!(
+    r: &NewUpstreamRepo<'_>,
+) -> Result<Uuid, sqlx::Error> {
+    let id = sqlx::query_scalar!(
         r#"
-        SELECT id, forge, repo_owner, repo_name, repo_url, contribution_type, title, url, merged_at, synced_at
-        FROM contributions
-        WHERE merged_at IS NULL OR merged_at > NOW() - INTERVAL '1 year' * $2
-        ORDER BY merged_at DESC NULLS LAST
-        LIMIT $1
+        INSERT INTO upstream_repos (forge, owner, name, url, description, language, stars, synced_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, now())
+        ON CONFLICT (forge, owner, name) DO UPDATE SET
+            url = EXCLUDED.url,
+            description = EXCLUDED.description,
+            language = EXCLUDED.language,
+            stars = EXCLUDED.stars,
+            synced_at = now()
+        RETURNING id
         "#,
-        limit,
-        max_age,
+        r.forge,
+        r.owner,
+        r.name,
+        r.url,
+        r.description,
+        r.language,
+        r.stars,
     )
-    .fetch_all(pool)
+    .fetch_one(pool)
     .await?;
 
-    Ok(rows
-        .into_iter()
-        .map(|row| Contribution {
-            id: row.id,
-            forge: row.forge,
-            repo_owner: row.repo_owner,
-            repo_name: row.repo_name,
-            repo_url: row.repo_url,
-            contribution_type: row.contribution_type,
-            title: row.title,
-            url: row.url,
-            merged_at: row.merged_at,
-            synced_at: row.synced_at,
-        })
-        .collect())
+    Ok(id)
 }
 
-struct ProjectRow {
-    id: Uuid,
-    kind: String,
-    name: String,
-    description: Option<String>,
-    url: String,
-    language: Option<String>,
-    topics: Option<Vec<String>>,
-    popularity: Option<i32>,
-    version: Option<String>,
-    commit_count: Option<i32>,
-    updated_at: Option<DateTime<Utc>>,
-    synced_at: DateTime<Utc>,
-}
+/// # Errors
+/// Returns any `PostgreSQL` error from the insert/update.
+pub async fn upsert_release(pool: &PgPool, r: &NewRelease<'_>) -> Result<Uuid, sqlx::Error> {
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO releases (artifact_kind, artifact, version, released_at, yanked, notes_url, synced_at)
+        VALUES ($1, $2, $3, $4, $5, $6, now())
+        ON CONFLICT (artifact_kind, artifact, version) DO UPDATE SET
+            released_at = EXCLUDED.released_at,
+            yanked = EXCLUDED.yanked,
+            notes_url = EXCLUDED.notes_url,
+            synced_at = now()
+        RETURNING id
+        "#,
+        r.artifact_kind,
+        r.artifact,
+        r.version,
+        r.released_at,
+        r.yanked,
+        r.notes_url,
+    )
+    .fetch_one(pool)
+    .await?;
 
-impl From<ProjectRow> for ProjectView {
-    fn from(row: ProjectRow) -> Self {
-        Self {
-            id: row.id,
-            kind: row.kind.parse().unwrap_or(ProjectKind::Repo),
-            name: row.name,
-            description: row.description,
-            url: row.url,
-            language: row.language,
-            topics: row.topics.unwrap_or_default(),
-            popularity: row.popularity.unwrap_or(0),
-            version: row.version,
-            commit_count: row.commit_count,
-            updated_at: row.updated_at,
-            synced_at: row.synced_at,
-        }
-    }
+    Ok(id)
 }
 
-async fn query_projects_by_popularity(
+/// Hash of the README stored for a repository, if it has one.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_readme_hash(
     pool: &PgPool,
-    kind_filter: Option<String>,
-    language_filter: Option<String>,
-    topic_filter: Option<String>,
-    limit: i64,
-) -> Result<Vec<ProjectRow>, sqlx::Error> {
-    sqlx::query_as!(
-        ProjectRow,
-        r#"
-        SELECT
-            id as "id!", kind as "kind!", name as "name!", description, url as "url!",
-            language, topics, popularity, version, commit_count, updated_at,
-            synced_at as "synced_at!"
-        FROM projects
-        WHERE ($1::TEXT IS NULL OR kind = $1)
-          AND ($2::TEXT IS NULL OR LOWER(language) = LOWER($2))
-          AND ($3::TEXT IS NULL OR $3 = ANY(topics))
-        ORDER BY popularity DESC NULLS LAST
-        LIMIT $4
-        "#,
-        kind_filter,
-        language_filter,
-        topic_filter,
-        limit,
+    repository_id: Uuid,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT content_hash FROM readmes WHERE repository_id = $1",
+        repository_id,
     )
-    .fetch_all(pool)
+    .fetch_optional(pool)
     .await
 }
 
-async fn query_projects_by_name(
-    pool: &PgPool,
-    kind_filter: Option<String>,
-    language_filter: Option<String>,
-    topic_filter: Option<String>,
-    limit: i64,
-) -> Result<Vec<ProjectRow>, sqlx::Error> {
-    sqlx::query_as!(
-        ProjectRow,
+/// # Errors
+/// Returns any `PostgreSQL` error from the insert/update.
+pub async fn upsert_readme(pool: &PgPool, r: &NewReadme<'_>) -> Result<(), sqlx::Error> {
+    sqlx::query!(
         r#"
-        SELECT
-            id as "id!", kind as "kind!", name as "name!", description, url as "url!",
-            language, topics, popularity, version, commit_count, updated_at,
-            synced_at as "synced_at!"
-        FROM projects
-        WHERE ($1::TEXT IS NULL OR kind = $1)
-          AND ($2::TEXT IS NULL OR LOWER(language) = LOWER($2))
-          AND ($3::TEXT IS NULL OR $3 = ANY(topics))
-        ORDER BY name ASC
-        LIMIT $4
+        INSERT INTO readmes (repository_id, content_hash, html, synced_at)
+        VALUES ($1, $2, $3, now())
+        ON CONFLICT (repository_id) DO UPDATE SET
+            content_hash = EXCLUDED.content_hash,
+            html = EXCLUDED.html,
+            synced_at = now()
         "#,
-        kind_filter,
-        language_filter,
-        topic_filter,
-        limit,
+        r.repository_id,
+        r.content_hash,
+        r.html,
     )
-    .fetch_all(pool)
-    .await
+    .execute(pool)
+    .await?;
+
+    Ok(())
 }
 
-async fn query_projects_by_updated(
-    pool: &PgPool,
-    kind_filter: Option<String>,
-    language_filter: Option<String>,
-    topic_filter: Option<String>,
-    limit: i64,
-) -> Result<Vec<ProjectRow>, sqlx::Error> {
+/// The repository's `pushed_at` when its workspace was last read, if it has been.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_workspace_pushed_at(
+    pool: &PgPool,
+    repository_id: Uuid,
+) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    let pushed_at = sqlx::query_scalar!(
+        "SELECT workspace_pushed_at FROM repositories WHERE id = $1",
+        repository_id,
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(pushed_at.flatten())
+}
+
+/// Replace the crates listed in a repository's Cargo workspace, read as of its `pushed_at`.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the delete or insert.
+pub async fn set_workspace_members(
+    pool: &PgPool,
+    repository_id: Uuid,
+    pushed_at: Option<DateTime<Utc>>,
+    members: &[NewWorkspaceMember<'_>],
+) -> Result<(), sqlx::Error> {
+    let (names, paths): (Vec<&str>, Vec<&str>) =
+        members.iter().map(|m| (m.crate_name, m.path)).unzip();
+
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        "DELETE FROM workspace_members WHERE repository_id = $1",
+        repository_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO workspace_members (repository_id, crate_name, path)
+        SELECT $1, crate_name, path FROM UNNEST($2::TEXT[], $3::TEXT[]) AS m(crate_name, path)
+        ON CONFLICT DO NOTHING
+        "#,
+        repository_id,
+        &names as &[&str],
+        &paths as &[&str],
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "UPDATE repositories SET workspace_pushed_at = $2 WHERE id = $1",
+        repository_id,
+        pushed_at,
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await
+}
+
+/// Remove a repository's README, returning whether there was one.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the delete.
+pub async fn delete_readme(pool: &PgPool, repository_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "DELETE FROM readmes WHERE repository_id = $1",
+        repository_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Most recent non-yanked releases, newest first, optionally limited to one artifact kind.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_recent_releases(
+    pool: &PgPool,
+    artifact_kind: Option<&str>,
+    limit: i64,
+) -> Result<Vec<Release>, sqlx::Error> {
     sqlx::query_as!(
-        ProjectRow,
+        Release,
+        r#"
+        SELECT id, artifact_kind, artifact, version, released_at, yanked, notes_url, synced_at
+        FROM releases
+        WHERE released_at IS NOT NULL AND NOT yanked
+          AND ($1::TEXT IS NULL OR artifact_kind = $1)
+        ORDER BY released_at DESC, artifact ASC
+        LIMIT $2
+        "#,
+        artifact_kind,
+        limit,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Every release of a single artifact, yanked ones included, newest first.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_releases_for(
+    pool: &PgPool,
+    artifact_kind: &str,
+    artifact: &str,
+) -> Result<Vec<Release>, sqlx::Error> {
+    sqlx::query_as!(
+        Release,
+        r#"
+        SELECT id, artifact_kind, artifact, version, released_at, yanked, notes_url, synced_at
+        FROM releases
+        WHERE artifact_kind = $1 AND artifact = $2
+        ORDER BY released_at DESC NULLS LAST, version DESC
+        "#,
+        artifact_kind,
+        artifact,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Impact weighs the size of a change by the reach of the repository it landed in, using the
+/// log of upstream stars so a handful of very popular repositories don't drown out everything else.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_contributions(
+    pool: &PgPool,
+    limit: i64,
+    max_age_years: i32,
+    sort: ContributionSort,
+) -> Result<Vec<Contribution>, sqlx::Error> {
+    let max_age = f64::from(max_age_years);
+    sqlx::query_as!(
+        Contribution,
         r#"
         SELECT
-            id as "id!", kind as "kind!", name as "name!", description, url as "url!",
-            language, topics, popularity, version, commit_count, updated_at,
-            synced_at as "synced_at!"
-        FROM projects
-        WHERE ($1::TEXT IS NULL OR kind = $1)
-          AND ($2::TEXT IS NULL OR LOWER(language) = LOWER($2))
-          AND ($3::TEXT IS NULL OR $3 = ANY(topics))
-        ORDER BY synced_at DESC
-        LIMIT $4
+            c.id, c.forge, c.repo_owner, c.repo_name, c.repo_url, c.contribution_type, c.title,
+            c.url, c.merged_at, c.additions, c.deletions, c.changed_files, c.labels,
+            u.stars as "upstream_stars?", u.language as "upstream_language?", c.synced_at
+        FROM contributions c
+        LEFT JOIN upstream_repos u
+            ON u.forge = c.forge AND u.owner = c.repo_owner AND u.name = c.repo_name
+        WHERE c.merged_at IS NULL OR c.merged_at > NOW() - INTERVAL '1 year' * $2
+        ORDER BY
+            CASE WHEN $3 = 'impact' THEN
+                (COALESCE(c.additions, 0) + COALESCE(c.deletions, 0))
+                    * LN(COALESCE(u.stars, 0) + 2)
+            END DESC NULLS LAST,
+            c.merged_at DESC NULLS LAST
+        LIMIT $1
         "#,
-        kind_filter,
-        language_filter,
-        topic_filter,
         limit,
+        max_age,
+        sort.as_str(),
     )
     .fetch_all(pool)
     .await
 }
 
+/// Fetch one page of contributions, newest first, using keyset pagination on
+/// `(merged_at, id)`. Unmerged contributions sort as if merged at the Unix epoch.
+///
 /// # Errors
 /// Returns any `PostgreSQL` error from the underlying query.
-pub async fn get_projects(
+pub async fn get_contribution_page(
     pool: &PgPool,
-    filters: &ProjectFilters,
-) -> Result<Vec<ProjectView>, sqlx::Error> {
-    let kind_filter = filters.kind.map(|k| k.to_string());
-    let language_filter = filters.language.clone();
-    let topic_filter = filters.topic.clone();
-    // 1000 is a de-facto "no limit" for our dataset size; the SQL always needs a LIMIT.
-    let limit = i64::from(filters.limit.unwrap_or(1000));
-
-    let rows = match filters.sort.unwrap_or_default() {
-        SortOrder::Popularity => {
-            query_projects_by_popularity(pool, kind_filter, language_filter, topic_filter, limit)
-                .await?
+    filters: &ContributionFilters,
+    limit: i64,
+) -> Result<ContributionPage, sqlx::Error> {
+    let cursor_merged_at = filters.cursor.map(|c| c.merged_at);
+    let cursor_id = filters.cursor.map(|c| c.id);
+
+    // Fetch one extra row to find out whether another page follows.
+    let mut contributions = sqlx::query_as!(
+        Contribution,
+        r#"
+        SELECT
+            c.id, c.forge, c.repo_owner, c.repo_name, c.repo_url, c.contribution_type, c.title,
+            c.url, c.merged_at, c.additions, c.deletions, c.changed_files, c.labels,
+            u.stars as "upstream_stars?", u.language as "upstream_language?", c.synced_at
+        FROM contributions c
+        LEFT JOIN upstream_repos u
+            ON u.forge = c.forge AND u.owner = c.repo_owner AND u.name = c.repo_name
+        WHERE ($1::TEXT IS NULL OR c.forge = $1)
+          AND ($2::TEXT IS NULL OR LOWER(c.repo_owner) = LOWER($2))
+          AND ($3::INT IS NULL OR EXTRACT(YEAR FROM c.merged_at)::INT = $3)
+          AND ($4::TEXT IS NULL OR c.contribution_type = $4)
+          AND ($5::TIMESTAMPTZ IS NULL
+               OR (COALESCE(c.merged_at, 'epoch'::TIMESTAMPTZ), c.id) < ($5, $6::UUID))
+        ORDER BY COALESCE(c.merged_at, 'epoch'::TIMESTAMPTZ) DESC, c.id DESC
+        LIMIT $7
+        "#,
+        filters.forge,
+        filters.owner,
+        filters.year,
+        filters.contribution_type,
+        cursor_merged_at,
+        cursor_id,
+        limit + 1,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let page_len = usize::try_from(limit).unwrap_or(0);
+    let has_more = contributions.len() > page_len;
+    contributions.truncate(page_len);
+
+    let next_cursor = if has_more {
+        contributions.last().map(|c| ContributionCursor {
+            merged_at: c.merged_at.unwrap_or(DateTime::<Utc>::UNIX_EPOCH),
+            id: c.id,
+        })
+    } else {
+        None
+    };
+
+    Ok(ContributionPage {
+        contributions,
+        next_cursor,
+    })
+}
+
+/// Every contribution, newest first, as [`get_contribution_page`] orders them.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_all_contributions(pool: &PgPool) -> Result<Vec<Contribution>, sqlx::Error> {
+    sqlx::query_as!(
+        Contribution,
+        r#"
+        SELECT
+            c.id, c.forge, c.repo_owner, c.repo_name, c.repo_url, c.contribution_type, c.title,
+            c.url, c.merged_at, c.additions, c.deletions, c.changed_files, c.labels,
+            u.stars as "upstream_stars?", u.language as "upstream_language?", c.synced_at
+        FROM contributions c
+        LEFT JOIN upstream_repos u
+            ON u.forge = c.forge AND u.owner = c.repo_owner AND u.name = c.repo_name
+        ORDER BY COALESCE(c.merged_at, 'epoch'::TIMESTAMPTZ) DESC, c.id DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Distinct years with at least one merged contribution, newest first.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_contribution_years(pool: &PgPool) -> Result<Vec<i32>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT EXTRACT(YEAR FROM merged_at)::INT as "year!"
+        FROM contributions
+        WHERE merged_at IS NOT NULL
+        ORDER BY 1 DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Distinct upstream owners, most contributed-to first.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_contribution_owners(pool: &PgPool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT MIN(repo_owner COLLATE "C") AS "repo_owner!"
+        FROM contributions
+        GROUP BY LOWER(repo_owner)
+        ORDER BY COUNT(*) DESC, 1 ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Aggregate contribution counts per owner, year and month, plus per-repository activity.
+/// Unmerged contributions count towards owners and repositories but not towards any period.
+/// Months with no merged contributions between the first and latest are included as zero.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying queries.
+pub async fn get_contribution_stats(pool: &PgPool) -> Result<ContributionStats, sqlx::Error> {
+    let by_owner = sqlx::query_as!(
+        ContributionCount,
+        r#"
+        SELECT repo_owner as "key!", COUNT(*) as "count!"
+        FROM contributions
+        GROUP BY repo_owner
+        ORDER BY 2 DESC, 1 ASC
+        "#
+    )
+    .fetch_all(pool);
+
+    let by_year = sqlx::query_as!(
+        ContributionCount,
+        r#"
+        SELECT TO_CHAR(merged_at, 'YYYY') as "key!", COUNT(*) as "count!"
+        FROM contributions
+        WHERE merged_at IS NOT NULL
+        GROUP BY 1
+        ORDER BY 1 ASC
+        "#
+    )
+    .fetch_all(pool);
+
+    let by_month = sqlx::query_as!(
+        ContributionCount,
+        r#"
+        WITH monthly AS (
+            SELECT DATE_TRUNC('month', merged_at) as month, COUNT(*) as count
+            FROM contributions
+            WHERE merged_at IS NOT NULL
+            GROUP BY 1
+        )
+        SELECT TO_CHAR(s.month, 'YYYY-MM') as "key!", COALESCE(m.count, 0) as "count!"
+        FROM GENERATE_SERIES(
+            (SELECT MIN(month) FROM monthly),
+            (SELECT MAX(month) FROM monthly),
+            INTERVAL '1 month'
+        ) as s(month)
+        LEFT JOIN monthly m ON m.month = s.month
+        ORDER BY s.month ASC
+        "#
+    )
+    .fetch_all(pool);
+
+    let repos = sqlx::query_as!(
+        RepoActivity,
+        r#"
+        SELECT
+            forge, repo_owner as owner, repo_name as name, MIN(repo_url) as "url!",
+            COUNT(*) as "count!",
+            MIN(merged_at) as first_merged_at, MAX(merged_at) as latest_merged_at
+        FROM contributions
+        GROUP BY forge, repo_owner, repo_name
+        ORDER BY MAX(merged_at) DESC NULLS LAST, repo_owner ASC, repo_name ASC
+        "#
+    )
+    .fetch_all(pool);
+
+    let (by_owner, by_year, by_month, repos) =
+        tokio::try_join!(by_owner, by_year, by_month, repos)?;
+
+    Ok(ContributionStats {
+        by_owner,
+        by_year,
+        by_month,
+        repos,
+    })
+}
+
+#[derive(sqlx::FromRow)]
+struct ProjectRow {
+    id: Uuid,
+    kind: String,
+    name: String,
+    description: Option<String>,
+    url: String,
+    language: Option<String>,
+    topics: Option<Vec<String>>,
+    popularity: Option<i32>,
+    version: Option<String>,
+    commit_count: Option<i32>,
+    updated_at: Option<DateTime<Utc>>,
+    synced_at: DateTime<Utc>,
+    first_seen_at: DateTime<Utc>,
+    artifacts: Json<Vec<ProjectArtifact>>,
+}
+
+impl From<ProjectRow> for ProjectView {
+    fn from(row: ProjectRow) -> Self {
+        Self {
+            id: row.id,
+            kind: row.kind.parse().unwrap_or(ProjectKind::Repo),
+            name: row.name,
+            description: row.description,
+            url: row.url,
+            language: row.language,
+            topics: row.topics.unwrap_or_default(),
+            popularity: row.popularity.unwrap_or(0),
+            version: row.version,
+            commit_count: row.commit_count,
+            updated_at: row.updated_at,
+            synced_at: row.synced_at,
+            first_seen_at: row.first_seen_at,
+            artifacts: row.artifacts.0,
         }
-        SortOrder::Name => {
-            query_projects_by_name(pool, kind_filter, language_filter, topic_filter, limit).await?
+    }
+}
+
+/// A project with the sort key it was ordered by
+#[derive(sqlx::FromRow)]
+struct SortedProjectRow {
+    #[sqlx(flatten)]
+    project: ProjectRow,
+    /// Empty when sorting by name
+    sort_numbers: Vec<f64>,
+    /// Empty unless sorting by name
+    sort_text: String,
+}
+
+impl SortedProjectRow {
+    /// Cursor continuing `sort` in `direction` after this row.
+    fn cursor(&self, sort: SortOrder, direction: SortDirection) -> ProjectCursor {
+        let key = match sort {
+            SortOrder::Name => CursorKey::Text(self.sort_text.clone()),
+            _ => CursorKey::Numbers(self.sort_numbers.clone()),
+        };
+        ProjectCursor {
+            sort,
+            direction,
+            key,
+            id: self.project.id,
         }
-        SortOrder::Updated => {
-            query_projects_by_updated(pool, kind_filter, language_filter, topic_filter, limit)
-                .await?
+    }
+}
+
+/// Bind parameters shared by the project queries.
+struct ProjectQuery<'a> {
+    kinds: Vec<String>,
+    /// Lowercased, to compare against `LOWER(language)`
+    languages: Vec<String>,
+    topics: &'a [String],
+    /// Require every topic rather than any of them
+    match_all: bool,
+    exclude_topics: &'a [String],
+    search: Option<&'a str>,
+    cursor: Option<&'a ProjectCursor>,
+    /// One more than the page size, to tell whether another page follows
+    limit: i64,
+}
+
+impl<'a> ProjectQuery<'a> {
+    fn new(filters: &'a ProjectFilters) -> Self {
+        Self {
+            kinds: filters.kinds.iter().map(ToString::to_string).collect(),
+            languages: filters.languages.iter().map(|l| l.to_lowercase()).collect(),
+            topics: &filters.topics,
+            match_all: filters.topic_match == TopicMatch::All,
+            exclude_topics: &filters.exclude_topics,
+            search: filters
+                .q
+                .as_deref()
+                .map(str::trim)
+                .filter(|q| !q.is_empty()),
+            cursor: filters.cursor.as_ref(),
+            // 1000 is a de-facto "no limit" for our dataset size; the SQL always needs a LIMIT.
+            limit: i64::from(filters.limit.unwrap_or(1000)) + 1,
+        }
+    }
+}
+
+/// Conditions on `projects` for the filters bound as `$1` to `$6`, in [`ProjectQuery`]'s order.
+const PROJECT_FILTERS: &str = r"
+    (cardinality($1::TEXT[]) = 0 OR kinds && $1)
+    AND (cardinality($2::TEXT[]) = 0 OR LOWER(language) = ANY($2))
+    AND (cardinality($3::TEXT[]) = 0
+         OR ($4::BOOLEAN AND topics @> $3)
+         OR (NOT $4 AND topics && $3))
+    AND NOT (COALESCE(topics, '{}') && $5::TEXT[])
+    AND ($6::TEXT IS NULL OR search @@ websearch_to_tsquery('english', $6))
+";
+
+/// SQL for the numbers a sort compares, as a `FLOAT8[]` compared element by element. Name
+/// sorts compare text instead and have no numbers.
+///
+/// Dates compare as epoch microseconds. Missing dates, stars and downloads become an
+/// infinity that puts them last whichever way the list runs.
+fn project_sort_numbers(sort: SortOrder, direction: SortDirection) -> String {
+    let missing = match direction {
+        SortDirection::Asc => "'Infinity'::FLOAT8",
+        SortDirection::Desc => "'-Infinity'::FLOAT8",
+    };
+    let date = |column: &str| {
+        format!("ARRAY[COALESCE((EXTRACT(EPOCH FROM {column}) * 1000000)::FLOAT8, {missing})]")
+    };
+    let count = |column: &str| format!("ARRAY[COALESCE({column}::FLOAT8, {missing})]");
+    match sort {
+        SortOrder::Name => "'{}'::FLOAT8[]".to_owned(),
+        SortOrder::Popularity => "ARRAY[score]".to_owned(),
+        SortOrder::Updated => date("updated_at"),
+        SortOrder::Created => date("created_at"),
+        SortOrder::Released => date("latest_released_at"),
+        SortOrder::Stars => count("stars"),
+        SortOrder::Downloads => count("downloads"),
+        // Best match first by `ts_rank`, with name matches weighted above topics and
+        // keywords, and those above descriptions. Ties, and every row when there is no
+        // search, fall back to the popularity score.
+        SortOrder::Relevance => "ARRAY[COALESCE(ts_rank(search, websearch_to_tsquery('english', $6)), 0)::FLOAT8, score]".to_owned(),
+    }
+}
+
+/// Fetch one page of projects using keyset pagination on `(sort key, id)`. `filters.limit`
+/// is the page size, and `filters.cursor` continues from a previous page.
+///
+/// Every sort runs the same query; only the sort key expression and the direction differ,
+/// and both come from [`project_sort_numbers`] rather than from user input.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_projects(
+    pool: &PgPool,
+    filters: &ProjectFilters,
+) -> Result<ProjectPage, sqlx::Error> {
+    let query = ProjectQuery::new(filters);
+    let sort = filters.effective_sort();
+    let direction = filters.effective_direction();
+
+    let sort_numbers = project_sort_numbers(sort, direction);
+    let sort_text = if sort == SortOrder::Name {
+        "name"
+    } else {
+        "''"
+    };
+    let (order, after) = match direction {
+        SortDirection::Asc => ("ASC", ">"),
+        SortDirection::Desc => ("DESC", "<"),
+    };
+    let sql = format!(
+        r"
+        SELECT * FROM (
+            SELECT
+                id, kind, name, description, url, language, topics, popularity, version,
+                commit_count, updated_at, synced_at, first_seen_at, artifacts,
+                {sort_numbers} AS sort_numbers, {sort_text} AS sort_text
+            FROM projects
+            WHERE {PROJECT_FILTERS}
+        ) sorted
+        WHERE $9::UUID IS NULL OR (sort_numbers, sort_text, id) {after} ($7::FLOAT8[], $8::TEXT, $9)
+        ORDER BY sort_numbers {order}, sort_text {order}, id {order}
+        LIMIT $10
+        "
+    );
+
+    // A cursor from another sort or direction has keys that mean nothing here.
+    let (after_numbers, after_text, after_id) = match query.cursor {
+        Some(cursor) if cursor.sort == sort && cursor.direction == direction => match &cursor.key {
+            CursorKey::Text(text) => (Vec::new(), text.as_str(), Some(cursor.id)),
+            CursorKey::Numbers(numbers) => (numbers.clone(), "", Some(cursor.id)),
+        },
+        _ => (Vec::new(), "", None),
+    };
+
+    let mut rows = sqlx::query_as::<_, SortedProjectRow>(&sql)
+        .bind(&query.kinds)
+        .bind(&query.languages)
+        .bind(query.topics)
+        .bind(query.match_all)
+        .bind(query.exclude_topics)
+        .bind(query.search)
+        .bind(after_numbers)
+        .bind(after_text)
+        .bind(after_id)
+        .bind(query.limit)
+        .fetch_all(pool)
+        .await?;
+
+    let page_len = usize::try_from(query.limit - 1).unwrap_or(0);
+    let has_more = rows.len() > page_len;
+    rows.truncate(page_len);
+    let next_cursor = if has_more {
+        rows.last().map(|row| row.cursor(sort, direction))
+    } else {
+        None
+    };
+
+    Ok(ProjectPage {
+        projects: rows
+            .into_iter()
+            .map(|row| ProjectView::from(row.project))
+            .collect(),
+        next_cursor,
+    })
+}
+
+struct ProjectDetailRow {
+    id: Uuid,
+    kind: String,
+    name: String,
+    description: Option<String>,
+    url: String,
+    language: Option<String>,
+    topics: Option<Vec<String>>,
+    popularity: Option<i32>,
+    version: Option<String>,
+    commit_count: Option<i32>,
+    updated_at: Option<DateTime<Utc>>,
+    synced_at: DateTime<Utc>,
+    first_seen_at: DateTime<Utc>,
+    artifacts: Json<Vec<ProjectArtifact>>,
+    created_at: Option<DateTime<Utc>>,
+    latest_released_at: Option<DateTime<Utc>>,
+    stars: Option<i32>,
+    downloads: Option<i32>,
+    documentation_url: Option<String>,
+    homepage_url: Option<String>,
+    repository_url: Option<String>,
+    repository_forge: Option<String>,
+    readme_html: Option<String>,
+}
+
+impl From<ProjectDetailRow> for ProjectDetail {
+    fn from(row: ProjectDetailRow) -> Self {
+        let project = ProjectRow {
+            id: row.id,
+            kind: row.kind,
+            name: row.name,
+            description: row.description,
+            url: row.url,
+            language: row.language,
+            topics: row.topics,
+            popularity: row.popularity,
+            version: row.version,
+            commit_count: row.commit_count,
+            updated_at: row.updated_at,
+            synced_at: row.synced_at,
+            first_seen_at: row.first_seen_at,
+            artifacts: row.artifacts,
+        };
+        Self {
+            project: project.into(),
+            created_at: row.created_at,
+            latest_released_at: row.latest_released_at,
+            stars: row.stars,
+            downloads: row.downloads,
+            documentation_url: row.documentation_url,
+            homepage_url: row.homepage_url,
+            repository_url: row.repository_url,
+            repository_forge: row.repository_forge,
+            readme_html: row.readme_html,
         }
+    }
+}
+
+/// The project with an artifact of this kind and name, with its links and repository. Any of
+/// a project's artifacts finds it, not only the primary one.
+///
+/// Repository names are only unique per owner, so when several projects match the most
+/// popular one wins. Documentation and homepage links come from the package named like the
+/// project, else the most downloaded one, with the repository's homepage as a fallback.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_project_detail(
+    pool: &PgPool,
+    kind: ProjectKind,
+    name: &str,
+) -> Result<Option<ProjectDetail>, sqlx::Error> {
+    let row = sqlx::query_as!(
+        ProjectDetailRow,
+        r#"
+        SELECT
+            p.id as "id!", p.kind as "kind!", p.name as "name!", p.description, p.url as "url!",
+            p.language, p.topics, p.popularity, p.version, p.commit_count, p.updated_at,
+            p.synced_at as "synced_at!", p.first_seen_at as "first_seen_at!",
+            p.artifacts as "artifacts!: Json<Vec<ProjectArtifact>>",
+            p.created_at, p.latest_released_at, p.stars, p.downloads,
+            (
+                SELECT c.documentation_url FROM crates c
+                WHERE COALESCE(c.repository_id, c.id) = p.id AND c.documentation_url IS NOT NULL
+                ORDER BY c.name = p.name DESC, c.downloads DESC
+                LIMIT 1
+            ) as documentation_url,
+            COALESCE(
+                (
+                    SELECT homepage_url FROM (
+                        SELECT c.name, c.homepage_url, c.downloads FROM crates c
+                        WHERE COALESCE(c.repository_id, c.id) = p.id
+                        UNION ALL
+                        SELECT n.name, n.homepage_url, n.downloads_weekly FROM npm_packages n
+                        WHERE COALESCE(n.repository_id, n.id) = p.id
+                    ) packages
+                    WHERE homepage_url IS NOT NULL
+                    ORDER BY name = p.name DESC, downloads DESC
+                    LIMIT 1
+                ),
+                r.homepage_url
+            ) as homepage_url,
+            r.url as "repository_url?", r.forge as "repository_forge?",
+            rm.html as "readme_html?"
+        FROM projects p
+        LEFT JOIN repositories r ON r.id = p.id
+        LEFT JOIN readmes rm ON rm.repository_id = p.id
+        WHERE p.id IN (SELECT project_id FROM project_artifacts WHERE kind = $1 AND name = $2)
+        ORDER BY COALESCE(p.popularity, 0) DESC, p.id
+        LIMIT 1
+        "#,
+        kind.to_string(),
+        name,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(ProjectDetail::from))
+}
+
+/// Most recent releases of any of a project's artifacts, yanked ones included, newest first.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_project_releases(
+    pool: &PgPool,
+    project_id: Uuid,
+    limit: i64,
+) -> Result<Vec<Release>, sqlx::Error> {
+    sqlx::query_as!(
+        Release,
+        r#"
+        SELECT
+            rel.id, rel.artifact_kind, rel.artifact, rel.version, rel.released_at, rel.yanked,
+            rel.notes_url, rel.synced_at
+        FROM releases rel
+        JOIN project_artifacts a
+            ON a.kind = rel.artifact_kind AND a.release_artifact = rel.artifact
+        WHERE a.project_id = $1
+        ORDER BY rel.released_at DESC NULLS LAST, rel.version DESC
+        LIMIT $2
+        "#,
+        project_id,
+        limit,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Merged contributions to upstream repositories whose name or owner matches one of `terms`
+/// (say a project's name and topics), case-insensitively, newest first.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_related_contributions(
+    pool: &PgPool,
+    terms: &[String],
+    limit: i64,
+) -> Result<Vec<Contribution>, sqlx::Error> {
+    let terms: Vec<String> = terms.iter().map(|t| t.to_lowercase()).collect();
+    sqlx::query_as!(
+        Contribution,
+        r#"
+        SELECT
+            c.id, c.forge, c.repo_owner, c.repo_name, c.repo_url, c.contribution_type, c.title,
+            c.url, c.merged_at, c.additions, c.deletions, c.changed_files, c.labels,
+            u.stars as "upstream_stars?", u.language as "upstream_language?", c.synced_at
+        FROM contributions c
+        LEFT JOIN upstream_repos u
+            ON u.forge = c.forge AND u.owner = c.repo_owner AND u.name = c.repo_name
+        WHERE c.merged_at IS NOT NULL
+          AND (LOWER(c.repo_name) = ANY($1) OR LOWER(c.repo_owner) = ANY($1))
+        ORDER BY c.merged_at DESC, c.id DESC
+        LIMIT $2
+        "#,
+        &terms[..],
+        limit,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Count each value of a facet, `value` and `facet_from` naming it and where it comes from,
+/// against `query`.
+async fn facet_counts(
+    pool: &PgPool,
+    query: &ProjectQuery<'_>,
+    value: &str,
+    facet_from: &str,
+) -> Result<Vec<FacetCount>, sqlx::Error> {
+    let sql = format!(
+        r"
+        SELECT {value}, COUNT(*) FILTER (WHERE {PROJECT_FILTERS})
+        {facet_from}
+        "
+    );
+    let counts: Vec<(String, i64)> = sqlx::query_as(&sql)
+        .bind(&query.kinds)
+        .bind(&query.languages)
+        .bind(query.topics)
+        .bind(query.match_all)
+        .bind(query.exclude_topics)
+        .bind(query.search)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(counts
+        .into_iter()
+        .map(|(value, count)| FacetCount { value, count })
+        .collect())
+}
+
+/// Distinct kinds, languages and topics with the number of projects each would match.
+///
+/// Each group is counted with every filter applied except its own, so the counts show what
+/// selecting another value in a multi-select group would add. Required topics are the
+/// exception when they must all match: adding a topic then narrows the current results, so
+/// topics are counted within them. Languages differing only in case are merged, and a project
+/// counts towards the kind of each of its artifacts.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying queries.
+pub async fn get_project_facets(
+    pool: &PgPool,
+    filters: &ProjectFilters,
+) -> Result<ProjectFacets, sqlx::Error> {
+    let kind_query = ProjectQuery {
+        kinds: Vec::new(),
+        ..ProjectQuery::new(filters)
+    };
+    let kinds = facet_counts(
+        pool,
+        &kind_query,
+        "project_kind",
+        r"FROM projects, unnest(kinds) AS project_kind
+        GROUP BY project_kind
+        ORDER BY project_kind ASC",
+    );
+
+    let language_query = ProjectQuery {
+        languages: Vec::new(),
+        ..ProjectQuery::new(filters)
     };
+    let languages = facet_counts(
+        pool,
+        &language_query,
+        "MIN(language)",
+        r"FROM projects
+        WHERE language IS NOT NULL
+        GROUP BY LOWER(language)
+        ORDER BY LOWER(language) ASC",
+    );
+
+    let base = ProjectQuery::new(filters);
+    let topic_query = ProjectQuery {
+        topics: if base.match_all { base.topics } else { &[] },
+        ..base
+    };
+    let topics = facet_counts(
+        pool,
+        &topic_query,
+        "topic",
+        r"FROM projects, unnest(topics) AS topic
+        GROUP BY topic
+        ORDER BY topic ASC",
+    );
+
+    let (kinds, languages, topics) = tokio::try_join!(kinds, languages, topics)?;
+
+    Ok(ProjectFacets {
+        kinds,
+        languages,
+        topics,
+    })
+}
+
+/// Projects in order of first appearance, newest first, optionally only those with an
+/// artifact of `kind`.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_new_projects(
+    pool: &PgPool,
+    kind: Option<ProjectKind>,
+    limit: i64,
+) -> Result<Vec<ProjectView>, sqlx::Error> {
+    let kind_filter = kind.map(|k| k.to_string());
+    let rows = sqlx::query_as!(
+        ProjectRow,
+        r#"
+        SELECT
+            id as "id!", kind as "kind!", name as "name!", description, url as "url!",
+            language, topics, popularity, version, commit_count, updated_at,
+            synced_at as "synced_at!", first_seen_at as "first_seen_at!",
+            artifacts as "artifacts!: Json<Vec<ProjectArtifact>>"
+        FROM projects
+        WHERE ($1::TEXT IS NULL OR $1 = ANY(kinds))
+        ORDER BY first_seen_at DESC, name ASC
+        LIMIT $2
+        "#,
+        kind_filter,
+        limit,
+    )
+    .fetch_all(pool)
+    .await?;
 
     Ok(rows.into_iter().map(ProjectView::from).collect())
 }
@@ -428,7 +1615,9 @@ pub async fn get_projects(
 /// # Errors
 /// Returns any `PostgreSQL` error from the underlying query.
 pub async fn get_all_projects(pool: &PgPool) -> Result<Vec<ProjectView>, sqlx::Error> {
-    get_projects(pool, &ProjectFilters::default()).await
+    Ok(get_projects(pool, &ProjectFilters::default())
+        .await?
+        .projects)
 }
 
 /// # Errors
@@ -447,3 +1636,171 @@ pub async fn get_distinct_topics(pool: &PgPool) -> Result<Vec<String>, sqlx::Err
 
     Ok(rows)
 }
+
+/// Row count and most recent write for each sync source.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_sync_status(pool: &PgPool) -> Result<Vec<SourceSyncStatus>, sqlx::Error> {
+    sqlx::query_as!(
+        SourceSyncStatus,
+        r#"
+        SELECT source as "source!", items as "items!", last_synced_at
+        FROM (
+            SELECT forge as source, COUNT(*) as items, MAX(synced_at) as last_synced_at
+            FROM repositories GROUP BY forge
+            UNION ALL
+            SELECT 'crates.io', COUNT(*), MAX(synced_at) FROM crates
+            UNION ALL
+            SELECT 'npm', COUNT(*), MAX(synced_at) FROM npm_packages
+            UNION ALL
+            SELECT 'contributions', COUNT(*), MAX(synced_at) FROM contributions
+            UNION ALL
+            SELECT 'releases', COUNT(*), MAX(synced_at) FROM releases
+        ) s
+        ORDER BY source
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Repositories by star count, most starred first.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_repositories(pool: &PgPool, limit: i64) -> Result<Vec<Repository>, sqlx::Error> {
+    sqlx::query_as!(
+        Repository,
+        r#"
+        SELECT
+            id, forge, forge_id, name, description, url, language,
+            COALESCE(stars, 0) as "stars!", COALESCE(topics, '{}') as "topics!",
+            updated_at, synced_at
+        FROM repositories
+        ORDER BY stars DESC NULLS LAST, name ASC
+        LIMIT $1
+        "#,
+        limit,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_repositories_by_ids(
+    pool: &PgPool,
+    ids: &[Uuid],
+) -> Result<Vec<Repository>, sqlx::Error> {
+    sqlx::query_as!(
+        Repository,
+        r#"
+        SELECT
+            id, forge, forge_id, name, description, url, language,
+            COALESCE(stars, 0) as "stars!", COALESCE(topics, '{}') as "topics!",
+            updated_at, synced_at
+        FROM repositories
+        WHERE id = ANY($1)
+        "#,
+        ids,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Crates by download count, most downloaded first.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_crates(pool: &PgPool, limit: i64) -> Result<Vec<Crate>, sqlx::Error> {
+    sqlx::query_as!(
+        Crate,
+        r#"
+        SELECT
+            id, name, description, repository_id, crates_io_url, documentation_url,
+            COALESCE(downloads, 0) as "downloads!", version,
+            COALESCE(keywords, '{}') as "keywords!", COALESCE(categories, '{}') as "categories!",
+            synced_at
+        FROM crates
+        ORDER BY downloads DESC NULLS LAST, name ASC
+        LIMIT $1
+        "#,
+        limit,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Crates published from any of the given repositories.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_crates_by_repository_ids(
+    pool: &PgPool,
+    repository_ids: &[Uuid],
+) -> Result<Vec<Crate>, sqlx::Error> {
+    sqlx::query_as!(
+        Crate,
+        r#"
+        SELECT
+            id, name, description, repository_id, crates_io_url, documentation_url,
+            COALESCE(downloads, 0) as "downloads!", version,
+            COALESCE(keywords, '{}') as "keywords!", COALESCE(categories, '{}') as "categories!",
+            synced_at
+        FROM crates
+        WHERE repository_id = ANY($1)
+        ORDER BY name ASC
+        "#,
+        repository_ids,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// NPM packages by weekly downloads, most downloaded first.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_npm_packages(pool: &PgPool, limit: i64) -> Result<Vec<NpmPackage>, sqlx::Error> {
+    sqlx::query_as!(
+        NpmPackage,
+        r#"
+        SELECT
+            id, name, scope, description, repository_id, npm_url,
+            COALESCE(downloads_weekly, 0) as "downloads_weekly!", version,
+            COALESCE(keywords, '{}') as "keywords!", synced_at
+        FROM npm_packages
+        ORDER BY downloads_weekly DESC NULLS LAST, name ASC
+        LIMIT $1
+        "#,
+        limit,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// NPM packages published from any of the given repositories.
+///
+/// # Errors
+/// Returns any `PostgreSQL` error from the underlying query.
+pub async fn get_npm_packages_by_repository_ids(
+    pool: &PgPool,
+    repository_ids: &[Uuid],
+) -> Result<Vec<NpmPackage>, sqlx::Error> {
+    sqlx::query_as!(
+        NpmPackage,
+        r#"
+        SELECT
+            id, name, scope, description, repository_id, npm_url,
+            COALESCE(downloads_weekly, 0) as "downloads_weekly!", version,
+            COALESCE(keywords, '{}') as "keywords!", synced_at
+        FROM npm_packages
+        WHERE repository_id = ANY($1)
+        ORDER BY name ASC
+        "#,
+        repository_ids,
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -1,7 +1,8 @@
 //! Database integration tests using sqlx::test
 //!
-//! These tests require a PostgreSQL database. Each test runs in a transaction
-//! that is rolled back after the test completes.
+//! Tests taking a `&dyn Store` run against both backends, listed in [`store_tests!`]; the
+//! rest cover queries only `PostgreSQL` has. Each `PostgreSQL` test gets a fresh database
+//! from `DATABASE_URL`, and each `SQLite` one a fresh file under `target/sqlx`.
 //!
 //! Run with: DATABASE_URL="postgres:///djv_test" cargo test --features ssr
 
@@ -12,11 +13,73 @@
     clippy::wildcard_imports
 )]
 
-use chrono::Utc;
+use chrono::{TimeZone, Utc};
 use sqlx::PgPool;
 
+use super::canonical_url::CanonicalUrl;
 use super::models::*;
 use super::queries::*;
+use crate::store::Store;
+
+/// For each named `async fn(&dyn Store)`, a module of the same name running it against
+/// `PostgreSQL` and against `SQLite`.
+macro_rules! store_tests {
+    ($($name:ident),* $(,)?) => {$(
+        mod $name {
+            #[sqlx::test(migrations = "./migrations")]
+            async fn postgres(pool: sqlx::PgPool) {
+                super::$name(&pool).await;
+            }
+
+            #[sqlx::test(migrations = "./migrations/sqlite")]
+            async fn sqlite(pool: sqlx::SqlitePool) {
+                super::$name(&crate::store::SqliteStore::from(pool)).await;
+            }
+        }
+    )*};
+}
+
+store_tests!(
+    upsert_repository_insert,
+    upsert_repository_update,
+    upsert_crate_standalone,
+    upsert_crate_with_repo_link,
+    upsert_npm_package_insert,
+    upsert_contribution_insert,
+    upsert_contribution_update,
+    get_projects_unfiltered,
+    get_projects_filter_by_kind_crate,
+    get_projects_filter_by_language,
+    get_projects_filter_by_topic,
+    get_projects_sort_by_name,
+    get_projects_sort_by_popularity,
+    get_contributions_with_limit,
+    get_distinct_topics_returns_unique,
+    upsert_upstream_repo_update,
+    get_contributions_sorted_by_impact,
+    get_contribution_page_walks_all_pages,
+    get_contribution_page_filters,
+    get_contribution_facets,
+    get_contribution_stats_aggregates,
+    releases_upsert_and_recent,
+    get_projects_full_text_search,
+    get_projects_multi_value_and_excluded_filters,
+    get_project_facets_counts_against_other_filters,
+    get_projects_pages_through_every_sort_order,
+    get_projects_sorts_by_each_key_in_either_direction,
+    get_projects_ignores_cursor_from_another_sort,
+    get_project_detail_joins_links_and_repository,
+    get_related_contributions_matches_owner_or_name,
+    readmes_store_and_show_on_project_detail,
+    artifacts_sharing_a_repository_form_one_project,
+    artifacts_link_to_repositories_by_canonical_url,
+    workspace_members_mark_their_crates,
+    projects_change_only_when_refreshed,
+    sync_runs_record_the_last_finish,
+    get_new_projects_keeps_first_seen,
+    get_sync_status_reports_each_source,
+    batched_lookups_by_repository,
+);
 
 fn repo<'a>(
     forge_id: &'a str,
@@ -35,16 +98,16 @@ fn repo<'a>(
         language,
         stars,
         topics,
+        created_at: None,
+        homepage_url: None,
         updated_at: None,
     }
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn upsert_repository_insert(pool: PgPool) {
+async fn upsert_repository_insert(store: &dyn Store) {
     let topics = ["rust".to_owned(), "cli".to_owned()];
-    let id = upsert_repository(
-        &pool,
-        &NewRepository {
+    let id = store
+        .upsert_repository(&NewRepository {
             forge: "github",
             forge_id: "user/new-repo",
             name: "new-repo",
@@ -53,20 +116,19 @@ async fn upsert_repository_insert(pool: PgPool) {
             language: Some("Rust"),
             stars: 42,
             topics: &topics,
+            created_at: None,
+            homepage_url: None,
             updated_at: Some(Utc::now()),
-        },
-    )
-    .await
-    .expect("should insert repository");
+        })
+        .await
+        .expect("should insert repository");
 
     assert!(!id.is_nil());
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn upsert_repository_update(pool: PgPool) {
-    let id1 = upsert_repository(
-        &pool,
-        &NewRepository {
+async fn upsert_repository_update(store: &dyn Store) {
+    let id1 = store
+        .upsert_repository(&NewRepository {
             forge: "github",
             forge_id: "user/repo",
             name: "repo",
@@ -75,16 +137,16 @@ async fn upsert_repository_update(pool: PgPool) {
             language: Some("Rust"),
             stars: 10,
             topics: &[],
+            created_at: None,
+            homepage_url: None,
             updated_at: None,
-        },
-    )
-    .await
-    .expect("should insert");
+        })
+        .await
+        .expect("should insert");
 
     let updated_topics = ["updated".to_owned()];
-    let id2 = upsert_repository(
-        &pool,
-        &NewRepository {
+    let id2 = store
+        .upsert_repository(&NewRepository {
             forge: "github",
             forge_id: "user/repo",
             name: "repo",
@@ -93,44 +155,43 @@ async fn upsert_repository_update(pool: PgPool) {
             language: Some("Rust"),
             stars: 100,
             topics: &updated_topics,
+            created_at: None,
+            homepage_url: None,
             updated_at: Some(Utc::now()),
-        },
-    )
-    .await
-    .expect("should update");
+        })
+        .await
+        .expect("should update");
 
     assert_eq!(id1, id2);
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn upsert_crate_standalone(pool: PgPool) {
+async fn upsert_crate_standalone(store: &dyn Store) {
     let keywords = ["cli".to_owned()];
     let categories = ["command-line-utilities".to_owned()];
-    let id = upsert_crate(
-        &pool,
-        &NewCrate {
+    let id = store
+        .upsert_crate(&NewCrate {
             name: "my-crate",
             description: Some("A standalone crate"),
             repository_id: None,
+            repository_url: None,
             crates_io_url: "https://crates.io/crates/my-crate",
             documentation_url: Some("https://docs.rs/my-crate"),
             downloads: 1000,
             version: Some("1.0.0"),
             keywords: &keywords,
             categories: &categories,
-        },
-    )
-    .await
-    .expect("should insert crate");
+            created_at: None,
+            homepage_url: None,
+        })
+        .await
+        .expect("should insert crate");
 
     assert!(!id.is_nil());
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn upsert_crate_with_repo_link(pool: PgPool) {
-    let repo_id = upsert_repository(
-        &pool,
-        &NewRepository {
+async fn upsert_crate_with_repo_link(store: &dyn Store) {
+    let repo_id = store
+        .upsert_repository(&NewRepository {
             forge: "github",
             forge_id: "user/my-crate",
             name: "my-crate",
@@ -139,59 +200,58 @@ async fn upsert_crate_with_repo_link(pool: PgPool) {
             language: Some("Rust"),
             stars: 50,
             topics: &[],
+            created_at: None,
+            homepage_url: None,
             updated_at: None,
-        },
-    )
-    .await
-    .expect("should insert repository");
+        })
+        .await
+        .expect("should insert repository");
 
-    let crate_id = upsert_crate(
-        &pool,
-        &NewCrate {
+    let crate_id = store
+        .upsert_crate(&NewCrate {
             name: "my-crate",
             description: Some("A crate with repo"),
             repository_id: Some(repo_id),
+            repository_url: None,
             crates_io_url: "https://crates.io/crates/my-crate",
             documentation_url: Some("https://docs.rs/my-crate"),
             downloads: 5000,
             version: Some("2.0.0"),
             keywords: &[],
             categories: &[],
-        },
-    )
-    .await
-    .expect("should insert crate");
+            created_at: None,
+            homepage_url: None,
+        })
+        .await
+        .expect("should insert crate");
 
     assert!(!crate_id.is_nil());
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn upsert_npm_package_insert(pool: PgPool) {
+async fn upsert_npm_package_insert(store: &dyn Store) {
     let keywords = ["typescript".to_owned()];
-    let id = upsert_npm_package(
-        &pool,
-        &NewNpmPackage {
+    let id = store
+        .upsert_npm_package(&NewNpmPackage {
             name: "my-package",
             scope: Some("@scope"),
             description: Some("An NPM package"),
             repository_id: None,
+            repository_url: None,
             npm_url: "https://www.npmjs.com/package/my-package",
+            homepage_url: None,
             downloads_weekly: 10_000,
             version: Some("3.0.0"),
             keywords: &keywords,
-        },
-    )
-    .await
-    .expect("should insert npm package");
+        })
+        .await
+        .expect("should insert npm package");
 
     assert!(!id.is_nil());
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn upsert_contribution_insert(pool: PgPool) {
-    let id = upsert_contribution(
-        &pool,
-        &NewContribution {
+async fn upsert_contribution_insert(store: &dyn Store) {
+    let id = store
+        .upsert_contribution(&NewContribution {
             forge: "github",
             repo_owner: "rust-lang",
             repo_name: "rust",
@@ -200,19 +260,20 @@ async fn upsert_contribution_insert(pool: PgPool) {
             title: Some("Fix compiler bug"),
             url: "https://github.com/rust-lang/rust/pull/12345",
             merged_at: Some(Utc::now()),
-        },
-    )
-    .await
-    .expect("should insert contribution");
+            additions: None,
+            deletions: None,
+            changed_files: None,
+            labels: &[],
+        })
+        .await
+        .expect("should insert contribution");
 
     assert!(!id.is_nil());
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn upsert_contribution_update(pool: PgPool) {
-    let id1 = upsert_contribution(
-        &pool,
-        &NewContribution {
+async fn upsert_contribution_update(store: &dyn Store) {
+    let id1 = store
+        .upsert_contribution(&NewContribution {
             forge: "github",
             repo_owner: "owner",
             repo_name: "repo",
@@ -221,14 +282,16 @@ async fn upsert_contribution_update(pool: PgPool) {
             title: Some("Original title"),
             url: "https://github.com/owner/repo/pull/1",
             merged_at: None,
-        },
-    )
-    .await
-    .expect("should insert");
+            additions: None,
+            deletions: None,
+            changed_files: None,
+            labels: &[],
+        })
+        .await
+        .expect("should insert");
 
-    let id2 = upsert_contribution(
-        &pool,
-        &NewContribution {
+    let id2 = store
+        .upsert_contribution(&NewContribution {
             forge: "github",
             repo_owner: "owner",
             repo_name: "repo",
@@ -237,20 +300,21 @@ async fn upsert_contribution_update(pool: PgPool) {
             title: Some("Updated title"),
             url: "https://github.com/owner/repo/pull/1",
             merged_at: Some(Utc::now()),
-        },
-    )
-    .await
-    .expect("should update");
+            additions: None,
+            deletions: None,
+            changed_files: None,
+            labels: &[],
+        })
+        .await
+        .expect("should update");
 
     assert_eq!(id1, id2);
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn get_projects_unfiltered(pool: PgPool) {
+async fn get_projects_unfiltered(store: &dyn Store) {
     let topics = ["rust".to_owned()];
-    upsert_repository(
-        &pool,
-        &NewRepository {
+    store
+        .upsert_repository(&NewRepository {
             forge: "github",
             forge_id: "user/test-repo",
             name: "test-repo",
@@ -259,156 +323,163 @@ async fn get_projects_unfiltered(pool: PgPool) {
             language: Some("Rust"),
             stars: 100,
             topics: &topics,
+            created_at: None,
+            homepage_url: None,
             updated_at: Some(Utc::now()),
-        },
-    )
-    .await
-    .expect("should insert");
+        })
+        .await
+        .expect("should insert");
 
-    let projects = get_projects(&pool, &ProjectFilters::default())
+    store.refresh_projects().await.unwrap();
+    let projects = store
+        .get_projects(&ProjectFilters::default())
         .await
-        .expect("should query projects");
+        .expect("should query projects")
+        .projects;
 
     assert!(!projects.is_empty());
     assert!(projects.iter().any(|p| p.name == "test-repo"));
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn get_projects_filter_by_kind_crate(pool: PgPool) {
-    upsert_crate(
-        &pool,
-        &NewCrate {
+async fn get_projects_filter_by_kind_crate(store: &dyn Store) {
+    store
+        .upsert_crate(&NewCrate {
             name: "filter-test-crate",
             description: Some("A crate for testing"),
             repository_id: None,
+            repository_url: None,
             crates_io_url: "https://crates.io/crates/filter-test-crate",
             documentation_url: None,
             downloads: 500,
             version: Some("1.0.0"),
             keywords: &[],
             categories: &[],
-        },
-    )
-    .await
-    .expect("should insert crate");
+            created_at: None,
+            homepage_url: None,
+        })
+        .await
+        .expect("should insert crate");
 
-    upsert_repository(
-        &pool,
-        &repo(
+    store
+        .upsert_repository(&repo(
             "user/filter-test-repo",
             "filter-test-repo",
             "https://github.com/user/filter-test-repo",
             Some("Python"),
             10,
             &[],
-        ),
-    )
-    .await
-    .expect("should insert repo");
+        ))
+        .await
+        .expect("should insert repo");
 
     let filters = ProjectFilters {
-        kind: Some(ProjectKind::Crate),
+        kinds: vec![ProjectKind::Crate],
         ..Default::default()
     };
 
-    let projects = get_projects(&pool, &filters).await.expect("should query");
+    store.refresh_projects().await.unwrap();
+    let projects = store
+        .get_projects(&filters)
+        .await
+        .expect("should query")
+        .projects;
 
     assert!(projects.iter().all(|p| p.kind == ProjectKind::Crate));
     assert!(projects.iter().any(|p| p.name == "filter-test-crate"));
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn get_projects_filter_by_language(pool: PgPool) {
-    upsert_repository(
-        &pool,
-        &repo(
+async fn get_projects_filter_by_language(store: &dyn Store) {
+    store
+        .upsert_repository(&repo(
             "user/rust-project",
             "rust-project",
             "https://github.com/user/rust-project",
             Some("Rust"),
             50,
             &[],
-        ),
-    )
-    .await
-    .expect("should insert");
+        ))
+        .await
+        .expect("should insert");
 
-    upsert_repository(
-        &pool,
-        &repo(
+    store
+        .upsert_repository(&repo(
             "user/python-project",
             "python-project",
             "https://github.com/user/python-project",
             Some("Python"),
             50,
             &[],
-        ),
-    )
-    .await
-    .expect("should insert");
+        ))
+        .await
+        .expect("should insert");
 
     let filters = ProjectFilters {
-        language: Some("Rust".to_owned()),
+        languages: vec!["Rust".to_owned()],
         ..Default::default()
     };
 
-    let projects = get_projects(&pool, &filters).await.expect("should query");
+    store.refresh_projects().await.unwrap();
+    let projects = store
+        .get_projects(&filters)
+        .await
+        .expect("should query")
+        .projects;
 
     assert!(projects
         .iter()
         .all(|p| p.language.as_deref() == Some("Rust") || p.language.as_deref() == Some("rust")));
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn get_projects_filter_by_topic(pool: PgPool) {
+async fn get_projects_filter_by_topic(store: &dyn Store) {
     let otel_topics = ["opentelemetry".to_owned(), "tracing".to_owned()];
-    upsert_repository(
-        &pool,
-        &repo(
+    store
+        .upsert_repository(&repo(
             "user/otel-project",
             "otel-project",
             "https://github.com/user/otel-project",
             Some("Rust"),
             100,
             &otel_topics,
-        ),
-    )
-    .await
-    .expect("should insert");
+        ))
+        .await
+        .expect("should insert");
 
     let other_topics = ["other".to_owned()];
-    upsert_repository(
-        &pool,
-        &repo(
+    store
+        .upsert_repository(&repo(
             "user/other-project",
             "other-project",
             "https://github.com/user/other-project",
             Some("Rust"),
             50,
             &other_topics,
-        ),
-    )
-    .await
-    .expect("should insert");
+        ))
+        .await
+        .expect("should insert");
 
     let filters = ProjectFilters {
-        topic: Some("opentelemetry".to_owned()),
+        topics: vec!["opentelemetry".to_owned()],
         ..Default::default()
     };
 
-    let projects = get_projects(&pool, &filters).await.expect("should query");
+    store.refresh_projects().await.unwrap();
+    let projects = store
+        .get_projects(&filters)
+        .await
+        .expect("should query")
+        .projects;
 
     assert!(projects
         .iter()
         .all(|p| p.topics.contains(&"opentelemetry".to_owned())));
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn get_projects_sort_by_name(pool: PgPool) {
+async fn get_projects_sort_by_name(store: &dyn Store) {
     for name in ["zebra", "alpha", "middle"] {
         let forge_id = format!("user/{name}");
         let url = format!("https://github.com/user/{name}");
-        upsert_repository(&pool, &repo(&forge_id, name, &url, Some("Rust"), 50, &[]))
+        store
+            .upsert_repository(&repo(&forge_id, name, &url, Some("Rust"), 50, &[]))
             .await
             .expect("should insert");
     }
@@ -418,7 +489,12 @@ async fn get_projects_sort_by_name(pool: PgPool) {
         ..Default::default()
     };
 
-    let projects = get_projects(&pool, &filters).await.expect("should query");
+    store.refresh_projects().await.unwrap();
+    let projects = store
+        .get_projects(&filters)
+        .await
+        .expect("should query")
+        .projects;
 
     let names: Vec<_> = projects.iter().map(|p| p.name.as_str()).collect();
     let mut sorted_names = names.clone();
@@ -426,56 +502,54 @@ async fn get_projects_sort_by_name(pool: PgPool) {
     assert_eq!(names, sorted_names);
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn get_projects_sort_by_popularity(pool: PgPool) {
-    upsert_repository(
-        &pool,
-        &repo(
+async fn get_projects_sort_by_popularity(store: &dyn Store) {
+    store
+        .upsert_repository(&repo(
             "user/popular",
             "popular",
             "https://github.com/user/popular",
             Some("Rust"),
             1000,
             &[],
-        ),
-    )
-    .await
-    .expect("should insert");
+        ))
+        .await
+        .expect("should insert");
 
-    upsert_repository(
-        &pool,
-        &repo(
+    store
+        .upsert_repository(&repo(
             "user/unpopular",
             "unpopular",
             "https://github.com/user/unpopular",
             Some("Rust"),
             10,
             &[],
-        ),
-    )
-    .await
-    .expect("should insert");
+        ))
+        .await
+        .expect("should insert");
 
     let filters = ProjectFilters {
         sort: Some(SortOrder::Popularity),
         ..Default::default()
     };
 
-    let projects = get_projects(&pool, &filters).await.expect("should query");
+    store.refresh_projects().await.unwrap();
+    let projects = store
+        .get_projects(&filters)
+        .await
+        .expect("should query")
+        .projects;
 
     if projects.len() >= 2 {
         assert!(projects[0].popularity >= projects[1].popularity);
     }
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn get_contributions_with_limit(pool: PgPool) {
+async fn get_contributions_with_limit(store: &dyn Store) {
     for i in 1..=5 {
         let title = format!("PR {i}");
         let url = format!("https://github.com/owner/repo/pull/{i}");
-        upsert_contribution(
-            &pool,
-            &NewContribution {
+        store
+            .upsert_contribution(&NewContribution {
                 forge: "github",
                 repo_owner: "owner",
                 repo_name: "repo",
@@ -484,50 +558,52 @@ async fn get_contributions_with_limit(pool: PgPool) {
                 title: Some(&title),
                 url: &url,
                 merged_at: Some(Utc::now()),
-            },
-        )
-        .await
-        .expect("should insert");
+                additions: None,
+                deletions: None,
+                changed_files: None,
+                labels: &[],
+            })
+            .await
+            .expect("should insert");
     }
 
-    let contributions = get_contributions(&pool, 3, 5).await.expect("should query");
+    let contributions = store
+        .get_contributions(3, 5, ContributionSort::Recent)
+        .await
+        .expect("should query");
 
     assert!(contributions.len() <= 3);
 }
 
-#[sqlx::test(migrations = "./migrations")]
-async fn get_distinct_topics_returns_unique(pool: PgPool) {
+async fn get_distinct_topics_returns_unique(store: &dyn Store) {
     let topics_1 = ["rust".to_owned(), "cli".to_owned()];
-    upsert_repository(
-        &pool,
-        &repo(
+    store
+        .upsert_repository(&repo(
             "user/repo1",
             "repo1",
             "https://github.com/user/repo1",
             Some("Rust"),
             50,
             &topics_1,
-        ),
-    )
-    .await
-    .expect("should insert");
+        ))
+        .await
+        .expect("should insert");
 
     let topics_2 = ["rust".to_owned(), "web".to_owned()];
-    upsert_repository(
-        &pool,
-        &repo(
+    store
+        .upsert_repository(&repo(
             "user/repo2",
             "repo2",
             "https://github.com/user/repo2",
             Some("Rust"),
             50,
             &topics_2,
-        ),
-    )
-    .await
-    .expect("should insert");
+        ))
+        .await
+        .expect("should insert");
 
-    let topics = get_distinct_topics(&pool).await.expect("should query");
+    store.refresh_projects().await.unwrap();
+    let topics = store.get_distinct_topics().await.expect("should query");
 
     let unique_count = topics.len();
     let mut deduped = topics.clone();
@@ -539,3 +615,1623 @@ async fn get_distinct_topics_returns_unique(pool: PgPool) {
     assert!(topics.contains(&"cli".to_owned()));
     assert!(topics.contains(&"web".to_owned()));
 }
+
+async fn upsert_upstream_repo_update(store: &dyn Store) {
+    let upstream = |stars| NewUpstreamRepo {
+        forge: "github",
+        owner: "tokio-rs",
+        name: "tokio",
+        url: "https://github.com/tokio-rs/tokio",
+        description: Some("A runtime for writing reliable asynchronous applications"),
+        language: Some("Rust"),
+        stars,
+    };
+
+    let id1 = store
+        .upsert_upstream_repo(&upstream(25_000))
+        .await
+        .expect("should insert");
+    let id2 = store
+        .upsert_upstream_repo(&upstream(26_000))
+        .await
+        .expect("should update");
+
+    assert_eq!(id1, id2);
+}
+
+async fn get_contributions_sorted_by_impact(store: &dyn Store) {
+    let labels = ["enhancement".to_owned()];
+    let cases = [
+        (
+            "small-repo",
+            "https://github.com/someone/small-repo/pull/1",
+            400,
+            100,
+        ),
+        ("tokio", "https://github.com/tokio-rs/tokio/pull/2", 120, 30),
+        ("tokio", "https://github.com/tokio-rs/tokio/pull/3", 1, 1),
+    ];
+    for (name, url, additions, deletions) in cases {
+        let owner = if name == "tokio" {
+            "tokio-rs"
+        } else {
+            "someone"
+        };
+        let repo_url = format!("https://github.com/{owner}/{name}");
+        store
+            .upsert_contribution(&NewContribution {
+                forge: "github",
+                repo_owner: owner,
+                repo_name: name,
+                repo_url: &repo_url,
+                contribution_type: "pr",
+                title: Some("Change"),
+                url,
+                merged_at: Some(Utc::now()),
+                additions: Some(additions),
+                deletions: Some(deletions),
+                changed_files: Some(1),
+                labels: &labels,
+            })
+            .await
+            .expect("should insert");
+    }
+
+    store
+        .upsert_upstream_repo(&NewUpstreamRepo {
+            forge: "github",
+            owner: "tokio-rs",
+            name: "tokio",
+            url: "https://github.com/tokio-rs/tokio",
+            description: None,
+            language: Some("Rust"),
+            stars: 25_000,
+        })
+        .await
+        .expect("should insert upstream");
+
+    let contributions = store
+        .get_contributions(10, 5, ContributionSort::Impact)
+        .await
+        .expect("should query");
+
+    let urls: Vec<_> = contributions.iter().map(|c| c.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        [
+            "https://github.com/tokio-rs/tokio/pull/2",
+            "https://github.com/someone/small-repo/pull/1",
+            "https://github.com/tokio-rs/tokio/pull/3",
+        ]
+    );
+    assert_eq!(contributions[0].upstream_stars, Some(25_000));
+    assert_eq!(contributions[0].upstream_language.as_deref(), Some("Rust"));
+    assert_eq!(contributions[0].labels, labels);
+    assert!(contributions[1].upstream_stars.is_none());
+}
+
+async fn insert_contribution(
+    store: &dyn Store,
+    forge: &str,
+    owner: &str,
+    number: i32,
+    merged_at: Option<chrono::DateTime<Utc>>,
+) {
+    let repo_url = format!("https://{forge}.com/{owner}/repo");
+    let url = format!("{repo_url}/pull/{number}");
+    let contribution_type = if forge == "gitlab" { "mr" } else { "pr" };
+    store
+        .upsert_contribution(&NewContribution {
+            forge,
+            repo_owner: owner,
+            repo_name: "repo",
+            repo_url: &repo_url,
+            contribution_type,
+            title: Some("Change"),
+            url: &url,
+            merged_at,
+            additions: None,
+            deletions: None,
+            changed_files: None,
+            labels: &[],
+        })
+        .await
+        .expect("should insert");
+}
+
+async fn get_contribution_page_walks_all_pages(store: &dyn Store) {
+    let base = Utc::now();
+    for i in 0..7 {
+        insert_contribution(
+            store,
+            "github",
+            "owner",
+            i,
+            Some(base - chrono::Duration::days(i.into())),
+        )
+        .await;
+    }
+    insert_contribution(store, "github", "owner", 99, None).await;
+
+    let mut filters = ContributionFilters::default();
+    let mut seen = Vec::new();
+    loop {
+        let page = store
+            .get_contribution_page(&filters, 3)
+            .await
+            .expect("should query");
+        assert!(page.contributions.len() <= 3);
+        seen.extend(page.contributions.into_iter().map(|c| c.url));
+        match page.next_cursor {
+            Some(cursor) => filters.cursor = Some(cursor),
+            None => break,
+        }
+    }
+
+    assert_eq!(seen.len(), 8);
+    assert_eq!(seen[0], "https://github.com/owner/repo/pull/0");
+    assert_eq!(seen[7], "https://github.com/owner/repo/pull/99");
+}
+
+async fn get_contribution_page_filters(store: &dyn Store) {
+    let last_year = Utc::now() - chrono::Duration::days(400);
+    insert_contribution(store, "github", "tokio-rs", 1, Some(Utc::now())).await;
+    insert_contribution(store, "github", "Tokio-RS", 2, Some(last_year)).await;
+    insert_contribution(store, "gitlab", "gnome", 3, Some(Utc::now())).await;
+
+    let by_owner = store
+        .get_contribution_page(
+            &ContributionFilters {
+                owner: Some("tokio-rs".to_owned()),
+                ..Default::default()
+            },
+            10,
+        )
+        .await
+        .expect("should query");
+    assert_eq!(by_owner.contributions.len(), 2);
+
+    let by_type = store
+        .get_contribution_page(
+            &ContributionFilters {
+                contribution_type: Some("mr".to_owned()),
+                ..Default::default()
+            },
+            10,
+        )
+        .await
+        .expect("should query");
+    assert_eq!(by_type.contributions.len(), 1);
+    assert_eq!(by_type.contributions[0].forge, "gitlab");
+
+    let by_year = store
+        .get_contribution_page(
+            &ContributionFilters {
+                year: Some(chrono::Datelike::year(&last_year)),
+                forge: Some("github".to_owned()),
+                ..Default::default()
+            },
+            10,
+        )
+        .await
+        .expect("should query");
+    assert!(by_year
+        .contributions
+        .iter()
+        .any(|c| c.url.ends_with("/pull/2")));
+    assert!(by_year.next_cursor.is_none());
+}
+
+async fn get_contribution_facets(store: &dyn Store) {
+    insert_contribution(store, "github", "tokio-rs", 1, Some(Utc::now())).await;
+    insert_contribution(store, "github", "tokio-rs", 2, Some(Utc::now())).await;
+    insert_contribution(store, "github", "serde-rs", 3, None).await;
+    // The owner filter ignores case, so the facet counts both spellings as one.
+    insert_contribution(store, "github", "Tokio-rs", 4, None).await;
+
+    let owners = store.get_contribution_owners().await.expect("should query");
+    assert_eq!(owners, ["Tokio-rs", "serde-rs"]);
+
+    let years = store.get_contribution_years().await.expect("should query");
+    assert_eq!(years, [chrono::Datelike::year(&Utc::now())]);
+}
+
+async fn get_contribution_stats_aggregates(store: &dyn Store) {
+    let at = |y: i32, m: u32| Utc.with_ymd_and_hms(y, m, 15, 12, 0, 0).single();
+    insert_contribution(store, "github", "tokio-rs", 1, at(2024, 11)).await;
+    insert_contribution(store, "github", "tokio-rs", 2, at(2025, 2)).await;
+    insert_contribution(store, "github", "tokio-rs", 3, at(2025, 2)).await;
+    insert_contribution(store, "gitlab", "gnome", 4, at(2025, 1)).await;
+    insert_contribution(store, "github", "serde-rs", 5, None).await;
+
+    let stats = store.get_contribution_stats().await.expect("should query");
+
+    let owners: Vec<_> = stats
+        .by_owner
+        .iter()
+        .map(|c| (c.key.as_str(), c.count))
+        .collect();
+    assert_eq!(owners, [("tokio-rs", 3), ("gnome", 1), ("serde-rs", 1)]);
+
+    let years: Vec<_> = stats
+        .by_year
+        .iter()
+        .map(|c| (c.key.as_str(), c.count))
+        .collect();
+    assert_eq!(years, [("2024", 1), ("2025", 3)]);
+
+    let months: Vec<_> = stats
+        .by_month
+        .iter()
+        .map(|c| (c.key.as_str(), c.count))
+        .collect();
+    assert_eq!(
+        months,
+        [
+            ("2024-11", 1),
+            ("2024-12", 0),
+            ("2025-01", 1),
+            ("2025-02", 2)
+        ]
+    );
+
+    assert_eq!(stats.repos.len(), 3);
+    let tokio = &stats.repos[0];
+    assert_eq!((tokio.owner.as_str(), tokio.count), ("tokio-rs", 3));
+    assert_eq!(tokio.first_merged_at, at(2024, 11));
+    assert_eq!(tokio.latest_merged_at, at(2025, 2));
+    let serde = &stats.repos[2];
+    assert_eq!(serde.owner, "serde-rs");
+    assert!(serde.latest_merged_at.is_none());
+}
+
+async fn releases_upsert_and_recent(store: &dyn Store) {
+    let release = |kind, artifact, version, days_ago: i64, yanked| NewRelease {
+        artifact_kind: kind,
+        artifact,
+        version,
+        released_at: Some(Utc::now() - chrono::Duration::days(days_ago)),
+        yanked,
+        notes_url: None,
+    };
+
+    store
+        .upsert_release(&release("crate", "djv", "0.1.0", 30, false))
+        .await
+        .expect("should insert");
+    store
+        .upsert_release(&release("crate", "djv", "0.2.0", 2, true))
+        .await
+        .expect("should insert");
+    store
+        .upsert_release(&release("npm", "djv-ui", "1.0.0", 5, false))
+        .await
+        .expect("should insert");
+    store
+        .upsert_release(&release("repo", "user/djv", "v0.1.0", 1, false))
+        .await
+        .expect("should insert");
+
+    // Re-syncing a version updates it in place.
+    let first = store
+        .upsert_release(&release("crate", "djv", "0.2.0", 2, false))
+        .await
+        .expect("should update");
+    let second = store
+        .upsert_release(&release("crate", "djv", "0.2.0", 2, true))
+        .await
+        .expect("should update");
+    assert_eq!(first, second);
+
+    let recent = store
+        .get_recent_releases(None, 10)
+        .await
+        .expect("should query");
+    let versions: Vec<_> = recent.iter().map(|r| r.version.as_str()).collect();
+    assert_eq!(versions, ["v0.1.0", "1.0.0", "0.1.0"]);
+
+    let limited = store
+        .get_recent_releases(None, 1)
+        .await
+        .expect("should query");
+    assert_eq!(limited.len(), 1);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn get_releases_for_includes_yanked(pool: PgPool) {
+    let release = |version, days_ago: i64, yanked| NewRelease {
+        artifact_kind: "crate",
+        artifact: "djv",
+        version,
+        released_at: Some(Utc::now() - chrono::Duration::days(days_ago)),
+        yanked,
+        notes_url: None,
+    };
+    upsert_release(&pool, &release("0.1.0", 30, false))
+        .await
+        .expect("should insert");
+    upsert_release(&pool, &release("0.2.0", 2, true))
+        .await
+        .expect("should insert");
+
+    let djv = get_releases_for(&pool, "crate", "djv")
+        .await
+        .expect("should query");
+    assert_eq!(djv.len(), 2);
+    assert_eq!(djv[0].version, "0.2.0");
+    assert!(djv[0].yanked);
+}
+
+async fn get_new_projects_keeps_first_seen(store: &dyn Store) {
+    let new_crate = |name| NewCrate {
+        name,
+        description: None,
+        repository_id: None,
+        repository_url: None,
+        crates_io_url: "https://crates.io/crates/x",
+        documentation_url: None,
+        downloads: 0,
+        version: None,
+        keywords: &[],
+        categories: &[],
+        created_at: None,
+        homepage_url: None,
+    };
+
+    store.upsert_crate(&new_crate("older")).await.unwrap();
+    // Far enough apart that the two are seen at different times
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    store.upsert_crate(&new_crate("newer")).await.unwrap();
+
+    // Re-syncing an existing crate must not make it look new again.
+    store.upsert_crate(&new_crate("older")).await.unwrap();
+
+    store.refresh_projects().await.unwrap();
+    let projects = store
+        .get_new_projects(Some(ProjectKind::Crate), 10)
+        .await
+        .expect("should query");
+    let names: Vec<_> = projects.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["newer", "older"]);
+
+    let repos = store
+        .get_new_projects(Some(ProjectKind::Repo), 10)
+        .await
+        .expect("should query");
+    assert!(repos.is_empty());
+}
+
+async fn get_sync_status_reports_each_source(store: &dyn Store) {
+    insert_contribution(store, "github", "tokio-rs", 1, Some(Utc::now())).await;
+    insert_contribution(store, "github", "tokio-rs", 2, None).await;
+
+    let status = store.get_sync_status().await.expect("should query");
+    let sources: Vec<_> = status
+        .iter()
+        .map(|s| (s.source.as_str(), s.items, s.last_synced_at.is_some()))
+        .collect();
+
+    assert_eq!(
+        sources,
+        [
+            ("contributions", 2, true),
+            ("crates.io", 0, false),
+            ("npm", 0, false),
+            ("releases", 0, false),
+        ]
+    );
+}
+
+async fn batched_lookups_by_repository(store: &dyn Store) {
+    let a = store
+        .upsert_repository(&repo("1", "a", "https://github.com/u/a", None, 5, &[]))
+        .await
+        .unwrap();
+    let b = store
+        .upsert_repository(&repo("2", "b", "https://github.com/u/b", None, 9, &[]))
+        .await
+        .unwrap();
+    let new_crate = |name, repository_id| NewCrate {
+        name,
+        description: None,
+        repository_id,
+        repository_url: None,
+        crates_io_url: "https://crates.io/crates/x",
+        documentation_url: None,
+        downloads: 0,
+        version: None,
+        keywords: &[],
+        categories: &[],
+        created_at: None,
+        homepage_url: None,
+    };
+    for (name, repository_id) in [("a-core", Some(a)), ("a-cli", Some(a)), ("loose", None)] {
+        store
+            .upsert_crate(&new_crate(name, repository_id))
+            .await
+            .unwrap();
+    }
+
+    let repos = store
+        .get_repositories_by_ids(&[a, b, uuid::Uuid::new_v4()])
+        .await
+        .expect("should query");
+    assert_eq!(repos.len(), 2);
+
+    let by_stars = store.get_repositories(10).await.expect("should query");
+    let names: Vec<_> = by_stars.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["b", "a"]);
+
+    let crates = store
+        .get_crates_by_repository_ids(&[a, b])
+        .await
+        .expect("should query");
+    let names: Vec<_> = crates.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["a-cli", "a-core"]);
+
+    assert_eq!(store.get_crates(10).await.expect("should query").len(), 3);
+    assert!(store
+        .get_npm_packages_by_repository_ids(&[a])
+        .await
+        .expect("should query")
+        .is_empty());
+}
+
+async fn get_projects_full_text_search(store: &dyn Store) {
+    let parser_topics = ["parser".to_owned()];
+    for (forge_id, name, description, stars, topics) in [
+        (
+            "1",
+            "tokenizer",
+            Some("Fast tokenizer for config parsers"),
+            50,
+            &[][..],
+        ),
+        ("2", "parsekit", Some("Combinators"), 10, &parser_topics[..]),
+        ("3", "dotfiles", Some("My shell setup"), 99, &[][..]),
+    ] {
+        let url = format!("https://github.com/u/{name}");
+        store
+            .upsert_repository(&NewRepository {
+                description,
+                ..repo(forge_id, name, &url, None, stars, topics)
+            })
+            .await
+            .expect("should insert");
+    }
+
+    let search = |q: &str, sort: Option<SortOrder>| ProjectFilters {
+        q: Some(q.to_owned()),
+        sort,
+        ..Default::default()
+    };
+
+    // Stemming matches "parsers" in a description against "parser"; the topic match outranks
+    // the description match even though the description match is more popular.
+    store.refresh_projects().await.unwrap();
+    let projects = store
+        .get_projects(&search("parser", None))
+        .await
+        .expect("should query")
+        .projects;
+    let names: Vec<_> = projects.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["parsekit", "tokenizer"]);
+
+    let projects = store
+        .get_projects(&search("parser", Some(SortOrder::Popularity)))
+        .await
+        .expect("should query")
+        .projects;
+    let names: Vec<_> = projects.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["tokenizer", "parsekit"]);
+
+    let projects = store
+        .get_projects(&search("shell -config", None))
+        .await
+        .expect("should query")
+        .projects;
+    let names: Vec<_> = projects.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["dotfiles"]);
+
+    // A blank search is ignored rather than matching nothing.
+    let projects = store
+        .get_projects(&search("   ", None))
+        .await
+        .expect("should query")
+        .projects;
+    assert_eq!(projects.len(), 3);
+}
+
+async fn get_projects_multi_value_and_excluded_filters(store: &dyn Store) {
+    let topics = |ts: &[&str]| ts.iter().map(|t| (*t).to_owned()).collect::<Vec<_>>();
+    let (both, otel, archived) = (
+        topics(&["opentelemetry", "tracing"]),
+        topics(&["opentelemetry"]),
+        topics(&["tracing", "archived"]),
+    );
+    for (forge_id, name, language, stars, topics) in [
+        ("1", "collector", Some("Rust"), 40, &both),
+        ("2", "exporter", Some("Nix"), 30, &otel),
+        ("3", "old-tracer", Some("Rust"), 20, &archived),
+        ("4", "website", Some("TypeScript"), 10, &Vec::new()),
+    ] {
+        let url = format!("https://github.com/u/{name}");
+        store
+            .upsert_repository(&repo(forge_id, name, &url, language, stars, topics))
+            .await
+            .expect("should insert");
+    }
+
+    store.refresh_projects().await.unwrap();
+    let names =
+        |projects: Vec<ProjectView>| projects.into_iter().map(|p| p.name).collect::<Vec<_>>();
+
+    // Languages combine with OR, case-insensitively.
+    let filters = ProjectFilters {
+        languages: vec!["rust".to_owned(), "NIX".to_owned()],
+        ..Default::default()
+    };
+    assert_eq!(
+        names(store.get_projects(&filters).await.unwrap().projects),
+        ["collector", "exporter", "old-tracer"]
+    );
+
+    // Topics combine with AND by default...
+    let filters = ProjectFilters::default()
+        .with_topic_terms(["opentelemetry".to_owned(), "tracing".to_owned()]);
+    assert_eq!(
+        names(store.get_projects(&filters).await.unwrap().projects),
+        ["collector"]
+    );
+
+    // ...or OR on request, and exclusions drop anything carrying the topic.
+    let filters = ProjectFilters {
+        topic_match: TopicMatch::Any,
+        ..Default::default()
+    }
+    .with_topic_terms(["opentelemetry", "tracing", "-archived"].map(String::from));
+    assert_eq!(
+        names(store.get_projects(&filters).await.unwrap().projects),
+        ["collector", "exporter"]
+    );
+
+    // Exclusions alone keep projects with no topics at all.
+    let filters = ProjectFilters::default().with_topic_terms(["-tracing".to_owned()]);
+    assert_eq!(
+        names(store.get_projects(&filters).await.unwrap().projects),
+        ["exporter", "website"]
+    );
+}
+
+async fn get_project_facets_counts_against_other_filters(store: &dyn Store) {
+    let topics = |ts: &[&str]| ts.iter().map(|t| (*t).to_owned()).collect::<Vec<_>>();
+    let (both, otel, web) = (
+        topics(&["opentelemetry", "tracing"]),
+        topics(&["opentelemetry"]),
+        topics(&["web"]),
+    );
+    for (forge_id, name, language, topics) in [
+        ("1", "collector", Some("Rust"), &both),
+        ("2", "exporter", Some("Nix"), &otel),
+        ("3", "website", Some("TypeScript"), &web),
+    ] {
+        let url = format!("https://github.com/u/{name}");
+        store
+            .upsert_repository(&repo(forge_id, name, &url, language, 10, topics))
+            .await
+            .expect("should insert");
+    }
+    let keywords = topics(&["tracing"]);
+    store
+        .upsert_crate(&NewCrate {
+            name: "tracer",
+            description: None,
+            repository_id: None,
+            repository_url: None,
+            crates_io_url: "https://crates.io/crates/tracer",
+            documentation_url: None,
+            downloads: 5,
+            version: None,
+            keywords: &keywords,
+            categories: &[],
+            created_at: None,
+            homepage_url: None,
+        })
+        .await
+        .expect("should insert crate");
+
+    let counts = |facets: &[FacetCount]| {
+        facets
+            .iter()
+            .map(|f| (f.value.to_lowercase(), f.count))
+            .collect::<Vec<_>>()
+    };
+    let pairs = |ps: &[(&str, i64)]| {
+        ps.iter()
+            .map(|(v, c)| ((*v).to_owned(), *c))
+            .collect::<Vec<_>>()
+    };
+
+    store.refresh_projects().await.unwrap();
+    let facets = store
+        .get_project_facets(&ProjectFilters::default())
+        .await
+        .unwrap();
+    assert_eq!(counts(&facets.kinds), pairs(&[("crate", 1), ("repo", 3)]));
+    // The crate's `rust` and the repository's `Rust` are one language.
+    assert_eq!(
+        counts(&facets.languages),
+        pairs(&[("nix", 1), ("rust", 2), ("typescript", 1)])
+    );
+    assert_eq!(
+        counts(&facets.topics),
+        pairs(&[("opentelemetry", 2), ("tracing", 2), ("web", 1)])
+    );
+
+    // A kind filter narrows the other groups but not the kind counts themselves, and values
+    // that no longer match stay listed with a zero count.
+    let filters = ProjectFilters {
+        kinds: vec![ProjectKind::Crate],
+        ..Default::default()
+    };
+    let facets = store.get_project_facets(&filters).await.unwrap();
+    assert_eq!(counts(&facets.kinds), pairs(&[("crate", 1), ("repo", 3)]));
+    assert_eq!(
+        counts(&facets.languages),
+        pairs(&[("nix", 0), ("rust", 1), ("typescript", 0)])
+    );
+
+    // Required topics must all match, so topics are counted within the current results...
+    let filters = ProjectFilters::default().with_topic_terms(["opentelemetry".to_owned()]);
+    let facets = store.get_project_facets(&filters).await.unwrap();
+    assert_eq!(
+        counts(&facets.topics),
+        pairs(&[("opentelemetry", 2), ("tracing", 1), ("web", 0)])
+    );
+
+    // ...unless any of them may match, when another topic widens the results instead.
+    let filters = ProjectFilters {
+        topic_match: TopicMatch::Any,
+        ..filters
+    };
+    let facets = store.get_project_facets(&filters).await.unwrap();
+    assert_eq!(
+        counts(&facets.topics),
+        pairs(&[("opentelemetry", 2), ("tracing", 2), ("web", 1)])
+    );
+    assert_eq!(counts(&facets.kinds), pairs(&[("crate", 0), ("repo", 2)]));
+}
+
+async fn get_projects_pages_through_every_sort_order(store: &dyn Store) {
+    let parser = ["parser".to_owned()];
+    let day = |d| Some(Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0).unwrap());
+    // Repeated keys make the id tie-breaker matter, and missing dates must page too.
+    for (forge_id, name, stars, date) in [
+        ("1", "parser", 30, day(1)),
+        ("2", "parser", 30, day(1)),
+        ("3", "lexer", 30, None),
+        ("4", "parser-kit", 10, day(2)),
+        ("5", "tokenizer", 10, None),
+        ("6", "grammar", 0, day(3)),
+        ("7", "parser-combinators", 50, day(2)),
+    ] {
+        let url = format!("https://github.com/u{forge_id}/{name}");
+        store
+            .upsert_repository(&NewRepository {
+                created_at: date,
+                updated_at: date,
+                ..repo(forge_id, name, &url, Some("Rust"), stars, &parser)
+            })
+            .await
+            .expect("should insert");
+    }
+    for (name, downloads) in [("parsec", 100), ("lexec", 100), ("scanner", 5)] {
+        store
+            .upsert_crate(&NewCrate {
+                name,
+                description: None,
+                repository_id: None,
+                repository_url: None,
+                crates_io_url: "https://crates.io/crates/x",
+                documentation_url: None,
+                downloads,
+                version: None,
+                keywords: &parser,
+                categories: &[],
+                created_at: None,
+                homepage_url: None,
+            })
+            .await
+            .expect("should insert crate");
+    }
+    for artifact in ["1", "4", "parsec"] {
+        let kind = if artifact == "parsec" {
+            "crate"
+        } else {
+            "repo"
+        };
+        store
+            .upsert_release(&NewRelease {
+                artifact_kind: kind,
+                artifact,
+                version: "1.0.0",
+                released_at: day(5),
+                yanked: false,
+                notes_url: None,
+            })
+            .await
+            .expect("should insert release");
+    }
+
+    store.refresh_projects().await.unwrap();
+    for q in [None, Some("parser")] {
+        for sort in SortOrder::ALL {
+            for direction in [SortDirection::Asc, SortDirection::Desc] {
+                let filters = ProjectFilters {
+                    q: q.map(str::to_owned),
+                    sort: Some(sort),
+                    direction: Some(direction),
+                    ..Default::default()
+                };
+                let everything = store.get_projects(&filters).await.unwrap();
+                assert_eq!(everything.next_cursor, None);
+
+                let mut paged = Vec::new();
+                let mut cursor = None;
+                loop {
+                    let page = store
+                        .get_projects(&ProjectFilters {
+                            limit: Some(3),
+                            cursor: cursor.take(),
+                            ..filters.clone()
+                        })
+                        .await
+                        .unwrap();
+                    assert!(page.projects.len() <= 3);
+                    paged.extend(page.projects.into_iter().map(|p| p.id));
+                    match page.next_cursor {
+                        Some(next) => {
+                            assert_eq!((next.sort, next.direction), (sort, direction));
+                            // Round-trip through the URL form, as a client would.
+                            cursor = Some(next.to_string().parse().unwrap());
+                        }
+                        None => break,
+                    }
+                }
+
+                let expected: Vec<_> = everything.projects.iter().map(|p| p.id).collect();
+                assert_eq!(paged, expected, "{sort:?} {direction:?} with q={q:?}");
+            }
+        }
+    }
+}
+
+async fn get_projects_sorts_by_each_key_in_either_direction(store: &dyn Store) {
+    let day = |d| Some(Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0).unwrap());
+    for (forge_id, name, stars, created, updated) in [
+        ("1", "alpha", 5, day(3), day(9)),
+        ("2", "beta", 50, day(1), day(1)),
+        ("3", "gamma", 0, None, day(4)),
+    ] {
+        let url = format!("https://github.com/u/{name}");
+        store
+            .upsert_repository(&NewRepository {
+                created_at: created,
+                updated_at: updated,
+                ..repo(forge_id, name, &url, None, stars, &[])
+            })
+            .await
+            .expect("should insert");
+    }
+    store
+        .upsert_crate(&NewCrate {
+            name: "delta",
+            description: None,
+            repository_id: None,
+            repository_url: None,
+            crates_io_url: "https://crates.io/crates/delta",
+            documentation_url: None,
+            downloads: 10_000,
+            version: None,
+            keywords: &[],
+            categories: &[],
+            created_at: day(2),
+            homepage_url: None,
+        })
+        .await
+        .expect("should insert crate");
+    for (artifact, version, released, yanked) in [
+        ("1", "1.0.0", day(5), false),
+        ("3", "1.0.0", day(6), false),
+        ("3", "1.1.0", day(8), true),
+    ] {
+        store
+            .upsert_release(&NewRelease {
+                artifact_kind: "repo",
+                artifact,
+                version,
+                released_at: released,
+                yanked,
+                notes_url: None,
+            })
+            .await
+            .expect("should insert release");
+    }
+    store
+        .upsert_release(&NewRelease {
+            artifact_kind: "crate",
+            artifact: "delta",
+            version: "0.1.0",
+            released_at: day(7),
+            yanked: false,
+            notes_url: None,
+        })
+        .await
+        .expect("should insert release");
+
+    store.refresh_projects().await.unwrap();
+    let names = |sort, direction| async move {
+        let filters = ProjectFilters {
+            sort: Some(sort),
+            direction,
+            ..Default::default()
+        };
+        store
+            .get_projects(&filters)
+            .await
+            .unwrap()
+            .projects
+            .into_iter()
+            .map(|p| p.name)
+            .collect::<Vec<_>>()
+    };
+    let asc = Some(SortDirection::Asc);
+
+    // Missing values go last whichever way the list runs.
+    assert_eq!(
+        names(SortOrder::Updated, None).await,
+        ["alpha", "gamma", "beta", "delta"]
+    );
+    assert_eq!(
+        names(SortOrder::Updated, asc).await,
+        ["beta", "gamma", "alpha", "delta"]
+    );
+    assert_eq!(
+        names(SortOrder::Created, None).await,
+        ["alpha", "delta", "beta", "gamma"]
+    );
+    assert_eq!(
+        names(SortOrder::Created, asc).await,
+        ["beta", "delta", "alpha", "gamma"]
+    );
+    // Yanked releases don't count.
+    assert_eq!(
+        names(SortOrder::Released, None).await,
+        ["delta", "gamma", "alpha", "beta"]
+    );
+    assert_eq!(
+        names(SortOrder::Stars, None).await,
+        ["beta", "alpha", "gamma", "delta"]
+    );
+    assert_eq!(names(SortOrder::Downloads, asc).await[0], "delta");
+    // Ten thousand downloads outweigh fifty stars.
+    assert_eq!(
+        names(SortOrder::Popularity, None).await,
+        ["delta", "beta", "alpha", "gamma"]
+    );
+    assert_eq!(
+        names(SortOrder::Name, None).await,
+        ["alpha", "beta", "delta", "gamma"]
+    );
+    assert_eq!(
+        names(SortOrder::Name, Some(SortDirection::Desc)).await,
+        ["gamma", "delta", "beta", "alpha"]
+    );
+}
+
+/// The memory store sorts from each project's details as the SQL stores sort from columns.
+#[tokio::test]
+async fn memory_store_sorts_by_each_key_in_either_direction() {
+    get_projects_sorts_by_each_key_in_either_direction(&crate::store::MemoryStore::default()).await;
+}
+
+async fn get_projects_ignores_cursor_from_another_sort(store: &dyn Store) {
+    for (forge_id, name) in [("1", "alpha"), ("2", "beta")] {
+        let url = format!("https://github.com/u/{name}");
+        store
+            .upsert_repository(&repo(forge_id, name, &url, None, 1, &[]))
+            .await
+            .expect("should insert");
+    }
+
+    store.refresh_projects().await.unwrap();
+    for (sort, direction) in [
+        (SortOrder::Popularity, SortDirection::Desc),
+        (SortOrder::Name, SortDirection::Desc),
+    ] {
+        let filters = ProjectFilters {
+            sort: Some(SortOrder::Name),
+            cursor: Some(ProjectCursor {
+                sort,
+                direction,
+                key: CursorKey::Text("zzz".to_owned()),
+                id: uuid::Uuid::nil(),
+            }),
+            ..Default::default()
+        };
+        let page = store.get_projects(&filters).await.unwrap();
+        assert_eq!(page.projects.len(), 2, "{sort:?} {direction:?}");
+    }
+}
+
+async fn get_project_detail_joins_links_and_repository(store: &dyn Store) {
+    let repo_id = store
+        .upsert_repository(&NewRepository {
+            homepage_url: Some("https://widget.dev"),
+            ..repo(
+                "user/widget",
+                "widget",
+                "https://github.com/user/widget",
+                Some("Rust"),
+                12,
+                &[],
+            )
+        })
+        .await
+        .expect("should insert repository");
+    store
+        .upsert_crate(&NewCrate {
+            name: "widget",
+            description: Some("Widgets"),
+            repository_id: Some(repo_id),
+            repository_url: None,
+            crates_io_url: "https://crates.io/crates/widget",
+            documentation_url: Some("https://docs.rs/widget"),
+            downloads: 300,
+            version: Some("0.2.0"),
+            keywords: &[],
+            categories: &[],
+            created_at: None,
+            homepage_url: None,
+        })
+        .await
+        .expect("should insert crate");
+
+    store.refresh_projects().await.unwrap();
+    let krate = store
+        .get_project_detail(ProjectKind::Crate, "widget")
+        .await
+        .unwrap()
+        .expect("crate should be found");
+    assert_eq!(krate.project.name, "widget");
+    assert_eq!(krate.downloads, Some(300));
+    assert_eq!(krate.stars, Some(12));
+    assert_eq!(
+        krate.documentation_url.as_deref(),
+        Some("https://docs.rs/widget")
+    );
+    // Falls back to the repository's homepage when the crate has none.
+    assert_eq!(krate.homepage_url.as_deref(), Some("https://widget.dev"));
+    assert_eq!(
+        krate.repository_url.as_deref(),
+        Some("https://github.com/user/widget")
+    );
+    assert_eq!(krate.project.id, repo_id);
+
+    // The repository is part of the same project, so it finds the same page.
+    let by_repo = store
+        .get_project_detail(ProjectKind::Repo, "widget")
+        .await
+        .unwrap()
+        .expect("project should be found through its repository");
+    assert_eq!(by_repo.project.id, repo_id);
+    assert_eq!(by_repo.project.kind, ProjectKind::Crate);
+
+    store
+        .upsert_repository(&repo(
+            "user/gadget",
+            "gadget",
+            "https://github.com/user/gadget",
+            None,
+            3,
+            &[],
+        ))
+        .await
+        .expect("should insert repository");
+    store.refresh_projects().await.unwrap();
+    let repository = store
+        .get_project_detail(ProjectKind::Repo, "gadget")
+        .await
+        .unwrap()
+        .expect("repository should be found");
+    assert_eq!(repository.project.kind, ProjectKind::Repo);
+    assert_eq!(repository.repository_forge.as_deref(), Some("github"));
+    assert!(repository.documentation_url.is_none());
+
+    assert!(store
+        .get_project_detail(ProjectKind::Npm, "widget")
+        .await
+        .unwrap()
+        .is_none());
+}
+
+async fn get_related_contributions_matches_owner_or_name(store: &dyn Store) {
+    let day = |d| Some(Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0).unwrap());
+    insert_contribution(store, "github", "Tokio-rs", 1, day(1)).await;
+    insert_contribution(store, "github", "tokio-rs", 2, day(3)).await;
+    insert_contribution(store, "github", "tokio-rs", 3, None).await;
+    insert_contribution(store, "github", "serde-rs", 4, day(2)).await;
+
+    let related = store
+        .get_related_contributions(&["TOKIO-RS".to_owned()], 10)
+        .await
+        .unwrap();
+    let urls: Vec<_> = related.iter().map(|c| c.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        [
+            "https://github.com/tokio-rs/repo/pull/2",
+            "https://github.com/Tokio-rs/repo/pull/1",
+        ]
+    );
+
+    // Every contribution here is to a repository called "repo".
+    let by_name = store
+        .get_related_contributions(&["repo".to_owned()], 2)
+        .await
+        .unwrap();
+    assert_eq!(by_name.len(), 2);
+    assert!(store
+        .get_related_contributions(&[], 10)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+async fn readmes_store_and_show_on_project_detail(store: &dyn Store) {
+    let id = store
+        .upsert_repository(&repo(
+            "user/tool",
+            "tool",
+            "https://github.com/user/tool",
+            None,
+            1,
+            &[],
+        ))
+        .await
+        .expect("should insert repository");
+
+    assert!(store.get_readme_hash(id).await.unwrap().is_none());
+
+    for (hash, html) in [("a", "<p>first</p>"), ("b", "<p>second</p>")] {
+        store
+            .upsert_readme(&NewReadme {
+                repository_id: id,
+                content_hash: hash,
+                html,
+            })
+            .await
+            .expect("should store readme");
+    }
+
+    assert_eq!(
+        store.get_readme_hash(id).await.unwrap().as_deref(),
+        Some("b")
+    );
+    store.refresh_projects().await.unwrap();
+    let detail = store
+        .get_project_detail(ProjectKind::Repo, "tool")
+        .await
+        .unwrap()
+        .expect("repository should be found");
+    assert_eq!(detail.readme_html.as_deref(), Some("<p>second</p>"));
+
+    assert!(store.delete_readme(id).await.unwrap());
+    assert!(!store.delete_readme(id).await.unwrap());
+    assert!(store.get_readme_hash(id).await.unwrap().is_none());
+}
+
+async fn artifacts_sharing_a_repository_form_one_project(store: &dyn Store) {
+    let repo_id = store
+        .upsert_repository(&repo(
+            "user/ferry",
+            "ferry",
+            "https://github.com/user/ferry",
+            Some("Rust"),
+            40,
+            &[],
+        ))
+        .await
+        .expect("should insert repository");
+    store
+        .upsert_crate(&NewCrate {
+            name: "ferry",
+            description: Some("Moves things"),
+            repository_id: Some(repo_id),
+            repository_url: None,
+            crates_io_url: "https://crates.io/crates/ferry",
+            documentation_url: None,
+            downloads: 100,
+            version: Some("1.0.0"),
+            keywords: &[],
+            categories: &[],
+            created_at: None,
+            homepage_url: None,
+        })
+        .await
+        .expect("should insert crate");
+    store
+        .upsert_npm_package(&NewNpmPackage {
+            name: "@user/ferry-wasm",
+            scope: Some("user"),
+            description: None,
+            repository_id: Some(repo_id),
+            repository_url: None,
+            npm_url: "https://www.npmjs.com/package/@user/ferry-wasm",
+            homepage_url: None,
+            downloads_weekly: 25,
+            version: Some("1.0.1"),
+            keywords: &[],
+        })
+        .await
+        .expect("should insert npm package");
+
+    store.refresh_projects().await.unwrap();
+    let page = store
+        .get_projects(&ProjectFilters::default())
+        .await
+        .unwrap();
+    assert_eq!(page.projects.len(), 1);
+    let project = &page.projects[0];
+    assert_eq!(project.id, repo_id);
+    // The crate is named like the repository, so it leads; the repository ranks next since
+    // it publishes more than one package.
+    assert_eq!(project.kind, ProjectKind::Crate);
+    assert_eq!(project.popularity, 100);
+    let artifacts: Vec<_> = project
+        .artifacts
+        .iter()
+        .map(|a| (a.kind, a.name.as_str()))
+        .collect();
+    assert_eq!(
+        artifacts,
+        [
+            (ProjectKind::Crate, "ferry"),
+            (ProjectKind::Repo, "ferry"),
+            (ProjectKind::Npm, "@user/ferry-wasm"),
+        ]
+    );
+
+    // Any of its artifacts' kinds matches the project.
+    let npm = store
+        .get_projects(&ProjectFilters {
+            kinds: vec![ProjectKind::Npm],
+            ..ProjectFilters::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(npm.projects.len(), 1);
+    assert_eq!(npm.projects[0].id, repo_id);
+
+    let facets = store
+        .get_project_facets(&ProjectFilters::default())
+        .await
+        .unwrap();
+    for facet in &facets.kinds {
+        assert_eq!(facet.count, 1, "{}", facet.value);
+    }
+}
+
+async fn artifacts_link_to_repositories_by_canonical_url(store: &dyn Store) {
+    let declared = CanonicalUrl::parse("git+https://github.com/User/Late.git").unwrap();
+    store
+        .upsert_crate(&NewCrate {
+            name: "late",
+            description: None,
+            repository_id: None,
+            repository_url: Some(&declared),
+            crates_io_url: "https://crates.io/crates/late",
+            documentation_url: None,
+            downloads: 1,
+            version: None,
+            keywords: &[],
+            categories: &[],
+            created_at: None,
+            homepage_url: None,
+        })
+        .await
+        .expect("should insert crate");
+    assert_eq!(store.relink_artifacts().await.unwrap(), 0);
+
+    // The repository syncs afterwards, spelled the way the forge reports it.
+    let repo_id = store
+        .upsert_repository(&repo(
+            "user/late",
+            "Late",
+            "https://github.com/user/Late",
+            None,
+            0,
+            &[],
+        ))
+        .await
+        .expect("should insert repository");
+
+    let found = store
+        .get_repositories_by_urls(std::slice::from_ref(&declared))
+        .await
+        .unwrap();
+    assert_eq!(found.get(declared.as_str()), Some(&repo_id));
+
+    assert_eq!(store.relink_artifacts().await.unwrap(), 1);
+    store.refresh_projects().await.unwrap();
+    let projects = store.get_all_projects().await.unwrap();
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].id, repo_id);
+    assert!(projects[0]
+        .artifacts
+        .iter()
+        .any(|a| a.kind == ProjectKind::Crate && a.name == "late"));
+    assert_eq!(store.relink_artifacts().await.unwrap(), 0);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn get_repository_by_url_matches_canonical_url(pool: PgPool) {
+    let repo_id = upsert_repository(
+        &pool,
+        &repo(
+            "user/late",
+            "Late",
+            "https://github.com/user/Late",
+            None,
+            0,
+            &[],
+        ),
+    )
+    .await
+    .expect("should insert repository");
+
+    let declared = CanonicalUrl::parse("git+https://github.com/User/Late.git").unwrap();
+    assert_eq!(
+        get_repository_by_url(&pool, &declared).await.unwrap(),
+        Some(repo_id)
+    );
+}
+
+async fn workspace_members_mark_their_crates(store: &dyn Store) {
+    let repo_id = store
+        .upsert_repository(&repo(
+            "user/tools",
+            "tools",
+            "https://github.com/user/tools",
+            Some("Rust"),
+            5,
+            &[],
+        ))
+        .await
+        .expect("should insert repository");
+    for name in ["tools-core", "tools-cli", "tools-extra"] {
+        store
+            .upsert_crate(&NewCrate {
+                name,
+                description: None,
+                repository_id: Some(repo_id),
+                repository_url: None,
+                crates_io_url: "https://crates.io/crates/x",
+                documentation_url: None,
+                downloads: 1,
+                version: None,
+                keywords: &[],
+                categories: &[],
+                created_at: None,
+                homepage_url: None,
+            })
+            .await
+            .expect("should insert crate");
+    }
+
+    let members = [
+        NewWorkspaceMember {
+            crate_name: "tools-cli",
+            path: "crates/cli",
+        },
+        NewWorkspaceMember {
+            crate_name: "tools-core",
+            path: "crates/core",
+        },
+    ];
+    assert!(store
+        .get_workspace_pushed_at(repo_id)
+        .await
+        .unwrap()
+        .is_none());
+    let pushed_at = Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap();
+    store
+        .set_workspace_members(repo_id, Some(pushed_at), &members)
+        .await
+        .unwrap();
+    // Setting them again replaces rather than adds.
+    store
+        .set_workspace_members(repo_id, Some(pushed_at), &members)
+        .await
+        .unwrap();
+    assert_eq!(
+        store.get_workspace_pushed_at(repo_id).await.unwrap(),
+        Some(pushed_at)
+    );
+
+    let workspace_paths = |project: &ProjectView| {
+        let mut paths: Vec<(String, Option<String>)> = project
+            .artifacts
+            .iter()
+            .filter(|a| a.kind == ProjectKind::Crate)
+            .map(|a| (a.name.clone(), a.workspace_path.clone()))
+            .collect();
+        paths.sort();
+        paths
+    };
+    store.refresh_projects().await.unwrap();
+    let page = store
+        .get_projects(&ProjectFilters::default())
+        .await
+        .unwrap();
+    assert_eq!(page.projects.len(), 1);
+    assert_eq!(
+        workspace_paths(&page.projects[0]),
+        [
+            ("tools-cli".to_owned(), Some("crates/cli".to_owned())),
+            ("tools-core".to_owned(), Some("crates/core".to_owned())),
+            ("tools-extra".to_owned(), None),
+        ]
+    );
+
+    store
+        .set_workspace_members(repo_id, None, &[])
+        .await
+        .unwrap();
+    assert!(store
+        .get_workspace_pushed_at(repo_id)
+        .await
+        .unwrap()
+        .is_none());
+    store.refresh_projects().await.unwrap();
+    let page = store
+        .get_projects(&ProjectFilters::default())
+        .await
+        .unwrap();
+    assert!(workspace_paths(&page.projects[0])
+        .iter()
+        .all(|(_, path)| path.is_none()));
+}
+
+async fn projects_change_only_when_refreshed(store: &dyn Store) {
+    let count = || async {
+        store
+            .get_projects(&ProjectFilters::default())
+            .await
+            .unwrap()
+            .projects
+            .len()
+    };
+
+    store
+        .upsert_repository(&repo(
+            "user/tool",
+            "tool",
+            "https://github.com/user/tool",
+            None,
+            1,
+            &[],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(count().await, 0);
+
+    store.refresh_projects().await.unwrap();
+    assert_eq!(count().await, 1);
+
+    store
+        .delete_stale_repositories("github", &[])
+        .await
+        .unwrap();
+    assert_eq!(count().await, 1);
+    store.refresh_projects().await.unwrap();
+    assert_eq!(count().await, 0);
+}
+
+async fn sync_runs_record_the_last_finish(store: &dyn Store) {
+    assert_eq!(store.get_last_sync_at().await.unwrap(), None);
+
+    store.record_sync_run(None).await.unwrap();
+    let first = store
+        .get_last_sync_at()
+        .await
+        .unwrap()
+        .expect("should record a run");
+
+    store.record_sync_run(Some("rate limited")).await.unwrap();
+    let second = store
+        .get_last_sync_at()
+        .await
+        .unwrap()
+        .expect("should record a run");
+    assert!(second >= first);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn sync_runs_keep_the_error_that_stopped_them(pool: PgPool) {
+    record_sync_run(&pool, None).await.unwrap();
+    record_sync_run(&pool, Some("rate limited")).await.unwrap();
+
+    let errors: Vec<Option<String>> = sqlx::query_scalar("SELECT error FROM sync_runs ORDER BY id")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(errors, [None, Some("rate limited".to_owned())]);
+}
+
+fn sync_job(kind: &str, max_attempts: i32) -> NewSyncJob<'_> {
+    NewSyncJob {
+        kind,
+        payload: serde_json::json!({ "kind": kind }),
+        max_attempts,
+    }
+}
+
+const LEASE: std::time::Duration = std::time::Duration::from_secs(60);
+
+#[sqlx::test(migrations = "./migrations")]
+async fn sync_jobs_finish_only_after_the_rest(pool: PgPool) {
+    let run = [
+        sync_job("crates", 3),
+        sync_job("npm_packages", 3),
+        sync_job("finish", 3),
+    ];
+    let mut conn = pool.acquire().await.unwrap();
+    assert!(start_sync_run(&mut conn, &run).await.unwrap());
+
+    let crates = claim_sync_job(&pool, LEASE).await.unwrap().unwrap();
+    assert_eq!(crates.kind, "crates");
+    assert_eq!(crates.attempts, 1);
+    let npm = claim_sync_job(&pool, LEASE).await.unwrap().unwrap();
+    assert_eq!(npm.kind, "npm_packages");
+    assert!(claim_sync_job(&pool, LEASE).await.unwrap().is_none());
+
+    // A run still going isn't started again.
+    assert!(!start_sync_run(&mut conn, &run).await.unwrap());
+
+    assert!(
+        complete_sync_job(&pool, &crates, &[sync_job("crate_versions", 3)])
+            .await
+            .unwrap()
+    );
+    assert!(complete_sync_job(&pool, &npm, &[]).await.unwrap());
+
+    let versions = claim_sync_job(&pool, LEASE).await.unwrap().unwrap();
+    assert_eq!(versions.kind, "crate_versions");
+    assert!(claim_sync_job(&pool, LEASE).await.unwrap().is_none());
+    assert!(complete_sync_job(&pool, &versions, &[]).await.unwrap());
+
+    let finish = claim_sync_job(&pool, LEASE).await.unwrap().unwrap();
+    assert_eq!(finish.kind, "finish");
+    assert!(complete_sync_job(&pool, &finish, &[]).await.unwrap());
+
+    assert!(start_sync_run(&mut conn, &run).await.unwrap());
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn sync_jobs_retry_until_their_attempts_run_out(pool: PgPool) {
+    let started_at = Utc::now() - chrono::Duration::seconds(1);
+    enqueue_sync_jobs(&pool, &[sync_job("crates", 2)])
+        .await
+        .unwrap();
+
+    let first = claim_sync_job(&pool, LEASE).await.unwrap().unwrap();
+    assert!(fail_sync_job(&pool, &first, "timed out", Some(LEASE))
+        .await
+        .unwrap());
+    // Not due until the backoff has passed
+    assert!(claim_sync_job(&pool, LEASE).await.unwrap().is_none());
+
+    sqlx::query("UPDATE sync_jobs SET run_at = now()")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let second = claim_sync_job(&pool, LEASE).await.unwrap().unwrap();
+    assert_eq!(second.id, first.id);
+    assert_eq!(second.attempts, 2);
+    assert!(fail_sync_job(&pool, &second, "timed out again", None)
+        .await
+        .unwrap());
+
+    assert!(claim_sync_job(&pool, LEASE).await.unwrap().is_none());
+    assert_eq!(count_dead_sync_jobs(&pool, started_at).await.unwrap(), 1);
+    let (status, error): (String, Option<String>) =
+        sqlx::query_as("SELECT status, last_error FROM sync_jobs")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "dead");
+    assert_eq!(error.as_deref(), Some("timed out again"));
+
+    assert_eq!(
+        delete_finished_sync_jobs(&pool, started_at).await.unwrap(),
+        0
+    );
+    assert_eq!(
+        delete_finished_sync_jobs(&pool, Utc::now() + chrono::Duration::days(1))
+            .await
+            .unwrap(),
+        1
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn sync_jobs_skip_locked_rows_and_reclaim_expired_leases(pool: PgPool) {
+    enqueue_sync_jobs(&pool, &[sync_job("crates", 3), sync_job("npm_packages", 3)])
+        .await
+        .unwrap();
+
+    // Another worker in the middle of claiming the first job
+    let mut tx = pool.begin().await.unwrap();
+    let locked: i64 = sqlx::query_scalar("SELECT id FROM sync_jobs ORDER BY id LIMIT 1 FOR UPDATE")
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+    let claimed = claim_sync_job(&pool, LEASE).await.unwrap().unwrap();
+    assert_ne!(claimed.id, locked);
+    assert_eq!(claimed.kind, "npm_packages");
+    tx.rollback().await.unwrap();
+
+    // Its worker died
+    sqlx::query("UPDATE sync_jobs SET locked_until = now() - interval '1 second' WHERE id = $1")
+        .bind(claimed.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let crates = claim_sync_job(&pool, LEASE).await.unwrap().unwrap();
+    assert_eq!(crates.id, locked);
+    let reclaimed = claim_sync_job(&pool, LEASE).await.unwrap().unwrap();
+    assert_eq!(reclaimed.id, claimed.id);
+    assert_eq!(reclaimed.attempts, 2);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn sync_jobs_queue_what_they_found_once_however_often_they_run(pool: PgPool) {
+    enqueue_sync_jobs(&pool, &[sync_job("crates", 3)])
+        .await
+        .unwrap();
+    let found = [sync_job("crate_versions", 3)];
+
+    // The first attempt's lease runs out mid-run and another worker retries it.
+    let first = claim_sync_job(&pool, LEASE).await.unwrap().unwrap();
+    sqlx::query("UPDATE sync_jobs SET locked_until = now() - interval '1 second'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let retry = claim_sync_job(&pool, LEASE).await.unwrap().unwrap();
+    assert_eq!(retry.id, first.id);
+    assert!(complete_sync_job(&pool, &retry, &found).await.unwrap());
+
+    // The first attempt finishing late changes nothing.
+    assert!(!complete_sync_job(&pool, &first, &found).await.unwrap());
+    assert!(!fail_sync_job(&pool, &first, "too late", None)
+        .await
+        .unwrap());
+
+    let children: Vec<(String, String)> =
+        sqlx::query_as("SELECT kind, status FROM sync_jobs WHERE id <> $1")
+            .bind(first.id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        children,
+        [("crate_versions".to_owned(), "queued".to_owned())]
+    );
+    let status: String = sqlx::query_scalar("SELECT status FROM sync_jobs WHERE id = $1")
+        .bind(first.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "done");
+}
@@ -1,3 +1,5 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -5,9 +7,31 @@ pub enum AppError {
     #[error("database error: {0}")]
     Database(#[from] sqlx::Error),
 
+    #[error("database not available")]
+    DatabaseUnavailable,
+
     #[error("sync error: {0}")]
     Sync(#[from] crate::sync::SyncError),
 
+    #[error("serialisation error: {0}")]
+    Serialisation(#[from] serde_json::Error),
+
     #[error("not found: {0}")]
     NotFound(String),
 }
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        match self {
+            AppError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()).into_response(),
+            AppError::DatabaseUnavailable => {
+                (StatusCode::SERVICE_UNAVAILABLE, self.to_string()).into_response()
+            }
+            // Internal details stay in the logs rather than the response body.
+            AppError::Database(_) | AppError::Sync(_) | AppError::Serialisation(_) => {
+                tracing::error!(error = %self, "request failed");
+                (StatusCode::INTERNAL_SERVER_ERROR, "internal server error").into_response()
+            }
+        }
+    }
+}
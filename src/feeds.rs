@@ -0,0 +1,560 @@
+//! Atom and JSON Feed output for releases, contributions and new projects.
+//!
+//! Every feed is served in both formats, at `/feed.atom` and `/feed.json` for everything and
+//! under a per-kind prefix (`/crates/feed.atom`, `/contributions/feed.json`, ...) for a single
+//! stream. Entry IDs are derived from natural keys rather than database IDs so they survive a
+//! rebuilt database.
+
+use axum::{
+    extract::Extension,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::db::{ContributionFilters, ProjectKind};
+use crate::error::AppError;
+use crate::http_cache::if_none_match;
+use crate::state::AppState;
+use crate::store::Store;
+
+const SITE_URL: &str = "https://djv.sh";
+const AUTHOR: &str = "Daniel Verrall";
+const TAG_PREFIX: &str = "tag:djv.sh,2024:";
+const FEED_LIMIT: i64 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeedScope {
+    All,
+    Crates,
+    Npm,
+    Repos,
+    Contributions,
+}
+
+impl FeedScope {
+    const ALL: [FeedScope; 5] = [
+        FeedScope::All,
+        FeedScope::Crates,
+        FeedScope::Npm,
+        FeedScope::Repos,
+        FeedScope::Contributions,
+    ];
+
+    fn prefix(self) -> &'static str {
+        match self {
+            FeedScope::All => "",
+            FeedScope::Crates => "/crates",
+            FeedScope::Npm => "/npm",
+            FeedScope::Repos => "/repos",
+            FeedScope::Contributions => "/contributions",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            FeedScope::All => "Daniel Verrall",
+            FeedScope::Crates => "Daniel Verrall · crates",
+            FeedScope::Npm => "Daniel Verrall · npm packages",
+            FeedScope::Repos => "Daniel Verrall · repositories",
+            FeedScope::Contributions => "Daniel Verrall · contributions",
+        }
+    }
+
+    /// The project kind (and matching release `artifact_kind`) this feed is limited to
+    fn project_kind(self) -> Option<ProjectKind> {
+        match self {
+            FeedScope::Crates => Some(ProjectKind::Crate),
+            FeedScope::Npm => Some(ProjectKind::Npm),
+            FeedScope::Repos => Some(ProjectKind::Repo),
+            FeedScope::All | FeedScope::Contributions => None,
+        }
+    }
+
+    fn includes_projects(self) -> bool {
+        self != FeedScope::Contributions
+    }
+
+    fn includes_contributions(self) -> bool {
+        matches!(self, FeedScope::All | FeedScope::Contributions)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeedFormat {
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    fn file_name(self) -> &'static str {
+        match self {
+            FeedFormat::Atom => "feed.atom",
+            FeedFormat::Json => "feed.json",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+            FeedFormat::Json => "application/feed+json; charset=utf-8",
+        }
+    }
+}
+
+fn feed_path(scope: FeedScope, format: FeedFormat) -> String {
+    format!("{}/{}", scope.prefix(), format.file_name())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct FeedEntry {
+    id: String,
+    title: String,
+    url: String,
+    updated: DateTime<Utc>,
+    summary: Option<String>,
+    category: &'static str,
+}
+
+async fn load_entries(store: &dyn Store, scope: FeedScope) -> Result<Vec<FeedEntry>, sqlx::Error> {
+    let mut entries = Vec::new();
+
+    if scope.includes_projects() {
+        let kind = scope.project_kind();
+        let artifact_kind = kind.map(|k| k.to_string());
+
+        for r in store
+            .get_recent_releases(artifact_kind.as_deref(), FEED_LIMIT)
+            .await?
+        {
+            let Some(released_at) = r.released_at else {
+                continue;
+            };
+            entries.push(FeedEntry {
+                id: format!(
+                    "{TAG_PREFIX}release:{}/{}@{}",
+                    r.artifact_kind, r.artifact, r.version
+                ),
+                title: format!("{} {}", r.artifact, r.version),
+                url: r.notes_url.unwrap_or_else(|| SITE_URL.to_owned()),
+                updated: released_at,
+                summary: Some(format!("Released {} {}", r.artifact, r.version)),
+                category: "release",
+            });
+        }
+
+        for p in store.get_new_projects(kind, FEED_LIMIT).await? {
+            entries.push(FeedEntry {
+                id: format!("{TAG_PREFIX}project:{}/{}", p.kind, p.name),
+                title: format!("New {}: {}", p.kind, p.name),
+                url: p.url,
+                updated: p.first_seen_at,
+                summary: p.description,
+                category: "project",
+            });
+        }
+    }
+
+    if scope.includes_contributions() {
+        let page = store
+            .get_contribution_page(&ContributionFilters::default(), FEED_LIMIT)
+            .await?;
+        for c in page.contributions {
+            let Some(merged_at) = c.merged_at else {
+                continue;
+            };
+            let repo = format!("{}/{}", c.repo_owner, c.repo_name);
+            entries.push(FeedEntry {
+                id: format!("{TAG_PREFIX}contribution:{}", c.url),
+                title: match c.title {
+                    Some(title) => format!("{repo}: {title}"),
+                    None => repo.clone(),
+                },
+                url: c.url,
+                updated: merged_at,
+                summary: Some(match (c.additions, c.deletions) {
+                    (Some(a), Some(d)) => format!("Merged into {repo} (+{a}/\u{2212}{d})"),
+                    _ => format!("Merged into {repo}"),
+                }),
+                category: "contribution",
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| b.updated.cmp(&a.updated).then_with(|| a.id.cmp(&b.id)));
+    entries.truncate(usize::try_from(FEED_LIMIT).unwrap_or(0));
+    Ok(entries)
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+fn rfc3339(dt: DateTime<Utc>) -> String {
+    dt.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn render_atom(scope: FeedScope, entries: &[FeedEntry], updated: DateTime<Utc>) -> String {
+    use std::fmt::Write;
+
+    let self_url = format!("{SITE_URL}{}", feed_path(scope, FeedFormat::Atom));
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    let _ = writeln!(xml, "  <id>{TAG_PREFIX}feed{}</id>", scope.prefix());
+    let _ = writeln!(xml, "  <title>{}</title>", escape_xml(scope.title()));
+    let _ = writeln!(xml, "  <updated>{}</updated>", rfc3339(updated));
+    let _ = writeln!(
+        xml,
+        "  <link rel=\"self\" href=\"{}\"/>",
+        escape_xml(&self_url)
+    );
+    let _ = writeln!(xml, "  <link rel=\"alternate\" href=\"{SITE_URL}/\"/>");
+    let _ = writeln!(
+        xml,
+        "  <author><name>{}</name></author>",
+        escape_xml(AUTHOR)
+    );
+
+    for entry in entries {
+        xml.push_str("  <entry>\n");
+        let _ = writeln!(xml, "    <id>{}</id>", escape_xml(&entry.id));
+        let _ = writeln!(xml, "    <title>{}</title>", escape_xml(&entry.title));
+        let _ = writeln!(xml, "    <link href=\"{}\"/>", escape_xml(&entry.url));
+        let _ = writeln!(xml, "    <updated>{}</updated>", rfc3339(entry.updated));
+        let _ = writeln!(xml, "    <category term=\"{}\"/>", entry.category);
+        if let Some(ref summary) = entry.summary {
+            let _ = writeln!(xml, "    <summary>{}</summary>", escape_xml(summary));
+        }
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+#[derive(Serialize)]
+struct JsonFeed<'a> {
+    version: &'static str,
+    title: &'static str,
+    home_page_url: String,
+    feed_url: String,
+    authors: [JsonFeedAuthor; 1],
+    items: Vec<JsonFeedItem<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonFeedAuthor {
+    name: &'static str,
+}
+
+#[derive(Serialize)]
+struct JsonFeedItem<'a> {
+    id: &'a str,
+    url: &'a str,
+    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_text: Option<&'a str>,
+    date_published: String,
+    tags: [&'static str; 1],
+}
+
+fn render_json(scope: FeedScope, entries: &[FeedEntry]) -> Result<String, serde_json::Error> {
+    let feed = JsonFeed {
+        version: "https://jsonfeed.org/version/1.1",
+        title: scope.title(),
+        home_page_url: format!("{SITE_URL}/"),
+        feed_url: format!("{SITE_URL}{}", feed_path(scope, FeedFormat::Json)),
+        authors: [JsonFeedAuthor { name: AUTHOR }],
+        items: entries
+            .iter()
+            .map(|e| JsonFeedItem {
+                id: &e.id,
+                url: &e.url,
+                title: &e.title,
+                // JSON Feed requires content; fall back to the title when there's no summary.
+                content_text: Some(e.summary.as_deref().unwrap_or(&e.title)),
+                date_published: rfc3339(e.updated),
+                tags: [e.category],
+            })
+            .collect(),
+    };
+
+    serde_json::to_string_pretty(&feed)
+}
+
+/// Strong validator for the rendered `body` as sent to a client asking for
+/// `accept_encoding`, which picks the compressed representation.
+fn etag_for(body: &str, accept_encoding: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(body.as_bytes());
+    hasher.update([0]);
+    hasher.update(accept_encoding.as_bytes());
+    let digest = format!("{:x}", hasher.finalize());
+    format!("\"{}\"", &digest[..32])
+}
+
+fn http_date(dt: DateTime<Utc>) -> String {
+    dt.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Whether the client's cached copy is still current. `If-None-Match` takes precedence over
+/// `If-Modified-Since`, as RFC 9110 requires.
+fn is_not_modified(headers: &HeaderMap, etag: &str, last_modified: DateTime<Utc>) -> bool {
+    if let Some(matched) = if_none_match(headers, etag) {
+        return matched;
+    }
+
+    headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+        .is_some_and(|since| last_modified.timestamp() <= since.timestamp())
+}
+
+#[tracing::instrument(skip(state, headers))]
+async fn serve_feed(
+    state: AppState,
+    headers: HeaderMap,
+    scope: FeedScope,
+    format: FeedFormat,
+) -> Result<Response, AppError> {
+    let store = state.store.as_ref().ok_or(AppError::DatabaseUnavailable)?;
+
+    let entries = load_entries(store.as_ref(), scope).await?;
+    let updated = entries
+        .iter()
+        .map(|e| e.updated)
+        .max()
+        .unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
+    let body = match format {
+        FeedFormat::Atom => render_atom(scope, &entries, updated),
+        FeedFormat::Json => render_json(scope, &entries)?,
+    };
+    let accept_encoding = headers
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let etag = etag_for(&body, accept_encoding);
+
+    let mut response = if is_not_modified(&headers, &etag, updated) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(format.content_type()),
+            )],
+            body,
+        )
+            .into_response()
+    };
+
+    let response_headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&http_date(updated)) {
+        response_headers.insert(header::LAST_MODIFIED, value);
+    }
+
+    Ok(response)
+}
+
+/// Routes for every feed scope in both formats.
+pub fn router<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let mut router = Router::new();
+    for scope in FeedScope::ALL {
+        for format in [FeedFormat::Atom, FeedFormat::Json] {
+            router = router.route(
+                &feed_path(scope, format),
+                get(
+                    move |Extension(state): Extension<AppState>, headers: HeaderMap| {
+                        serve_feed(state, headers, scope, format)
+                    },
+                ),
+            );
+        }
+    }
+    router
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use chrono::TimeZone;
+    use tower::ServiceExt;
+
+    fn entry(id: &str, title: &str, updated: DateTime<Utc>) -> FeedEntry {
+        FeedEntry {
+            id: id.to_string(),
+            title: title.to_string(),
+            url: "https://example.com/a?b=1&c=2".to_string(),
+            updated,
+            summary: None,
+            category: "release",
+        }
+    }
+
+    fn at(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, day, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn feed_paths_per_scope() {
+        assert_eq!(feed_path(FeedScope::All, FeedFormat::Atom), "/feed.atom");
+        assert_eq!(feed_path(FeedScope::All, FeedFormat::Json), "/feed.json");
+        assert_eq!(
+            feed_path(FeedScope::Crates, FeedFormat::Atom),
+            "/crates/feed.atom"
+        );
+        assert_eq!(
+            feed_path(FeedScope::Contributions, FeedFormat::Json),
+            "/contributions/feed.json"
+        );
+    }
+
+    #[test]
+    fn escapes_xml_special_characters() {
+        assert_eq!(
+            escape_xml(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn renders_atom_with_escaped_entries() {
+        let entries = [entry(
+            "tag:djv.sh,2024:release:crate/djv@1.0.0",
+            "djv <1.0>",
+            at(2),
+        )];
+        let xml = render_atom(FeedScope::Crates, &entries, at(2));
+
+        assert!(xml.contains("<id>tag:djv.sh,2024:feed/crates</id>"));
+        assert!(xml.contains("<updated>2025-03-02T12:00:00Z</updated>"));
+        assert!(xml.contains("<title>djv &lt;1.0&gt;</title>"));
+        assert!(xml.contains("href=\"https://example.com/a?b=1&amp;c=2\""));
+        assert!(xml.contains("<link rel=\"self\" href=\"https://djv.sh/crates/feed.atom\"/>"));
+    }
+
+    #[test]
+    fn renders_json_feed_1_1() {
+        let entries = [entry("id-1", "First", at(1))];
+        let json = render_json(FeedScope::All, &entries).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["version"], "https://jsonfeed.org/version/1.1");
+        assert_eq!(value["feed_url"], "https://djv.sh/feed.json");
+        assert_eq!(value["items"][0]["id"], "id-1");
+        assert_eq!(value["items"][0]["content_text"], "First");
+        assert_eq!(value["items"][0]["date_published"], "2025-03-01T12:00:00Z");
+        assert_eq!(value["items"][0]["tags"][0], "release");
+    }
+
+    #[test]
+    fn etag_changes_with_any_entry_and_the_encoding() {
+        let entries = [entry("a", "A", at(1)), entry("b", "B", at(2))];
+        let body = render_json(FeedScope::All, &entries).unwrap();
+        let etag = etag_for(&body, "gzip");
+        assert_eq!(etag, etag_for(&body, "gzip"));
+
+        // An older entry edited without being re-dated
+        let edited = [entry("a", "A, renamed", at(1)), entry("b", "B", at(2))];
+        let edited = render_json(FeedScope::All, &edited).unwrap();
+        assert_ne!(etag, etag_for(&edited, "gzip"));
+
+        assert_ne!(etag, etag_for(&body, "br"));
+    }
+
+    #[test]
+    fn conditional_get_with_etag() {
+        let etag = "\"abc-1\"";
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"abc-1\""));
+        assert!(is_not_modified(&headers, etag, at(2)));
+
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_static("W/\"abc-1\", \"x\""),
+        );
+        assert!(is_not_modified(&headers, etag, at(2)));
+
+        // A mismatched ETag wins over a matching date.
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"other\""));
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_str(&http_date(at(3))).unwrap(),
+        );
+        assert!(!is_not_modified(&headers, etag, at(2)));
+    }
+
+    #[test]
+    fn conditional_get_with_date() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_not_modified(&headers, "\"x\"", at(2)));
+
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_str(&http_date(at(2))).unwrap(),
+        );
+        assert!(is_not_modified(&headers, "\"x\"", at(2)));
+        assert!(!is_not_modified(&headers, "\"x\"", at(3)));
+    }
+
+    #[tokio::test]
+    async fn reads_the_store() {
+        let store = crate::store::MemoryStore::default();
+        store
+            .upsert_release(&crate::db::NewRelease {
+                artifact_kind: "crate",
+                artifact: "tracer",
+                version: "1.2.0",
+                released_at: Some(at(1)),
+                yanked: false,
+                notes_url: None,
+            })
+            .await
+            .unwrap();
+        store.refresh_projects().await.unwrap();
+        let app = router().layer(Extension(AppState {
+            store: Some(std::sync::Arc::new(store)),
+            cache: crate::cache::ResponseCache::default(),
+            snapshots: crate::snapshot::SnapshotStore::default(),
+        }));
+
+        let response = app
+            .oneshot(
+                Request::get("/crates/feed.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let feed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(feed["items"][0]["title"], "tracer 1.2.0");
+    }
+}
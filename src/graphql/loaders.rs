@@ -0,0 +1,66 @@
+//! Batched loaders for nested resolvers. Each one collects the keys requested while a query
+//! level is resolved and fetches them with a single query, so listing 100 crates with their
+//! repositories costs two round trips rather than 101.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_graphql::dataloader::Loader;
+use uuid::Uuid;
+
+use crate::db::{Crate, NpmPackage, Repository};
+use crate::store::SharedStore;
+
+/// Repositories by id.
+pub struct RepositoryLoader(pub SharedStore);
+
+impl Loader<Uuid> for RepositoryLoader {
+    type Value = Repository;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Repository>, Self::Error> {
+        Ok(self
+            .0
+            .get_repositories_by_ids(keys)
+            .await?
+            .into_iter()
+            .map(|r| (r.id, r))
+            .collect())
+    }
+}
+
+/// Crates published from each repository, keyed by repository id.
+pub struct CratesByRepositoryLoader(pub SharedStore);
+
+impl Loader<Uuid> for CratesByRepositoryLoader {
+    type Value = Vec<Crate>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Vec<Crate>>, Self::Error> {
+        let mut grouped: HashMap<Uuid, Vec<Crate>> = HashMap::new();
+        for c in self.0.get_crates_by_repository_ids(keys).await? {
+            if let Some(repository_id) = c.repository_id {
+                grouped.entry(repository_id).or_default().push(c);
+            }
+        }
+        Ok(grouped)
+    }
+}
+
+/// NPM packages published from each repository, keyed by repository id.
+pub struct NpmPackagesByRepositoryLoader(pub SharedStore);
+
+impl Loader<Uuid> for NpmPackagesByRepositoryLoader {
+    type Value = Vec<NpmPackage>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Vec<NpmPackage>>, Self::Error> {
+        let mut grouped: HashMap<Uuid, Vec<NpmPackage>> = HashMap::new();
+        for p in self.0.get_npm_packages_by_repository_ids(keys).await? {
+            if let Some(repository_id) = p.repository_id {
+                grouped.entry(repository_id).or_default().push(p);
+            }
+        }
+        Ok(grouped)
+    }
+}
@@ -0,0 +1,424 @@
+//! GraphQL endpoint at `/graphql`. `POST` executes a query; `GET` serves `GraphiQL`.
+//!
+//! Nested fields (a crate's repository, a repository's crates) go through the batched loaders
+//! in [`loaders`], and every query is checked against [`MAX_DEPTH`] and [`MAX_COMPLEXITY`]
+//! before it runs. The schema SDL is checked in at `schema.graphql`; regenerate it with
+//! `cargo run --example graphql_schema --features ssr > schema.graphql`.
+
+mod loaders;
+mod types;
+
+use std::fmt::Display;
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Error, Object, Result, Schema};
+use axum::{extract::Extension, response::Html, routing::get, Json, Router};
+use uuid::Uuid;
+
+use crate::db::{ContributionCursor, ContributionFilters, ProjectFilters, MAX_PROJECT_LIMIT};
+use crate::state::AppState;
+use crate::store::{SharedStore, Store};
+
+use loaders::{CratesByRepositoryLoader, NpmPackagesByRepositoryLoader, RepositoryLoader};
+use types::{
+    Contribution, ContributionConnection, Crate, NpmPackage, Project, ProjectKind, Repository,
+    SortDirection, SortOrder, TopicMatch,
+};
+
+/// Deepest selection set a query may nest
+pub const MAX_DEPTH: usize = 8;
+
+/// Upper bound on the estimated cost of a query. Each field costs one, and list fields
+/// multiply the cost of their children by the page size they ask for.
+pub const MAX_COMPLEXITY: usize = 2000;
+
+/// Assumed length of nested lists whose size the client cannot bound, such as a repository's
+/// crates, when estimating query complexity.
+const NESTED_LIST_COST: usize = 10;
+
+const DEFAULT_FIRST: i32 = 25;
+
+pub type DjvSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Build the schema with its depth and complexity limits.
+#[must_use]
+pub fn schema() -> DjvSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+/// The schema in GraphQL SDL.
+#[must_use]
+pub fn sdl() -> String {
+    schema().sdl()
+}
+
+/// Route for `/graphql`.
+pub fn router<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/graphql", get(graphiql).post(execute))
+        .layer(Extension(schema()))
+}
+
+async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+async fn execute(
+    Extension(schema): Extension<DjvSchema>,
+    Extension(state): Extension<AppState>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let mut request = request;
+    if let Some(store) = state.store {
+        // Loaders are per request so cached rows never outlive the query that fetched them.
+        request = request
+            .data(DataLoader::new(
+                RepositoryLoader(store.clone()),
+                tokio::spawn,
+            ))
+            .data(DataLoader::new(
+                CratesByRepositoryLoader(store.clone()),
+                tokio::spawn,
+            ))
+            .data(DataLoader::new(
+                NpmPackagesByRepositoryLoader(store.clone()),
+                tokio::spawn,
+            ))
+            .data(store);
+    }
+    Json(schema.execute(request).await)
+}
+
+fn store<'a>(ctx: &Context<'a>) -> Result<&'a dyn Store> {
+    ctx.data_opt::<SharedStore>()
+        .map(AsRef::as_ref)
+        .ok_or_else(|| Error::new("database not available"))
+}
+
+fn loader<'a, T: Loader<Uuid>>(ctx: &Context<'a>) -> Result<&'a DataLoader<T>> {
+    ctx.data_opt::<DataLoader<T>>()
+        .ok_or_else(|| Error::new("database not available"))
+}
+
+/// Internal details stay in the logs rather than the response.
+fn internal_error(e: impl Display) -> Error {
+    tracing::error!(error = %e, "graphql query failed");
+    Error::new("internal server error")
+}
+
+fn page_size(first: i32) -> Result<i64> {
+    if (1..=MAX_PROJECT_LIMIT).contains(&first) {
+        Ok(i64::from(first))
+    } else {
+        Err(Error::new(format!(
+            "`first` must be between 1 and {MAX_PROJECT_LIMIT}"
+        )))
+    }
+}
+
+/// Complexity of a list field returning up to `first` items.
+fn list_cost(first: i32, child_complexity: usize) -> usize {
+    usize::try_from(first)
+        .unwrap_or(0)
+        .saturating_mul(child_complexity)
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Projects: repositories grouped with the crates and NPM packages built from them. A
+    /// project matches if it has an artifact of any of the given kinds, any of the given
+    /// languages, all (or with `topicMatch: ANY`, any) of `topic`, and none of `excludeTopic`. `q` searches name, description, topics and keywords; results then
+    /// default to `RELEVANCE` order rather than `POPULARITY`. `direction` defaults to `ASC`
+    /// for `NAME` and `DESC` otherwise.
+    #[graphql(complexity = "list_cost(first, child_complexity)")]
+    #[allow(clippy::too_many_arguments)]
+    async fn projects(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] kind: Vec<ProjectKind>,
+        #[graphql(default)] language: Vec<String>,
+        #[graphql(default)] topic: Vec<String>,
+        #[graphql(default)] topic_match: TopicMatch,
+        #[graphql(default)] exclude_topic: Vec<String>,
+        q: Option<String>,
+        sort: Option<SortOrder>,
+        direction: Option<SortDirection>,
+        #[graphql(default_with = "DEFAULT_FIRST")] first: i32,
+    ) -> Result<Vec<Project>> {
+        let limit = page_size(first)?;
+        let filters = ProjectFilters {
+            kinds: kind.into_iter().map(Into::into).collect(),
+            languages: language,
+            topics: topic,
+            topic_match: topic_match.into(),
+            exclude_topics: exclude_topic,
+            q,
+            sort: sort.map(Into::into),
+            direction: direction.map(Into::into),
+            limit: i32::try_from(limit).ok(),
+            cursor: None,
+        };
+        let page = store(ctx)?
+            .get_projects(&filters)
+            .await
+            .map_err(internal_error)?;
+        Ok(page.projects.into_iter().map(Into::into).collect())
+    }
+
+    /// Every distinct topic, alphabetically
+    async fn topics(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        store(ctx)?
+            .get_distinct_topics()
+            .await
+            .map_err(internal_error)
+    }
+
+    /// Repositories, most starred first
+    #[graphql(complexity = "list_cost(first, child_complexity)")]
+    async fn repositories(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_FIRST")] first: i32,
+    ) -> Result<Vec<Repository>> {
+        let repositories = store(ctx)?
+            .get_repositories(page_size(first)?)
+            .await
+            .map_err(internal_error)?;
+        Ok(repositories.into_iter().map(Into::into).collect())
+    }
+
+    async fn repository(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Repository>> {
+        let repository = loader::<RepositoryLoader>(ctx)?
+            .load_one(id)
+            .await
+            .map_err(internal_error)?;
+        Ok(repository.map(Into::into))
+    }
+
+    /// Crates, most downloaded first
+    #[graphql(complexity = "list_cost(first, child_complexity)")]
+    async fn crates(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_FIRST")] first: i32,
+    ) -> Result<Vec<Crate>> {
+        let crates = store(ctx)?
+            .get_crates(page_size(first)?)
+            .await
+            .map_err(internal_error)?;
+        Ok(crates.into_iter().map(Into::into).collect())
+    }
+
+    /// NPM packages, most downloaded first
+    #[graphql(complexity = "list_cost(first, child_complexity)")]
+    async fn npm_packages(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_FIRST")] first: i32,
+    ) -> Result<Vec<NpmPackage>> {
+        let packages = store(ctx)?
+            .get_npm_packages(page_size(first)?)
+            .await
+            .map_err(internal_error)?;
+        Ok(packages.into_iter().map(Into::into).collect())
+    }
+
+    /// Upstream contributions, newest first
+    #[graphql(complexity = "list_cost(first, child_complexity)")]
+    #[allow(clippy::too_many_arguments)]
+    async fn contributions(
+        &self,
+        ctx: &Context<'_>,
+        forge: Option<String>,
+        owner: Option<String>,
+        year: Option<i32>,
+        #[graphql(name = "type")] contribution_type: Option<String>,
+        #[graphql(default_with = "DEFAULT_FIRST")] first: i32,
+        after: Option<String>,
+    ) -> Result<ContributionConnection> {
+        let limit = page_size(first)?;
+        let cursor = after
+            .map(|a| a.parse::<ContributionCursor>())
+            .transpose()
+            .map_err(|_| Error::new("malformed cursor"))?;
+        let filters = ContributionFilters {
+            forge,
+            owner,
+            year,
+            contribution_type,
+            cursor,
+        };
+        let page = store(ctx)?
+            .get_contribution_page(&filters, limit)
+            .await
+            .map_err(internal_error)?;
+        Ok(ContributionConnection {
+            nodes: page
+                .contributions
+                .into_iter()
+                .map(Contribution::from)
+                .collect(),
+            next_cursor: page.next_cursor.map(|c| c.to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    async fn errors(query: &str) -> Vec<String> {
+        schema()
+            .execute(query)
+            .await
+            .errors
+            .into_iter()
+            .map(|e| e.message)
+            .collect()
+    }
+
+    #[test]
+    fn checked_in_sdl_is_current() {
+        assert_eq!(
+            include_str!("../../schema.graphql"),
+            sdl(),
+            "schema.graphql is stale; regenerate it with \
+             `cargo run --example graphql_schema --features ssr > schema.graphql`"
+        );
+    }
+
+    #[test]
+    fn default_page_size_matches_sdl() {
+        assert!(sdl().contains(&format!("first: Int! = {DEFAULT_FIRST}")));
+    }
+
+    #[test]
+    fn page_size_is_capped() {
+        assert_eq!(page_size(1).unwrap(), 1);
+        assert_eq!(page_size(MAX_PROJECT_LIMIT).unwrap(), 100);
+        assert!(page_size(0).is_err());
+        assert!(page_size(MAX_PROJECT_LIMIT + 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_deep_queries() {
+        let errors = errors(
+            "{ __schema { types { fields { type { ofType { ofType { ofType { ofType { name } } } } } } } } }",
+        )
+        .await;
+        assert_eq!(errors, ["Query is nested too deep."]);
+    }
+
+    #[tokio::test]
+    async fn rejects_complex_queries() {
+        let errors =
+            errors("{ repositories(first: 100) { name crates { name repository { name } } } }")
+                .await;
+        assert_eq!(errors, ["Query is too complex."]);
+    }
+
+    #[tokio::test]
+    async fn accepts_full_page_of_projects() {
+        let errors = errors(
+            "{ projects(first: 100) { id kind name description url language topics \
+             popularity version commitCount updatedAt firstSeenAt } }",
+        )
+        .await;
+        // The query is within limits, so it only fails for want of a database.
+        assert_eq!(errors, ["database not available"]);
+    }
+
+    #[tokio::test]
+    async fn executes_over_http() {
+        let app = Router::new().merge(router()).layer(Extension(AppState {
+            store: None,
+            cache: crate::cache::ResponseCache::default(),
+            snapshots: crate::snapshot::SnapshotStore::default(),
+        }));
+        let response = app
+            .oneshot(
+                Request::post("/graphql")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"query":"{ __typename }"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["data"]["__typename"], "QueryRoot");
+    }
+
+    #[tokio::test]
+    async fn resolves_nested_fields_from_the_store() {
+        let store = crate::store::MemoryStore::default();
+        let repository_id = store
+            .upsert_repository(&crate::db::NewRepository {
+                forge: "github",
+                forge_id: "someone/tracer",
+                name: "tracer",
+                description: None,
+                url: "https://github.com/someone/tracer",
+                homepage_url: None,
+                language: Some("Rust"),
+                stars: 3,
+                topics: &[],
+                created_at: None,
+                updated_at: None,
+            })
+            .await
+            .unwrap();
+        store
+            .upsert_crate(&crate::db::NewCrate {
+                name: "tracer",
+                description: None,
+                repository_id: Some(repository_id),
+                repository_url: None,
+                crates_io_url: "https://crates.io/crates/tracer",
+                documentation_url: None,
+                homepage_url: None,
+                downloads: 10,
+                version: Some("1.0.0"),
+                keywords: &[],
+                categories: &[],
+                created_at: None,
+            })
+            .await
+            .unwrap();
+        let app = Router::new().merge(router()).layer(Extension(AppState {
+            store: Some(std::sync::Arc::new(store)),
+            cache: crate::cache::ResponseCache::default(),
+            snapshots: crate::snapshot::SnapshotStore::default(),
+        }));
+        let response = app
+            .oneshot(
+                Request::post("/graphql")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        r#"{"query":"{ crates { name repository { name crates { name } } } }"}"#,
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["errors"], serde_json::Value::Null);
+        let tracer = &body["data"]["crates"][0];
+        assert_eq!(tracer["repository"]["name"], "tracer");
+        assert_eq!(tracer["repository"]["crates"][0]["name"], "tracer");
+    }
+}
@@ -0,0 +1,328 @@
+//! GraphQL object types. Like the REST wire types, these are converted from the database
+//! models rather than derived on them, so the schema can evolve independently.
+
+use async_graphql::{ComplexObject, Context, Enum, Result, SimpleObject};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::db;
+
+use super::loaders::{CratesByRepositoryLoader, NpmPackagesByRepositoryLoader, RepositoryLoader};
+use super::{internal_error, loader, NESTED_LIST_COST};
+
+/// Resolve a repository id through the batched loader.
+async fn load_repository(ctx: &Context<'_>, id: Option<Uuid>) -> Result<Option<Repository>> {
+    let Some(id) = id else {
+        return Ok(None);
+    };
+    let repository = loader::<RepositoryLoader>(ctx)?
+        .load_one(id)
+        .await
+        .map_err(internal_error)?;
+    Ok(repository.map(Into::into))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(remote = "crate::db::ProjectKind")]
+pub enum ProjectKind {
+    Crate,
+    Npm,
+    Repo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(remote = "crate::db::SortOrder")]
+pub enum SortOrder {
+    /// Composite score of stars and downloads
+    Popularity,
+    Name,
+    /// When the repository last changed upstream
+    Updated,
+    /// When the project was created upstream
+    Created,
+    /// Date of the latest non-yanked release
+    Released,
+    Stars,
+    Downloads,
+    Relevance,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(remote = "crate::db::SortDirection")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Enum)]
+#[graphql(remote = "crate::db::TopicMatch")]
+pub enum TopicMatch {
+    #[default]
+    All,
+    Any,
+}
+
+/// A git repository on one of the forges
+#[derive(Debug, SimpleObject)]
+#[graphql(complex)]
+pub struct Repository {
+    pub id: Uuid,
+    pub forge: String,
+    pub forge_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub url: String,
+    pub language: Option<String>,
+    pub stars: i32,
+    pub topics: Vec<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub synced_at: DateTime<Utc>,
+}
+
+#[ComplexObject]
+impl Repository {
+    /// Crates published from this repository
+    #[graphql(complexity = "NESTED_LIST_COST * child_complexity")]
+    async fn crates(&self, ctx: &Context<'_>) -> Result<Vec<Crate>> {
+        let crates = loader::<CratesByRepositoryLoader>(ctx)?
+            .load_one(self.id)
+            .await
+            .map_err(internal_error)?
+            .unwrap_or_default();
+        Ok(crates.into_iter().map(Into::into).collect())
+    }
+
+    /// NPM packages published from this repository
+    #[graphql(complexity = "NESTED_LIST_COST * child_complexity")]
+    async fn npm_packages(&self, ctx: &Context<'_>) -> Result<Vec<NpmPackage>> {
+        let packages = loader::<NpmPackagesByRepositoryLoader>(ctx)?
+            .load_one(self.id)
+            .await
+            .map_err(internal_error)?
+            .unwrap_or_default();
+        Ok(packages.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<db::Repository> for Repository {
+    fn from(r: db::Repository) -> Self {
+        Self {
+            id: r.id,
+            forge: r.forge,
+            forge_id: r.forge_id,
+            name: r.name,
+            description: r.description,
+            url: r.url,
+            language: r.language,
+            stars: r.stars,
+            topics: r.topics,
+            updated_at: r.updated_at,
+            synced_at: r.synced_at,
+        }
+    }
+}
+
+/// A Rust crate from crates.io
+#[derive(Debug, SimpleObject)]
+#[graphql(complex)]
+pub struct Crate {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub crates_io_url: String,
+    pub documentation_url: Option<String>,
+    pub downloads: i32,
+    pub version: Option<String>,
+    pub keywords: Vec<String>,
+    pub categories: Vec<String>,
+    pub synced_at: DateTime<Utc>,
+    #[graphql(skip)]
+    pub repository_id: Option<Uuid>,
+}
+
+#[ComplexObject]
+impl Crate {
+    /// Source repository, if the crate links to one we track
+    async fn repository(&self, ctx: &Context<'_>) -> Result<Option<Repository>> {
+        load_repository(ctx, self.repository_id).await
+    }
+}
+
+impl From<db::Crate> for Crate {
+    fn from(c: db::Crate) -> Self {
+        Self {
+            id: c.id,
+            name: c.name,
+            description: c.description,
+            crates_io_url: c.crates_io_url,
+            documentation_url: c.documentation_url,
+            downloads: c.downloads,
+            version: c.version,
+            keywords: c.keywords,
+            categories: c.categories,
+            synced_at: c.synced_at,
+            repository_id: c.repository_id,
+        }
+    }
+}
+
+/// An NPM package from npmjs.com
+#[derive(Debug, SimpleObject)]
+#[graphql(complex)]
+pub struct NpmPackage {
+    pub id: Uuid,
+    pub name: String,
+    pub scope: Option<String>,
+    pub description: Option<String>,
+    pub npm_url: String,
+    pub downloads_weekly: i32,
+    pub version: Option<String>,
+    pub keywords: Vec<String>,
+    pub synced_at: DateTime<Utc>,
+    #[graphql(skip)]
+    pub repository_id: Option<Uuid>,
+}
+
+#[ComplexObject]
+impl NpmPackage {
+    /// Source repository, if the package links to one we track
+    async fn repository(&self, ctx: &Context<'_>) -> Result<Option<Repository>> {
+        load_repository(ctx, self.repository_id).await
+    }
+}
+
+impl From<db::NpmPackage> for NpmPackage {
+    fn from(p: db::NpmPackage) -> Self {
+        Self {
+            id: p.id,
+            name: p.name,
+            scope: p.scope,
+            description: p.description,
+            npm_url: p.npm_url,
+            downloads_weekly: p.downloads_weekly,
+            version: p.version,
+            keywords: p.keywords,
+            synced_at: p.synced_at,
+            repository_id: p.repository_id,
+        }
+    }
+}
+
+/// A merged pull or merge request to an upstream repository
+#[derive(Debug, SimpleObject)]
+pub struct Contribution {
+    pub id: Uuid,
+    pub forge: String,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub repo_url: String,
+    /// `pr` or `mr`
+    #[graphql(name = "type")]
+    pub kind: String,
+    pub title: Option<String>,
+    pub url: String,
+    pub merged_at: Option<DateTime<Utc>>,
+    pub additions: Option<i32>,
+    pub deletions: Option<i32>,
+    pub changed_files: Option<i32>,
+    pub labels: Vec<String>,
+    pub upstream_stars: Option<i32>,
+    pub upstream_language: Option<String>,
+}
+
+impl From<db::Contribution> for Contribution {
+    fn from(c: db::Contribution) -> Self {
+        Self {
+            id: c.id,
+            forge: c.forge,
+            repo_owner: c.repo_owner,
+            repo_name: c.repo_name,
+            repo_url: c.repo_url,
+            kind: c.contribution_type,
+            title: c.title,
+            url: c.url,
+            merged_at: c.merged_at,
+            additions: c.additions,
+            deletions: c.deletions,
+            changed_files: c.changed_files,
+            labels: c.labels,
+            upstream_stars: c.upstream_stars,
+            upstream_language: c.upstream_language,
+        }
+    }
+}
+
+/// One page of contributions and the cursor for the next page, if any
+#[derive(Debug, SimpleObject)]
+pub struct ContributionConnection {
+    pub nodes: Vec<Contribution>,
+    pub next_cursor: Option<String>,
+}
+
+/// A crate, NPM package or repository making up a project
+#[derive(Debug, SimpleObject)]
+pub struct ProjectArtifact {
+    pub kind: ProjectKind,
+    pub name: String,
+    pub url: String,
+    pub version: Option<String>,
+    /// All-time for crates, weekly for NPM packages
+    pub downloads: Option<i32>,
+    /// Directory of a crate within its repository's Cargo workspace, if it is a member
+    pub workspace_path: Option<String>,
+}
+
+impl From<db::ProjectArtifact> for ProjectArtifact {
+    fn from(a: db::ProjectArtifact) -> Self {
+        Self {
+            kind: a.kind.into(),
+            name: a.name,
+            url: a.url,
+            version: a.version,
+            downloads: a.downloads,
+            workspace_path: a.workspace_path,
+        }
+    }
+}
+
+/// A repository with the crates and NPM packages built from it, or a crate or package on its
+/// own. Kind, name and URL are those of its primary artifact.
+#[derive(Debug, SimpleObject)]
+#[graphql(name = "ProjectView")]
+pub struct Project {
+    pub id: Uuid,
+    pub kind: ProjectKind,
+    pub name: String,
+    pub description: Option<String>,
+    pub url: String,
+    pub language: Option<String>,
+    pub topics: Vec<String>,
+    /// Stars for repositories, downloads for crates and packages
+    pub popularity: i32,
+    pub version: Option<String>,
+    pub commit_count: Option<i32>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub first_seen_at: DateTime<Utc>,
+    /// Primary artifact first
+    pub artifacts: Vec<ProjectArtifact>,
+}
+
+impl From<db::ProjectView> for Project {
+    fn from(p: db::ProjectView) -> Self {
+        Self {
+            id: p.id,
+            kind: p.kind.into(),
+            name: p.name,
+            description: p.description,
+            url: p.url,
+            language: p.language,
+            topics: p.topics,
+            popularity: p.popularity,
+            version: p.version,
+            commit_count: p.commit_count,
+            updated_at: p.updated_at,
+            first_seen_at: p.first_seen_at,
+            artifacts: p.artifacts.into_iter().map(Into::into).collect(),
+        }
+    }
+}
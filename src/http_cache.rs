@@ -0,0 +1,407 @@
+//! `ETag` and `Cache-Control` for everything the server sends.
+//!
+//! Pages and API responses only change when a sync run finishes or a new build is deployed,
+//! so their validator is derived from the latest run, the build and the request instead of
+//! the rendered body. A matching `If-None-Match` is answered with 304 before anything is
+//! rendered or queried.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use axum::extract::Request;
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use tower::{Layer, Service};
+
+use crate::state::AppState;
+
+/// How a response may be cached, by what served it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CachePolicy {
+    /// SSR pages, the REST and GraphQL APIs and feeds
+    Page,
+    /// Leptos server functions, fetched by the hydrated app as it navigates. They're
+    /// registered with `GetUrl`, so their arguments are in the query like a page's filters.
+    ServerFn,
+    /// The WASM bundle, JS and CSS under `/pkg`
+    Asset,
+}
+
+impl CachePolicy {
+    fn for_path(path: &str) -> Self {
+        if path.starts_with("/pkg/") {
+            Self::Asset
+        } else if path.starts_with("/api/") && !path.starts_with("/api/v1") {
+            Self::ServerFn
+        } else {
+            Self::Page
+        }
+    }
+
+    fn cache_control(self) -> &'static str {
+        match self {
+            // Shared caches may answer for a minute, then serve the stale copy while they
+            // revalidate, which costs a 304 until the next sync.
+            Self::Page => "public, max-age=60, stale-while-revalidate=3600",
+            Self::ServerFn => "no-cache",
+            // Bundle names aren't fingerprinted, so they're revalidated to stay in step with
+            // the HTML loading them; the file server answers with `Last-Modified`.
+            Self::Asset => "public, no-cache",
+        }
+    }
+
+    /// Assets are files on disk rather than synced data, and carry their own validator.
+    fn uses_sync_validator(self) -> bool {
+        self != Self::Asset
+    }
+}
+
+/// Strong validator for a response rendered from the sync run that finished at
+/// `last_sync_at`. The requested path and query carry the filters, and `Accept-Encoding`
+/// picks the compressed representation, so both are part of it.
+fn etag_for(last_sync_at: DateTime<Utc>, path_and_query: &str, accept_encoding: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        env!("CARGO_PKG_VERSION"),
+        env!("VCS_REF_HEAD_REVISION"),
+        &last_sync_at.timestamp_micros().to_string(),
+        path_and_query,
+        accept_encoding,
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    let digest = format!("{:x}", hasher.finalize());
+    format!("\"{}\"", &digest[..32])
+}
+
+/// Whether `If-None-Match` lists `etag`, or `None` if the request doesn't send one.
+/// Comparison is weak, as RFC 9110 requires for `If-None-Match`.
+pub(crate) fn if_none_match(headers: &HeaderMap, etag: &str) -> Option<bool> {
+    let if_none_match = headers.get(header::IF_NONE_MATCH)?;
+    Some(if_none_match.to_str().is_ok_and(|v| {
+        v.split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+    }))
+}
+
+/// Add `Cache-Control`, and `etag` to a full response, unless the handler chose its own.
+/// Errors are left alone so they're never cached for longer than their handler allows, and
+/// `no-store` responses, such as those answered from the snapshot while the database fails,
+/// get no `etag`, which describes current data.
+fn apply_headers(response: &mut Response, policy: CachePolicy, etag: Option<&str>) {
+    let status = response.status();
+    if !status.is_success() && status != StatusCode::NOT_MODIFIED {
+        return;
+    }
+    let headers = response.headers_mut();
+    let stored = headers
+        .entry(header::CACHE_CONTROL)
+        .or_insert(HeaderValue::from_static(policy.cache_control()))
+        .to_str()
+        .is_ok_and(|v| !v.contains("no-store"));
+    if let Some(value) = etag
+        .filter(|_| stored)
+        .and_then(|e| HeaderValue::from_str(e).ok())
+    {
+        if matches!(status, StatusCode::OK | StatusCode::NOT_MODIFIED) {
+            headers.entry(header::ETAG).or_insert(value);
+        }
+    }
+}
+
+fn not_modified(policy: CachePolicy, etag: &str) -> Response {
+    let mut response = StatusCode::NOT_MODIFIED.into_response();
+    response
+        .headers_mut()
+        .insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+    apply_headers(&mut response, policy, Some(etag));
+    response
+}
+
+/// When the latest sync run finished, kept with the cached responses so it's read once per
+/// run. `None` without a store, before the first run, or if it can't be read.
+async fn last_sync_at(state: &AppState) -> Option<DateTime<Utc>> {
+    let store = state.store.clone()?;
+    state
+        .cache
+        .get_or_try_insert("last_sync_at".to_owned(), || async move {
+            store.get_last_sync_at().await
+        })
+        .await
+        .inspect_err(|e| tracing::warn!(error = %e, "failed to read last sync time"))
+        .ok()
+        .flatten()
+}
+
+/// Sends `ETag` and `Cache-Control`, and answers conditional requests with 304. Sits outside
+/// `CompressionLayer`, so it sees the `Accept-Encoding` that picks the representation.
+#[derive(Clone)]
+pub struct HttpCacheLayer {
+    state: AppState,
+}
+
+impl HttpCacheLayer {
+    #[must_use]
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+impl<S> Layer<S> for HttpCacheLayer {
+    type Service = HttpCacheService<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        HttpCacheService {
+            inner: service,
+            state: self.state.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct HttpCacheService<S> {
+    inner: S,
+    state: AppState,
+}
+
+impl<S, B> Service<Request<B>> for HttpCacheService<S>
+where
+    S: Service<Request<B>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        // The service that was polled ready has to be the one called.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let state = self.state.clone();
+
+        let policy = CachePolicy::for_path(req.uri().path());
+        let validated =
+            policy.uses_sync_validator() && matches!(*req.method(), Method::GET | Method::HEAD);
+        let path_and_query = req
+            .uri()
+            .path_and_query()
+            .map_or_else(String::new, ToString::to_string);
+        let accept_encoding = req
+            .headers()
+            .get(header::ACCEPT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_owned();
+
+        Box::pin(async move {
+            let etag = if validated {
+                last_sync_at(&state)
+                    .await
+                    .map(|at| etag_for(at, &path_and_query, &accept_encoding))
+            } else {
+                None
+            };
+
+            if let Some(ref etag) = etag {
+                if if_none_match(req.headers(), etag) == Some(true) {
+                    return Ok(not_modified(policy, etag));
+                }
+            }
+
+            let mut response = inner.call(req).await?;
+            apply_headers(&mut response, policy, etag.as_deref());
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::{Extension, Router};
+    use chrono::TimeZone;
+    use leptos::server_fn::ServerFn;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    use crate::app::FetchProjects;
+    use crate::store::{MemoryStore, Store};
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 1, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn classifies_paths() {
+        assert_eq!(CachePolicy::for_path("/"), CachePolicy::Page);
+        assert_eq!(CachePolicy::for_path("/projects"), CachePolicy::Page);
+        assert_eq!(CachePolicy::for_path("/api/v1/projects"), CachePolicy::Page);
+        assert_eq!(CachePolicy::for_path("/feed.atom"), CachePolicy::Page);
+        assert_eq!(
+            CachePolicy::for_path("/api/fetch_projects123"),
+            CachePolicy::ServerFn
+        );
+        assert_eq!(CachePolicy::for_path("/pkg/djv.wasm"), CachePolicy::Asset);
+    }
+
+    #[test]
+    fn etag_changes_with_sync_filters_and_encoding() {
+        let etag = etag_for(at(1), "/projects?kind=crate", "gzip, br");
+        assert_eq!(etag, etag_for(at(1), "/projects?kind=crate", "gzip, br"));
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+
+        assert_ne!(etag, etag_for(at(2), "/projects?kind=crate", "gzip, br"));
+        assert_ne!(etag, etag_for(at(1), "/projects?kind=npm", "gzip, br"));
+        assert_ne!(etag, etag_for(at(1), "/projects?kind=crate", "gzip"));
+    }
+
+    #[test]
+    fn matches_if_none_match() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(if_none_match(&headers, "\"a\""), None);
+
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_static("\"x\", W/\"a\""),
+        );
+        assert_eq!(if_none_match(&headers, "\"a\""), Some(true));
+        assert_eq!(if_none_match(&headers, "\"b\""), Some(false));
+
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("*"));
+        assert_eq!(if_none_match(&headers, "\"b\""), Some(true));
+    }
+
+    #[test]
+    fn headers_respect_handlers_and_errors() {
+        let mut ok = StatusCode::OK.into_response();
+        apply_headers(&mut ok, CachePolicy::Page, Some("\"a\""));
+        assert_eq!(ok.headers()[header::ETAG], "\"a\"");
+        assert_eq!(
+            ok.headers()[header::CACHE_CONTROL],
+            CachePolicy::Page.cache_control()
+        );
+
+        let mut own = StatusCode::OK.into_response();
+        own.headers_mut()
+            .insert(header::ETAG, HeaderValue::from_static("\"feed\""));
+        own.headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        apply_headers(&mut own, CachePolicy::Page, Some("\"a\""));
+        assert_eq!(own.headers()[header::ETAG], "\"feed\"");
+        assert_eq!(own.headers()[header::CACHE_CONTROL], "no-store");
+
+        let mut degraded = StatusCode::OK.into_response();
+        degraded
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        apply_headers(&mut degraded, CachePolicy::Page, Some("\"a\""));
+        assert!(degraded.headers().get(header::ETAG).is_none());
+
+        let mut missing = StatusCode::NOT_FOUND.into_response();
+        apply_headers(&mut missing, CachePolicy::Page, Some("\"a\""));
+        assert!(missing.headers().get(header::ETAG).is_none());
+        assert!(missing.headers().get(header::CACHE_CONTROL).is_none());
+
+        let not_modified = not_modified(CachePolicy::ServerFn, "\"a\"");
+        assert_eq!(not_modified.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(not_modified.headers()[header::ETAG], "\"a\"");
+        assert_eq!(not_modified.headers()[header::CACHE_CONTROL], "no-cache");
+    }
+
+    #[tokio::test]
+    async fn server_fns_answer_if_none_match_with_304() {
+        let store = Arc::new(MemoryStore::default());
+        store.record_sync_run(None).await.unwrap();
+        let state = AppState {
+            store: Some(store),
+            cache: crate::cache::ResponseCache::default(),
+            snapshots: crate::snapshot::SnapshotStore::default(),
+        };
+        let app = Router::new()
+            .route(
+                "/api/{*fn_name}",
+                axum::routing::get(leptos_axum::handle_server_fns),
+            )
+            .layer(HttpCacheLayer::new(state.clone()))
+            .layer(Extension(state));
+        let uri = format!("{}?filters%5Bkind%5D%5B0%5D=crate", FetchProjects::PATH);
+
+        let first = app
+            .clone()
+            .oneshot(Request::get(&uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        let etag = first.headers()[header::ETAG].clone();
+
+        let revalidated = app
+            .oneshot(
+                Request::get(&uri)
+                    .header(header::IF_NONE_MATCH, etag.clone())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(revalidated.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(revalidated.headers()[header::ETAG], etag);
+    }
+
+    #[tokio::test]
+    async fn snapshot_fallbacks_are_not_stored_or_validated() {
+        let dir = std::env::temp_dir().join(format!("djv-http-cache-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("snapshot.json");
+        crate::snapshot::Snapshot::default().write(&path).unwrap();
+
+        // A database that was up for the last sync and is down now
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_secs(1))
+            .connect_lazy("postgres://127.0.0.1:1/down")
+            .unwrap();
+        let state = AppState {
+            store: Some(Arc::new(pool)),
+            cache: crate::cache::ResponseCache::default(),
+            snapshots: crate::snapshot::SnapshotStore::open(&path),
+        };
+        state
+            .cache
+            .get_or_try_insert("last_sync_at".to_owned(), || async {
+                Ok::<_, std::convert::Infallible>(Some(at(1)))
+            })
+            .await
+            .unwrap();
+        let app = Router::new()
+            .route(
+                "/api/{*fn_name}",
+                axum::routing::get(leptos_axum::handle_server_fns),
+            )
+            .layer(HttpCacheLayer::new(state.clone()))
+            .layer(Extension(state));
+
+        let response = app
+            .oneshot(
+                Request::get(FetchProjects::PATH)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+        assert!(response.headers().get(header::ETAG).is_none());
+    }
+}
@@ -2,9 +2,15 @@
 // (ProjectGrid, FilterBar) push rustc's layout query past the default depth of 128.
 #![recursion_limit = "512"]
 
+#[cfg(feature = "ssr")]
+pub mod api;
+
 pub mod app;
 pub mod components;
 
+#[cfg(feature = "ssr")]
+pub mod cache;
+
 #[cfg(feature = "ssr")]
 pub mod config;
 
@@ -17,9 +23,24 @@ pub mod state;
 #[cfg(feature = "ssr")]
 pub mod db;
 
+#[cfg(feature = "ssr")]
+pub mod feeds;
+
+#[cfg(feature = "ssr")]
+pub mod graphql;
+
+#[cfg(feature = "ssr")]
+pub mod http_cache;
+
 #[cfg(feature = "ssr")]
 pub mod proxy_headers;
 
+#[cfg(feature = "ssr")]
+pub mod snapshot;
+
+#[cfg(feature = "ssr")]
+pub mod store;
+
 #[cfg(feature = "ssr")]
 pub mod sync;
 
@@ -5,8 +5,11 @@ async fn main() -> anyhow::Result<()> {
     use axum::Router;
     use axum_tracing_opentelemetry::middleware::{OtelAxumLayer, OtelInResponseLayer};
     use djv::app::{app, shell};
+    use djv::cache::ResponseCache;
     use djv::config::Config;
+    use djv::http_cache::HttpCacheLayer;
     use djv::proxy_headers::RecordProxyHeadersLayer;
+    use djv::snapshot::SnapshotStore;
     use djv::state::AppState;
     use leptos::prelude::*;
     use leptos_axum::{generate_route_list, LeptosRoutes};
@@ -39,29 +42,37 @@ async fn main() -> anyhow::Result<()> {
         .build()
         .context("failed to initialise OpenTelemetry")?;
 
-    let db_pool = if let Some(ref db_config) = config.database {
-        match djv::db::init_pool_with_url(&db_config.url).await {
-            Ok(pool) => {
-                tracing::info!("database pool initialised");
-                if let Err(e) = djv::db::run_migrations(&pool).await {
-                    tracing::error!("failed to run migrations: {}", e);
-                }
-                Some(pool)
-            }
-            Err(e) => {
-                tracing::warn!("failed to initialise database pool: {}", e);
-                None
-            }
-        }
-    } else {
-        tracing::info!("database not configured, running without database");
-        None
-    };
+    let db_pool = open_pool(&config).await;
+
+    // Loaded even without a database, so the site can start from the last good data.
+    let snapshots = SnapshotStore::open(&config.snapshot.path);
+
+    let store = open_store(db_pool.as_ref(), &config, &snapshots).await;
+
+    // Reads from memory cost no more than a cache hit, and `SQLite`'s little more, so only
+    // `PostgreSQL` reads are cached.
+    let cache = ResponseCache::new(
+        if store.is_some() && db_pool.is_none() {
+            0
+        } else {
+            config.cache.entries
+        },
+        std::time::Duration::from_secs(config.cache.ttl),
+    );
 
     if let Some(ref pool) = db_pool {
-        start_sync(pool.clone(), &config);
+        djv::cache::spawn_invalidation_listener(pool.clone(), cache.clone(), snapshots.clone());
+    }
+    if let Some(ref store) = store {
+        start_sync(store.clone(), db_pool.clone(), &config, snapshots.clone());
     }
 
+    let app_state = AppState {
+        store,
+        cache,
+        snapshots,
+    };
+
     let leptos_conf = get_configuration(None).context("failed to load Leptos configuration")?;
     let leptos_options = leptos_conf.leptos_options;
     let routes = generate_route_list(app);
@@ -71,14 +82,17 @@ async fn main() -> anyhow::Result<()> {
             let leptos_options = leptos_options.clone();
             move || shell(leptos_options.clone())
         })
+        .nest("/api/v1", djv::api::router())
+        .merge(djv::feeds::router())
+        .merge(djv::graphql::router())
         .fallback(leptos_axum::file_and_error_handler(shell))
         .layer(CompressionLayer::new())
+        .layer(HttpCacheLayer::new(app_state.clone()))
         .layer(OtelInResponseLayer)
         .layer(OtelAxumLayer::default())
         .layer(RecordProxyHeadersLayer)
         .with_state(leptos_options);
 
-    let app_state = AppState { pool: db_pool };
     app = app.layer(axum::Extension(app_state));
 
     let addr: std::net::SocketAddr = config
@@ -97,8 +111,76 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Connect to and migrate the configured `PostgreSQL` database, if there is one and it can be
+/// reached.
+#[cfg(feature = "ssr")]
+async fn open_pool(config: &djv::config::Config) -> Option<sqlx::PgPool> {
+    match config.database {
+        Some(ref db_config) if djv::store::SqliteStore::handles(&db_config.url) => None,
+        Some(ref db_config) => match djv::db::init_pool_with_url(&db_config.url).await {
+            Ok(pool) => {
+                tracing::info!("database pool initialised");
+                if let Err(e) = djv::db::run_migrations(&pool).await {
+                    tracing::error!("failed to run migrations: {}", e);
+                }
+                Some(pool)
+            }
+            Err(e) => {
+                tracing::warn!("failed to initialise database pool: {}", e);
+                None
+            }
+        },
+        None => {
+            tracing::info!("database not configured, running without database");
+            None
+        }
+    }
+}
+
+/// Where the pages read and sync writes: the configured database, which may be a `SQLite` file,
+/// or memory without one. A configured one that can't be opened leaves none, so the pages
+/// serve the snapshot instead.
+#[cfg(feature = "ssr")]
+async fn open_store(
+    pool: Option<&sqlx::PgPool>,
+    config: &djv::config::Config,
+    snapshots: &djv::snapshot::SnapshotStore,
+) -> Option<djv::store::SharedStore> {
+    use djv::store::{MemoryStore, SqliteStore};
+    use std::sync::Arc;
+
+    match (pool, &config.database) {
+        (Some(pool), _) => Some(Arc::new(pool.clone())),
+        (None, Some(db_config)) if SqliteStore::handles(&db_config.url) => {
+            match SqliteStore::open(&db_config.url).await {
+                Ok(store) => {
+                    tracing::info!("keeping synced data in SQLite");
+                    Some(Arc::new(store))
+                }
+                Err(e) => {
+                    tracing::warn!("failed to open SQLite database: {}", e);
+                    None
+                }
+            }
+        }
+        (None, None) => {
+            tracing::info!("keeping synced data in memory");
+            let memory = snapshots.current().map_or_else(MemoryStore::default, |s| {
+                MemoryStore::from_snapshot((*s).clone())
+            });
+            Some(Arc::new(memory))
+        }
+        (None, Some(_)) => None,
+    }
+}
+
 #[cfg(feature = "ssr")]
-fn start_sync(pool: sqlx::PgPool, config: &djv::config::Config) {
+fn start_sync(
+    store: djv::store::SharedStore,
+    pool: Option<sqlx::PgPool>,
+    config: &djv::config::Config,
+    snapshots: djv::snapshot::SnapshotStore,
+) {
     use djv::sync::{
         forges::{GitHubForge, GitLabForge},
         spawn_sync_task, ContributionsSync, CratesIoRegistry, NpmRegistry, SyncSource, SyncSources,
@@ -156,9 +238,10 @@ fn start_sync(pool: sqlx::PgPool, config: &djv::config::Config) {
     let sync_config = djv::sync::SyncConfig {
         enabled: config.sync.enabled,
         interval_secs: config.sync.interval_secs,
+        workers: config.sync.workers,
     };
 
-    spawn_sync_task(pool, sources, &sync_config);
+    spawn_sync_task(store, sources, &sync_config, snapshots, pool);
 }
 
 #[cfg(not(feature = "ssr"))]
@@ -0,0 +1,202 @@
+//! Last-known-good copy of what the site shows, for when the database can't be reached.
+//!
+//! After every successful sync the projects, topics and contributions are written to one
+//! JSON file. It's read back at startup, so a server started without `PostgreSQL`, or one
+//! that loses it later, still answers from the last good data instead of placeholders.
+//! Queries against it live in [`query`] and mirror their `PostgreSQL` counterparts.
+
+mod query;
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::db::{Contribution, ProjectView};
+use crate::store::Store;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("failed to read or write snapshot: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("invalid snapshot: {0}")]
+    Serialisation(#[from] serde_json::Error),
+}
+
+/// Everything the pages list, as of one sync
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// When the snapshot was taken, shown as "data as of" while it's being served
+    pub taken_at: DateTime<Utc>,
+    pub projects: Vec<ProjectView>,
+    pub topics: Vec<String>,
+    pub contributions: Vec<Contribution>,
+}
+
+impl Snapshot {
+    /// Read the current data from the store.
+    ///
+    /// # Errors
+    /// Returns any `PostgreSQL` error from the underlying queries.
+    pub async fn take(store: &dyn Store) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            taken_at: Utc::now(),
+            projects: store.get_all_projects().await?,
+            topics: store.get_distinct_topics().await?,
+            contributions: store.get_all_contributions().await?,
+        })
+    }
+
+    /// # Errors
+    /// Returns an error if the file can't be read or isn't a snapshot.
+    pub fn read(path: &Path) -> Result<Self, SnapshotError> {
+        let json = std::fs::read(path)?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+
+    /// Write to `path` through a temporary file beside it, so a crash mid-write leaves the
+    /// previous snapshot in place. Each write has its own temporary file, so instances
+    /// sharing the path never rename one another's half-written copy into place.
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be written.
+    pub fn write(&self, path: &Path) -> Result<(), SnapshotError> {
+        let json = serde_json::to_vec(self)?;
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(format!(
+            ".{}.{}.partial",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        let written = std::fs::write(&partial, json).and_then(|()| std::fs::rename(&partial, path));
+        if written.is_err() {
+            let _ = std::fs::remove_file(&partial);
+        }
+        Ok(written?)
+    }
+
+    /// `taken_at` as shown in the "data as of" notice
+    #[must_use]
+    pub fn as_of(&self) -> String {
+        self.taken_at.format("%Y-%m-%d %H:%M UTC").to_string()
+    }
+}
+
+/// The snapshot on disk and its loaded copy. Cheap to clone; clones share the copy.
+#[derive(Clone, Default)]
+pub struct SnapshotStore {
+    path: Option<PathBuf>,
+    current: Arc<RwLock<Option<Arc<Snapshot>>>>,
+}
+
+impl SnapshotStore {
+    /// A store writing to `path`, with whatever snapshot is already there loaded. A missing
+    /// or unreadable file only means there's nothing to fall back on yet.
+    #[must_use]
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let current = match Snapshot::read(&path) {
+            Ok(snapshot) => {
+                tracing::info!(
+                    path = %path.display(),
+                    taken_at = %snapshot.taken_at,
+                    "loaded snapshot"
+                );
+                Some(Arc::new(snapshot))
+            }
+            Err(SnapshotError::Io(e)) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "ignoring unreadable snapshot");
+                None
+            }
+        };
+        Self {
+            path: Some(path),
+            current: Arc::new(RwLock::new(current)),
+        }
+    }
+
+    /// The latest snapshot, if there is one.
+    #[must_use]
+    pub fn current(&self) -> Option<Arc<Snapshot>> {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Take a snapshot of the store, write it out and serve it from now on.
+    ///
+    /// # Errors
+    /// Returns an error if the store can't be read or the file can't be written; the
+    /// previous snapshot stays in use.
+    pub async fn save(&self, store: &dyn Store) -> Result<(), SnapshotError> {
+        let snapshot = Snapshot::take(store).await?;
+        if let Some(ref path) = self.path {
+            snapshot.write(path)?;
+        }
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(snapshot));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_a_file() {
+        let dir = std::env::temp_dir().join(format!("djv-snapshot-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("snapshot.json");
+
+        assert!(SnapshotStore::open(&path).current().is_none());
+
+        let snapshot = Snapshot {
+            topics: vec!["cli".to_owned()],
+            ..Snapshot::default()
+        };
+        snapshot.write(&path).unwrap();
+
+        let loaded = SnapshotStore::open(&path).current().unwrap();
+        assert_eq!(loaded.topics, ["cli"]);
+        assert_eq!(loaded.taken_at, snapshot.taken_at);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(SnapshotStore::open(&path).current().is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn concurrent_writes_leave_a_whole_snapshot() {
+        let dir = std::env::temp_dir().join(format!("djv-snapshot-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("snapshot.json");
+
+        std::thread::scope(|scope| {
+            for n in 0..8 {
+                let path = &path;
+                scope.spawn(move || {
+                    let snapshot = Snapshot {
+                        topics: vec![format!("topic-{n}"); 1000],
+                        ..Snapshot::default()
+                    };
+                    snapshot.write(path).unwrap();
+                });
+            }
+        });
+
+        let loaded = SnapshotStore::open(&path).current().unwrap();
+        assert_eq!(loaded.topics.len(), 1000);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,784 @@
+//! The queries the server functions make, answered from a [`Snapshot`].
+//!
+//! Each follows its `PostgreSQL` counterpart in [`crate::db`], with two simplifications:
+//! search matches words as substrings rather than ranking full-text matches, and a snapshot
+//! only keeps each project's popularity, so sorts by stars, downloads, creation or release
+//! date fall back to popularity unless the caller has each project's [`ProjectDetail`], as
+//! [`crate::store::MemoryStore`] does.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::{DateTime, Datelike, Months, Utc};
+use uuid::Uuid;
+
+use super::Snapshot;
+use crate::db::{
+    Contribution, ContributionCount, ContributionCursor, ContributionFilters, ContributionPage,
+    ContributionSort, ContributionStats, CursorKey, FacetCount, ProjectCursor, ProjectDetail,
+    ProjectFacets, ProjectFilters, ProjectKind, ProjectPage, ProjectView, RepoActivity,
+    SortDirection, SortOrder, TopicMatch,
+};
+
+/// The filters of a project query, ready to test projects against
+#[derive(Clone)]
+struct Query<'a> {
+    kinds: &'a [ProjectKind],
+    /// Lowercased
+    languages: Vec<String>,
+    topics: &'a [String],
+    match_all: bool,
+    exclude_topics: &'a [String],
+    /// Lowercased words a project must contain, and those it mustn't (written `-word`)
+    words: Vec<String>,
+    excluded_words: Vec<String>,
+}
+
+impl<'a> Query<'a> {
+    fn new(filters: &'a ProjectFilters) -> Self {
+        let (mut words, mut excluded_words) = (Vec::new(), Vec::new());
+        for word in filters.q.as_deref().unwrap_or_default().split_whitespace() {
+            let word = word.trim_matches('"').to_lowercase();
+            match word.strip_prefix('-') {
+                Some("") => {}
+                Some(excluded) => excluded_words.push(excluded.to_owned()),
+                None if word.is_empty() || word == "or" => {}
+                None => words.push(word),
+            }
+        }
+        Self {
+            kinds: &filters.kinds,
+            languages: filters.languages.iter().map(|l| l.to_lowercase()).collect(),
+            topics: &filters.topics,
+            match_all: filters.topic_match == TopicMatch::All,
+            exclude_topics: &filters.exclude_topics,
+            words,
+            excluded_words,
+        }
+    }
+
+    fn matches(&self, project: &ProjectView) -> bool {
+        let has_topic = |topic: &String| project.topics.contains(topic);
+        let topics_match = if self.topics.is_empty() {
+            true
+        } else if self.match_all {
+            self.topics.iter().all(has_topic)
+        } else {
+            self.topics.iter().any(has_topic)
+        };
+
+        (self.kinds.is_empty() || kinds(project).any(|k| self.kinds.contains(&k)))
+            && (self.languages.is_empty()
+                || project
+                    .language
+                    .as_ref()
+                    .is_some_and(|l| self.languages.contains(&l.to_lowercase())))
+            && topics_match
+            && !self.exclude_topics.iter().any(has_topic)
+            && self.matches_search(project)
+    }
+
+    fn matches_search(&self, project: &ProjectView) -> bool {
+        if self.words.is_empty() && self.excluded_words.is_empty() {
+            return true;
+        }
+        let text = std::iter::once(project.name.as_str())
+            .chain(project.description.as_deref())
+            .chain(project.topics.iter().map(String::as_str))
+            .chain(project.artifacts.iter().map(|a| a.name.as_str()))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        self.words.iter().all(|w| text.contains(w.as_str()))
+            && !self
+                .excluded_words
+                .iter()
+                .any(|w| text.contains(w.as_str()))
+    }
+}
+
+/// The distinct kinds of a project's artifacts
+fn kinds(project: &ProjectView) -> impl Iterator<Item = ProjectKind> + '_ {
+    [ProjectKind::Crate, ProjectKind::Npm, ProjectKind::Repo]
+        .into_iter()
+        .filter(|kind| project.artifacts.iter().any(|a| a.kind == *kind))
+}
+
+/// A project with the key it sorts by, shaped like the keys `db::get_projects` compares so
+/// cursors mean the same thing
+struct SortedProject<'a> {
+    project: &'a ProjectView,
+    numbers: Vec<f64>,
+    text: &'a str,
+}
+
+impl<'a> SortedProject<'a> {
+    // Epoch microseconds stay exact in an `f64` until the 23rd century.
+    #[allow(clippy::cast_precision_loss)]
+    fn new(
+        project: &'a ProjectView,
+        detail: Option<&ProjectDetail>,
+        sort: SortOrder,
+        direction: SortDirection,
+    ) -> Self {
+        let missing = match direction {
+            SortDirection::Asc => f64::INFINITY,
+            SortDirection::Desc => f64::NEG_INFINITY,
+        };
+        let date =
+            |at: Option<DateTime<Utc>>| at.map_or(missing, |at| at.timestamp_micros() as f64);
+        let count = |n: Option<i32>| n.map_or(missing, f64::from);
+        let numbers = match (sort, detail) {
+            (SortOrder::Name, _) => Vec::new(),
+            (SortOrder::Updated, _) => vec![date(project.updated_at)],
+            (SortOrder::Created, Some(detail)) => vec![date(detail.created_at)],
+            (SortOrder::Released, Some(detail)) => vec![date(detail.latest_released_at)],
+            (SortOrder::Stars, Some(detail)) => vec![count(detail.stars)],
+            (SortOrder::Downloads, Some(detail)) => vec![count(detail.downloads)],
+            (
+                SortOrder::Popularity
+                | SortOrder::Created
+                | SortOrder::Released
+                | SortOrder::Stars
+                | SortOrder::Downloads
+                | SortOrder::Relevance,
+                _,
+            ) => vec![f64::from(project.popularity)],
+        };
+        let text = if sort == SortOrder::Name {
+            project.name.as_str()
+        } else {
+            ""
+        };
+        Self {
+            project,
+            numbers,
+            text,
+        }
+    }
+
+    fn cmp_key(&self, numbers: &[f64], text: &str, id: Uuid) -> Ordering {
+        self.numbers
+            .iter()
+            .zip(numbers)
+            .map(|(a, b)| a.total_cmp(b))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| self.numbers.len().cmp(&numbers.len()))
+            .then_with(|| self.text.cmp(text))
+            .then_with(|| self.project.id.cmp(&id))
+    }
+
+    fn cursor(&self, sort: SortOrder, direction: SortDirection) -> ProjectCursor {
+        let key = match sort {
+            SortOrder::Name => CursorKey::Text(self.text.to_owned()),
+            _ => CursorKey::Numbers(self.numbers.clone()),
+        };
+        ProjectCursor {
+            sort,
+            direction,
+            key,
+            id: self.project.id,
+        }
+    }
+}
+
+fn directed(direction: SortDirection, ordering: Ordering) -> Ordering {
+    match direction {
+        SortDirection::Asc => ordering,
+        SortDirection::Desc => ordering.reverse(),
+    }
+}
+
+/// Largest first, with `None` after everything
+fn desc_nulls_last<T: PartialOrd>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => b.partial_cmp(&a).unwrap_or(Ordering::Equal),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Occurrences of each key, most frequent first, then by key
+fn counted<'a>(keys: impl Iterator<Item = &'a str>) -> Vec<ContributionCount> {
+    let mut counts: HashMap<&str, i64> = HashMap::new();
+    for key in keys {
+        *counts.entry(key).or_default() += 1;
+    }
+    let mut counts: Vec<ContributionCount> = counts
+        .into_iter()
+        .map(|(key, count)| ContributionCount {
+            key: key.to_owned(),
+            count,
+        })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    counts
+}
+
+impl Snapshot {
+    /// One page of projects, as [`crate::db::get_projects`] returns it.
+    #[must_use]
+    pub fn projects(&self, filters: &ProjectFilters) -> ProjectPage {
+        self.projects_with_details(filters, |_| None)
+    }
+
+    /// [`Self::projects`], sorting by stars, downloads, creation or release date as
+    /// `detail` gives them for each project id.
+    #[must_use]
+    pub fn projects_with_details<'d>(
+        &self,
+        filters: &ProjectFilters,
+        detail: impl Fn(Uuid) -> Option<&'d ProjectDetail>,
+    ) -> ProjectPage {
+        let sort = filters.effective_sort();
+        let direction = filters.effective_direction();
+        let query = Query::new(filters);
+
+        let mut rows: Vec<SortedProject> = self
+            .projects
+            .iter()
+            .filter(|p| query.matches(p))
+            .map(|p| SortedProject::new(p, detail(p.id), sort, direction))
+            .collect();
+        rows.sort_by(|a, b| directed(direction, a.cmp_key(&b.numbers, b.text, b.project.id)));
+
+        if let Some(cursor) = filters
+            .cursor
+            .as_ref()
+            .filter(|c| c.sort == sort && c.direction == direction)
+        {
+            let (numbers, text) = match &cursor.key {
+                CursorKey::Text(text) => (&[][..], text.as_str()),
+                CursorKey::Numbers(numbers) => (&numbers[..], ""),
+            };
+            rows.retain(|row| {
+                directed(direction, row.cmp_key(numbers, text, cursor.id)) == Ordering::Greater
+            });
+        }
+
+        let limit = usize::try_from(filters.limit.unwrap_or(1000)).unwrap_or(0);
+        let has_more = rows.len() > limit;
+        rows.truncate(limit);
+        let next_cursor = if has_more {
+            rows.last().map(|row| row.cursor(sort, direction))
+        } else {
+            None
+        };
+
+        ProjectPage {
+            projects: rows.into_iter().map(|row| row.project.clone()).collect(),
+            next_cursor,
+        }
+    }
+
+    /// Kinds, languages and topics counted as [`crate::db::get_project_facets`] counts them.
+    #[must_use]
+    pub fn project_facets(&self, filters: &ProjectFilters) -> ProjectFacets {
+        let base = Query::new(filters);
+        let kind_query = Query {
+            kinds: &[],
+            ..base.clone()
+        };
+        let language_query = Query {
+            languages: Vec::new(),
+            ..base.clone()
+        };
+        let topic_query = Query {
+            topics: if base.match_all { base.topics } else { &[] },
+            ..base
+        };
+
+        let mut kind_counts: BTreeMap<String, i64> = BTreeMap::new();
+        // Keyed by the lowercased language, with the first spelling in sort order
+        let mut language_counts: BTreeMap<String, (&str, i64)> = BTreeMap::new();
+        let mut topic_counts: BTreeMap<&str, i64> =
+            self.topics.iter().map(|t| (t.as_str(), 0)).collect();
+
+        for project in &self.projects {
+            let matched = i64::from(kind_query.matches(project));
+            for kind in kinds(project) {
+                *kind_counts.entry(kind.to_string()).or_default() += matched;
+            }
+
+            if let Some(ref language) = project.language {
+                let entry = language_counts
+                    .entry(language.to_lowercase())
+                    .or_insert((language, 0));
+                entry.0 = entry.0.min(language.as_str());
+                entry.1 += i64::from(language_query.matches(project));
+            }
+
+            let matched = i64::from(topic_query.matches(project));
+            for topic in &project.topics {
+                *topic_counts.entry(topic).or_default() += matched;
+            }
+        }
+
+        ProjectFacets {
+            kinds: kind_counts
+                .into_iter()
+                .map(|(value, count)| FacetCount { value, count })
+                .collect(),
+            languages: language_counts
+                .into_values()
+                .map(|(value, count)| FacetCount {
+                    value: value.to_owned(),
+                    count,
+                })
+                .collect(),
+            topics: topic_counts
+                .into_iter()
+                .map(|(value, count)| FacetCount {
+                    value: value.to_owned(),
+                    count,
+                })
+                .collect(),
+        }
+    }
+
+    /// The project with an artifact of this kind and name, the most popular if several do.
+    #[must_use]
+    pub fn project(&self, kind: ProjectKind, name: &str) -> Option<&ProjectView> {
+        self.projects
+            .iter()
+            .filter(|p| p.artifacts.iter().any(|a| a.kind == kind && a.name == name))
+            .min_by(|a, b| b.popularity.cmp(&a.popularity).then(a.id.cmp(&b.id)))
+    }
+
+    /// That project's detail, as far as the listing knows it: nothing beyond the project itself
+    /// but its repository's URL.
+    #[must_use]
+    pub fn project_detail(&self, kind: ProjectKind, name: &str) -> Option<ProjectDetail> {
+        let project = self.project(kind, name)?.clone();
+        let repository_url = project
+            .artifacts
+            .iter()
+            .find(|a| a.kind == ProjectKind::Repo)
+            .map(|a| a.url.clone());
+        Some(ProjectDetail {
+            project,
+            created_at: None,
+            latest_released_at: None,
+            stars: None,
+            downloads: None,
+            documentation_url: None,
+            homepage_url: None,
+            repository_url,
+            repository_forge: None,
+            readme_html: None,
+        })
+    }
+
+    /// Recent contributions, as [`crate::db::get_contributions`] returns them.
+    #[must_use]
+    pub fn contributions(
+        &self,
+        limit: i64,
+        max_age_years: i32,
+        sort: ContributionSort,
+    ) -> Vec<Contribution> {
+        let months = u32::try_from(max_age_years.saturating_mul(12)).unwrap_or(0);
+        let since = Utc::now()
+            .checked_sub_months(Months::new(months))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        let impact = |c: &Contribution| match sort {
+            ContributionSort::Impact => Some(
+                (f64::from(c.additions.unwrap_or(0)) + f64::from(c.deletions.unwrap_or(0)))
+                    * (f64::from(c.upstream_stars.unwrap_or(0)) + 2.0).ln(),
+            ),
+            ContributionSort::Recent => None,
+        };
+
+        let mut rows: Vec<&Contribution> = self
+            .contributions
+            .iter()
+            .filter(|c| c.merged_at.is_none_or(|at| at > since))
+            .collect();
+        rows.sort_by(|a, b| {
+            desc_nulls_last(impact(a), impact(b))
+                .then_with(|| desc_nulls_last(a.merged_at, b.merged_at))
+        });
+        rows.truncate(usize::try_from(limit).unwrap_or(0));
+        rows.into_iter().cloned().collect()
+    }
+
+    /// One page of contributions, as [`crate::db::get_contribution_page`] returns it.
+    #[must_use]
+    pub fn contribution_page(&self, filters: &ContributionFilters, limit: i64) -> ContributionPage {
+        let key = |c: &Contribution| (c.merged_at.unwrap_or(DateTime::<Utc>::UNIX_EPOCH), c.id);
+        let owner = filters.owner.as_deref().map(str::to_lowercase);
+
+        let mut rows: Vec<&Contribution> = self
+            .contributions
+            .iter()
+            .filter(|c| filters.forge.as_ref().is_none_or(|f| *f == c.forge))
+            .filter(|c| {
+                owner
+                    .as_ref()
+                    .is_none_or(|o| *o == c.repo_owner.to_lowercase())
+            })
+            .filter(|c| {
+                filters
+                    .year
+                    .is_none_or(|y| c.merged_at.is_some_and(|at| at.year() == y))
+            })
+            .filter(|c| {
+                filters
+                    .contribution_type
+                    .as_ref()
+                    .is_none_or(|t| *t == c.contribution_type)
+            })
+            .filter(|c| {
+                filters
+                    .cursor
+                    .is_none_or(|cur| key(c) < (cur.merged_at, cur.id))
+            })
+            .collect();
+        rows.sort_by_key(|c| std::cmp::Reverse(key(c)));
+
+        let page_len = usize::try_from(limit).unwrap_or(0);
+        let has_more = rows.len() > page_len;
+        rows.truncate(page_len);
+        let next_cursor = if has_more {
+            rows.last().map(|c| {
+                let (merged_at, id) = key(c);
+                ContributionCursor { merged_at, id }
+            })
+        } else {
+            None
+        };
+
+        ContributionPage {
+            contributions: rows.into_iter().cloned().collect(),
+            next_cursor,
+        }
+    }
+
+    /// Distinct years with a merged contribution, newest first.
+    #[must_use]
+    pub fn contribution_years(&self) -> Vec<i32> {
+        let years: BTreeSet<i32> = self
+            .contributions
+            .iter()
+            .filter_map(|c| c.merged_at.map(|at| at.year()))
+            .collect();
+        years.into_iter().rev().collect()
+    }
+
+    /// Distinct upstream owners, most contributed-to first. Owners differing only in case are
+    /// one, as the owner filter matches them alike, shown by the spelling that sorts first.
+    #[must_use]
+    pub fn contribution_owners(&self) -> Vec<String> {
+        let mut spellings: HashMap<String, &str> = HashMap::new();
+        for c in &self.contributions {
+            let spelling = spellings
+                .entry(c.repo_owner.to_lowercase())
+                .or_insert(&c.repo_owner);
+            *spelling = (*spelling).min(&c.repo_owner);
+        }
+        let owners: Vec<String> = self
+            .contributions
+            .iter()
+            .map(|c| spellings[&c.repo_owner.to_lowercase()].to_owned())
+            .collect();
+        counted(owners.iter().map(String::as_str))
+            .into_iter()
+            .map(|c| c.key)
+            .collect()
+    }
+
+    /// Aggregates as [`crate::db::get_contribution_stats`] computes them.
+    #[must_use]
+    pub fn contribution_stats(&self) -> ContributionStats {
+        let merged: Vec<DateTime<Utc>> = self
+            .contributions
+            .iter()
+            .filter_map(|c| c.merged_at)
+            .collect();
+
+        let years: Vec<String> = merged
+            .iter()
+            .map(|at| format!("{:04}", at.year()))
+            .collect();
+        let mut by_year = counted(years.iter().map(String::as_str));
+        by_year.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let mut months: BTreeMap<(i32, u32), i64> = BTreeMap::new();
+        for at in &merged {
+            *months.entry((at.year(), at.month())).or_default() += 1;
+        }
+        let mut by_month = Vec::new();
+        if let (Some(&first), Some(&last)) = (months.keys().next(), months.keys().next_back()) {
+            let (mut year, mut month) = first;
+            while (year, month) <= last {
+                by_month.push(ContributionCount {
+                    key: format!("{year:04}-{month:02}"),
+                    count: months.get(&(year, month)).copied().unwrap_or(0),
+                });
+                (year, month) = if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+            }
+        }
+
+        let mut repos: BTreeMap<(&str, &str, &str), RepoActivity> = BTreeMap::new();
+        for c in &self.contributions {
+            let repo = repos
+                .entry((&c.forge, &c.repo_owner, &c.repo_name))
+                .or_insert_with(|| RepoActivity {
+                    forge: c.forge.clone(),
+                    owner: c.repo_owner.clone(),
+                    name: c.repo_name.clone(),
+                    url: c.repo_url.clone(),
+                    count: 0,
+                    first_merged_at: None,
+                    latest_merged_at: None,
+                });
+            if c.repo_url < repo.url {
+                repo.url.clone_from(&c.repo_url);
+            }
+            repo.count += 1;
+            repo.first_merged_at = repo.first_merged_at.min(c.merged_at).or(c.merged_at);
+            repo.latest_merged_at = repo.latest_merged_at.max(c.merged_at);
+        }
+        let mut repos: Vec<RepoActivity> = repos.into_values().collect();
+        repos.sort_by(|a, b| {
+            desc_nulls_last(a.latest_merged_at, b.latest_merged_at)
+                .then_with(|| a.owner.cmp(&b.owner))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        ContributionStats {
+            by_owner: counted(self.contributions.iter().map(|c| c.repo_owner.as_str())),
+            by_year,
+            by_month,
+            repos,
+        }
+    }
+
+    /// Merged contributions to repositories named or owned like one of `terms`, as
+    /// [`crate::db::get_related_contributions`] finds them.
+    #[must_use]
+    pub fn related_contributions(&self, terms: &[String], limit: i64) -> Vec<Contribution> {
+        let terms: Vec<String> = terms.iter().map(|t| t.to_lowercase()).collect();
+        let mut rows: Vec<&Contribution> = self
+            .contributions
+            .iter()
+            .filter(|c| c.merged_at.is_some())
+            .filter(|c| {
+                terms.contains(&c.repo_name.to_lowercase())
+                    || terms.contains(&c.repo_owner.to_lowercase())
+            })
+            .collect();
+        rows.sort_by(|a, b| b.merged_at.cmp(&a.merged_at).then(b.id.cmp(&a.id)));
+        rows.truncate(usize::try_from(limit).unwrap_or(0));
+        rows.into_iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ProjectArtifact;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, 1, 12, 0, 0).unwrap()
+    }
+
+    fn project(
+        name: &str,
+        kind: ProjectKind,
+        language: &str,
+        topics: &[&str],
+        popularity: i32,
+    ) -> ProjectView {
+        ProjectView {
+            id: Uuid::new_v4(),
+            kind,
+            name: name.to_owned(),
+            description: None,
+            url: format!("https://example.com/{name}"),
+            language: Some(language.to_owned()),
+            topics: topics.iter().map(|t| (*t).to_owned()).collect(),
+            popularity,
+            version: None,
+            commit_count: None,
+            updated_at: None,
+            synced_at: at(2025, 1),
+            first_seen_at: at(2025, 1),
+            artifacts: vec![ProjectArtifact {
+                kind,
+                name: name.to_owned(),
+                url: format!("https://example.com/{name}"),
+                version: None,
+                downloads: None,
+                workspace_path: None,
+            }],
+        }
+    }
+
+    fn contribution(owner: &str, name: &str, merged_at: Option<DateTime<Utc>>) -> Contribution {
+        Contribution {
+            id: Uuid::new_v4(),
+            forge: "github".to_owned(),
+            repo_owner: owner.to_owned(),
+            repo_name: name.to_owned(),
+            repo_url: format!("https://github.com/{owner}/{name}"),
+            contribution_type: "pull_request".to_owned(),
+            title: None,
+            url: format!("https://github.com/{owner}/{name}/pull/1"),
+            merged_at,
+            additions: None,
+            deletions: None,
+            changed_files: None,
+            labels: Vec::new(),
+            upstream_stars: None,
+            upstream_language: None,
+            synced_at: at(2025, 1),
+        }
+    }
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            projects: vec![
+                project(
+                    "tracer",
+                    ProjectKind::Crate,
+                    "Rust",
+                    &["tracing", "cli"],
+                    30,
+                ),
+                project("exporter", ProjectKind::Npm, "TypeScript", &["tracing"], 20),
+                project("dotfiles", ProjectKind::Repo, "Nix", &["config"], 10),
+            ],
+            topics: vec!["cli".to_owned(), "config".to_owned(), "tracing".to_owned()],
+            contributions: vec![
+                contribution("tokio-rs", "tokio", Some(at(2024, 11))),
+                contribution("tokio-rs", "axum", Some(at(2025, 2))),
+                contribution("serde-rs", "serde", None),
+            ],
+            ..Snapshot::default()
+        }
+    }
+
+    fn names(page: &ProjectPage) -> Vec<&str> {
+        page.projects.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn filters_and_sorts_projects() {
+        let snapshot = snapshot();
+
+        let all = snapshot.projects(&ProjectFilters::default());
+        assert_eq!(names(&all), ["tracer", "exporter", "dotfiles"]);
+
+        let filters = ProjectFilters {
+            topics: vec!["tracing".to_owned()],
+            sort: Some(SortOrder::Name),
+            ..ProjectFilters::default()
+        };
+        assert_eq!(names(&snapshot.projects(&filters)), ["exporter", "tracer"]);
+
+        let filters = ProjectFilters {
+            languages: vec!["nix".to_owned(), "RUST".to_owned()],
+            q: Some("-dotfiles".to_owned()),
+            ..ProjectFilters::default()
+        };
+        assert_eq!(names(&snapshot.projects(&filters)), ["tracer"]);
+    }
+
+    #[test]
+    fn pages_through_every_project() {
+        let snapshot = snapshot();
+        for sort in SortOrder::ALL {
+            for direction in [SortDirection::Asc, SortDirection::Desc] {
+                let mut filters = ProjectFilters {
+                    sort: Some(sort),
+                    direction: Some(direction),
+                    limit: Some(1),
+                    ..ProjectFilters::default()
+                };
+                let mut seen = Vec::new();
+                loop {
+                    let page = snapshot.projects(&filters);
+                    seen.extend(page.projects.into_iter().map(|p| p.name));
+                    match page.next_cursor {
+                        Some(cursor) => filters.cursor = Some(cursor),
+                        None => break,
+                    }
+                }
+                seen.sort();
+                assert_eq!(
+                    seen,
+                    ["dotfiles", "exporter", "tracer"],
+                    "{sort:?} {direction:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn counts_facets_against_other_filters() {
+        let filters = ProjectFilters {
+            kinds: vec![ProjectKind::Crate],
+            ..ProjectFilters::default()
+        };
+        let facets = snapshot().project_facets(&filters);
+
+        let count = |facets: &[FacetCount], value: &str| {
+            facets.iter().find(|f| f.value == value).map(|f| f.count)
+        };
+        assert_eq!(count(&facets.kinds, "npm"), Some(1));
+        assert_eq!(count(&facets.languages, "Rust"), Some(1));
+        assert_eq!(count(&facets.languages, "Nix"), Some(0));
+        assert_eq!(count(&facets.topics, "cli"), Some(1));
+        assert_eq!(count(&facets.topics, "config"), Some(0));
+    }
+
+    #[test]
+    fn pages_and_aggregates_contributions() {
+        let snapshot = snapshot();
+
+        let page = snapshot.contribution_page(&ContributionFilters::default(), 2);
+        let repos: Vec<&str> = page
+            .contributions
+            .iter()
+            .map(|c| c.repo_name.as_str())
+            .collect();
+        assert_eq!(repos, ["axum", "tokio"]);
+        let rest = snapshot.contribution_page(
+            &ContributionFilters {
+                cursor: page.next_cursor,
+                ..ContributionFilters::default()
+            },
+            2,
+        );
+        assert_eq!(rest.contributions[0].repo_name, "serde");
+        assert_eq!(rest.next_cursor, None);
+
+        assert_eq!(snapshot.contribution_years(), [2025, 2024]);
+        assert_eq!(snapshot.contribution_owners(), ["tokio-rs", "serde-rs"]);
+
+        let stats = snapshot.contribution_stats();
+        let months: Vec<(&str, i64)> = stats
+            .by_month
+            .iter()
+            .map(|m| (m.key.as_str(), m.count))
+            .collect();
+        assert_eq!(
+            months,
+            [
+                ("2024-11", 1),
+                ("2024-12", 0),
+                ("2025-01", 0),
+                ("2025-02", 1)
+            ]
+        );
+        assert_eq!(stats.repos[0].name, "axum");
+        assert_eq!(stats.repos.last().map(|r| r.name.as_str()), Some("serde"));
+    }
+}
@@ -1,6 +1,13 @@
-use sqlx::PgPool;
+use crate::cache::ResponseCache;
+use crate::snapshot::SnapshotStore;
+use crate::store::SharedStore;
 
 #[derive(Clone)]
 pub struct AppState {
-    pub pool: Option<PgPool>,
+    /// What the pages, the APIs and the feeds read: `PostgreSQL`, `SQLite`, or memory when no
+    /// database is configured
+    pub store: Option<SharedStore>,
+    pub cache: ResponseCache,
+    /// Served while `store` is missing or failing
+    pub snapshots: SnapshotStore,
 }
@@ -0,0 +1,1371 @@
+//! A [`Store`] holding everything in the process, for running without `PostgreSQL`.
+//!
+//! Writes go to plain tables shaped like the database's. `refresh_projects` groups them into
+//! projects the way the materialised `projects` view does and swaps in the result, which the
+//! reads then answer from with the [`Snapshot`] queries. Until the first refresh they answer
+//! from the snapshot the store was seeded with, if any. Listings of repositories, crates and
+//! packages read the tables directly, as their `PostgreSQL` queries do.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::Store;
+use crate::db::{
+    CanonicalUrl, Contribution, ContributionFilters, ContributionPage, ContributionSort,
+    ContributionStats, Crate, NewContribution, NewCrate, NewNpmPackage, NewReadme, NewRelease,
+    NewRepository, NewUpstreamRepo, NewWorkspaceMember, NpmPackage, ProjectArtifact, ProjectDetail,
+    ProjectFacets, ProjectFilters, ProjectKind, ProjectPage, ProjectView, Release, Repository,
+    SourceSyncStatus,
+};
+use crate::snapshot::Snapshot;
+
+struct RepositoryRow {
+    id: Uuid,
+    forge: String,
+    forge_id: String,
+    name: String,
+    description: Option<String>,
+    url: String,
+    canonical_url: Option<String>,
+    homepage_url: Option<String>,
+    language: Option<String>,
+    stars: i32,
+    topics: Vec<String>,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+    synced_at: DateTime<Utc>,
+    first_seen_at: DateTime<Utc>,
+}
+
+struct CrateRow {
+    id: Uuid,
+    name: String,
+    description: Option<String>,
+    repository_id: Option<Uuid>,
+    repository_url: Option<String>,
+    crates_io_url: String,
+    documentation_url: Option<String>,
+    homepage_url: Option<String>,
+    downloads: i32,
+    version: Option<String>,
+    keywords: Vec<String>,
+    categories: Vec<String>,
+    created_at: Option<DateTime<Utc>>,
+    synced_at: DateTime<Utc>,
+    first_seen_at: DateTime<Utc>,
+}
+
+struct NpmPackageRow {
+    id: Uuid,
+    name: String,
+    scope: Option<String>,
+    description: Option<String>,
+    repository_id: Option<Uuid>,
+    repository_url: Option<String>,
+    npm_url: String,
+    homepage_url: Option<String>,
+    downloads_weekly: i32,
+    version: Option<String>,
+    keywords: Vec<String>,
+    synced_at: DateTime<Utc>,
+    first_seen_at: DateTime<Utc>,
+}
+
+struct UpstreamRepoRow {
+    id: Uuid,
+    stars: i32,
+    language: Option<String>,
+}
+
+struct ReadmeRow {
+    content_hash: String,
+    html: String,
+}
+
+#[derive(Default)]
+struct Tables {
+    repositories: HashMap<Uuid, RepositoryRow>,
+    crates: HashMap<Uuid, CrateRow>,
+    npm_packages: HashMap<Uuid, NpmPackageRow>,
+    /// Keyed by forge, owner, name and URL, as the table's unique constraint is
+    contributions: HashMap<(String, String, String, String), Contribution>,
+    /// Keyed by forge, owner and name
+    upstream_repos: HashMap<(String, String, String), UpstreamRepoRow>,
+    /// Keyed by artifact kind, artifact and version
+    releases: HashMap<(String, String, String), Release>,
+    readmes: HashMap<Uuid, ReadmeRow>,
+    /// Crate names and their paths, by repository
+    workspace_members: HashMap<Uuid, Vec<(String, String)>>,
+    /// Each repository's `pushed_at` when its workspace was last read
+    workspace_pushed_at: HashMap<Uuid, DateTime<Utc>>,
+    last_sync_at: Option<DateTime<Utc>>,
+}
+
+/// What the reads see, rebuilt from the tables by `refresh_projects`
+#[derive(Default)]
+struct View {
+    snapshot: Snapshot,
+    details: HashMap<Uuid, ProjectDetail>,
+    /// Each project's artifacts as releases name them: the kind, and the crate or package
+    /// name or the repository's `forge_id`
+    release_artifacts: HashMap<Uuid, Vec<(String, String)>>,
+    releases: Vec<Release>,
+}
+
+/// Keeps synced data in memory. Nothing survives a restart beyond the snapshot it's seeded
+/// with.
+#[derive(Default)]
+pub struct MemoryStore {
+    tables: RwLock<Tables>,
+    view: RwLock<Arc<View>>,
+}
+
+impl MemoryStore {
+    /// A store answering from `snapshot` until its first sync run finishes.
+    #[must_use]
+    pub fn from_snapshot(snapshot: Snapshot) -> Self {
+        Self {
+            tables: RwLock::default(),
+            view: RwLock::new(Arc::new(View {
+                snapshot,
+                ..View::default()
+            })),
+        }
+    }
+
+    // A poisoned lock only means a writer panicked between statements, which a database
+    // would have left half-applied too.
+    fn read_tables(&self) -> RwLockReadGuard<'_, Tables> {
+        self.tables.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_tables(&self) -> RwLockWriteGuard<'_, Tables> {
+        self.tables.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn view(&self) -> Arc<View> {
+        self.view
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+/// The earlier of two optional times, ignoring `None` as SQL's `MIN` does
+fn earliest<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Sum of the values present, or `None` if there are none, as SQL's `SUM` does
+fn total(values: impl Iterator<Item = Option<i32>>) -> Option<i32> {
+    values
+        .flatten()
+        .map(i64::from)
+        .reduce(|a, b| a + b)
+        .map(|sum| i32::try_from(sum).unwrap_or(i32::MAX))
+}
+
+/// A package name without its npm scope
+fn unscoped(name: &str) -> &str {
+    name.strip_prefix('@')
+        .and_then(|rest| rest.split_once('/'))
+        .filter(|(scope, _)| !scope.is_empty())
+        .map_or(name, |(_, unscoped)| unscoped)
+}
+
+/// A row of the `project_artifacts` view, with the links the project detail picks from
+struct Artifact<'a> {
+    project_id: Uuid,
+    kind: ProjectKind,
+    id: Uuid,
+    name: &'a str,
+    /// The crate or package name, or the repository's `forge_id`, as releases name it
+    release_name: &'a str,
+    description: Option<&'a str>,
+    url: &'a str,
+    language: Option<&'a str>,
+    topics: &'a [String],
+    version: Option<&'a str>,
+    downloads: Option<i32>,
+    synced_at: DateTime<Utc>,
+    first_seen_at: DateTime<Utc>,
+    created_at: Option<DateTime<Utc>>,
+    latest_released_at: Option<DateTime<Utc>>,
+    workspace_path: Option<&'a str>,
+    documentation_url: Option<&'a str>,
+    homepage_url: Option<&'a str>,
+}
+
+/// An artifact's first release, and its latest that wasn't yanked
+type ReleaseDates = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// Every artifact, as the `project_artifacts` view lists them
+fn artifacts(tables: &Tables) -> Vec<Artifact<'_>> {
+    let mut released: HashMap<(&str, &str), ReleaseDates> = HashMap::new();
+    for r in tables.releases.values() {
+        let dates = released.entry((&r.artifact_kind, &r.artifact)).or_default();
+        dates.0 = earliest(dates.0, r.released_at);
+        if !r.yanked {
+            dates.1 = dates.1.max(r.released_at);
+        }
+    }
+    let released =
+        |kind: &str, artifact: &str| released.get(&(kind, artifact)).copied().unwrap_or_default();
+
+    let crates = tables.crates.values().map(|c| {
+        let (first_released_at, latest_released_at) = released("crate", &c.name);
+        let workspace_path = c
+            .repository_id
+            .and_then(|id| tables.workspace_members.get(&id))
+            .and_then(|members| members.iter().find(|(name, _)| *name == c.name))
+            .map(|(_, path)| path.as_str());
+        Artifact {
+            project_id: c.repository_id.unwrap_or(c.id),
+            kind: ProjectKind::Crate,
+            id: c.id,
+            name: &c.name,
+            release_name: &c.name,
+            description: c.description.as_deref(),
+            url: &c.crates_io_url,
+            language: Some("rust"),
+            topics: &c.keywords,
+            version: c.version.as_deref(),
+            downloads: Some(c.downloads),
+            synced_at: c.synced_at,
+            first_seen_at: c.first_seen_at,
+            created_at: c.created_at.or(first_released_at),
+            latest_released_at,
+            workspace_path,
+            documentation_url: c.documentation_url.as_deref(),
+            homepage_url: c.homepage_url.as_deref(),
+        }
+    });
+
+    let packages = tables.npm_packages.values().map(|n| {
+        let (first_released_at, latest_released_at) = released("npm", &n.name);
+        Artifact {
+            project_id: n.repository_id.unwrap_or(n.id),
+            kind: ProjectKind::Npm,
+            id: n.id,
+            name: &n.name,
+            release_name: &n.name,
+            description: n.description.as_deref(),
+            url: &n.npm_url,
+            language: Some("typescript"),
+            topics: &n.keywords,
+            version: n.version.as_deref(),
+            downloads: Some(n.downloads_weekly),
+            synced_at: n.synced_at,
+            first_seen_at: n.first_seen_at,
+            created_at: first_released_at,
+            latest_released_at,
+            workspace_path: None,
+            documentation_url: None,
+            homepage_url: n.homepage_url.as_deref(),
+        }
+    });
+
+    let repositories = tables.repositories.values().map(|r| Artifact {
+        project_id: r.id,
+        kind: ProjectKind::Repo,
+        id: r.id,
+        name: &r.name,
+        release_name: &r.forge_id,
+        description: r.description.as_deref(),
+        url: &r.url,
+        language: r.language.as_deref(),
+        topics: &r.topics,
+        version: None,
+        downloads: None,
+        synced_at: r.synced_at,
+        first_seen_at: r.first_seen_at,
+        created_at: r.created_at,
+        latest_released_at: released("repo", &r.forge_id).1,
+        workspace_path: None,
+        documentation_url: None,
+        homepage_url: None,
+    });
+
+    crates.chain(packages).chain(repositories).collect()
+}
+
+/// A link from the artifact named like the project, else the most downloaded one that has it
+fn preferred<'a>(
+    artifacts: &[Artifact<'a>],
+    name: &str,
+    link: impl Fn(&Artifact<'a>) -> Option<&'a str>,
+) -> Option<String> {
+    artifacts
+        .iter()
+        .filter(|a| link(a).is_some())
+        .max_by_key(|a| (a.name == name, a.downloads))
+        .and_then(link)
+        .map(str::to_owned)
+}
+
+/// Order a project's artifacts primary first: the package named like its repository, else a
+/// repository publishing several packages, else the most downloaded package
+fn rank(repository: Option<&RepositoryRow>, artifacts: &mut [Artifact<'_>]) {
+    let packages = artifacts
+        .iter()
+        .filter(|a| a.kind != ProjectKind::Repo)
+        .count();
+
+    let class = |a: &Artifact| match a.kind {
+        ProjectKind::Crate | ProjectKind::Npm
+            if repository
+                .is_some_and(|r| unscoped(a.name).to_lowercase() == r.name.to_lowercase()) =>
+        {
+            0
+        }
+        ProjectKind::Repo if packages > 1 => 1,
+        ProjectKind::Crate | ProjectKind::Npm => 2,
+        ProjectKind::Repo => 3,
+    };
+    artifacts.sort_by(|a, b| {
+        class(a)
+            .cmp(&class(b))
+            .then_with(|| b.downloads.cmp(&a.downloads))
+            .then_with(|| a.name.cmp(b.name))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+}
+
+/// One project from its artifacts, as the `projects` view and `db::get_project_detail` build
+/// it
+fn project_detail(
+    tables: &Tables,
+    project_id: Uuid,
+    mut artifacts: Vec<Artifact<'_>>,
+) -> Option<ProjectDetail> {
+    let repository = tables.repositories.get(&project_id);
+    rank(repository, &mut artifacts);
+    let primary = artifacts.first()?;
+
+    let downloads_of = |kind: ProjectKind| {
+        total(
+            artifacts
+                .iter()
+                .filter(|a| a.kind == kind)
+                .map(|a| a.downloads),
+        )
+    };
+    let popularity = match primary.kind {
+        ProjectKind::Crate => downloads_of(ProjectKind::Crate),
+        ProjectKind::Npm => downloads_of(ProjectKind::Npm),
+        ProjectKind::Repo => repository.map(|r| r.stars),
+    };
+
+    let mut topics: Vec<String> = Vec::new();
+    for topic in artifacts.iter().flat_map(|a| a.topics) {
+        if !topics.contains(topic) {
+            topics.push(topic.clone());
+        }
+    }
+
+    let project = ProjectView {
+        id: project_id,
+        kind: primary.kind,
+        name: primary.name.to_owned(),
+        description: primary
+            .description
+            .filter(|d| !d.is_empty())
+            .or(repository.and_then(|r| r.description.as_deref()))
+            .map(str::to_owned),
+        url: primary.url.to_owned(),
+        language: primary
+            .language
+            .or(repository.and_then(|r| r.language.as_deref()))
+            .map(str::to_owned),
+        topics,
+        popularity: popularity.unwrap_or(0),
+        version: primary.version.map(str::to_owned),
+        commit_count: None,
+        updated_at: repository.and_then(|r| r.updated_at),
+        synced_at: artifacts
+            .iter()
+            .map(|a| a.synced_at)
+            .max()
+            .unwrap_or(primary.synced_at),
+        first_seen_at: artifacts
+            .iter()
+            .map(|a| a.first_seen_at)
+            .min()
+            .unwrap_or(primary.first_seen_at),
+        artifacts: artifacts
+            .iter()
+            .map(|a| ProjectArtifact {
+                kind: a.kind,
+                name: a.name.to_owned(),
+                url: a.url.to_owned(),
+                version: a.version.map(str::to_owned),
+                downloads: a.downloads,
+                workspace_path: a.workspace_path.map(str::to_owned),
+            })
+            .collect(),
+    };
+
+    Some(ProjectDetail {
+        created_at: artifacts
+            .iter()
+            .map(|a| a.created_at)
+            .reduce(earliest)
+            .flatten(),
+        latest_released_at: artifacts.iter().filter_map(|a| a.latest_released_at).max(),
+        stars: repository.map(|r| r.stars),
+        downloads: total(artifacts.iter().map(|a| a.downloads)),
+        documentation_url: preferred(&artifacts, primary.name, |a| a.documentation_url),
+        homepage_url: preferred(&artifacts, primary.name, |a| a.homepage_url)
+            .or_else(|| repository.and_then(|r| r.homepage_url.clone())),
+        repository_url: repository.map(|r| r.url.clone()),
+        repository_forge: repository.map(|r| r.forge.clone()),
+        readme_html: tables.readmes.get(&project_id).map(|r| r.html.clone()),
+        project,
+    })
+}
+
+/// Everything the reads need, as of now
+fn build_view(tables: &Tables) -> View {
+    let mut grouped: HashMap<Uuid, Vec<Artifact<'_>>> = HashMap::new();
+    for artifact in artifacts(tables) {
+        grouped
+            .entry(artifact.project_id)
+            .or_default()
+            .push(artifact);
+    }
+
+    let mut release_artifacts = HashMap::new();
+    let mut details = HashMap::new();
+    for (project_id, artifacts) in grouped {
+        release_artifacts.insert(
+            project_id,
+            artifacts
+                .iter()
+                .map(|a| (a.kind.to_string(), a.release_name.to_owned()))
+                .collect(),
+        );
+        if let Some(detail) = project_detail(tables, project_id, artifacts) {
+            details.insert(project_id, detail);
+        }
+    }
+
+    let mut projects: Vec<ProjectView> = details.values().map(|d| d.project.clone()).collect();
+    projects.sort_by(|a, b| b.popularity.cmp(&a.popularity).then(a.id.cmp(&b.id)));
+    let topics: BTreeSet<String> = projects.iter().flat_map(|p| p.topics.clone()).collect();
+
+    let mut contributions: Vec<Contribution> = tables
+        .contributions
+        .values()
+        .map(|c| {
+            let upstream = tables.upstream_repos.get(&(
+                c.forge.clone(),
+                c.repo_owner.clone(),
+                c.repo_name.clone(),
+            ));
+            Contribution {
+                upstream_stars: upstream.map(|u| u.stars),
+                upstream_language: upstream.and_then(|u| u.language.clone()),
+                ..c.clone()
+            }
+        })
+        .collect();
+    contributions.sort_by_key(|c| {
+        std::cmp::Reverse((c.merged_at.unwrap_or(DateTime::<Utc>::UNIX_EPOCH), c.id))
+    });
+
+    View {
+        snapshot: Snapshot {
+            taken_at: Utc::now(),
+            projects,
+            topics: topics.into_iter().collect(),
+            contributions,
+        },
+        details,
+        release_artifacts,
+        releases: tables.releases.values().cloned().collect(),
+    }
+}
+
+impl From<&RepositoryRow> for Repository {
+    fn from(r: &RepositoryRow) -> Self {
+        Self {
+            id: r.id,
+            forge: r.forge.clone(),
+            forge_id: r.forge_id.clone(),
+            name: r.name.clone(),
+            description: r.description.clone(),
+            url: r.url.clone(),
+            language: r.language.clone(),
+            stars: r.stars,
+            topics: r.topics.clone(),
+            updated_at: r.updated_at,
+            synced_at: r.synced_at,
+        }
+    }
+}
+
+impl From<&CrateRow> for Crate {
+    fn from(c: &CrateRow) -> Self {
+        Self {
+            id: c.id,
+            name: c.name.clone(),
+            description: c.description.clone(),
+            repository_id: c.repository_id,
+            crates_io_url: c.crates_io_url.clone(),
+            documentation_url: c.documentation_url.clone(),
+            downloads: c.downloads,
+            version: c.version.clone(),
+            keywords: c.keywords.clone(),
+            categories: c.categories.clone(),
+            synced_at: c.synced_at,
+        }
+    }
+}
+
+impl From<&NpmPackageRow> for NpmPackage {
+    fn from(n: &NpmPackageRow) -> Self {
+        Self {
+            id: n.id,
+            name: n.name.clone(),
+            scope: n.scope.clone(),
+            description: n.description.clone(),
+            repository_id: n.repository_id,
+            npm_url: n.npm_url.clone(),
+            downloads_weekly: n.downloads_weekly,
+            version: n.version.clone(),
+            keywords: n.keywords.clone(),
+            synced_at: n.synced_at,
+        }
+    }
+}
+
+/// How many rows a source has written and when it last wrote one, given each row's `synced_at`
+fn sync_status(source: &str, synced: impl Iterator<Item = DateTime<Utc>>) -> SourceSyncStatus {
+    let (items, last_synced_at) = synced.fold((0, None), |(items, last), at| {
+        (items + 1, last.max(Some(at)))
+    });
+    SourceSyncStatus {
+        source: source.to_owned(),
+        items,
+        last_synced_at,
+    }
+}
+
+/// The first `limit` of `items`, ordered by `compare`
+fn top<T>(
+    mut items: Vec<T>,
+    limit: i64,
+    compare: impl FnMut(&T, &T) -> std::cmp::Ordering,
+) -> Vec<T> {
+    items.sort_by(compare);
+    items.truncate(usize::try_from(limit).unwrap_or(0));
+    items
+}
+
+#[async_trait]
+impl Store for MemoryStore {
+    async fn upsert_repository(&self, r: &NewRepository<'_>) -> Result<Uuid, sqlx::Error> {
+        let now = Utc::now();
+        let mut tables = self.write_tables();
+        let (id, first_seen_at) = tables
+            .repositories
+            .values()
+            .find(|row| row.forge == r.forge && row.forge_id == r.forge_id)
+            .map_or((Uuid::new_v4(), now), |row| (row.id, row.first_seen_at));
+        tables.repositories.insert(
+            id,
+            RepositoryRow {
+                id,
+                forge: r.forge.to_owned(),
+                forge_id: r.forge_id.to_owned(),
+                name: r.name.to_owned(),
+                description: r.description.map(str::to_owned),
+                url: r.url.to_owned(),
+                canonical_url: CanonicalUrl::parse(r.url).map(|u| u.as_str().to_owned()),
+                homepage_url: r.homepage_url.map(str::to_owned),
+                language: r.language.map(str::to_owned),
+                stars: r.stars,
+                topics: r.topics.to_vec(),
+                created_at: r.created_at,
+                updated_at: r.updated_at,
+                synced_at: now,
+                first_seen_at,
+            },
+        );
+        Ok(id)
+    }
+
+    async fn delete_stale_repositories(
+        &self,
+        forge: &str,
+        synced_ids: &[Uuid],
+    ) -> Result<u64, sqlx::Error> {
+        let mut tables = self.write_tables();
+        let stale: Vec<Uuid> = tables
+            .repositories
+            .values()
+            .filter(|r| r.forge == forge && !synced_ids.contains(&r.id))
+            .map(|r| r.id)
+            .collect();
+
+        for id in &stale {
+            tables.repositories.remove(id);
+            tables.readmes.remove(id);
+            tables.workspace_members.remove(id);
+            tables.workspace_pushed_at.remove(id);
+            for c in tables.crates.values_mut() {
+                if c.repository_id == Some(*id) {
+                    c.repository_id = None;
+                }
+            }
+            for n in tables.npm_packages.values_mut() {
+                if n.repository_id == Some(*id) {
+                    n.repository_id = None;
+                }
+            }
+        }
+        Ok(stale.len() as u64)
+    }
+
+    async fn get_repositories_by_urls(
+        &self,
+        urls: &[CanonicalUrl],
+    ) -> Result<HashMap<String, Uuid>, sqlx::Error> {
+        let tables = self.read_tables();
+        Ok(tables
+            .repositories
+            .values()
+            .filter_map(|r| {
+                let url = r.canonical_url.as_ref()?;
+                urls.iter()
+                    .any(|u| u.as_str() == url)
+                    .then(|| (url.clone(), r.id))
+            })
+            .collect())
+    }
+
+    async fn relink_artifacts(&self) -> Result<u64, sqlx::Error> {
+        let mut tables = self.write_tables();
+        let Tables {
+            repositories,
+            crates,
+            npm_packages,
+            ..
+        } = &mut *tables;
+        let by_url: HashMap<&str, Uuid> = repositories
+            .values()
+            .filter_map(|r| Some((r.canonical_url.as_deref()?, r.id)))
+            .collect();
+
+        let mut linked = 0;
+        let unlinked = crates
+            .values_mut()
+            .map(|c| (&mut c.repository_id, c.repository_url.as_deref()))
+            .chain(
+                npm_packages
+                    .values_mut()
+                    .map(|n| (&mut n.repository_id, n.repository_url.as_deref())),
+            );
+        for (repository_id, url) in unlinked {
+            if repository_id.is_none() {
+                if let Some(&id) = url.and_then(|u| by_url.get(u)) {
+                    *repository_id = Some(id);
+                    linked += 1;
+                }
+            }
+        }
+        Ok(linked)
+    }
+
+    async fn upsert_crate(&self, c: &NewCrate<'_>) -> Result<Uuid, sqlx::Error> {
+        let now = Utc::now();
+        let mut tables = self.write_tables();
+        let (id, first_seen_at) = tables
+            .crates
+            .values()
+            .find(|row| row.name == c.name)
+            .map_or((Uuid::new_v4(), now), |row| (row.id, row.first_seen_at));
+        tables.crates.insert(
+            id,
+            CrateRow {
+                id,
+                name: c.name.to_owned(),
+                description: c.description.map(str::to_owned),
+                repository_id: c.repository_id,
+                repository_url: c.repository_url.map(|u| u.as_str().to_owned()),
+                crates_io_url: c.crates_io_url.to_owned(),
+                documentation_url: c.documentation_url.map(str::to_owned),
+                homepage_url: c.homepage_url.map(str::to_owned),
+                downloads: c.downloads,
+                version: c.version.map(str::to_owned),
+                keywords: c.keywords.to_vec(),
+                categories: c.categories.to_vec(),
+                created_at: c.created_at,
+                synced_at: now,
+                first_seen_at,
+            },
+        );
+        Ok(id)
+    }
+
+    async fn upsert_npm_package(&self, p: &NewNpmPackage<'_>) -> Result<Uuid, sqlx::Error> {
+        let now = Utc::now();
+        let mut tables = self.write_tables();
+        let (id, first_seen_at) = tables
+            .npm_packages
+            .values()
+            .find(|row| row.name == p.name)
+            .map_or((Uuid::new_v4(), now), |row| (row.id, row.first_seen_at));
+        tables.npm_packages.insert(
+            id,
+            NpmPackageRow {
+                id,
+                name: p.name.to_owned(),
+                scope: p.scope.map(str::to_owned),
+                description: p.description.map(str::to_owned),
+                repository_id: p.repository_id,
+                repository_url: p.repository_url.map(|u| u.as_str().to_owned()),
+                npm_url: p.npm_url.to_owned(),
+                homepage_url: p.homepage_url.map(str::to_owned),
+                downloads_weekly: p.downloads_weekly,
+                version: p.version.map(str::to_owned),
+                keywords: p.keywords.to_vec(),
+                synced_at: now,
+                first_seen_at,
+            },
+        );
+        Ok(id)
+    }
+
+    async fn upsert_contribution(&self, c: &NewContribution<'_>) -> Result<Uuid, sqlx::Error> {
+        let key = (
+            c.forge.to_owned(),
+            c.repo_owner.to_owned(),
+            c.repo_name.to_owned(),
+            c.url.to_owned(),
+        );
+        let mut tables = self.write_tables();
+        let id = tables
+            .contributions
+            .get(&key)
+            .map_or_else(Uuid::new_v4, |row| row.id);
+        tables.contributions.insert(
+            key,
+            Contribution {
+                id,
+                forge: c.forge.to_owned(),
+                repo_owner: c.repo_owner.to_owned(),
+                repo_name: c.repo_name.to_owned(),
+                repo_url: c.repo_url.to_owned(),
+                contribution_type: c.contribution_type.to_owned(),
+                title: c.title.map(str::to_owned),
+                url: c.url.to_owned(),
+                merged_at: c.merged_at,
+                additions: c.additions,
+                deletions: c.deletions,
+                changed_files: c.changed_files,
+                labels: c.labels.to_vec(),
+                upstream_stars: None,
+                upstream_language: None,
+                synced_at: Utc::now(),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn upsert_upstream_repo(&self, r: &NewUpstreamRepo<'_>) -> Result<Uuid, sqlx::Error> {
+        let key = (r.forge.to_owned(), r.owner.to_owned(), r.name.to_owned());
+        let mut tables = self.write_tables();
+        let id = tables
+            .upstream_repos
+            .get(&key)
+            .map_or_else(Uuid::new_v4, |row| row.id);
+        tables.upstream_repos.insert(
+            key,
+            UpstreamRepoRow {
+                id,
+                stars: r.stars,
+                language: r.language.map(str::to_owned),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn upsert_release(&self, r: &NewRelease<'_>) -> Result<Uuid, sqlx::Error> {
+        let key = (
+            r.artifact_kind.to_owned(),
+            r.artifact.to_owned(),
+            r.version.to_owned(),
+        );
+        let mut tables = self.write_tables();
+        let id = tables
+            .releases
+            .get(&key)
+            .map_or_else(Uuid::new_v4, |row| row.id);
+        tables.releases.insert(
+            key,
+            Release {
+                id,
+                artifact_kind: r.artifact_kind.to_owned(),
+                artifact: r.artifact.to_owned(),
+                version: r.version.to_owned(),
+                released_at: r.released_at,
+                yanked: r.yanked,
+                notes_url: r.notes_url.map(str::to_owned),
+                synced_at: Utc::now(),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn get_readme_hash(&self, repository_id: Uuid) -> Result<Option<String>, sqlx::Error> {
+        Ok(self
+            .read_tables()
+            .readmes
+            .get(&repository_id)
+            .map(|r| r.content_hash.clone()))
+    }
+
+    async fn upsert_readme(&self, r: &NewReadme<'_>) -> Result<(), sqlx::Error> {
+        self.write_tables().readmes.insert(
+            r.repository_id,
+            ReadmeRow {
+                content_hash: r.content_hash.to_owned(),
+                html: r.html.to_owned(),
+            },
+        );
+        Ok(())
+    }
+
+    async fn delete_readme(&self, repository_id: Uuid) -> Result<bool, sqlx::Error> {
+        Ok(self.write_tables().readmes.remove(&repository_id).is_some())
+    }
+
+    async fn get_workspace_pushed_at(
+        &self,
+        repository_id: Uuid,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        Ok(self
+            .read_tables()
+            .workspace_pushed_at
+            .get(&repository_id)
+            .copied())
+    }
+
+    async fn set_workspace_members(
+        &self,
+        repository_id: Uuid,
+        pushed_at: Option<DateTime<Utc>>,
+        members: &[NewWorkspaceMember<'_>],
+    ) -> Result<(), sqlx::Error> {
+        let mut rows: Vec<(String, String)> = Vec::with_capacity(members.len());
+        for m in members {
+            if !rows.iter().any(|(name, _)| name == m.crate_name) {
+                rows.push((m.crate_name.to_owned(), m.path.to_owned()));
+            }
+        }
+        let mut tables = self.write_tables();
+        match pushed_at {
+            Some(pushed_at) => tables.workspace_pushed_at.insert(repository_id, pushed_at),
+            None => tables.workspace_pushed_at.remove(&repository_id),
+        };
+        if rows.is_empty() {
+            tables.workspace_members.remove(&repository_id);
+        } else {
+            tables.workspace_members.insert(repository_id, rows);
+        }
+        Ok(())
+    }
+
+    async fn refresh_projects(&self) -> Result<(), sqlx::Error> {
+        let view = build_view(&self.read_tables());
+        *self.view.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(view);
+        Ok(())
+    }
+
+    async fn record_sync_run(&self, _error: Option<&str>) -> Result<(), sqlx::Error> {
+        self.write_tables().last_sync_at = Some(Utc::now());
+        Ok(())
+    }
+
+    async fn get_last_sync_at(&self) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        Ok(self.read_tables().last_sync_at)
+    }
+
+    async fn get_projects(&self, filters: &ProjectFilters) -> Result<ProjectPage, sqlx::Error> {
+        let view = self.view();
+        Ok(view
+            .snapshot
+            .projects_with_details(filters, |id| view.details.get(&id)))
+    }
+
+    async fn get_project_facets(
+        &self,
+        filters: &ProjectFilters,
+    ) -> Result<ProjectFacets, sqlx::Error> {
+        Ok(self.view().snapshot.project_facets(filters))
+    }
+
+    async fn get_project_detail(
+        &self,
+        kind: ProjectKind,
+        name: &str,
+    ) -> Result<Option<ProjectDetail>, sqlx::Error> {
+        let view = self.view();
+        Ok(view
+            .snapshot
+            .project(kind, name)
+            .and_then(|p| view.details.get(&p.id).cloned())
+            .or_else(|| view.snapshot.project_detail(kind, name)))
+    }
+
+    async fn get_project_releases(
+        &self,
+        project_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Release>, sqlx::Error> {
+        let view = self.view();
+        let Some(artifacts) = view.release_artifacts.get(&project_id) else {
+            return Ok(Vec::new());
+        };
+        let mut releases: Vec<Release> = view
+            .releases
+            .iter()
+            .filter(|r| {
+                artifacts
+                    .iter()
+                    .any(|(kind, artifact)| *kind == r.artifact_kind && *artifact == r.artifact)
+            })
+            .cloned()
+            .collect();
+        releases.sort_by(|a, b| {
+            b.released_at
+                .cmp(&a.released_at)
+                .then_with(|| b.version.cmp(&a.version))
+        });
+        releases.truncate(usize::try_from(limit).unwrap_or(0));
+        Ok(releases)
+    }
+
+    async fn get_recent_releases(
+        &self,
+        artifact_kind: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Release>, sqlx::Error> {
+        let view = self.view();
+        let mut releases: Vec<Release> = view
+            .releases
+            .iter()
+            .filter(|r| r.released_at.is_some() && !r.yanked)
+            .filter(|r| artifact_kind.is_none_or(|k| k == r.artifact_kind))
+            .cloned()
+            .collect();
+        releases.sort_by(|a, b| {
+            b.released_at
+                .cmp(&a.released_at)
+                .then_with(|| a.artifact.cmp(&b.artifact))
+        });
+        releases.truncate(usize::try_from(limit).unwrap_or(0));
+        Ok(releases)
+    }
+
+    async fn get_contributions(
+        &self,
+        limit: i64,
+        max_age_years: i32,
+        sort: ContributionSort,
+    ) -> Result<Vec<Contribution>, sqlx::Error> {
+        Ok(self
+            .view()
+            .snapshot
+            .contributions(limit, max_age_years, sort))
+    }
+
+    async fn get_contribution_page(
+        &self,
+        filters: &ContributionFilters,
+        limit: i64,
+    ) -> Result<ContributionPage, sqlx::Error> {
+        Ok(self.view().snapshot.contribution_page(filters, limit))
+    }
+
+    async fn get_related_contributions(
+        &self,
+        terms: &[String],
+        limit: i64,
+    ) -> Result<Vec<Contribution>, sqlx::Error> {
+        Ok(self.view().snapshot.related_contributions(terms, limit))
+    }
+
+    async fn get_contribution_years(&self) -> Result<Vec<i32>, sqlx::Error> {
+        Ok(self.view().snapshot.contribution_years())
+    }
+
+    async fn get_contribution_owners(&self) -> Result<Vec<String>, sqlx::Error> {
+        Ok(self.view().snapshot.contribution_owners())
+    }
+
+    async fn get_contribution_stats(&self) -> Result<ContributionStats, sqlx::Error> {
+        Ok(self.view().snapshot.contribution_stats())
+    }
+
+    async fn get_all_projects(&self) -> Result<Vec<ProjectView>, sqlx::Error> {
+        Ok(self.view().snapshot.projects.clone())
+    }
+
+    async fn get_distinct_topics(&self) -> Result<Vec<String>, sqlx::Error> {
+        Ok(self.view().snapshot.topics.clone())
+    }
+
+    async fn get_all_contributions(&self) -> Result<Vec<Contribution>, sqlx::Error> {
+        Ok(self.view().snapshot.contributions.clone())
+    }
+
+    async fn get_new_projects(
+        &self,
+        kind: Option<ProjectKind>,
+        limit: i64,
+    ) -> Result<Vec<ProjectView>, sqlx::Error> {
+        let projects = self
+            .view()
+            .snapshot
+            .projects
+            .iter()
+            .filter(|p| kind.is_none_or(|k| p.artifacts.iter().any(|a| a.kind == k)))
+            .cloned()
+            .collect();
+        Ok(top(projects, limit, |a, b| {
+            b.first_seen_at
+                .cmp(&a.first_seen_at)
+                .then_with(|| a.name.cmp(&b.name))
+        }))
+    }
+
+    async fn get_sync_status(&self) -> Result<Vec<SourceSyncStatus>, sqlx::Error> {
+        let tables = self.read_tables();
+        let forges: BTreeSet<&str> = tables
+            .repositories
+            .values()
+            .map(|r| r.forge.as_str())
+            .collect();
+        let mut sources: Vec<SourceSyncStatus> = forges
+            .into_iter()
+            .map(|forge| {
+                let synced = tables.repositories.values().filter(|r| r.forge == forge);
+                sync_status(forge, synced.map(|r| r.synced_at))
+            })
+            .collect();
+        sources.extend([
+            sync_status("crates.io", tables.crates.values().map(|c| c.synced_at)),
+            sync_status("npm", tables.npm_packages.values().map(|n| n.synced_at)),
+            sync_status(
+                "contributions",
+                tables.contributions.values().map(|c| c.synced_at),
+            ),
+            sync_status("releases", tables.releases.values().map(|r| r.synced_at)),
+        ]);
+        sources.sort_by(|a, b| a.source.cmp(&b.source));
+        Ok(sources)
+    }
+
+    async fn get_repositories(&self, limit: i64) -> Result<Vec<Repository>, sqlx::Error> {
+        let repositories = self
+            .read_tables()
+            .repositories
+            .values()
+            .map(Repository::from)
+            .collect();
+        Ok(top(repositories, limit, |a, b| {
+            b.stars.cmp(&a.stars).then_with(|| a.name.cmp(&b.name))
+        }))
+    }
+
+    async fn get_repositories_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Repository>, sqlx::Error> {
+        let tables = self.read_tables();
+        Ok(ids
+            .iter()
+            .filter_map(|id| tables.repositories.get(id))
+            .map(Repository::from)
+            .collect())
+    }
+
+    async fn get_crates(&self, limit: i64) -> Result<Vec<Crate>, sqlx::Error> {
+        let crates = self
+            .read_tables()
+            .crates
+            .values()
+            .map(Crate::from)
+            .collect();
+        Ok(top(crates, limit, |a, b| {
+            b.downloads
+                .cmp(&a.downloads)
+                .then_with(|| a.name.cmp(&b.name))
+        }))
+    }
+
+    async fn get_crates_by_repository_ids(
+        &self,
+        repository_ids: &[Uuid],
+    ) -> Result<Vec<Crate>, sqlx::Error> {
+        let crates = self
+            .read_tables()
+            .crates
+            .values()
+            .filter(|c| {
+                c.repository_id
+                    .is_some_and(|id| repository_ids.contains(&id))
+            })
+            .map(Crate::from)
+            .collect();
+        Ok(top(crates, i64::MAX, |a, b| a.name.cmp(&b.name)))
+    }
+
+    async fn get_npm_packages(&self, limit: i64) -> Result<Vec<NpmPackage>, sqlx::Error> {
+        let packages = self
+            .read_tables()
+            .npm_packages
+            .values()
+            .map(NpmPackage::from)
+            .collect();
+        Ok(top(packages, limit, |a, b| {
+            b.downloads_weekly
+                .cmp(&a.downloads_weekly)
+                .then_with(|| a.name.cmp(&b.name))
+        }))
+    }
+
+    async fn get_npm_packages_by_repository_ids(
+        &self,
+        repository_ids: &[Uuid],
+    ) -> Result<Vec<NpmPackage>, sqlx::Error> {
+        let packages = self
+            .read_tables()
+            .npm_packages
+            .values()
+            .filter(|n| {
+                n.repository_id
+                    .is_some_and(|id| repository_ids.contains(&id))
+            })
+            .map(NpmPackage::from)
+            .collect();
+        Ok(top(packages, i64::MAX, |a, b| a.name.cmp(&b.name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repository<'a>(forge_id: &'a str, name: &'a str, url: &'a str) -> NewRepository<'a> {
+        NewRepository {
+            forge: "github",
+            forge_id,
+            name,
+            description: Some("from the repository"),
+            url,
+            homepage_url: Some("https://example.com"),
+            language: Some("Rust"),
+            stars: 42,
+            topics: &[],
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn krate<'a>(
+        name: &'a str,
+        repository_id: Option<Uuid>,
+        repository_url: Option<&'a CanonicalUrl>,
+        downloads: i32,
+    ) -> NewCrate<'a> {
+        NewCrate {
+            name,
+            description: Some(""),
+            repository_id,
+            repository_url,
+            crates_io_url: "https://crates.io/crates/x",
+            documentation_url: Some("https://docs.rs/x"),
+            homepage_url: None,
+            downloads,
+            version: Some("1.0.0"),
+            keywords: &[],
+            categories: &[],
+            created_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn groups_artifacts_into_projects() {
+        let store = MemoryStore::default();
+        let url = "https://github.com/someone/tracer";
+        let repo_id = store
+            .upsert_repository(&repository("someone/tracer", "tracer", url))
+            .await
+            .unwrap();
+        store
+            .upsert_crate(&krate("tracer", Some(repo_id), None, 100))
+            .await
+            .unwrap();
+        // Synced before its repository would have been, so only linked by URL
+        let canonical = CanonicalUrl::parse(url).unwrap();
+        store
+            .upsert_crate(&krate("tracer-macros", None, Some(&canonical), 10))
+            .await
+            .unwrap();
+        assert_eq!(store.relink_artifacts().await.unwrap(), 1);
+        store
+            .upsert_readme(&NewReadme {
+                repository_id: repo_id,
+                content_hash: "abc",
+                html: "<p>hi</p>",
+            })
+            .await
+            .unwrap();
+
+        // Nothing is visible until the projects are refreshed.
+        assert!(store.get_all_projects().await.unwrap().is_empty());
+        store.refresh_projects().await.unwrap();
+
+        let projects = store.get_all_projects().await.unwrap();
+        assert_eq!(projects.len(), 1);
+        let project = &projects[0];
+        assert_eq!(project.kind, ProjectKind::Crate);
+        assert_eq!(project.name, "tracer");
+        assert_eq!(project.popularity, 110);
+        assert_eq!(project.description.as_deref(), Some("from the repository"));
+        let names: Vec<&str> = project.artifacts.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["tracer", "tracer", "tracer-macros"]);
+        assert_eq!(project.artifacts[1].kind, ProjectKind::Repo);
+
+        let detail = store
+            .get_project_detail(ProjectKind::Repo, "tracer")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(detail.stars, Some(42));
+        assert_eq!(detail.downloads, Some(110));
+        assert_eq!(detail.repository_url.as_deref(), Some(url));
+        assert_eq!(detail.homepage_url.as_deref(), Some("https://example.com"));
+        assert_eq!(detail.readme_html.as_deref(), Some("<p>hi</p>"));
+    }
+
+    #[tokio::test]
+    async fn upserts_keep_ids_and_stale_repositories_go() {
+        let store = MemoryStore::default();
+        let first = store
+            .upsert_repository(&repository("a/one", "one", "https://github.com/a/one"))
+            .await
+            .unwrap();
+        let again = store
+            .upsert_repository(&repository("a/one", "one", "https://github.com/a/one"))
+            .await
+            .unwrap();
+        assert_eq!(first, again);
+        let other = store
+            .upsert_repository(&repository("a/two", "two", "https://github.com/a/two"))
+            .await
+            .unwrap();
+        store
+            .upsert_crate(&krate("two", Some(other), None, 1))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store
+                .delete_stale_repositories("github", &[first])
+                .await
+                .unwrap(),
+            1
+        );
+        store.refresh_projects().await.unwrap();
+
+        // The crate outlives its repository as a project of its own.
+        let mut kinds: Vec<ProjectKind> = store
+            .get_all_projects()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.kind)
+            .collect();
+        kinds.sort_by_key(ToString::to_string);
+        assert_eq!(kinds, [ProjectKind::Crate, ProjectKind::Repo]);
+    }
+
+    #[tokio::test]
+    async fn serves_releases_and_contributions() {
+        let store = MemoryStore::default();
+        store
+            .upsert_crate(&krate("tracer", None, None, 1))
+            .await
+            .unwrap();
+        for (version, yanked) in [("1.0.0", false), ("1.1.0", true)] {
+            store
+                .upsert_release(&NewRelease {
+                    artifact_kind: "crate",
+                    artifact: "tracer",
+                    version,
+                    released_at: Some(Utc::now()),
+                    yanked,
+                    notes_url: None,
+                })
+                .await
+                .unwrap();
+        }
+        store
+            .upsert_contribution(&NewContribution {
+                forge: "github",
+                repo_owner: "tokio-rs",
+                repo_name: "tracing",
+                repo_url: "https://github.com/tokio-rs/tracing",
+                contribution_type: "pull_request",
+                title: Some("Fix a typo"),
+                url: "https://github.com/tokio-rs/tracing/pull/1",
+                merged_at: Some(Utc::now()),
+                additions: Some(1),
+                deletions: Some(1),
+                changed_files: Some(1),
+                labels: &[],
+            })
+            .await
+            .unwrap();
+        store
+            .upsert_upstream_repo(&NewUpstreamRepo {
+                forge: "github",
+                owner: "tokio-rs",
+                name: "tracing",
+                url: "https://github.com/tokio-rs/tracing",
+                description: None,
+                language: Some("Rust"),
+                stars: 5000,
+            })
+            .await
+            .unwrap();
+        store.refresh_projects().await.unwrap();
+
+        let project = store.get_all_projects().await.unwrap().remove(0);
+        assert_eq!(
+            store
+                .get_project_releases(project.id, 10)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        let recent = store.get_recent_releases(None, 10).await.unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].version, "1.0.0");
+
+        let contributions = store
+            .get_contributions(10, 2, ContributionSort::Recent)
+            .await
+            .unwrap();
+        assert_eq!(contributions[0].upstream_stars, Some(5000));
+    }
+}
@@ -0,0 +1,174 @@
+//! Where synced data is written and read back from.
+//!
+//! [`Store`] covers what `sync::run_sync` writes and what the server functions read.
+//! `PostgreSQL` implements it through the queries in [`crate::db`]. [`SqliteStore`] keeps the
+//! same tables in a single file, for a deployment on one box. [`MemoryStore`] keeps
+//! everything in the process, so a small deployment or a local demo can sync from the APIs
+//! and serve real data without a database.
+
+mod memory;
+mod postgres;
+mod sqlite;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::db::{
+    CanonicalUrl, Contribution, ContributionFilters, ContributionPage, ContributionSort,
+    ContributionStats, Crate, NewContribution, NewCrate, NewNpmPackage, NewReadme, NewRelease,
+    NewRepository, NewUpstreamRepo, NewWorkspaceMember, NpmPackage, ProjectDetail, ProjectFacets,
+    ProjectFilters, ProjectKind, ProjectPage, ProjectView, Release, Repository, SourceSyncStatus,
+};
+
+pub use memory::MemoryStore;
+pub use sqlite::SqliteStore;
+
+/// A store shared between the sync task and request handlers
+pub type SharedStore = Arc<dyn Store>;
+
+/// Each method behaves as the [`crate::db`] query of the same name. Errors are `sqlx`'s, which
+/// [`MemoryStore`] never returns.
+#[async_trait]
+pub trait Store: Send + Sync {
+    // Written by a sync run
+
+    async fn upsert_repository(&self, r: &NewRepository<'_>) -> Result<Uuid, sqlx::Error>;
+
+    async fn delete_stale_repositories(
+        &self,
+        forge: &str,
+        synced_ids: &[Uuid],
+    ) -> Result<u64, sqlx::Error>;
+
+    async fn get_repositories_by_urls(
+        &self,
+        urls: &[CanonicalUrl],
+    ) -> Result<HashMap<String, Uuid>, sqlx::Error>;
+
+    async fn relink_artifacts(&self) -> Result<u64, sqlx::Error>;
+
+    async fn upsert_crate(&self, c: &NewCrate<'_>) -> Result<Uuid, sqlx::Error>;
+
+    async fn upsert_npm_package(&self, p: &NewNpmPackage<'_>) -> Result<Uuid, sqlx::Error>;
+
+    async fn upsert_contribution(&self, c: &NewContribution<'_>) -> Result<Uuid, sqlx::Error>;
+
+    async fn upsert_upstream_repo(&self, r: &NewUpstreamRepo<'_>) -> Result<Uuid, sqlx::Error>;
+
+    async fn upsert_release(&self, r: &NewRelease<'_>) -> Result<Uuid, sqlx::Error>;
+
+    async fn get_readme_hash(&self, repository_id: Uuid) -> Result<Option<String>, sqlx::Error>;
+
+    async fn upsert_readme(&self, r: &NewReadme<'_>) -> Result<(), sqlx::Error>;
+
+    async fn delete_readme(&self, repository_id: Uuid) -> Result<bool, sqlx::Error>;
+
+    async fn get_workspace_pushed_at(
+        &self,
+        repository_id: Uuid,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error>;
+
+    async fn set_workspace_members(
+        &self,
+        repository_id: Uuid,
+        pushed_at: Option<DateTime<Utc>>,
+        members: &[NewWorkspaceMember<'_>],
+    ) -> Result<(), sqlx::Error>;
+
+    /// Make a run's writes visible to the project queries
+    async fn refresh_projects(&self) -> Result<(), sqlx::Error>;
+
+    async fn record_sync_run(&self, error: Option<&str>) -> Result<(), sqlx::Error>;
+
+    async fn get_last_sync_at(&self) -> Result<Option<DateTime<Utc>>, sqlx::Error>;
+
+    // Read by the pages
+
+    async fn get_projects(&self, filters: &ProjectFilters) -> Result<ProjectPage, sqlx::Error>;
+
+    async fn get_project_facets(
+        &self,
+        filters: &ProjectFilters,
+    ) -> Result<ProjectFacets, sqlx::Error>;
+
+    async fn get_project_detail(
+        &self,
+        kind: ProjectKind,
+        name: &str,
+    ) -> Result<Option<ProjectDetail>, sqlx::Error>;
+
+    async fn get_project_releases(
+        &self,
+        project_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Release>, sqlx::Error>;
+
+    async fn get_recent_releases(
+        &self,
+        artifact_kind: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Release>, sqlx::Error>;
+
+    async fn get_contributions(
+        &self,
+        limit: i64,
+        max_age_years: i32,
+        sort: ContributionSort,
+    ) -> Result<Vec<Contribution>, sqlx::Error>;
+
+    async fn get_contribution_page(
+        &self,
+        filters: &ContributionFilters,
+        limit: i64,
+    ) -> Result<ContributionPage, sqlx::Error>;
+
+    async fn get_related_contributions(
+        &self,
+        terms: &[String],
+        limit: i64,
+    ) -> Result<Vec<Contribution>, sqlx::Error>;
+
+    async fn get_contribution_years(&self) -> Result<Vec<i32>, sqlx::Error>;
+
+    async fn get_contribution_owners(&self) -> Result<Vec<String>, sqlx::Error>;
+
+    async fn get_contribution_stats(&self) -> Result<ContributionStats, sqlx::Error>;
+
+    async fn get_all_projects(&self) -> Result<Vec<ProjectView>, sqlx::Error>;
+
+    async fn get_distinct_topics(&self) -> Result<Vec<String>, sqlx::Error>;
+
+    async fn get_all_contributions(&self) -> Result<Vec<Contribution>, sqlx::Error>;
+
+    // Read by the REST and GraphQL APIs and the feeds
+
+    async fn get_new_projects(
+        &self,
+        kind: Option<ProjectKind>,
+        limit: i64,
+    ) -> Result<Vec<ProjectView>, sqlx::Error>;
+
+    async fn get_sync_status(&self) -> Result<Vec<SourceSyncStatus>, sqlx::Error>;
+
+    async fn get_repositories(&self, limit: i64) -> Result<Vec<Repository>, sqlx::Error>;
+
+    async fn get_repositories_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Repository>, sqlx::Error>;
+
+    async fn get_crates(&self, limit: i64) -> Result<Vec<Crate>, sqlx::Error>;
+
+    async fn get_crates_by_repository_ids(
+        &self,
+        repository_ids: &[Uuid],
+    ) -> Result<Vec<Crate>, sqlx::Error>;
+
+    async fn get_npm_packages(&self, limit: i64) -> Result<Vec<NpmPackage>, sqlx::Error>;
+
+    async fn get_npm_packages_by_repository_ids(
+        &self,
+        repository_ids: &[Uuid],
+    ) -> Result<Vec<NpmPackage>, sqlx::Error>;
+}
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::Store;
+use crate::db::{
+    self, CanonicalUrl, Contribution, ContributionFilters, ContributionPage, ContributionSort,
+    ContributionStats, Crate, NewContribution, NewCrate, NewNpmPackage, NewReadme, NewRelease,
+    NewRepository, NewUpstreamRepo, NewWorkspaceMember, NpmPackage, ProjectDetail, ProjectFacets,
+    ProjectFilters, ProjectKind, ProjectPage, ProjectView, Release, Repository, SourceSyncStatus,
+};
+
+#[async_trait]
+impl Store for PgPool {
+    async fn upsert_repository(&self, r: &NewRepository<'_>) -> Result<Uuid, sqlx::Error> {
+        db::upsert_repository(self, r).await
+    }
+
+    async fn delete_stale_repositories(
+        &self,
+        forge: &str,
+        synced_ids: &[Uuid],
+    ) -> Result<u64, sqlx::Error> {
+        db::delete_stale_repositories(self, forge, synced_ids).await
+    }
+
+    async fn get_repositories_by_urls(
+        &self,
+        urls: &[CanonicalUrl],
+    ) -> Result<HashMap<String, Uuid>, sqlx::Error> {
+        db::get_repositories_by_urls(self, urls).await
+    }
+
+    async fn relink_artifacts(&self) -> Result<u64, sqlx::Error> {
+        db::relink_artifacts(self).await
+    }
+
+    async fn upsert_crate(&self, c: &NewCrate<'_>) -> Result<Uuid, sqlx::Error> {
+        db::upsert_crate(self, c).await
+    }
+
+    async fn upsert_npm_package(&self, p: &NewNpmPackage<'_>) -> Result<Uuid, sqlx::Error> {
+        db::upsert_npm_package(self, p).await
+    }
+
+    async fn upsert_contribution(&self, c: &NewContribution<'_>) -> Result<Uuid, sqlx::Error> {
+        db::upsert_contribution(self, c).await
+    }
+
+    async fn upsert_upstream_repo(&self, r: &NewUpstreamRepo<'_>) -> Result<Uuid, sqlx::Error> {
+        db::upsert_upstream_repo(self, r).await
+    }
+
+    async fn upsert_release(&self, r: &NewRelease<'_>) -> Result<Uuid, sqlx::Error> {
+        db::upsert_release(self, r).await
+    }
+
+    async fn get_readme_hash(&self, repository_id: Uuid) -> Result<Option<String>, sqlx::Error> {
+        db::get_readme_hash(self, repository_id).await
+    }
+
+    async fn upsert_readme(&self, r: &NewReadme<'_>) -> Result<(), sqlx::Error> {
+        db::upsert_readme(self, r).await
+    }
+
+    async fn delete_readme(&self, repository_id: Uuid) -> Result<bool, sqlx::Error> {
+        db::delete_readme(self, repository_id).await
+    }
+
+    async fn get_workspace_pushed_at(
+        &self,
+        repository_id: Uuid,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        db::get_workspace_pushed_at(self, repository_id).await
+    }
+
+    async fn set_workspace_members(
+        &self,
+        repository_id: Uuid,
+        pushed_at: Option<DateTime<Utc>>,
+        members: &[NewWorkspaceMember<'_>],
+    ) -> Result<(), sqlx::Error> {
+        db::set_workspace_members(self, repository_id, pushed_at, members).await
+    }
+
+    async fn refresh_projects(&self) -> Result<(), sqlx::Error> {
+        db::refresh_projects(self).await
+    }
+
+    async fn record_sync_run(&self, error: Option<&str>) -> Result<(), sqlx::Error> {
+        db::record_sync_run(self, error).await
+    }
+
+    async fn get_last_sync_at(&self) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        db::get_last_sync_at(self).await
+    }
+
+    async fn get_projects(&self, filters: &ProjectFilters) -> Result<ProjectPage, sqlx::Error> {
+        db::get_projects(self, filters).await
+    }
+
+    async fn get_project_facets(
+        &self,
+        filters: &ProjectFilters,
+    ) -> Result<ProjectFacets, sqlx::Error> {
+        db::get_project_facets(self, filters).await
+    }
+
+    async fn get_project_detail(
+        &self,
+        kind: ProjectKind,
+        name: &str,
+    ) -> Result<Option<ProjectDetail>, sqlx::Error> {
+        db::get_project_detail(self, kind, name).await
+    }
+
+    async fn get_project_releases(
+        &self,
+        project_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Release>, sqlx::Error> {
+        db::get_project_releases(self, project_id, limit).await
+    }
+
+    async fn get_recent_releases(
+        &self,
+        artifact_kind: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Release>, sqlx::Error> {
+        db::get_recent_releases(self, artifact_kind, limit).await
+    }
+
+    async fn get_contributions(
+        &self,
+        limit: i64,
+        max_age_years: i32,
+        sort: ContributionSort,
+    ) -> Result<Vec<Contribution>, sqlx::Error> {
+        db::get_contributions(self, limit, max_age_years, sort).await
+    }
+
+    async fn get_contribution_page(
+        &self,
+        filters: &ContributionFilters,
+        limit: i64,
+    ) -> Result<ContributionPage, sqlx::Error> {
+        db::get_contribution_page(self, filters, limit).await
+    }
+
+    async fn get_related_contributions(
+        &self,
+        terms: &[String],
+        limit: i64,
+    ) -> Result<Vec<Contribution>, sqlx::Error> {
+        db::get_related_contributions(self, terms, limit).await
+    }
+
+    async fn get_contribution_years(&self) -> Result<Vec<i32>, sqlx::Error> {
+        db::get_contribution_years(self).await
+    }
+
+    async fn get_contribution_owners(&self) -> Result<Vec<String>, sqlx::Error> {
+        db::get_contribution_owners(self).await
+    }
+
+    async fn get_contribution_stats(&self) -> Result<ContributionStats, sqlx::Error> {
+        db::get_contribution_stats(self).await
+    }
+
+    async fn get_all_projects(&self) -> Result<Vec<ProjectView>, sqlx::Error> {
+        db::get_all_projects(self).await
+    }
+
+    async fn get_distinct_topics(&self) -> Result<Vec<String>, sqlx::Error> {
+        db::get_distinct_topics(self).await
+    }
+
+    async fn get_all_contributions(&self) -> Result<Vec<Contribution>, sqlx::Error> {
+        db::get_all_contributions(self).await
+    }
+
+    async fn get_new_projects(
+        &self,
+        kind: Option<ProjectKind>,
+        limit: i64,
+    ) -> Result<Vec<ProjectView>, sqlx::Error> {
+        db::get_new_projects(self, kind, limit).await
+    }
+
+    async fn get_sync_status(&self) -> Result<Vec<SourceSyncStatus>, sqlx::Error> {
+        db::get_sync_status(self).await
+    }
+
+    async fn get_repositories(&self, limit: i64) -> Result<Vec<Repository>, sqlx::Error> {
+        db::get_repositories(self, limit).await
+    }
+
+    async fn get_repositories_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Repository>, sqlx::Error> {
+        db::get_repositories_by_ids(self, ids).await
+    }
+
+    async fn get_crates(&self, limit: i64) -> Result<Vec<Crate>, sqlx::Error> {
+        db::get_crates(self, limit).await
+    }
+
+    async fn get_crates_by_repository_ids(
+        &self,
+        repository_ids: &[Uuid],
+    ) -> Result<Vec<Crate>, sqlx::Error> {
+        db::get_crates_by_repository_ids(self, repository_ids).await
+    }
+
+    async fn get_npm_packages(&self, limit: i64) -> Result<Vec<NpmPackage>, sqlx::Error> {
+        db::get_npm_packages(self, limit).await
+    }
+
+    async fn get_npm_packages_by_repository_ids(
+        &self,
+        repository_ids: &[Uuid],
+    ) -> Result<Vec<NpmPackage>, sqlx::Error> {
+        db::get_npm_packages_by_repository_ids(self, repository_ids).await
+    }
+}
@@ -0,0 +1,227 @@
+//! The repository, crate and npm package queries, ported from [`crate::db`]'s, and the sync
+//! status counted from the same tables.
+
+use chrono::{DateTime, Utc};
+use sqlx::types::Json;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::hex_ids;
+use crate::db::{Crate, NpmPackage, Repository, SourceSyncStatus};
+
+#[derive(sqlx::FromRow)]
+struct RepositoryRow {
+    id: Uuid,
+    forge: String,
+    forge_id: String,
+    name: String,
+    description: Option<String>,
+    url: String,
+    language: Option<String>,
+    stars: i32,
+    topics: Json<Vec<String>>,
+    updated_at: Option<DateTime<Utc>>,
+    synced_at: DateTime<Utc>,
+}
+
+impl From<RepositoryRow> for Repository {
+    fn from(row: RepositoryRow) -> Self {
+        Self {
+            id: row.id,
+            forge: row.forge,
+            forge_id: row.forge_id,
+            name: row.name,
+            description: row.description,
+            url: row.url,
+            language: row.language,
+            stars: row.stars,
+            topics: row.topics.0,
+            updated_at: row.updated_at,
+            synced_at: row.synced_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CrateRow {
+    id: Uuid,
+    name: String,
+    description: Option<String>,
+    repository_id: Option<Uuid>,
+    crates_io_url: String,
+    documentation_url: Option<String>,
+    downloads: i32,
+    version: Option<String>,
+    keywords: Json<Vec<String>>,
+    categories: Json<Vec<String>>,
+    synced_at: DateTime<Utc>,
+}
+
+impl From<CrateRow> for Crate {
+    fn from(row: CrateRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            description: row.description,
+            repository_id: row.repository_id,
+            crates_io_url: row.crates_io_url,
+            documentation_url: row.documentation_url,
+            downloads: row.downloads,
+            version: row.version,
+            keywords: row.keywords.0,
+            categories: row.categories.0,
+            synced_at: row.synced_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct NpmPackageRow {
+    id: Uuid,
+    name: String,
+    scope: Option<String>,
+    description: Option<String>,
+    repository_id: Option<Uuid>,
+    npm_url: String,
+    downloads_weekly: i32,
+    version: Option<String>,
+    keywords: Json<Vec<String>>,
+    synced_at: DateTime<Utc>,
+}
+
+impl From<NpmPackageRow> for NpmPackage {
+    fn from(row: NpmPackageRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            scope: row.scope,
+            description: row.description,
+            repository_id: row.repository_id,
+            npm_url: row.npm_url,
+            downloads_weekly: row.downloads_weekly,
+            version: row.version,
+            keywords: row.keywords.0,
+            synced_at: row.synced_at,
+        }
+    }
+}
+
+const REPOSITORIES: &str = r"
+    SELECT
+        id, forge, forge_id, name, description, url, language, COALESCE(stars, 0) AS stars,
+        topics, updated_at, synced_at
+    FROM repositories
+";
+
+const CRATES: &str = r"
+    SELECT
+        id, name, description, repository_id, crates_io_url, documentation_url,
+        COALESCE(downloads, 0) AS downloads, version, keywords, categories, synced_at
+    FROM crates
+";
+
+const NPM_PACKAGES: &str = r"
+    SELECT
+        id, name, scope, description, repository_id, npm_url,
+        COALESCE(downloads_weekly, 0) AS downloads_weekly, version, keywords, synced_at
+    FROM npm_packages
+";
+
+pub(super) async fn get_sync_status(
+    pool: &SqlitePool,
+) -> Result<Vec<SourceSyncStatus>, sqlx::Error> {
+    let rows: Vec<(String, i64, Option<DateTime<Utc>>)> = sqlx::query_as(
+        r"
+        SELECT source, items, last_synced_at
+        FROM (
+            SELECT forge AS source, COUNT(*) AS items, MAX(synced_at) AS last_synced_at
+            FROM repositories GROUP BY forge
+            UNION ALL
+            SELECT 'crates.io', COUNT(*), MAX(synced_at) FROM crates
+            UNION ALL
+            SELECT 'npm', COUNT(*), MAX(synced_at) FROM npm_packages
+            UNION ALL
+            SELECT 'contributions', COUNT(*), MAX(synced_at) FROM contributions
+            UNION ALL
+            SELECT 'releases', COUNT(*), MAX(synced_at) FROM releases
+        )
+        ORDER BY source
+        ",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(source, items, last_synced_at)| SourceSyncStatus {
+            source,
+            items,
+            last_synced_at,
+        })
+        .collect())
+}
+
+pub(super) async fn get_repositories(
+    pool: &SqlitePool,
+    limit: i64,
+) -> Result<Vec<Repository>, sqlx::Error> {
+    let sql = format!("{REPOSITORIES} ORDER BY stars DESC NULLS LAST, name ASC LIMIT ?1");
+    let rows: Vec<RepositoryRow> = sqlx::query_as(&sql).bind(limit).fetch_all(pool).await?;
+    Ok(rows.into_iter().map(Repository::from).collect())
+}
+
+pub(super) async fn get_repositories_by_ids(
+    pool: &SqlitePool,
+    ids: &[Uuid],
+) -> Result<Vec<Repository>, sqlx::Error> {
+    let sql = format!("{REPOSITORIES} WHERE hex(id) IN (SELECT value FROM json_each(?1))");
+    let rows: Vec<RepositoryRow> = sqlx::query_as(&sql)
+        .bind(hex_ids(ids))
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(Repository::from).collect())
+}
+
+pub(super) async fn get_crates(pool: &SqlitePool, limit: i64) -> Result<Vec<Crate>, sqlx::Error> {
+    let sql = format!("{CRATES} ORDER BY downloads DESC NULLS LAST, name ASC LIMIT ?1");
+    let rows: Vec<CrateRow> = sqlx::query_as(&sql).bind(limit).fetch_all(pool).await?;
+    Ok(rows.into_iter().map(Crate::from).collect())
+}
+
+pub(super) async fn get_crates_by_repository_ids(
+    pool: &SqlitePool,
+    repository_ids: &[Uuid],
+) -> Result<Vec<Crate>, sqlx::Error> {
+    let sql = format!(
+        "{CRATES} WHERE hex(repository_id) IN (SELECT value FROM json_each(?1)) ORDER BY name ASC"
+    );
+    let rows: Vec<CrateRow> = sqlx::query_as(&sql)
+        .bind(hex_ids(repository_ids))
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(Crate::from).collect())
+}
+
+pub(super) async fn get_npm_packages(
+    pool: &SqlitePool,
+    limit: i64,
+) -> Result<Vec<NpmPackage>, sqlx::Error> {
+    let sql =
+        format!("{NPM_PACKAGES} ORDER BY downloads_weekly DESC NULLS LAST, name ASC LIMIT ?1");
+    let rows: Vec<NpmPackageRow> = sqlx::query_as(&sql).bind(limit).fetch_all(pool).await?;
+    Ok(rows.into_iter().map(NpmPackage::from).collect())
+}
+
+pub(super) async fn get_npm_packages_by_repository_ids(
+    pool: &SqlitePool,
+    repository_ids: &[Uuid],
+) -> Result<Vec<NpmPackage>, sqlx::Error> {
+    let sql = format!(
+        "{NPM_PACKAGES} WHERE hex(repository_id) IN (SELECT value FROM json_each(?1)) ORDER BY name ASC"
+    );
+    let rows: Vec<NpmPackageRow> = sqlx::query_as(&sql)
+        .bind(hex_ids(repository_ids))
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(NpmPackage::from).collect())
+}
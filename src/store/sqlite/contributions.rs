@@ -0,0 +1,335 @@
+//! The contribution queries, ported from [`crate::db`]'s. `SQLite` has no `LN`, so impact is
+//! ranked in Rust.
+
+use chrono::{DateTime, Months, Utc};
+use sqlx::types::Json;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::db::{
+    Contribution, ContributionCount, ContributionCursor, ContributionFilters, ContributionPage,
+    ContributionSort, ContributionStats, RepoActivity,
+};
+
+#[derive(sqlx::FromRow)]
+struct ContributionRow {
+    id: Uuid,
+    forge: String,
+    repo_owner: String,
+    repo_name: String,
+    repo_url: String,
+    contribution_type: String,
+    title: Option<String>,
+    url: String,
+    merged_at: Option<DateTime<Utc>>,
+    additions: Option<i32>,
+    deletions: Option<i32>,
+    changed_files: Option<i32>,
+    labels: Json<Vec<String>>,
+    upstream_stars: Option<i32>,
+    upstream_language: Option<String>,
+    synced_at: DateTime<Utc>,
+}
+
+impl From<ContributionRow> for Contribution {
+    fn from(row: ContributionRow) -> Self {
+        Self {
+            id: row.id,
+            forge: row.forge,
+            repo_owner: row.repo_owner,
+            repo_name: row.repo_name,
+            repo_url: row.repo_url,
+            contribution_type: row.contribution_type,
+            title: row.title,
+            url: row.url,
+            merged_at: row.merged_at,
+            additions: row.additions,
+            deletions: row.deletions,
+            changed_files: row.changed_files,
+            labels: row.labels.0,
+            upstream_stars: row.upstream_stars,
+            upstream_language: row.upstream_language,
+            synced_at: row.synced_at,
+        }
+    }
+}
+
+/// Contributions joined to their upstream repositories, for a `WHERE` and `ORDER BY` to follow
+const CONTRIBUTIONS: &str = r"
+    SELECT
+        c.id, c.forge, c.repo_owner, c.repo_name, c.repo_url, c.contribution_type, c.title,
+        c.url, c.merged_at, c.additions, c.deletions, c.changed_files, c.labels,
+        u.stars AS upstream_stars, u.language AS upstream_language, c.synced_at
+    FROM contributions c
+    LEFT JOIN upstream_repos u
+        ON u.forge = c.forge AND u.owner = c.repo_owner AND u.name = c.repo_name
+";
+
+/// Unmerged contributions sort as if merged at the Unix epoch, which is how it's stored.
+const EPOCH: &str = "'1970-01-01T00:00:00+00:00'";
+
+/// Size of the change weighed by the log of upstream stars, as [`crate::db::get_contributions`]
+/// computes it
+fn impact(c: &Contribution) -> f64 {
+    let size = f64::from(c.additions.unwrap_or(0)) + f64::from(c.deletions.unwrap_or(0));
+    size * (f64::from(c.upstream_stars.unwrap_or(0)) + 2.0).ln()
+}
+
+pub(super) async fn get_contributions(
+    pool: &SqlitePool,
+    limit: i64,
+    max_age_years: i32,
+    sort: ContributionSort,
+) -> Result<Vec<Contribution>, sqlx::Error> {
+    let now = Utc::now();
+    let max_age = Months::new(u32::try_from(max_age_years).unwrap_or(0).saturating_mul(12));
+    let cutoff = now.checked_sub_months(max_age).unwrap_or(now);
+
+    // Impact needs every candidate to rank; recency can stop at the limit.
+    let sql = format!(
+        r"
+        {CONTRIBUTIONS}
+        WHERE c.merged_at IS NULL OR c.merged_at > ?1
+        ORDER BY c.merged_at DESC NULLS LAST
+        LIMIT ?2
+        "
+    );
+    let fetch_limit = match sort {
+        ContributionSort::Recent => limit,
+        ContributionSort::Impact => -1,
+    };
+    let rows: Vec<ContributionRow> = sqlx::query_as(&sql)
+        .bind(cutoff)
+        .bind(fetch_limit)
+        .fetch_all(pool)
+        .await?;
+
+    let mut contributions: Vec<Contribution> = rows.into_iter().map(Contribution::from).collect();
+    if sort == ContributionSort::Impact {
+        // Stable, so equal impact keeps the most recent first.
+        contributions.sort_by(|a, b| impact(b).total_cmp(&impact(a)));
+        contributions.truncate(usize::try_from(limit).unwrap_or(0));
+    }
+    Ok(contributions)
+}
+
+/// Fetch one page of contributions, newest first, using keyset pagination on
+/// `(merged_at, id)`.
+pub(super) async fn get_contribution_page(
+    pool: &SqlitePool,
+    filters: &ContributionFilters,
+    limit: i64,
+) -> Result<ContributionPage, sqlx::Error> {
+    let sql = format!(
+        r"
+        {CONTRIBUTIONS}
+        WHERE (?1 IS NULL OR c.forge = ?1)
+          AND (?2 IS NULL OR LOWER(c.repo_owner) = LOWER(?2))
+          AND (?3 IS NULL OR CAST(strftime('%Y', c.merged_at) AS INTEGER) = ?3)
+          AND (?4 IS NULL OR c.contribution_type = ?4)
+          AND (?5 IS NULL OR (COALESCE(c.merged_at, {EPOCH}), c.id) < (?5, ?6))
+        ORDER BY COALESCE(c.merged_at, {EPOCH}) DESC, c.id DESC
+        LIMIT ?7
+        "
+    );
+
+    // Fetch one extra row to find out whether another page follows.
+    let rows: Vec<ContributionRow> = sqlx::query_as(&sql)
+        .bind(filters.forge.as_deref())
+        .bind(filters.owner.as_deref())
+        .bind(filters.year)
+        .bind(filters.contribution_type.as_deref())
+        .bind(filters.cursor.map(|c| c.merged_at))
+        .bind(filters.cursor.map(|c| c.id))
+        .bind(limit + 1)
+        .fetch_all(pool)
+        .await?;
+    let mut contributions: Vec<Contribution> = rows.into_iter().map(Contribution::from).collect();
+
+    let page_len = usize::try_from(limit).unwrap_or(0);
+    let has_more = contributions.len() > page_len;
+    contributions.truncate(page_len);
+
+    let next_cursor = if has_more {
+        contributions.last().map(|c| ContributionCursor {
+            merged_at: c.merged_at.unwrap_or(DateTime::<Utc>::UNIX_EPOCH),
+            id: c.id,
+        })
+    } else {
+        None
+    };
+
+    Ok(ContributionPage {
+        contributions,
+        next_cursor,
+    })
+}
+
+pub(super) async fn get_all_contributions(
+    pool: &SqlitePool,
+) -> Result<Vec<Contribution>, sqlx::Error> {
+    let sql = format!("{CONTRIBUTIONS} ORDER BY COALESCE(c.merged_at, {EPOCH}) DESC, c.id DESC");
+    let rows: Vec<ContributionRow> = sqlx::query_as(&sql).fetch_all(pool).await?;
+    Ok(rows.into_iter().map(Contribution::from).collect())
+}
+
+/// Merged contributions to upstream repositories whose name or owner matches one of `terms`,
+/// case-insensitively, newest first.
+pub(super) async fn get_related_contributions(
+    pool: &SqlitePool,
+    terms: &[String],
+    limit: i64,
+) -> Result<Vec<Contribution>, sqlx::Error> {
+    let terms: Vec<String> = terms.iter().map(|t| t.to_lowercase()).collect();
+    let sql = format!(
+        r"
+        {CONTRIBUTIONS}
+        WHERE c.merged_at IS NOT NULL
+          AND (LOWER(c.repo_name) IN (SELECT value FROM json_each(?1))
+               OR LOWER(c.repo_owner) IN (SELECT value FROM json_each(?1)))
+        ORDER BY c.merged_at DESC, c.id DESC
+        LIMIT ?2
+        "
+    );
+    let rows: Vec<ContributionRow> = sqlx::query_as(&sql)
+        .bind(Json(terms))
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(Contribution::from).collect())
+}
+
+pub(super) async fn get_contribution_years(pool: &SqlitePool) -> Result<Vec<i32>, sqlx::Error> {
+    sqlx::query_scalar(
+        r"
+        SELECT DISTINCT CAST(strftime('%Y', merged_at) AS INTEGER)
+        FROM contributions
+        WHERE merged_at IS NOT NULL
+        ORDER BY 1 DESC
+        ",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub(super) async fn get_contribution_owners(pool: &SqlitePool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r"
+        SELECT MIN(repo_owner)
+        FROM contributions
+        GROUP BY LOWER(repo_owner)
+        ORDER BY COUNT(*) DESC, 1 ASC
+        ",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Aggregate contribution counts per owner, year and month, plus per-repository activity,
+/// as [`crate::db::get_contribution_stats`] does. A recursive query stands in for
+/// `GENERATE_SERIES` to fill in empty months.
+pub(super) async fn get_contribution_stats(
+    pool: &SqlitePool,
+) -> Result<ContributionStats, sqlx::Error> {
+    let counts = |sql: &'static str| async move {
+        let counts: Vec<(String, i64)> = sqlx::query_as(sql).fetch_all(pool).await?;
+        Ok::<_, sqlx::Error>(
+            counts
+                .into_iter()
+                .map(|(key, count)| ContributionCount { key, count })
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    let by_owner = counts(
+        r"
+        SELECT repo_owner, COUNT(*)
+        FROM contributions
+        GROUP BY repo_owner
+        ORDER BY 2 DESC, 1 ASC
+        ",
+    );
+
+    let by_year = counts(
+        r"
+        SELECT strftime('%Y', merged_at), COUNT(*)
+        FROM contributions
+        WHERE merged_at IS NOT NULL
+        GROUP BY 1
+        ORDER BY 1 ASC
+        ",
+    );
+
+    let by_month = counts(
+        r"
+        WITH RECURSIVE monthly AS (
+            SELECT strftime('%Y-%m', merged_at) AS month, COUNT(*) AS count
+            FROM contributions
+            WHERE merged_at IS NOT NULL
+            GROUP BY 1
+        ),
+        series(month) AS (
+            SELECT MIN(month) FROM monthly
+            UNION ALL
+            SELECT strftime('%Y-%m', month || '-01', '+1 month') FROM series
+            WHERE month < (SELECT MAX(month) FROM monthly)
+        )
+        SELECT s.month, COALESCE(m.count, 0)
+        FROM series s
+        LEFT JOIN monthly m ON m.month = s.month
+        WHERE s.month IS NOT NULL
+        ORDER BY s.month ASC
+        ",
+    );
+
+    let repos = async {
+        let repos: Vec<RepoRow> = sqlx::query_as(
+            r"
+            SELECT
+                forge, repo_owner, repo_name, MIN(repo_url) AS url, COUNT(*) AS count,
+                MIN(merged_at) AS first_merged_at, MAX(merged_at) AS latest_merged_at
+            FROM contributions
+            GROUP BY forge, repo_owner, repo_name
+            ORDER BY MAX(merged_at) DESC NULLS LAST, repo_owner ASC, repo_name ASC
+            ",
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(repos.into_iter().map(RepoActivity::from).collect())
+    };
+
+    let (by_owner, by_year, by_month, repos) =
+        tokio::try_join!(by_owner, by_year, by_month, repos)?;
+
+    Ok(ContributionStats {
+        by_owner,
+        by_year,
+        by_month,
+        repos,
+    })
+}
+
+#[derive(sqlx::FromRow)]
+struct RepoRow {
+    forge: String,
+    repo_owner: String,
+    repo_name: String,
+    url: String,
+    count: i64,
+    first_merged_at: Option<DateTime<Utc>>,
+    latest_merged_at: Option<DateTime<Utc>>,
+}
+
+impl From<RepoRow> for RepoActivity {
+    fn from(row: RepoRow) -> Self {
+        Self {
+            forge: row.forge,
+            owner: row.repo_owner,
+            name: row.repo_name,
+            url: row.url,
+            count: row.count,
+            first_merged_at: row.first_merged_at,
+            latest_merged_at: row.latest_merged_at,
+        }
+    }
+}
@@ -0,0 +1,592 @@
+//! A [`Store`] in a `SQLite` file, for single-box deployments where a `PostgreSQL` cluster
+//! would be overkill.
+//!
+//! The schema lives in `migrations/sqlite` and mirrors the `PostgreSQL` one: `TEXT[]` columns
+//! hold JSON arrays, ids are generated here rather than by the database, and `projects` is a
+//! table that [`Store::refresh_projects`] refills, with an FTS5 index for search.
+
+mod artifacts;
+mod contributions;
+mod projects;
+mod search;
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use sqlx::types::Json;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::Store;
+use crate::db::{
+    CanonicalUrl, Contribution, ContributionFilters, ContributionPage, ContributionSort,
+    ContributionStats, Crate, NewContribution, NewCrate, NewNpmPackage, NewReadme, NewRelease,
+    NewRepository, NewUpstreamRepo, NewWorkspaceMember, NpmPackage, ProjectDetail, ProjectFacets,
+    ProjectFilters, ProjectKind, ProjectPage, ProjectView, Release, Repository, SourceSyncStatus,
+};
+
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    /// Whether `url` names a `SQLite` database rather than a `PostgreSQL` one
+    #[must_use]
+    pub fn handles(url: &str) -> bool {
+        url.starts_with("sqlite:")
+    }
+
+    /// Open the database at `url`, creating it if it doesn't exist, and migrate it.
+    ///
+    /// # Errors
+    /// Returns a [`sqlx::Error`] if the database can't be opened or a migration fails.
+    pub async fn open(url: &str) -> Result<Self, sqlx::Error> {
+        // WAL lets pages keep reading while a sync writes.
+        let options = SqliteConnectOptions::from_str(url)?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .foreign_keys(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(5)
+            .connect_with(options)
+            .await?;
+        sqlx::migrate!("./migrations/sqlite").run(&pool).await?;
+
+        Ok(Self { pool })
+    }
+}
+
+/// Ids as the hex `hex(id)` gives, since JSON has no blobs
+fn hex_ids(ids: &[Uuid]) -> Json<Vec<String>> {
+    Json(
+        ids.iter()
+            .map(|id| id.simple().to_string().to_uppercase())
+            .collect(),
+    )
+}
+
+/// An already migrated pool
+impl From<SqlitePool> for SqliteStore {
+    fn from(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    async fn upsert_repository(&self, r: &NewRepository<'_>) -> Result<Uuid, sqlx::Error> {
+        let canonical_url = CanonicalUrl::parse(r.url);
+        sqlx::query_scalar(
+            r"
+            INSERT INTO repositories (id, forge, forge_id, name, description, url, language, stars, topics, created_at, updated_at, homepage_url, canonical_url, synced_at, first_seen_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?14)
+            ON CONFLICT (forge, forge_id) DO UPDATE SET
+                name = excluded.name,
+                description = excluded.description,
+                url = excluded.url,
+                canonical_url = excluded.canonical_url,
+                language = excluded.language,
+                stars = excluded.stars,
+                topics = excluded.topics,
+                created_at = excluded.created_at,
+                updated_at = excluded.updated_at,
+                homepage_url = excluded.homepage_url,
+                synced_at = excluded.synced_at
+            RETURNING id
+            ",
+        )
+        .bind(Uuid::new_v4())
+        .bind(r.forge)
+        .bind(r.forge_id)
+        .bind(r.name)
+        .bind(r.description)
+        .bind(r.url)
+        .bind(r.language)
+        .bind(r.stars)
+        .bind(Json(r.topics))
+        .bind(r.created_at)
+        .bind(r.updated_at)
+        .bind(r.homepage_url)
+        .bind(canonical_url.as_ref().map(CanonicalUrl::as_str))
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn delete_stale_repositories(
+        &self,
+        forge: &str,
+        synced_ids: &[Uuid],
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r"
+            DELETE FROM repositories
+            WHERE forge = ?1 AND hex(id) NOT IN (SELECT value FROM json_each(?2))
+            ",
+        )
+        .bind(forge)
+        .bind(hex_ids(synced_ids))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn get_repositories_by_urls(
+        &self,
+        urls: &[CanonicalUrl],
+    ) -> Result<HashMap<String, Uuid>, sqlx::Error> {
+        if urls.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let urls: Vec<&str> = urls.iter().map(CanonicalUrl::as_str).collect();
+        let rows: Vec<(String, Uuid)> = sqlx::query_as(
+            r"
+            SELECT canonical_url, id FROM repositories
+            WHERE canonical_url IN (SELECT value FROM json_each(?1))
+            ",
+        )
+        .bind(Json(urls))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn relink_artifacts(&self) -> Result<u64, sqlx::Error> {
+        let crates = sqlx::query(
+            r"
+            UPDATE crates SET repository_id = r.id
+            FROM repositories r
+            WHERE crates.repository_id IS NULL AND r.canonical_url = crates.repository_url
+            ",
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        let packages = sqlx::query(
+            r"
+            UPDATE npm_packages SET repository_id = r.id
+            FROM repositories r
+            WHERE npm_packages.repository_id IS NULL
+              AND r.canonical_url = npm_packages.repository_url
+            ",
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(crates + packages)
+    }
+
+    async fn upsert_crate(&self, c: &NewCrate<'_>) -> Result<Uuid, sqlx::Error> {
+        sqlx::query_scalar(
+            r"
+            INSERT INTO crates (id, name, description, repository_id, crates_io_url, documentation_url, downloads, version, keywords, categories, created_at, homepage_url, repository_url, synced_at, first_seen_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?14)
+            ON CONFLICT (name) DO UPDATE SET
+                description = excluded.description,
+                repository_id = excluded.repository_id,
+                repository_url = excluded.repository_url,
+                crates_io_url = excluded.crates_io_url,
+                documentation_url = excluded.documentation_url,
+                downloads = excluded.downloads,
+                version = excluded.version,
+                keywords = excluded.keywords,
+                categories = excluded.categories,
+                created_at = excluded.created_at,
+                homepage_url = excluded.homepage_url,
+                synced_at = excluded.synced_at
+            RETURNING id
+            ",
+        )
+        .bind(Uuid::new_v4())
+        .bind(c.name)
+        .bind(c.description)
+        .bind(c.repository_id)
+        .bind(c.crates_io_url)
+        .bind(c.documentation_url)
+        .bind(c.downloads)
+        .bind(c.version)
+        .bind(Json(c.keywords))
+        .bind(Json(c.categories))
+        .bind(c.created_at)
+        .bind(c.homepage_url)
+        .bind(c.repository_url.map(CanonicalUrl::as_str))
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn upsert_npm_package(&self, p: &NewNpmPackage<'_>) -> Result<Uuid, sqlx::Error> {
+        sqlx::query_scalar(
+            r"
+            INSERT INTO npm_packages (id, name, scope, description, repository_id, npm_url, downloads_weekly, version, keywords, homepage_url, repository_url, synced_at, first_seen_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?12)
+            ON CONFLICT (name) DO UPDATE SET
+                scope = excluded.scope,
+                description = excluded.description,
+                repository_id = excluded.repository_id,
+                repository_url = excluded.repository_url,
+                npm_url = excluded.npm_url,
+                downloads_weekly = excluded.downloads_weekly,
+                version = excluded.version,
+                keywords = excluded.keywords,
+                homepage_url = excluded.homepage_url,
+                synced_at = excluded.synced_at
+            RETURNING id
+            ",
+        )
+        .bind(Uuid::new_v4())
+        .bind(p.name)
+        .bind(p.scope)
+        .bind(p.description)
+        .bind(p.repository_id)
+        .bind(p.npm_url)
+        .bind(p.downloads_weekly)
+        .bind(p.version)
+        .bind(Json(p.keywords))
+        .bind(p.homepage_url)
+        .bind(p.repository_url.map(CanonicalUrl::as_str))
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn upsert_contribution(&self, c: &NewContribution<'_>) -> Result<Uuid, sqlx::Error> {
+        sqlx::query_scalar(
+            r"
+            INSERT INTO contributions (id, forge, repo_owner, repo_name, repo_url, contribution_type, title, url, merged_at, additions, deletions, changed_files, labels, synced_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+            ON CONFLICT (forge, repo_owner, repo_name, url) DO UPDATE SET
+                repo_url = excluded.repo_url,
+                contribution_type = excluded.contribution_type,
+                title = excluded.title,
+                merged_at = excluded.merged_at,
+                additions = excluded.additions,
+                deletions = excluded.deletions,
+                changed_files = excluded.changed_files,
+                labels = excluded.labels,
+                synced_at = excluded.synced_at
+            RETURNING id
+            ",
+        )
+        .bind(Uuid::new_v4())
+        .bind(c.forge)
+        .bind(c.repo_owner)
+        .bind(c.repo_name)
+        .bind(c.repo_url)
+        .bind(c.contribution_type)
+        .bind(c.title)
+        .bind(c.url)
+        .bind(c.merged_at)
+        .bind(c.additions)
+        .bind(c.deletions)
+        .bind(c.changed_files)
+        .bind(Json(c.labels))
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn upsert_upstream_repo(&self, r: &NewUpstreamRepo<'_>) -> Result<Uuid, sqlx::Error> {
+        sqlx::query_scalar(
+            r"
+            INSERT INTO upstream_repos (id, forge, owner, name, url, description, language, stars, synced_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            ON CONFLICT (forge, owner, name) DO UPDATE SET
+                url = excluded.url,
+                description = excluded.description,
+                language = excluded.language,
+                stars = excluded.stars,
+                synced_at = excluded.synced_at
+            RETURNING id
+            ",
+        )
+        .bind(Uuid::new_v4())
+        .bind(r.forge)
+        .bind(r.owner)
+        .bind(r.name)
+        .bind(r.url)
+        .bind(r.description)
+        .bind(r.language)
+        .bind(r.stars)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn upsert_release(&self, r: &NewRelease<'_>) -> Result<Uuid, sqlx::Error> {
+        sqlx::query_scalar(
+            r"
+            INSERT INTO releases (id, artifact_kind, artifact, version, released_at, yanked, notes_url, synced_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT (artifact_kind, artifact, version) DO UPDATE SET
+                released_at = excluded.released_at,
+                yanked = excluded.yanked,
+                notes_url = excluded.notes_url,
+                synced_at = excluded.synced_at
+            RETURNING id
+            ",
+        )
+        .bind(Uuid::new_v4())
+        .bind(r.artifact_kind)
+        .bind(r.artifact)
+        .bind(r.version)
+        .bind(r.released_at)
+        .bind(r.yanked)
+        .bind(r.notes_url)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn get_readme_hash(&self, repository_id: Uuid) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT content_hash FROM readmes WHERE repository_id = ?1")
+            .bind(repository_id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    async fn upsert_readme(&self, r: &NewReadme<'_>) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r"
+            INSERT INTO readmes (repository_id, content_hash, html, synced_at)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT (repository_id) DO UPDATE SET
+                content_hash = excluded.content_hash,
+                html = excluded.html,
+                synced_at = excluded.synced_at
+            ",
+        )
+        .bind(r.repository_id)
+        .bind(r.content_hash)
+        .bind(r.html)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_readme(&self, repository_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM readmes WHERE repository_id = ?1")
+            .bind(repository_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_workspace_pushed_at(
+        &self,
+        repository_id: Uuid,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let pushed_at: Option<Option<DateTime<Utc>>> =
+            sqlx::query_scalar("SELECT workspace_pushed_at FROM repositories WHERE id = ?1")
+                .bind(repository_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(pushed_at.flatten())
+    }
+
+    async fn set_workspace_members(
+        &self,
+        repository_id: Uuid,
+        pushed_at: Option<DateTime<Utc>>,
+        members: &[NewWorkspaceMember<'_>],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM workspace_members WHERE repository_id = ?1")
+            .bind(repository_id)
+            .execute(&mut *tx)
+            .await?;
+        for member in members {
+            sqlx::query(
+                r"
+                INSERT INTO workspace_members (repository_id, crate_name, path)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT DO NOTHING
+                ",
+            )
+            .bind(repository_id)
+            .bind(member.crate_name)
+            .bind(member.path)
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query("UPDATE repositories SET workspace_pushed_at = ?2 WHERE id = ?1")
+            .bind(repository_id)
+            .bind(pushed_at)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await
+    }
+
+    async fn refresh_projects(&self) -> Result<(), sqlx::Error> {
+        projects::refresh_projects(&self.pool).await
+    }
+
+    /// Nothing else shares the file, so there's no one to announce the run to.
+    async fn record_sync_run(&self, error: Option<&str>) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT INTO sync_runs (finished_at, error) VALUES (?1, ?2)")
+            .bind(Utc::now())
+            .bind(error)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_last_sync_at(&self) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        sqlx::query_scalar("SELECT MAX(finished_at) FROM sync_runs")
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn get_projects(&self, filters: &ProjectFilters) -> Result<ProjectPage, sqlx::Error> {
+        projects::get_projects(&self.pool, filters).await
+    }
+
+    async fn get_project_facets(
+        &self,
+        filters: &ProjectFilters,
+    ) -> Result<ProjectFacets, sqlx::Error> {
+        projects::get_project_facets(&self.pool, filters).await
+    }
+
+    async fn get_project_detail(
+        &self,
+        kind: ProjectKind,
+        name: &str,
+    ) -> Result<Option<ProjectDetail>, sqlx::Error> {
+        projects::get_project_detail(&self.pool, kind, name).await
+    }
+
+    async fn get_project_releases(
+        &self,
+        project_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Release>, sqlx::Error> {
+        projects::get_project_releases(&self.pool, project_id, limit).await
+    }
+
+    async fn get_recent_releases(
+        &self,
+        artifact_kind: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Release>, sqlx::Error> {
+        sqlx::query_as(
+            r"
+            SELECT id, artifact_kind, artifact, version, released_at, yanked, notes_url, synced_at
+            FROM releases
+            WHERE released_at IS NOT NULL AND NOT yanked
+              AND (?1 IS NULL OR artifact_kind = ?1)
+            ORDER BY released_at DESC, artifact ASC
+            LIMIT ?2
+            ",
+        )
+        .bind(artifact_kind)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn get_contributions(
+        &self,
+        limit: i64,
+        max_age_years: i32,
+        sort: ContributionSort,
+    ) -> Result<Vec<Contribution>, sqlx::Error> {
+        contributions::get_contributions(&self.pool, limit, max_age_years, sort).await
+    }
+
+    async fn get_contribution_page(
+        &self,
+        filters: &ContributionFilters,
+        limit: i64,
+    ) -> Result<ContributionPage, sqlx::Error> {
+        contributions::get_contribution_page(&self.pool, filters, limit).await
+    }
+
+    async fn get_related_contributions(
+        &self,
+        terms: &[String],
+        limit: i64,
+    ) -> Result<Vec<Contribution>, sqlx::Error> {
+        contributions::get_related_contributions(&self.pool, terms, limit).await
+    }
+
+    async fn get_contribution_years(&self) -> Result<Vec<i32>, sqlx::Error> {
+        contributions::get_contribution_years(&self.pool).await
+    }
+
+    async fn get_contribution_owners(&self) -> Result<Vec<String>, sqlx::Error> {
+        contributions::get_contribution_owners(&self.pool).await
+    }
+
+    async fn get_contribution_stats(&self) -> Result<ContributionStats, sqlx::Error> {
+        contributions::get_contribution_stats(&self.pool).await
+    }
+
+    async fn get_all_projects(&self) -> Result<Vec<ProjectView>, sqlx::Error> {
+        Ok(self
+            .get_projects(&ProjectFilters::default())
+            .await?
+            .projects)
+    }
+
+    async fn get_distinct_topics(&self) -> Result<Vec<String>, sqlx::Error> {
+        projects::get_distinct_topics(&self.pool).await
+    }
+
+    async fn get_all_contributions(&self) -> Result<Vec<Contribution>, sqlx::Error> {
+        contributions::get_all_contributions(&self.pool).await
+    }
+
+    async fn get_new_projects(
+        &self,
+        kind: Option<ProjectKind>,
+        limit: i64,
+    ) -> Result<Vec<ProjectView>, sqlx::Error> {
+        projects::get_new_projects(&self.pool, kind, limit).await
+    }
+
+    async fn get_sync_status(&self) -> Result<Vec<SourceSyncStatus>, sqlx::Error> {
+        artifacts::get_sync_status(&self.pool).await
+    }
+
+    async fn get_repositories(&self, limit: i64) -> Result<Vec<Repository>, sqlx::Error> {
+        artifacts::get_repositories(&self.pool, limit).await
+    }
+
+    async fn get_repositories_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Repository>, sqlx::Error> {
+        artifacts::get_repositories_by_ids(&self.pool, ids).await
+    }
+
+    async fn get_crates(&self, limit: i64) -> Result<Vec<Crate>, sqlx::Error> {
+        artifacts::get_crates(&self.pool, limit).await
+    }
+
+    async fn get_crates_by_repository_ids(
+        &self,
+        repository_ids: &[Uuid],
+    ) -> Result<Vec<Crate>, sqlx::Error> {
+        artifacts::get_crates_by_repository_ids(&self.pool, repository_ids).await
+    }
+
+    async fn get_npm_packages(&self, limit: i64) -> Result<Vec<NpmPackage>, sqlx::Error> {
+        artifacts::get_npm_packages(&self.pool, limit).await
+    }
+
+    async fn get_npm_packages_by_repository_ids(
+        &self,
+        repository_ids: &[Uuid],
+    ) -> Result<Vec<NpmPackage>, sqlx::Error> {
+        artifacts::get_npm_packages_by_repository_ids(&self.pool, repository_ids).await
+    }
+}
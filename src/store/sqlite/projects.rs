@@ -0,0 +1,568 @@
+//! The project queries, ported from [`crate::db`]'s. Array filters go through `json_each`,
+//! and search through the `project_search` FTS5 table.
+
+use chrono::{DateTime, Utc};
+use sqlx::types::Json;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::search::fts_query;
+use crate::db::{
+    CursorKey, FacetCount, ProjectArtifact, ProjectCursor, ProjectDetail, ProjectFacets,
+    ProjectFilters, ProjectKind, ProjectPage, ProjectView, Release, SortDirection, SortOrder,
+    TopicMatch,
+};
+
+/// `project_score`, which needs the `LN` this `SQLite` lacks
+fn project_score(stars: Option<i32>, downloads: Option<i32>) -> f64 {
+    let ln_1p = |n: Option<i32>| f64::from(n.unwrap_or(0).max(0)).ln_1p();
+    2.0 * ln_1p(stars) + ln_1p(downloads)
+}
+
+/// Refill `projects` and `project_search` from the artifact tables. Readers keep seeing the
+/// previous rows until the transaction commits.
+pub(super) async fn refresh_projects(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM projects")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r"
+        INSERT INTO projects (
+            id, kind, name, description, url, language, topics, popularity, version,
+            commit_count, updated_at, synced_at, first_seen_at, created_at, latest_released_at,
+            stars, downloads, kinds, artifacts
+        )
+        SELECT
+            id, kind, name, description, url, language, topics, popularity, version,
+            commit_count, updated_at, synced_at, first_seen_at, created_at, latest_released_at,
+            stars, downloads, kinds, artifacts
+        FROM project_rows
+        ",
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let counts: Vec<(Uuid, Option<i32>, Option<i32>)> =
+        sqlx::query_as("SELECT id, stars, downloads FROM projects")
+            .fetch_all(&mut *tx)
+            .await?;
+    for (id, stars, downloads) in counts {
+        sqlx::query("UPDATE projects SET score = ?1 WHERE id = ?2")
+            .bind(project_score(stars, downloads))
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    sqlx::query("DELETE FROM project_search")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r"
+        INSERT INTO project_search (id, name, tags, description)
+        SELECT
+            project_id, group_concat(name, ' '), group_concat(search_tags, ' '),
+            group_concat(description, ' ')
+        FROM project_artifacts
+        GROUP BY project_id
+        ",
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+#[derive(sqlx::FromRow)]
+struct ProjectRow {
+    id: Uuid,
+    kind: String,
+    name: String,
+    description: Option<String>,
+    url: String,
+    language: Option<String>,
+    topics: Json<Vec<String>>,
+    popularity: Option<i32>,
+    version: Option<String>,
+    commit_count: Option<i32>,
+    updated_at: Option<DateTime<Utc>>,
+    synced_at: DateTime<Utc>,
+    first_seen_at: DateTime<Utc>,
+    artifacts: Json<Vec<ProjectArtifact>>,
+}
+
+impl From<ProjectRow> for ProjectView {
+    fn from(row: ProjectRow) -> Self {
+        Self {
+            id: row.id,
+            kind: row.kind.parse().unwrap_or(ProjectKind::Repo),
+            name: row.name,
+            description: row.description,
+            url: row.url,
+            language: row.language,
+            topics: row.topics.0,
+            popularity: row.popularity.unwrap_or(0),
+            version: row.version,
+            commit_count: row.commit_count,
+            updated_at: row.updated_at,
+            synced_at: row.synced_at,
+            first_seen_at: row.first_seen_at,
+            artifacts: row.artifacts.0,
+        }
+    }
+}
+
+const PROJECT_COLUMNS: &str = "p.id, p.kind, p.name, p.description, p.url, p.language, \
+    p.topics, p.popularity, p.version, p.commit_count, p.updated_at, p.synced_at, \
+    p.first_seen_at, p.artifacts";
+
+/// A project with the sort key it was ordered by. `SQLite` has no arrays, so the key is
+/// always two numbers and a text; the parts a sort doesn't use are constant.
+#[derive(sqlx::FromRow)]
+struct SortedProjectRow {
+    #[sqlx(flatten)]
+    project: ProjectRow,
+    sort_first: f64,
+    sort_second: f64,
+    sort_text: String,
+}
+
+impl SortedProjectRow {
+    /// Cursor continuing `sort` in `direction` after this row, keyed as `PostgreSQL`'s are.
+    fn cursor(&self, sort: SortOrder, direction: SortDirection) -> ProjectCursor {
+        let key = match sort {
+            SortOrder::Name => CursorKey::Text(self.sort_text.clone()),
+            SortOrder::Relevance => CursorKey::Numbers(vec![self.sort_first, self.sort_second]),
+            _ => CursorKey::Numbers(vec![self.sort_first]),
+        };
+        ProjectCursor {
+            sort,
+            direction,
+            key,
+            id: self.project.id,
+        }
+    }
+}
+
+/// Bind parameters shared by the project queries, as JSON arrays where `PostgreSQL` takes
+/// `TEXT[]`.
+struct ProjectQuery<'a> {
+    kinds: Json<Vec<String>>,
+    /// Lowercased, to compare against `LOWER(language)`
+    languages: Json<Vec<String>>,
+    topics: Json<&'a [String]>,
+    /// Require every topic rather than any of them
+    match_all: bool,
+    exclude_topics: Json<&'a [String]>,
+    /// An FTS5 query
+    search: Option<String>,
+}
+
+impl<'a> ProjectQuery<'a> {
+    fn new(filters: &'a ProjectFilters) -> Self {
+        Self {
+            kinds: Json(filters.kinds.iter().map(ToString::to_string).collect()),
+            languages: Json(filters.languages.iter().map(|l| l.to_lowercase()).collect()),
+            topics: Json(&filters.topics),
+            match_all: filters.topic_match == TopicMatch::All,
+            exclude_topics: Json(&filters.exclude_topics),
+            search: filters.q.as_deref().and_then(fts_query),
+        }
+    }
+}
+
+/// Conditions on `projects p` for the filters bound as `?1` to `?5`. Each query adds its own
+/// search condition, and only when there is a search, since FTS5 rejects a `NULL` query.
+const PROJECT_FILTERS: &str = r"
+    (json_array_length(?1) = 0
+     OR EXISTS (SELECT 1 FROM json_each(p.kinds) WHERE value IN (SELECT value FROM json_each(?1))))
+    AND (json_array_length(?2) = 0 OR LOWER(p.language) IN (SELECT value FROM json_each(?2)))
+    AND (json_array_length(?3) = 0
+         OR (?4 AND NOT EXISTS (
+             SELECT 1 FROM json_each(?3)
+             WHERE value NOT IN (SELECT value FROM json_each(p.topics))
+         ))
+         OR (NOT ?4 AND EXISTS (
+             SELECT 1 FROM json_each(p.topics) WHERE value IN (SELECT value FROM json_each(?3))
+         )))
+    AND NOT EXISTS (
+        SELECT 1 FROM json_each(p.topics) WHERE value IN (SELECT value FROM json_each(?5))
+    )
+";
+
+/// SQL for the two numbers a sort compares, given `rank` as the search's relevance. Name
+/// sorts compare text instead.
+///
+/// Dates compare as epoch microseconds. Missing dates, stars and downloads become an
+/// infinity that puts them last whichever way the list runs.
+fn project_sort_numbers(
+    sort: SortOrder,
+    direction: SortDirection,
+    rank: &str,
+) -> (String, &'static str) {
+    let missing = match direction {
+        SortDirection::Asc => "9e999",
+        SortDirection::Desc => "-9e999",
+    };
+    let date =
+        |column: &str| format!("COALESCE(unixepoch(p.{column}, 'subsec') * 1000000, {missing})");
+    let count = |column: &str| format!("COALESCE(CAST(p.{column} AS REAL), {missing})");
+    match sort {
+        SortOrder::Name => ("0.0".to_owned(), "0.0"),
+        SortOrder::Popularity => ("p.score".to_owned(), "0.0"),
+        SortOrder::Updated => (date("updated_at"), "0.0"),
+        SortOrder::Created => (date("created_at"), "0.0"),
+        SortOrder::Released => (date("latest_released_at"), "0.0"),
+        SortOrder::Stars => (count("stars"), "0.0"),
+        SortOrder::Downloads => (count("downloads"), "0.0"),
+        // `bm25` weights names above tags, and tags above descriptions, much as `ts_rank`
+        // weighs the `PostgreSQL` vectors.
+        SortOrder::Relevance => (rank.to_owned(), "p.score"),
+    }
+}
+
+/// Fetch one page of projects using keyset pagination on `(sort key, id)`, as
+/// [`crate::db::get_projects`] does.
+pub(super) async fn get_projects(
+    pool: &SqlitePool,
+    filters: &ProjectFilters,
+) -> Result<ProjectPage, sqlx::Error> {
+    let query = ProjectQuery::new(filters);
+    let sort = filters.effective_sort();
+    let direction = filters.effective_direction();
+
+    let (search_join, rank) = if query.search.is_some() {
+        (
+            r"JOIN (
+                SELECT id, -bm25(project_search, 0.0, 1.0, 0.4, 0.2) AS rank
+                FROM project_search
+                WHERE project_search MATCH ?11
+            ) s ON s.id = p.id",
+            "s.rank",
+        )
+    } else {
+        ("", "0.0")
+    };
+    let (sort_first, sort_second) = project_sort_numbers(sort, direction, rank);
+    let sort_text = if sort == SortOrder::Name {
+        "p.name"
+    } else {
+        "''"
+    };
+    let (order, after) = match direction {
+        SortDirection::Asc => ("ASC", ">"),
+        SortDirection::Desc => ("DESC", "<"),
+    };
+    let sql = format!(
+        r"
+        SELECT * FROM (
+            SELECT
+                {PROJECT_COLUMNS},
+                {sort_first} AS sort_first, {sort_second} AS sort_second,
+                {sort_text} AS sort_text
+            FROM projects p
+            {search_join}
+            WHERE {PROJECT_FILTERS}
+        ) sorted
+        WHERE ?9 IS NULL
+           OR (sort_first, sort_second, sort_text, id) {after} (?6, ?7, ?8, ?9)
+        ORDER BY sort_first {order}, sort_second {order}, sort_text {order}, id {order}
+        LIMIT ?10
+        "
+    );
+
+    // A cursor from another sort or direction has keys that mean nothing here.
+    let (after_numbers, after_text, after_id) = match filters.cursor.as_ref() {
+        Some(cursor) if cursor.sort == sort && cursor.direction == direction => match &cursor.key {
+            CursorKey::Text(text) => (&[][..], text.as_str(), Some(cursor.id)),
+            CursorKey::Numbers(numbers) => (&numbers[..], "", Some(cursor.id)),
+        },
+        _ => (&[][..], "", None),
+    };
+    // 1000 is a de-facto "no limit" for our dataset size; the SQL always needs a LIMIT.
+    let page_len = filters.limit.unwrap_or(1000);
+
+    let mut rows = sqlx::query_as::<_, SortedProjectRow>(&sql)
+        .bind(&query.kinds)
+        .bind(&query.languages)
+        .bind(query.topics)
+        .bind(query.match_all)
+        .bind(query.exclude_topics)
+        .bind(after_numbers.first().copied().unwrap_or(0.0))
+        .bind(after_numbers.get(1).copied().unwrap_or(0.0))
+        .bind(after_text)
+        .bind(after_id)
+        .bind(i64::from(page_len) + 1)
+        .bind(query.search)
+        .fetch_all(pool)
+        .await?;
+
+    let page_len = usize::try_from(page_len).unwrap_or(0);
+    let has_more = rows.len() > page_len;
+    rows.truncate(page_len);
+    let next_cursor = if has_more {
+        rows.last().map(|row| row.cursor(sort, direction))
+    } else {
+        None
+    };
+
+    Ok(ProjectPage {
+        projects: rows
+            .into_iter()
+            .map(|row| ProjectView::from(row.project))
+            .collect(),
+        next_cursor,
+    })
+}
+
+/// Count each value of a facet, `value` and `facet_from` naming it and where it comes from,
+/// against `query`.
+async fn facet_counts(
+    pool: &SqlitePool,
+    query: &ProjectQuery<'_>,
+    value: &str,
+    facet_from: &str,
+) -> Result<Vec<FacetCount>, sqlx::Error> {
+    let search = if query.search.is_some() {
+        "AND p.id IN (SELECT id FROM project_search WHERE project_search MATCH ?6)"
+    } else {
+        ""
+    };
+    let sql = format!(
+        r"
+        SELECT {value}, COUNT(*) FILTER (WHERE {PROJECT_FILTERS} {search})
+        {facet_from}
+        "
+    );
+    let counts: Vec<(String, i64)> = sqlx::query_as(&sql)
+        .bind(&query.kinds)
+        .bind(&query.languages)
+        .bind(query.topics)
+        .bind(query.match_all)
+        .bind(query.exclude_topics)
+        .bind(query.search.as_deref())
+        .fetch_all(pool)
+        .await?;
+
+    Ok(counts
+        .into_iter()
+        .map(|(value, count)| FacetCount { value, count })
+        .collect())
+}
+
+/// Distinct kinds, languages and topics with the number of projects each would match,
+/// counted as [`crate::db::get_project_facets`] counts them.
+pub(super) async fn get_project_facets(
+    pool: &SqlitePool,
+    filters: &ProjectFilters,
+) -> Result<ProjectFacets, sqlx::Error> {
+    let kind_query = ProjectQuery {
+        kinds: Json(Vec::new()),
+        ..ProjectQuery::new(filters)
+    };
+    let kinds = facet_counts(
+        pool,
+        &kind_query,
+        "k.value",
+        "FROM projects p, json_each(p.kinds) k GROUP BY k.value ORDER BY k.value ASC",
+    );
+
+    let language_query = ProjectQuery {
+        languages: Json(Vec::new()),
+        ..ProjectQuery::new(filters)
+    };
+    let languages = facet_counts(
+        pool,
+        &language_query,
+        "MIN(p.language)",
+        r"FROM projects p
+        WHERE p.language IS NOT NULL
+        GROUP BY LOWER(p.language)
+        ORDER BY LOWER(p.language) ASC",
+    );
+
+    let base = ProjectQuery::new(filters);
+    let topic_query = ProjectQuery {
+        topics: if base.match_all {
+            base.topics
+        } else {
+            Json(&[][..])
+        },
+        ..base
+    };
+    let topics = facet_counts(
+        pool,
+        &topic_query,
+        "t.value",
+        "FROM projects p, json_each(p.topics) t GROUP BY t.value ORDER BY t.value ASC",
+    );
+
+    let (kinds, languages, topics) = tokio::try_join!(kinds, languages, topics)?;
+
+    Ok(ProjectFacets {
+        kinds,
+        languages,
+        topics,
+    })
+}
+
+#[derive(sqlx::FromRow)]
+struct ProjectDetailRow {
+    #[sqlx(flatten)]
+    project: ProjectRow,
+    created_at: Option<DateTime<Utc>>,
+    latest_released_at: Option<DateTime<Utc>>,
+    stars: Option<i32>,
+    downloads: Option<i32>,
+    documentation_url: Option<String>,
+    homepage_url: Option<String>,
+    repository_url: Option<String>,
+    repository_forge: Option<String>,
+    readme_html: Option<String>,
+}
+
+impl From<ProjectDetailRow> for ProjectDetail {
+    fn from(row: ProjectDetailRow) -> Self {
+        Self {
+            project: row.project.into(),
+            created_at: row.created_at,
+            latest_released_at: row.latest_released_at,
+            stars: row.stars,
+            downloads: row.downloads,
+            documentation_url: row.documentation_url,
+            homepage_url: row.homepage_url,
+            repository_url: row.repository_url,
+            repository_forge: row.repository_forge,
+            readme_html: row.readme_html,
+        }
+    }
+}
+
+/// The project with an artifact of this kind and name, with its links and repository, chosen
+/// as [`crate::db::get_project_detail`] chooses it.
+pub(super) async fn get_project_detail(
+    pool: &SqlitePool,
+    kind: ProjectKind,
+    name: &str,
+) -> Result<Option<ProjectDetail>, sqlx::Error> {
+    let sql = format!(
+        r"
+        WITH packages AS (
+            SELECT
+                COALESCE(repository_id, id) AS project_id, name, documentation_url,
+                homepage_url, downloads
+            FROM crates
+            UNION ALL
+            SELECT COALESCE(repository_id, id), name, NULL, homepage_url, downloads_weekly
+            FROM npm_packages
+        )
+        SELECT
+            {PROJECT_COLUMNS},
+            p.created_at, p.latest_released_at, p.stars, p.downloads,
+            -- An `ORDER BY` here can't see `p`, so the package named like the project is
+            -- looked up first and the most downloaded one after.
+            COALESCE(
+                (
+                    SELECT documentation_url FROM packages
+                    WHERE project_id = p.id AND name = p.name AND documentation_url IS NOT NULL
+                    ORDER BY downloads DESC
+                    LIMIT 1
+                ),
+                (
+                    SELECT documentation_url FROM packages
+                    WHERE project_id = p.id AND documentation_url IS NOT NULL
+                    ORDER BY downloads DESC
+                    LIMIT 1
+                )
+            ) AS documentation_url,
+            COALESCE(
+                (
+                    SELECT homepage_url FROM packages
+                    WHERE project_id = p.id AND name = p.name AND homepage_url IS NOT NULL
+                    ORDER BY downloads DESC
+                    LIMIT 1
+                ),
+                (
+                    SELECT homepage_url FROM packages
+                    WHERE project_id = p.id AND homepage_url IS NOT NULL
+                    ORDER BY downloads DESC
+                    LIMIT 1
+                ),
+                r.homepage_url
+            ) AS homepage_url,
+            r.url AS repository_url, r.forge AS repository_forge,
+            rm.html AS readme_html
+        FROM projects p
+        LEFT JOIN repositories r ON r.id = p.id
+        LEFT JOIN readmes rm ON rm.repository_id = p.id
+        WHERE p.id IN (SELECT project_id FROM project_artifacts WHERE kind = ?1 AND name = ?2)
+        ORDER BY COALESCE(p.popularity, 0) DESC, p.id
+        LIMIT 1
+        "
+    );
+    let row = sqlx::query_as::<_, ProjectDetailRow>(&sql)
+        .bind(kind.to_string())
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(ProjectDetail::from))
+}
+
+/// Most recent releases of any of a project's artifacts, yanked ones included, newest first.
+pub(super) async fn get_project_releases(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    limit: i64,
+) -> Result<Vec<Release>, sqlx::Error> {
+    sqlx::query_as(
+        r"
+        SELECT
+            rel.id, rel.artifact_kind, rel.artifact, rel.version, rel.released_at, rel.yanked,
+            rel.notes_url, rel.synced_at
+        FROM releases rel
+        JOIN project_artifacts a
+            ON a.kind = rel.artifact_kind AND a.release_artifact = rel.artifact
+        WHERE a.project_id = ?1
+        ORDER BY rel.released_at DESC NULLS LAST, rel.version DESC
+        LIMIT ?2
+        ",
+    )
+    .bind(project_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+pub(super) async fn get_distinct_topics(pool: &SqlitePool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT DISTINCT t.value FROM projects p, json_each(p.topics) t ORDER BY 1")
+        .fetch_all(pool)
+        .await
+}
+
+/// Projects in order of first appearance, newest first, as [`crate::db::get_new_projects`]
+/// lists them.
+pub(super) async fn get_new_projects(
+    pool: &SqlitePool,
+    kind: Option<ProjectKind>,
+    limit: i64,
+) -> Result<Vec<ProjectView>, sqlx::Error> {
+    let sql = format!(
+        r"
+        SELECT {PROJECT_COLUMNS}
+        FROM projects p
+        WHERE ?1 IS NULL OR EXISTS (SELECT 1 FROM json_each(p.kinds) WHERE value = ?1)
+        ORDER BY p.first_seen_at DESC, p.name ASC
+        LIMIT ?2
+        "
+    );
+    let rows: Vec<ProjectRow> = sqlx::query_as(&sql)
+        .bind(kind.map(|k| k.to_string()))
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.into_iter().map(ProjectView::from).collect())
+}
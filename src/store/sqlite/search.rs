@@ -0,0 +1,99 @@
+//! The search box takes `websearch_to_tsquery` syntax: words, `"quoted phrases"`, `or`
+//! between alternatives and `-word` to exclude. This turns it into an FTS5 query.
+
+/// Quote `text` as an FTS5 string, which the tokenizer then splits and stems like the
+/// indexed text.
+fn quoted(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// The FTS5 query for a search, or `None` if nothing in it can match anything.
+///
+/// FTS5's `NOT` is binary, so an exclusion only applies after something to exclude from;
+/// a search of exclusions alone is ignored. Terms without letters or digits are dropped,
+/// as `PostgreSQL`'s parser drops them.
+pub(super) fn fts_query(q: &str) -> Option<String> {
+    let mut query = String::new();
+    let mut or = false;
+    let mut chars = q.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let negated = c == '-';
+        if negated {
+            chars.next();
+        }
+        let phrase = chars.peek() == Some(&'"');
+        let term: String = if phrase {
+            chars.next();
+            chars.by_ref().take_while(|&c| c != '"').collect()
+        } else {
+            let mut term = String::new();
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                term.push(c);
+            }
+            term
+        };
+
+        if !negated && !phrase && term.eq_ignore_ascii_case("or") {
+            or = !query.is_empty();
+            continue;
+        }
+        if !term.chars().any(char::is_alphanumeric) {
+            continue;
+        }
+
+        if negated {
+            if !query.is_empty() && !or {
+                query.push_str(" NOT ");
+                query.push_str(&quoted(&term));
+            }
+        } else {
+            if !query.is_empty() {
+                query.push_str(if or { " OR " } else { " AND " });
+            }
+            query.push_str(&quoted(&term));
+        }
+        or = false;
+    }
+
+    (!query.is_empty()).then_some(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_must_all_match() {
+        assert_eq!(
+            fts_query("fast  parser").as_deref(),
+            Some(r#""fast" AND "parser""#)
+        );
+    }
+
+    #[test]
+    fn phrases_alternatives_and_exclusions() {
+        assert_eq!(
+            fts_query(r#""shell setup" or dotfiles -config"#).as_deref(),
+            Some(r#""shell setup" OR "dotfiles" NOT "config""#)
+        );
+    }
+
+    #[test]
+    fn quotes_inside_words_are_escaped() {
+        assert_eq!(fts_query(r#"it"s"#).as_deref(), Some(r#""it""s""#));
+    }
+
+    #[test]
+    fn nothing_to_match() {
+        assert_eq!(fts_query("   "), None);
+        assert_eq!(fts_query("-config"), None);
+        assert_eq!(fts_query("or !!"), None);
+        assert_eq!(fts_query("parser or").as_deref(), Some(r#""parser""#));
+    }
+}
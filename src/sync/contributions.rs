@@ -25,6 +25,22 @@ pub struct FetchedContribution {
     pub title: Option<String>,
     pub url: String,
     pub merged_at: Option<DateTime<Utc>>,
+    pub additions: Option<i32>,
+    pub deletions: Option<i32>,
+    pub changed_files: Option<i32>,
+    pub labels: Vec<String>,
+}
+
+/// Metadata for a repository that contributions were made to
+#[derive(Debug, Clone)]
+pub struct FetchedUpstreamRepo {
+    pub forge: String,
+    pub owner: String,
+    pub name: String,
+    pub url: String,
+    pub description: Option<String>,
+    pub language: Option<String>,
+    pub stars: i32,
 }
 
 impl ContributionsSync {
@@ -126,9 +142,42 @@ impl ContributionsSync {
             page
         );
 
+        let search_result: SearchResult = self.github_get(&url).await?.json().await?;
+
+        let mut contributions = Vec::with_capacity(search_result.items.len());
+        for item in search_result.items {
+            let Some(mut contribution) = item.to_contribution() else {
+                continue;
+            };
+
+            // The search API only reports when the PR was closed; the pull itself carries the
+            // real merge time and diff size.
+            if let Some(ref pull) = item.pull_request {
+                match self.fetch_github_pull(&pull.url).await {
+                    Ok(details) => details.apply_to(&mut contribution),
+                    Err(e @ SyncError::RateLimited(_)) => return Err(e),
+                    Err(e) => {
+                        tracing::warn!(error = %e, url = %pull.url, "failed to fetch pull request details");
+                    }
+                }
+            }
+
+            contributions.push(contribution);
+        }
+
+        Ok(contributions)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn fetch_github_pull(&self, url: &str) -> Result<PullRequestDetails, SyncError> {
+        let details: PullRequestDetails = self.github_get(url).await?.json().await?;
+        Ok(details)
+    }
+
+    async fn github_get(&self, url: &str) -> Result<reqwest::Response, SyncError> {
         let mut request = self
             .client
-            .get(&url)
+            .get(url)
             .header(USER_AGENT, "djv-sync/1.0")
             .header(ACCEPT, "application/vnd.github+json");
 
@@ -153,35 +202,113 @@ impl ContributionsSync {
             }
         }
 
-        let search_result: SearchResult = response.error_for_status()?.json().await?;
+        Ok(response.error_for_status()?)
+    }
 
-        let contributions = search_result
-            .items
-            .into_iter()
-            .filter_map(|item| {
-                // Parse repo from URL: https://api.github.com/repos/owner/name/...
-                let repo_url = item.repository_url?;
-                let parts: Vec<&str> = repo_url.split('/').collect();
-                if parts.len() < 2 {
-                    return None;
+    /// Fetch stars, language and description for each distinct upstream repository in
+    /// `contributions`. Failures for individual repositories are logged and skipped.
+    ///
+    /// # Errors
+    /// Returns [`SyncError::RateLimited`] as soon as a forge rate limit is hit, rather than
+    /// carrying on against the remaining repositories.
+    #[tracing::instrument(skip_all, fields(count = contributions.len()))]
+    pub async fn fetch_upstream_repos(
+        &self,
+        contributions: &[FetchedContribution],
+    ) -> Result<Vec<FetchedUpstreamRepo>, SyncError> {
+        let mut seen = std::collections::HashSet::new();
+        let mut repos = Vec::new();
+
+        for c in contributions {
+            if !seen.insert((
+                c.forge.as_str(),
+                c.repo_owner.as_str(),
+                c.repo_name.as_str(),
+            )) {
+                continue;
+            }
+
+            let result = match c.forge.as_str() {
+                "github" => self.fetch_github_repo(&c.repo_owner, &c.repo_name).await,
+                "gitlab" => self.fetch_gitlab_project(&c.repo_url).await,
+                _ => continue,
+            };
+
+            match result {
+                Ok(metadata) => repos.push(FetchedUpstreamRepo {
+                    forge: c.forge.clone(),
+                    owner: c.repo_owner.clone(),
+                    name: c.repo_name.clone(),
+                    url: metadata.url,
+                    description: metadata.description,
+                    language: metadata.language,
+                    stars: metadata.stars,
+                }),
+                Err(e @ SyncError::RateLimited(_)) => return Err(e),
+                Err(e) => {
+                    tracing::warn!(error = %e, repo = %c.repo_url, "failed to fetch upstream repository");
                 }
-                let repo_name = parts[parts.len() - 1].to_string();
-                let repo_owner = parts[parts.len() - 2].to_string();
+            }
+        }
 
-                Some(FetchedContribution {
-                    forge: "github".to_string(),
-                    repo_owner: repo_owner.clone(),
-                    repo_name: repo_name.clone(),
-                    repo_url: format!("https://github.com/{repo_owner}/{repo_name}"),
-                    contribution_type: "pr".to_string(),
-                    title: Some(item.title),
-                    url: item.html_url,
-                    merged_at: item.closed_at,
-                })
-            })
-            .collect();
+        tracing::info!(count = repos.len(), "fetched upstream repositories");
+        Ok(repos)
+    }
 
-        Ok(contributions)
+    async fn fetch_github_repo(
+        &self,
+        owner: &str,
+        name: &str,
+    ) -> Result<UpstreamMetadata, SyncError> {
+        let url = format!("{GITHUB_API_BASE}/repos/{owner}/{name}");
+        let repo: GitHubUpstreamRepo = self.github_get(&url).await?.json().await?;
+
+        Ok(UpstreamMetadata {
+            url: repo.html_url,
+            description: repo.description,
+            language: repo.language,
+            stars: repo.stargazers_count,
+        })
+    }
+
+    async fn fetch_gitlab_project(&self, repo_url: &str) -> Result<UpstreamMetadata, SyncError> {
+        let parsed = reqwest::Url::parse(repo_url)
+            .map_err(|e| SyncError::Other(format!("invalid project URL {repo_url}: {e}")))?;
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| SyncError::Other(format!("project URL has no host: {repo_url}")))?;
+        let path = parsed.path().trim_matches('/');
+
+        let url = format!(
+            "https://{host}/api/v4/projects/{}",
+            urlencoding::encode(path)
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .header(USER_AGENT, "djv-sync/1.0 (https://djv.sh)")
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get("retry-after")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(60);
+            return Err(SyncError::RateLimited(retry_after));
+        }
+
+        let project: GitLabUpstreamProject = response.error_for_status()?.json().await?;
+
+        Ok(UpstreamMetadata {
+            url: project.web_url,
+            description: project.description,
+            language: None, // GitLab only reports languages through a separate endpoint
+            stars: project.star_count,
+        })
     }
 
     #[tracing::instrument(skip(self))]
@@ -266,6 +393,11 @@ impl ContributionsSync {
                     title: Some(mr.title),
                     url: mr.web_url,
                     merged_at: mr.merged_at,
+                    // Diff stats are only available per merge request, not in the listing
+                    additions: None,
+                    deletions: None,
+                    changed_files: None,
+                    labels: mr.labels,
                 })
             })
             .collect();
@@ -279,6 +411,8 @@ struct GitLabMergeRequest {
     title: String,
     web_url: String,
     merged_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    labels: Vec<String>,
 }
 
 impl GitLabMergeRequest {
@@ -314,6 +448,100 @@ struct SearchItem {
     html_url: String,
     repository_url: Option<String>,
     closed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    pull_request: Option<SearchPullRequest>,
+}
+
+impl SearchItem {
+    fn to_contribution(&self) -> Option<FetchedContribution> {
+        // Parse repo from URL: https://api.github.com/repos/owner/name/...
+        let repo_url = self.repository_url.as_ref()?;
+        let parts: Vec<&str> = repo_url.split('/').collect();
+        if parts.len() < 2 {
+            return None;
+        }
+        let repo_name = parts[parts.len() - 1].to_string();
+        let repo_owner = parts[parts.len() - 2].to_string();
+
+        let merged_at = self
+            .pull_request
+            .as_ref()
+            .and_then(|pr| pr.merged_at)
+            .or(self.closed_at);
+
+        Some(FetchedContribution {
+            forge: "github".to_string(),
+            repo_url: format!("https://github.com/{repo_owner}/{repo_name}"),
+            repo_owner,
+            repo_name,
+            contribution_type: "pr".to_string(),
+            title: Some(self.title.clone()),
+            url: self.html_url.clone(),
+            merged_at,
+            additions: None,
+            deletions: None,
+            changed_files: None,
+            labels: self.labels.iter().map(|l| l.name.clone()).collect(),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchPullRequest {
+    url: String,
+    merged_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequestDetails {
+    merged_at: Option<DateTime<Utc>>,
+    additions: Option<i32>,
+    deletions: Option<i32>,
+    changed_files: Option<i32>,
+    #[serde(default)]
+    labels: Vec<Label>,
+}
+
+impl PullRequestDetails {
+    fn apply_to(self, contribution: &mut FetchedContribution) {
+        if self.merged_at.is_some() {
+            contribution.merged_at = self.merged_at;
+        }
+        contribution.additions = self.additions;
+        contribution.deletions = self.deletions;
+        contribution.changed_files = self.changed_files;
+        if !self.labels.is_empty() {
+            contribution.labels = self.labels.into_iter().map(|l| l.name).collect();
+        }
+    }
+}
+
+struct UpstreamMetadata {
+    url: String,
+    description: Option<String>,
+    language: Option<String>,
+    stars: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubUpstreamRepo {
+    html_url: String,
+    description: Option<String>,
+    language: Option<String>,
+    stargazers_count: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabUpstreamProject {
+    web_url: String,
+    description: Option<String>,
+    star_count: i32,
 }
 
 #[cfg(test)]
@@ -347,23 +575,13 @@ mod tests {
             html_url: "https://github.com/owner/repo/pull/123".to_string(),
             repository_url: Some("https://api.github.com/repos/owner/repo".to_string()),
             closed_at: Some(chrono::Utc::now()),
+            labels: vec![Label {
+                name: "bug".to_string(),
+            }],
+            pull_request: None,
         };
 
-        let repo_url = item.repository_url.as_ref().unwrap();
-        let parts: Vec<&str> = repo_url.split('/').collect();
-        let repo_name = parts[parts.len() - 1].to_string();
-        let repo_owner = parts[parts.len() - 2].to_string();
-
-        let contribution = FetchedContribution {
-            forge: "github".to_string(),
-            repo_owner: repo_owner.clone(),
-            repo_name: repo_name.clone(),
-            repo_url: format!("https://github.com/{}/{}", repo_owner, repo_name),
-            contribution_type: "pr".to_string(),
-            title: Some(item.title.clone()),
-            url: item.html_url.clone(),
-            merged_at: item.closed_at,
-        };
+        let contribution = item.to_contribution().unwrap();
 
         assert_eq!(contribution.forge, "github");
         assert_eq!(contribution.repo_owner, "owner");
@@ -372,6 +590,62 @@ mod tests {
         assert_eq!(contribution.contribution_type, "pr");
         assert_eq!(contribution.title, Some("Fix memory leak".to_string()));
         assert_eq!(contribution.url, "https://github.com/owner/repo/pull/123");
+        assert_eq!(contribution.merged_at, item.closed_at);
+        assert_eq!(contribution.labels, vec!["bug"]);
+    }
+
+    #[test]
+    fn prefers_pull_request_merged_at_over_closed_at() {
+        let json = r#"{
+            "title": "Add feature",
+            "html_url": "https://github.com/owner/repo/pull/7",
+            "repository_url": "https://api.github.com/repos/owner/repo",
+            "closed_at": "2024-03-02T00:00:00Z",
+            "labels": [],
+            "pull_request": {
+                "url": "https://api.github.com/repos/owner/repo/pulls/7",
+                "merged_at": "2024-03-01T12:00:00Z"
+            }
+        }"#;
+
+        let item: SearchItem = serde_json::from_str(json).unwrap();
+        let contribution = item.to_contribution().unwrap();
+
+        assert_eq!(
+            contribution.merged_at.map(|d| d.to_rfc3339()),
+            Some("2024-03-01T12:00:00+00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn applies_pull_request_details() {
+        let json = r#"{
+            "merged_at": "2024-05-01T09:00:00Z",
+            "additions": 120,
+            "deletions": 30,
+            "changed_files": 4,
+            "labels": [{"name": "enhancement"}, {"name": "tracing"}]
+        }"#;
+
+        let details: PullRequestDetails = serde_json::from_str(json).unwrap();
+        let mut contribution = SearchItem {
+            title: "Some PR".to_string(),
+            html_url: "https://github.com/owner/repo/pull/1".to_string(),
+            repository_url: Some("https://api.github.com/repos/owner/repo".to_string()),
+            closed_at: None,
+            labels: Vec::new(),
+            pull_request: None,
+        }
+        .to_contribution()
+        .unwrap();
+
+        details.apply_to(&mut contribution);
+
+        assert!(contribution.merged_at.is_some());
+        assert_eq!(contribution.additions, Some(120));
+        assert_eq!(contribution.deletions, Some(30));
+        assert_eq!(contribution.changed_files, Some(4));
+        assert_eq!(contribution.labels, vec!["enhancement", "tracing"]);
     }
 
     #[test]
@@ -381,9 +655,24 @@ mod tests {
             html_url: "https://github.com/owner/repo/pull/456".to_string(),
             repository_url: None,
             closed_at: None,
+            labels: Vec::new(),
+            pull_request: None,
         };
 
-        assert!(item.repository_url.is_none());
+        assert!(item.to_contribution().is_none());
+    }
+
+    #[test]
+    fn parses_gitlab_merge_request_labels() {
+        let json = r#"{
+            "title": "Fix pipeline",
+            "web_url": "https://gitlab.com/group/project/-/merge_requests/3",
+            "merged_at": null,
+            "labels": ["ci"]
+        }"#;
+
+        let mr: GitLabMergeRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(mr.labels, vec!["ci"]);
     }
 
     #[test]
@@ -3,7 +3,9 @@ use chrono::{DateTime, Utc};
 use reqwest::header::{ACCEPT, AUTHORIZATION, USER_AGENT};
 use serde::Deserialize;
 
-use crate::sync::{FetchedRepository, SyncError, SyncSource};
+use base64::Engine;
+
+use crate::sync::{FetchedReadme, FetchedRelease, FetchedRepository, SyncError, SyncSource};
 
 const GITHUB_API_BASE: &str = "https://api.github.com";
 
@@ -31,16 +33,12 @@ impl GitHubForge {
         Some(Self::new(username, token))
     }
 
-    #[tracing::instrument(skip(self))]
-    async fn fetch_page(&self, page: u32) -> Result<Vec<GitHubRepo>, SyncError> {
-        let url = format!(
-            "{}/users/{}/repos?per_page=100&page={}&sort=updated",
-            GITHUB_API_BASE, self.username, page
-        );
-
+    /// Send an authenticated GET request, turning GitHub's rate-limit response into
+    /// [`SyncError::RateLimited`].
+    async fn get(&self, url: &str) -> Result<reqwest::Response, SyncError> {
         let mut request = self
             .client
-            .get(&url)
+            .get(url)
             .header(USER_AGENT, "djv-sync/1.0")
             .header(ACCEPT, "application/vnd.github+json");
 
@@ -65,7 +63,17 @@ impl GitHubForge {
             }
         }
 
-        let repos: Vec<GitHubRepo> = response.error_for_status()?.json().await?;
+        Ok(response.error_for_status()?)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn fetch_page(&self, page: u32) -> Result<Vec<GitHubRepo>, SyncError> {
+        let url = format!(
+            "{}/users/{}/repos?per_page=100&page={}&sort=updated",
+            GITHUB_API_BASE, self.username, page
+        );
+
+        let repos: Vec<GitHubRepo> = self.get(&url).await?.json().await?;
         Ok(repos)
     }
 }
@@ -98,6 +106,100 @@ impl SyncSource for GitHubForge {
         tracing::info!(count = all_repos.len(), "fetched all repositories");
         Ok(all_repos)
     }
+
+    #[tracing::instrument(skip(self, repo), fields(repo = %repo.forge_id))]
+    async fn fetch_releases(
+        &self,
+        repo: &FetchedRepository,
+    ) -> Result<Vec<FetchedRelease>, SyncError> {
+        let url = format!(
+            "{GITHUB_API_BASE}/repos/{}/releases?per_page=100",
+            repo.forge_id
+        );
+
+        let releases: Vec<GitHubRelease> = self.get(&url).await?.json().await?;
+
+        Ok(releases
+            .into_iter()
+            .filter(|r| !r.draft)
+            .map(|r| r.into_release(&repo.forge_id))
+            .collect())
+    }
+
+    #[tracing::instrument(skip(self, repo), fields(repo = %repo.forge_id))]
+    async fn fetch_readme(
+        &self,
+        repo: &FetchedRepository,
+    ) -> Result<Option<FetchedReadme>, SyncError> {
+        let url = format!("{GITHUB_API_BASE}/repos/{}/readme", repo.forge_id);
+
+        let readme: GitHubReadme = match self.get(&url).await {
+            Ok(response) => response.json().await?,
+            Err(SyncError::Http(e)) if e.status() == Some(reqwest::StatusCode::NOT_FOUND) => {
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+
+        readme.into_readme(repo).map(Some)
+    }
+
+    #[tracing::instrument(skip(self, repo), fields(repo = %repo.forge_id))]
+    async fn fetch_file(
+        &self,
+        repo: &FetchedRepository,
+        path: &str,
+    ) -> Result<Option<String>, SyncError> {
+        let url = format!("{GITHUB_API_BASE}/repos/{}/contents/{path}", repo.forge_id);
+
+        let file: GitHubFile = match self.get(&url).await {
+            Ok(response) => response.json().await?,
+            Err(SyncError::Http(e)) if e.status() == Some(reqwest::StatusCode::NOT_FOUND) => {
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+
+        decode_content(&file.content, &file.encoding).map(Some)
+    }
+
+    #[tracing::instrument(skip(self, repo), fields(repo = %repo.forge_id))]
+    async fn list_dirs(
+        &self,
+        repo: &FetchedRepository,
+        path: &str,
+    ) -> Result<Vec<String>, SyncError> {
+        let url = format!("{GITHUB_API_BASE}/repos/{}/contents/{path}", repo.forge_id);
+
+        let entries: Vec<GitHubEntry> = match self.get(&url).await {
+            Ok(response) => response.json().await?,
+            Err(SyncError::Http(e)) if e.status() == Some(reqwest::StatusCode::NOT_FOUND) => {
+                return Ok(Vec::new());
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(entries
+            .into_iter()
+            .filter(|e| e.kind == "dir")
+            .map(|e| e.name)
+            .collect())
+    }
+}
+
+/// Decode file content from the contents API, which GitHub base64-encodes and wraps at 60
+/// columns.
+fn decode_content(content: &str, encoding: &str) -> Result<String, SyncError> {
+    if encoding != "base64" {
+        return Err(SyncError::Other(format!(
+            "unexpected content encoding {encoding}"
+        )));
+    }
+    let content: String = content.split_whitespace().collect();
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(content)
+        .map_err(|e| SyncError::Other(format!("invalid file content: {e}")))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
 }
 
 #[derive(Debug, Deserialize)]
@@ -106,11 +208,15 @@ struct GitHubRepo {
     name: String,
     description: Option<String>,
     html_url: String,
+    homepage: Option<String>,
     language: Option<String>,
     stargazers_count: i32,
     fork: bool,
     topics: Option<Vec<String>>,
+    created_at: Option<DateTime<Utc>>,
     updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pushed_at: Option<DateTime<Utc>>,
 }
 
 impl From<GitHubRepo> for FetchedRepository {
@@ -121,10 +227,73 @@ impl From<GitHubRepo> for FetchedRepository {
             name: repo.name,
             description: repo.description,
             url: repo.html_url,
+            homepage_url: repo.homepage.filter(|h| !h.is_empty()),
             language: repo.language,
             stars: repo.stargazers_count,
             topics: repo.topics.unwrap_or_default(),
+            created_at: repo.created_at,
             updated_at: repo.updated_at,
+            pushed_at: repo.pushed_at,
+            readme_path: None,
+        }
+    }
+}
+
+/// Response from `/repos/{owner}/{repo}/readme`
+#[derive(Debug, Deserialize)]
+struct GitHubReadme {
+    path: String,
+    content: String,
+    encoding: String,
+}
+
+impl GitHubReadme {
+    fn into_readme(self, repo: &FetchedRepository) -> Result<FetchedReadme, SyncError> {
+        let markdown = decode_content(&self.content, &self.encoding)?;
+
+        // `HEAD` follows the default branch, wherever the README's links were written against.
+        FetchedReadme::new(
+            markdown,
+            &format!("{}/blob/HEAD/", repo.url),
+            &format!("https://raw.githubusercontent.com/{}/HEAD/", repo.forge_id),
+            &self.path,
+        )
+    }
+}
+
+/// A file from `/repos/{owner}/{repo}/contents/{path}`
+#[derive(Debug, Deserialize)]
+struct GitHubFile {
+    content: String,
+    encoding: String,
+}
+
+/// One entry of a directory listing from `/repos/{owner}/{repo}/contents/{path}`
+#[derive(Debug, Deserialize)]
+struct GitHubEntry {
+    name: String,
+    /// `file`, `dir`, `symlink` or `submodule`
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    html_url: String,
+    draft: bool,
+    published_at: Option<DateTime<Utc>>,
+}
+
+impl GitHubRelease {
+    fn into_release(self, full_name: &str) -> FetchedRelease {
+        FetchedRelease {
+            artifact_kind: "repo",
+            artifact: full_name.to_string(),
+            version: self.tag_name,
+            released_at: self.published_at,
+            yanked: false,
+            notes_url: Some(self.html_url),
         }
     }
 }
@@ -203,22 +372,28 @@ mod tests {
                 name: "owned".to_string(),
                 description: Some("Owned repo".to_string()),
                 html_url: "https://github.com/user/owned".to_string(),
+                homepage: None,
                 language: Some("Rust".to_string()),
                 stargazers_count: 10,
                 fork: false,
                 topics: None,
+                created_at: None,
                 updated_at: None,
+                pushed_at: None,
             },
             GitHubRepo {
                 full_name: "user/forked".to_string(),
                 name: "forked".to_string(),
                 description: Some("Forked repo".to_string()),
                 html_url: "https://github.com/user/forked".to_string(),
+                homepage: None,
                 language: Some("Python".to_string()),
                 stargazers_count: 100,
                 fork: true,
                 topics: None,
+                created_at: None,
                 updated_at: None,
+                pushed_at: None,
             },
         ];
 
@@ -239,11 +414,14 @@ mod tests {
             name: "repo".to_string(),
             description: Some("Description".to_string()),
             html_url: "https://github.com/user/repo".to_string(),
+            homepage: None,
             language: Some("Rust".to_string()),
             stargazers_count: 100,
             fork: false,
             topics: Some(vec!["topic1".to_string(), "topic2".to_string()]),
+            created_at: None,
             updated_at: None,
+            pushed_at: None,
         };
 
         let fetched: FetchedRepository = github_repo.into();
@@ -265,11 +443,14 @@ mod tests {
             name: "minimal".to_string(),
             description: None,
             html_url: "https://github.com/user/minimal".to_string(),
+            homepage: None,
             language: None,
             stargazers_count: 0,
             fork: false,
             topics: None,
+            created_at: None,
             updated_at: None,
+            pushed_at: None,
         };
 
         let fetched: FetchedRepository = github_repo.into();
@@ -280,4 +461,78 @@ mod tests {
         assert_eq!(fetched.stars, 0);
         assert!(fetched.topics.is_empty());
     }
+
+    #[test]
+    fn converts_github_release_skipping_drafts() {
+        let json = r#"[
+            {
+                "tag_name": "v1.2.0",
+                "html_url": "https://github.com/user/repo/releases/tag/v1.2.0",
+                "draft": false,
+                "published_at": "2024-03-01T12:00:00Z"
+            },
+            {
+                "tag_name": "v1.3.0",
+                "html_url": "https://github.com/user/repo/releases/tag/untagged-1",
+                "draft": true,
+                "published_at": null
+            }
+        ]"#;
+
+        let releases: Vec<GitHubRelease> = serde_json::from_str(json).unwrap();
+        let fetched: Vec<FetchedRelease> = releases
+            .into_iter()
+            .filter(|r| !r.draft)
+            .map(|r| r.into_release("user/repo"))
+            .collect();
+
+        assert_eq!(fetched.len(), 1);
+        assert_eq!(fetched[0].artifact_kind, "repo");
+        assert_eq!(fetched[0].artifact, "user/repo");
+        assert_eq!(fetched[0].version, "v1.2.0");
+        assert!(fetched[0].released_at.is_some());
+        assert!(!fetched[0].yanked);
+        assert_eq!(
+            fetched[0].notes_url.as_deref(),
+            Some("https://github.com/user/repo/releases/tag/v1.2.0")
+        );
+    }
+
+    #[test]
+    fn decodes_readme_response() {
+        let json = r#"{
+            "path": "docs/README.md",
+            "encoding": "base64",
+            "content": "IyBUaXRs\nZQo=\n"
+        }"#;
+        let repo: FetchedRepository = GitHubRepo {
+            full_name: "user/repo".to_string(),
+            name: "repo".to_string(),
+            description: None,
+            html_url: "https://github.com/user/repo".to_string(),
+            homepage: None,
+            language: None,
+            stargazers_count: 0,
+            fork: false,
+            topics: None,
+            created_at: None,
+            updated_at: None,
+            pushed_at: None,
+        }
+        .into();
+
+        let readme: GitHubReadme = serde_json::from_str(json).unwrap();
+        let readme = readme.into_readme(&repo).unwrap();
+
+        assert_eq!(readme.markdown, "# Title\n");
+        assert_eq!(
+            readme.blob_root.as_str(),
+            "https://github.com/user/repo/blob/HEAD/"
+        );
+        assert_eq!(
+            readme.raw_root.as_str(),
+            "https://raw.githubusercontent.com/user/repo/HEAD/"
+        );
+        assert_eq!(readme.dir, "docs/");
+    }
 }
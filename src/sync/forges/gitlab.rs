@@ -4,7 +4,7 @@ use reqwest::header::USER_AGENT;
 use serde::Deserialize;
 use serde_json::Value;
 
-use crate::sync::{FetchedRepository, SyncError, SyncSource};
+use crate::sync::{FetchedReadme, FetchedRepository, SyncError, SyncSource};
 
 pub struct GitLabForge {
     client: reqwest::Client,
@@ -34,18 +34,12 @@ impl GitLabForge {
         format!("https://{}/api/v4", self.host)
     }
 
-    #[tracing::instrument(skip(self))]
-    async fn fetch_page(&self, page: u32) -> Result<Vec<GitLabProject>, SyncError> {
-        let url = format!(
-            "{}/users/{}/projects?per_page=100&page={}&order_by=updated_at&visibility=public",
-            self.api_base(),
-            self.username,
-            page
-        );
-
+    /// Send a GET request, turning GitLab's rate-limit response into
+    /// [`SyncError::RateLimited`].
+    async fn get(&self, url: &str) -> Result<reqwest::Response, SyncError> {
         let response = self
             .client
-            .get(&url)
+            .get(url)
             .header(USER_AGENT, "djv-sync/1.0 (https://djv.sh)")
             .send()
             .await?;
@@ -60,7 +54,19 @@ impl GitLabForge {
             return Err(SyncError::RateLimited(retry_after));
         }
 
-        let projects: Vec<GitLabProject> = response.error_for_status()?.json().await?;
+        Ok(response.error_for_status()?)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn fetch_page(&self, page: u32) -> Result<Vec<GitLabProject>, SyncError> {
+        let url = format!(
+            "{}/users/{}/projects?per_page=100&page={}&order_by=updated_at&visibility=public",
+            self.api_base(),
+            self.username,
+            page
+        );
+
+        let projects: Vec<GitLabProject> = self.get(&url).await?.json().await?;
         Ok(projects)
     }
 }
@@ -98,6 +104,87 @@ impl SyncSource for GitLabForge {
         tracing::info!(count = all_repos.len(), "fetched all repositories");
         Ok(all_repos)
     }
+
+    #[tracing::instrument(skip(self, repo), fields(repo = %repo.forge_id))]
+    async fn fetch_readme(
+        &self,
+        repo: &FetchedRepository,
+    ) -> Result<Option<FetchedReadme>, SyncError> {
+        let Some(ref path) = repo.readme_path else {
+            return Ok(None);
+        };
+        // `HEAD` is the default branch, for the API and the web links alike.
+        let Some(markdown) = self.fetch_file(repo, path).await? else {
+            return Ok(None);
+        };
+
+        FetchedReadme::new(
+            markdown,
+            &format!("{}/-/blob/HEAD/", repo.url),
+            &format!("{}/-/raw/HEAD/", repo.url),
+            path,
+        )
+        .map(Some)
+    }
+
+    #[tracing::instrument(skip(self, repo), fields(repo = %repo.forge_id))]
+    async fn fetch_file(
+        &self,
+        repo: &FetchedRepository,
+        path: &str,
+    ) -> Result<Option<String>, SyncError> {
+        let url = format!(
+            "{}/projects/{}/repository/files/{}/raw?ref=HEAD",
+            self.api_base(),
+            repo.forge_id,
+            urlencoding::encode(path)
+        );
+
+        match self.get(&url).await {
+            Ok(response) => Ok(Some(response.text().await?)),
+            Err(SyncError::Http(e)) if e.status() == Some(reqwest::StatusCode::NOT_FOUND) => {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    #[tracing::instrument(skip(self, repo), fields(repo = %repo.forge_id))]
+    async fn list_dirs(
+        &self,
+        repo: &FetchedRepository,
+        path: &str,
+    ) -> Result<Vec<String>, SyncError> {
+        let url = format!(
+            "{}/projects/{}/repository/tree?path={}&ref=HEAD&per_page=100",
+            self.api_base(),
+            repo.forge_id,
+            urlencoding::encode(path)
+        );
+
+        let entries: Vec<GitLabTreeEntry> = match self.get(&url).await {
+            Ok(response) => response.json().await?,
+            Err(SyncError::Http(e)) if e.status() == Some(reqwest::StatusCode::NOT_FOUND) => {
+                return Ok(Vec::new());
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(entries
+            .into_iter()
+            .filter(|e| e.kind == "tree")
+            .map(|e| e.name)
+            .collect())
+    }
+}
+
+/// One entry of `/projects/{id}/repository/tree`
+#[derive(Debug, Deserialize)]
+struct GitLabTreeEntry {
+    name: String,
+    /// `tree` for directories, `blob` for files
+    #[serde(rename = "type")]
+    kind: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -114,21 +201,46 @@ struct GitLabProject {
     #[serde(default)]
     forked_from_project: Option<Value>,
     topics: Option<Vec<String>>,
+    created_at: Option<DateTime<Utc>>,
     last_activity_at: Option<DateTime<Utc>>,
+    default_branch: Option<String>,
+    /// Web URL of the README, e.g. `{web_url}/-/blob/{default_branch}/README.md`
+    readme_url: Option<String>,
+}
+
+impl GitLabProject {
+    /// The README's path within the repository, recovered from its web URL.
+    fn readme_path(&self) -> Option<String> {
+        let prefix = format!(
+            "{}/-/blob/{}/",
+            self.web_url,
+            self.default_branch.as_deref()?
+        );
+        self.readme_url
+            .as_deref()?
+            .strip_prefix(&prefix)
+            .map(str::to_owned)
+    }
 }
 
 impl From<GitLabProject> for FetchedRepository {
     fn from(project: GitLabProject) -> Self {
+        let readme_path = project.readme_path();
         Self {
             forge: "gitlab".to_string(),
             forge_id: project.id.to_string(),
             name: project.name,
             description: project.description,
             url: project.web_url,
+            homepage_url: None,
             language: None, // GitLab doesn't return primary language in this endpoint
             stars: project.star_count,
             topics: project.topics.unwrap_or_default(),
+            created_at: project.created_at,
             updated_at: project.last_activity_at,
+            // GitLab lists no push time; activity covers pushes and more.
+            pushed_at: project.last_activity_at,
+            readme_path,
         }
     }
 }
@@ -174,7 +286,10 @@ mod tests {
             archived: false,
             forked_from_project: None,
             topics: Some(vec!["rust".to_string(), "testing".to_string()]),
+            created_at: None,
             last_activity_at: None,
+            default_branch: None,
+            readme_url: None,
         };
 
         let fetched: FetchedRepository = project.into();
@@ -201,7 +316,10 @@ mod tests {
                 archived: false,
                 forked_from_project: None,
                 topics: None,
+                created_at: None,
                 last_activity_at: None,
+                default_branch: None,
+                readme_url: None,
             },
             GitLabProject {
                 id: 2,
@@ -213,7 +331,10 @@ mod tests {
                 archived: true,
                 forked_from_project: None,
                 topics: None,
+                created_at: None,
                 last_activity_at: None,
+                default_branch: None,
+                readme_url: None,
             },
         ];
 
@@ -243,4 +364,23 @@ mod tests {
         assert_eq!(forge.username, "testuser");
         assert_eq!(forge.host, "gitlab.example.com");
     }
+
+    #[test]
+    fn finds_readme_path_from_its_url() {
+        let json = r#"{
+            "id": 1,
+            "path_with_namespace": "user/project",
+            "name": "project",
+            "description": null,
+            "web_url": "https://gitlab.com/user/project",
+            "star_count": 0,
+            "default_branch": "release/2.x",
+            "readme_url": "https://gitlab.com/user/project/-/blob/release/2.x/docs/README.md"
+        }"#;
+
+        let project: GitLabProject = serde_json::from_str(json).unwrap();
+        let fetched: FetchedRepository = project.into();
+
+        assert_eq!(fetched.readme_path.as_deref(), Some("docs/README.md"));
+    }
 }
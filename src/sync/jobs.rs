@@ -0,0 +1,511 @@
+//! Sync as a queue of jobs in `sync_jobs`, used when the store is `PostgreSQL`.
+//!
+//! Each interval the leader queues a job per source and a [`SyncJob::Finish`]. A source job
+//! upserts what the source lists and queues a job per item, such as a repository's README or
+//! a crate's versions, rather than fetching it inline. Workers on every instance claim due
+//! jobs with `FOR UPDATE SKIP LOCKED`, so each job runs once however many instances there
+//! are. A failed job is retried with exponential backoff until its attempts run out, then
+//! left `dead` for inspection. A job is marked done in the same transaction that queues the
+//! jobs it found, and only while its claim holds, so a retry never queues them twice.
+//! `finish` runs once nothing else is queued or running.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::{
+    record_run, sync_contributions, sync_crate_versions, sync_npm_versions, sync_readme,
+    sync_releases, sync_repositories, sync_workspace, upsert_crates, upsert_npm_packages,
+    FetchedRepository, SyncError, SyncSource, SyncSources,
+};
+use crate::db::{self, ClaimedSyncJob, NewSyncJob};
+use crate::store::SharedStore;
+
+/// Attempts a job gets before it's left dead
+const MAX_ATTEMPTS: i32 = 5;
+
+/// Delay before the first retry, doubled for each one after
+const BASE_BACKOFF: Duration = Duration::from_secs(30);
+
+const MAX_BACKOFF: Duration = Duration::from_hours(1);
+
+/// How long a worker may hold a job before it's presumed dead and the job is claimed again.
+/// Long enough for a source job to page through everything its source lists.
+const LEASE: Duration = Duration::from_mins(15);
+
+/// How long an idle worker waits before looking for a job again
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long finished jobs, done or dead, are kept for inspection
+const RETENTION_DAYS: i64 = 7;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SyncJob {
+    /// A forge's repositories, queuing the three jobs below for each
+    Forge {
+        forge: String,
+    },
+    Releases {
+        repo: FetchedRepository,
+    },
+    Readme {
+        repository_id: Uuid,
+        repo: FetchedRepository,
+    },
+    Workspace {
+        repository_id: Uuid,
+        repo: FetchedRepository,
+    },
+    /// The user's crates, queuing each one's versions
+    Crates,
+    CrateVersions {
+        name: String,
+    },
+    /// The user's npm packages, queuing each one's versions
+    NpmPackages,
+    NpmVersions {
+        name: String,
+    },
+    Contributions,
+    /// Link artifacts, refresh `projects` and record the run
+    Finish,
+}
+
+impl SyncJob {
+    /// The `kind` tag it's serialised with
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Forge { .. } => "forge",
+            Self::Releases { .. } => "releases",
+            Self::Readme { .. } => "readme",
+            Self::Workspace { .. } => "workspace",
+            Self::Crates => "crates",
+            Self::CrateVersions { .. } => "crate_versions",
+            Self::NpmPackages => "npm_packages",
+            Self::NpmVersions { .. } => "npm_versions",
+            Self::Contributions => "contributions",
+            Self::Finish => "finish",
+        }
+    }
+}
+
+/// The jobs that start a run over `sources`
+fn run_jobs(sources: &SyncSources) -> Vec<SyncJob> {
+    let mut jobs: Vec<SyncJob> = sources
+        .forges
+        .iter()
+        .map(|f| SyncJob::Forge {
+            forge: f.name().to_owned(),
+        })
+        .collect();
+    if sources.crates_io.is_some() {
+        jobs.push(SyncJob::Crates);
+    }
+    if sources.npm.is_some() {
+        jobs.push(SyncJob::NpmPackages);
+    }
+    if sources.contributions.is_some() {
+        jobs.push(SyncJob::Contributions);
+    }
+    jobs.push(SyncJob::Finish);
+    jobs
+}
+
+fn encode(jobs: &[SyncJob]) -> Result<Vec<NewSyncJob<'static>>, SyncError> {
+    jobs.iter()
+        .map(|job| {
+            Ok(NewSyncJob {
+                kind: job.kind(),
+                payload: serde_json::to_value(job)
+                    .map_err(|e| SyncError::Other(format!("unencodable job: {e}")))?,
+                max_attempts: MAX_ATTEMPTS,
+            })
+        })
+        .collect()
+}
+
+/// Queue a run over `sources`, unless the last one is still going.
+pub(super) async fn start_run(
+    conn: &mut PgConnection,
+    sources: &SyncSources,
+) -> Result<(), SyncError> {
+    let jobs = run_jobs(sources);
+    if db::start_sync_run(conn, &encode(&jobs)?).await? {
+        tracing::info!(jobs = jobs.len(), "queued sync run");
+    } else {
+        tracing::info!("previous sync run still in progress, not queuing another");
+    }
+    Ok(())
+}
+
+/// How long to wait before retrying a job that failed its `attempts`th attempt with `error`
+fn retry_delay(attempts: i32, error: &SyncError) -> Duration {
+    let doublings = u32::try_from(attempts.saturating_sub(1))
+        .unwrap_or(0)
+        .min(16);
+    let backoff = BASE_BACKOFF.saturating_mul(1 << doublings).min(MAX_BACKOFF);
+    match *error {
+        SyncError::RateLimited(retry_after) => backoff.max(Duration::from_secs(retry_after)),
+        _ => backoff,
+    }
+}
+
+/// What a worker runs jobs with. Cheap to clone; clones share everything.
+#[derive(Clone)]
+struct Worker {
+    pool: PgPool,
+    store: SharedStore,
+    sources: Arc<SyncSources>,
+}
+
+/// Start `workers` workers claiming jobs from the queue. Instances that share the database
+/// share the work, so one with no workers leaves it to the others.
+pub(super) fn spawn_workers(
+    pool: PgPool,
+    store: SharedStore,
+    sources: Arc<SyncSources>,
+    workers: usize,
+) {
+    let worker = Worker {
+        pool,
+        store,
+        sources,
+    };
+    for _ in 0..workers {
+        tokio::spawn(worker.clone().work());
+    }
+    tracing::info!(workers, "sync workers spawned");
+}
+
+impl Worker {
+    async fn work(self) {
+        loop {
+            match db::claim_sync_job(&self.pool, LEASE).await {
+                Ok(Some(job)) => self.run(job).await,
+                Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to claim sync job");
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    /// Run a claimed job and record how it went.
+    #[tracing::instrument(
+        skip_all,
+        fields(job.id = claimed.id, job.kind = %claimed.kind, job.attempt = claimed.attempts)
+    )]
+    async fn run(&self, claimed: ClaimedSyncJob) {
+        let result = self.attempt(&claimed).await;
+        let recorded = match result {
+            Ok(found) => {
+                tracing::debug!(found = found.len(), "sync job done");
+                db::complete_sync_job(&self.pool, &claimed, &found).await
+            }
+            Err(e) if claimed.attempts >= claimed.max_attempts => {
+                tracing::error!(error = %e, "sync job failed for the last time");
+                db::fail_sync_job(&self.pool, &claimed, &e.to_string(), None).await
+            }
+            Err(e) => {
+                let retry_in = retry_delay(claimed.attempts, &e);
+                tracing::warn!(error = %e, retry_in = retry_in.as_secs(), "sync job failed");
+                db::fail_sync_job(&self.pool, &claimed, &e.to_string(), Some(retry_in)).await
+            }
+        };
+        match recorded {
+            Ok(true) => {}
+            // Its lease ran out mid-run, and the worker that claimed it since records it.
+            Ok(false) => tracing::warn!("sync job was claimed again before it finished"),
+            // The lease runs out and the job is claimed again.
+            Err(e) => tracing::warn!(error = %e, "failed to record sync job outcome"),
+        }
+    }
+
+    /// Run a claimed job, returning the jobs it found, ready to queue.
+    async fn attempt(
+        &self,
+        claimed: &ClaimedSyncJob,
+    ) -> Result<Vec<NewSyncJob<'static>>, SyncError> {
+        if claimed.attempts > claimed.max_attempts {
+            // Only a job whose workers kept dying gets here.
+            return Err(SyncError::Other("abandoned by its worker".to_owned()));
+        }
+
+        let job: SyncJob = serde_json::from_value(claimed.payload.clone())
+            .map_err(|e| SyncError::Other(format!("undecodable job: {e}")))?;
+        let found = self.execute(job, claimed.created_at).await?;
+        encode(&found)
+    }
+
+    /// Run one job, returning the jobs it found for the items it listed.
+    async fn execute(
+        &self,
+        job: SyncJob,
+        queued_at: DateTime<Utc>,
+    ) -> Result<Vec<SyncJob>, SyncError> {
+        let store = self.store.as_ref();
+        match job {
+            SyncJob::Forge { forge } => {
+                let repositories = sync_repositories(store, self.forge(&forge)?).await?;
+                Ok(repositories
+                    .into_iter()
+                    .flat_map(|(repo, repository_id)| {
+                        [
+                            SyncJob::Releases { repo: repo.clone() },
+                            SyncJob::Readme {
+                                repository_id,
+                                repo: repo.clone(),
+                            },
+                            SyncJob::Workspace {
+                                repository_id,
+                                repo,
+                            },
+                        ]
+                    })
+                    .collect())
+            }
+            SyncJob::Releases { repo } => {
+                sync_releases(store, self.forge(&repo.forge)?, &repo).await?;
+                Ok(Vec::new())
+            }
+            SyncJob::Readme {
+                repository_id,
+                repo,
+            } => {
+                sync_readme(store, self.forge(&repo.forge)?, repository_id, &repo).await?;
+                Ok(Vec::new())
+            }
+            SyncJob::Workspace {
+                repository_id,
+                repo,
+            } => {
+                sync_workspace(store, self.forge(&repo.forge)?, repository_id, &repo).await?;
+                Ok(Vec::new())
+            }
+            SyncJob::Crates => {
+                let names =
+                    upsert_crates(store, configured(self.sources.crates_io.as_ref())?).await?;
+                Ok(names
+                    .into_iter()
+                    .map(|name| SyncJob::CrateVersions { name })
+                    .collect())
+            }
+            SyncJob::CrateVersions { name } => {
+                sync_crate_versions(store, configured(self.sources.crates_io.as_ref())?, &name)
+                    .await?;
+                Ok(Vec::new())
+            }
+            SyncJob::NpmPackages => {
+                let names =
+                    upsert_npm_packages(store, configured(self.sources.npm.as_ref())?).await?;
+                Ok(names
+                    .into_iter()
+                    .map(|name| SyncJob::NpmVersions { name })
+                    .collect())
+            }
+            SyncJob::NpmVersions { name } => {
+                sync_npm_versions(store, configured(self.sources.npm.as_ref())?, &name).await?;
+                Ok(Vec::new())
+            }
+            SyncJob::Contributions => {
+                sync_contributions(store, configured(self.sources.contributions.as_ref())?).await?;
+                Ok(Vec::new())
+            }
+            SyncJob::Finish => {
+                self.finish(queued_at).await?;
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    fn forge(&self, name: &str) -> Result<&dyn SyncSource, SyncError> {
+        self.sources
+            .forges
+            .iter()
+            .find(|f| f.name() == name)
+            .map(AsRef::as_ref)
+            .ok_or_else(|| SyncError::Other(format!("{name} is not configured here")))
+    }
+
+    /// Refresh `projects` from what the run's jobs wrote and record the run, failed if any
+    /// job queued since it started ran out of attempts. Recording it has every instance take
+    /// its snapshot; see [`crate::cache::spawn_invalidation_listener`].
+    ///
+    /// A crate or package upserted while its repository's forge job ran may have missed
+    /// both the lookup by URL and the forge job's relinking, so artifacts are relinked once
+    /// more first.
+    async fn finish(&self, started_at: DateTime<Utc>) -> Result<(), SyncError> {
+        let store = self.store.as_ref();
+        let linked = store.relink_artifacts().await?;
+        store.refresh_projects().await?;
+
+        let dead = db::count_dead_sync_jobs(&self.pool, started_at).await?;
+        let result = if dead == 0 {
+            Ok(())
+        } else {
+            Err(SyncError::Other(format!("{dead} sync jobs failed")))
+        };
+        record_run(store, &result).await;
+
+        let cutoff = Utc::now() - chrono::Duration::days(RETENTION_DAYS);
+        let deleted = db::delete_finished_sync_jobs(&self.pool, cutoff).await?;
+        tracing::info!(linked, dead, deleted, "sync run finished");
+        Ok(())
+    }
+}
+
+/// A registry or contributions source, which this instance may not have been given
+fn configured<T>(source: Option<&T>) -> Result<&T, SyncError> {
+    source.ok_or_else(|| SyncError::Other("source is not configured here".to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo() -> FetchedRepository {
+        FetchedRepository {
+            forge: "github".to_owned(),
+            forge_id: "user/djv".to_owned(),
+            name: "djv".to_owned(),
+            description: None,
+            url: "https://github.com/user/djv".to_owned(),
+            homepage_url: None,
+            language: Some("Rust".to_owned()),
+            stars: 1,
+            topics: vec!["web".to_owned()],
+            created_at: None,
+            updated_at: None,
+            pushed_at: None,
+            readme_path: Some("README.md".to_owned()),
+        }
+    }
+
+    #[test]
+    fn kind_matches_the_serialised_tag() {
+        let jobs = [
+            SyncJob::Forge {
+                forge: "github".to_owned(),
+            },
+            SyncJob::Releases { repo: repo() },
+            SyncJob::Readme {
+                repository_id: Uuid::nil(),
+                repo: repo(),
+            },
+            SyncJob::Workspace {
+                repository_id: Uuid::nil(),
+                repo: repo(),
+            },
+            SyncJob::Crates,
+            SyncJob::CrateVersions {
+                name: "djv".to_owned(),
+            },
+            SyncJob::NpmPackages,
+            SyncJob::NpmVersions {
+                name: "djv".to_owned(),
+            },
+            SyncJob::Contributions,
+            SyncJob::Finish,
+        ];
+
+        for job in jobs {
+            let payload = serde_json::to_value(&job).unwrap();
+            assert_eq!(payload["kind"], job.kind());
+            let decoded: SyncJob = serde_json::from_value(payload.clone()).unwrap();
+            assert_eq!(serde_json::to_value(&decoded).unwrap(), payload);
+        }
+    }
+
+    #[test]
+    fn retries_back_off_exponentially_up_to_a_cap() {
+        let error = SyncError::Other("boom".to_owned());
+        assert_eq!(retry_delay(1, &error), Duration::from_secs(30));
+        assert_eq!(retry_delay(2, &error), Duration::from_secs(60));
+        assert_eq!(retry_delay(4, &error), Duration::from_secs(240));
+        assert_eq!(retry_delay(50, &error), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn rate_limited_retries_wait_at_least_as_asked() {
+        assert_eq!(
+            retry_delay(1, &SyncError::RateLimited(900)),
+            Duration::from_secs(900)
+        );
+        assert_eq!(
+            retry_delay(3, &SyncError::RateLimited(10)),
+            Duration::from_secs(120)
+        );
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn finish_links_crates_synced_after_their_repository(pool: PgPool) {
+        use crate::db::{CanonicalUrl, NewCrate, NewRepository, ProjectFilters, ProjectKind};
+        use crate::store::Store;
+
+        let worker = Worker {
+            pool: pool.clone(),
+            store: Arc::new(pool.clone()),
+            sources: Arc::new(SyncSources {
+                forges: Vec::new(),
+                crates_io: None,
+                npm: None,
+                contributions: None,
+            }),
+        };
+        let url = "https://github.com/user/djv";
+
+        // The crates job looks the repository up before the forge job has upserted it, and
+        // upserts the crate after the forge job has relinked.
+        let store = worker.store.as_ref();
+        store
+            .upsert_repository(&NewRepository {
+                forge: "github",
+                forge_id: "user/djv",
+                name: "djv",
+                description: None,
+                url,
+                homepage_url: None,
+                language: Some("Rust"),
+                stars: 1,
+                topics: &[],
+                created_at: None,
+                updated_at: None,
+            })
+            .await
+            .unwrap();
+        store
+            .upsert_crate(&NewCrate {
+                name: "djv",
+                description: None,
+                repository_id: None,
+                repository_url: Some(&CanonicalUrl::parse(url).unwrap()),
+                crates_io_url: "https://crates.io/crates/djv",
+                documentation_url: None,
+                homepage_url: None,
+                downloads: 10,
+                version: Some("1.0.0"),
+                keywords: &[],
+                categories: &[],
+                created_at: None,
+            })
+            .await
+            .unwrap();
+
+        worker.finish(Utc::now()).await.unwrap();
+
+        let projects = store
+            .get_projects(&ProjectFilters::default())
+            .await
+            .unwrap()
+            .projects;
+        assert_eq!(projects.len(), 1);
+        let kinds: Vec<ProjectKind> = projects[0].artifacts.iter().map(|a| a.kind).collect();
+        assert!(kinds.contains(&ProjectKind::Crate) && kinds.contains(&ProjectKind::Repo));
+    }
+}
@@ -0,0 +1,172 @@
+//! Which instance starts sync runs when several share a database.
+//!
+//! The leader holds a session-level `PostgreSQL` advisory lock on a connection kept out of the
+//! pool. If the leader dies, its session ends, the lock is released, and the next instance to
+//! try takes over. Without a `PostgreSQL` pool there is only one instance, which always leads.
+//!
+//! Each instance reports whether it leads as the `djv.sync.leader` gauge, 1 or 0.
+
+use std::time::Duration;
+
+use opentelemetry_configuration::opentelemetry::global;
+use opentelemetry_configuration::opentelemetry::metrics::Gauge;
+use sqlx::{PgConnection, PgPool};
+
+/// Advisory lock key for the sync loop, `djv_sync` in ASCII
+const LOCK_KEY: i64 = 0x646a_765f_7379_6e63;
+
+/// How long a follower waits before trying for the lock again
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+pub struct LeaderElection {
+    pool: Option<PgPool>,
+    /// The session holding the lock, while this instance leads, and its backend's process id
+    lock: Option<(PgConnection, i32)>,
+    leader: Gauge<u64>,
+}
+
+impl LeaderElection {
+    #[must_use]
+    pub fn new(pool: Option<PgPool>) -> Self {
+        let leader = global::meter("djv")
+            .u64_gauge("djv.sync.leader")
+            .with_description("1 while this instance leads sync, 0 otherwise")
+            .build();
+        Self {
+            pool,
+            lock: None,
+            leader,
+        }
+    }
+
+    /// The session holding the lock, while this instance leads with `PostgreSQL`. Work done
+    /// on it fails rather than carrying on once the lock is lost.
+    pub fn session(&mut self) -> Option<&mut PgConnection> {
+        self.lock.as_mut().map(|(conn, _)| conn)
+    }
+
+    /// The process id of the backend holding the lock, which identifies this instance's
+    /// session in `pg_stat_activity` and `pg_locks`.
+    #[must_use]
+    pub fn session_pid(&self) -> Option<i32> {
+        self.lock.as_ref().map(|&(_, pid)| pid)
+    }
+
+    /// Wait until this instance leads, trying for the lock every [`RETRY_DELAY`].
+    pub async fn wait_for_leadership(&mut self) {
+        while !self.is_leader().await {
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+    }
+
+    /// Whether this instance leads, confirming that the session holding the lock is still up
+    /// or, failing that, trying to take it.
+    pub async fn is_leader(&mut self) -> bool {
+        let leads = self.check().await;
+        self.leader.record(u64::from(leads), &[]);
+        leads
+    }
+
+    async fn check(&mut self) -> bool {
+        let Some(ref pool) = self.pool else {
+            return true;
+        };
+
+        if let Some((ref mut lock, _)) = self.lock {
+            match sqlx::query("SELECT 1").execute(&mut *lock).await {
+                Ok(_) => return true,
+                Err(e) => {
+                    tracing::warn!(error = %e, lock = LOCK_KEY, "lost sync leadership");
+                    self.lock = None;
+                }
+            }
+        }
+
+        match try_lock(pool).await {
+            Ok(Some(lock)) => {
+                tracing::info!(lock = LOCK_KEY, "acquired sync leadership");
+                self.lock = Some(lock);
+                true
+            }
+            Ok(None) => {
+                tracing::debug!(lock = LOCK_KEY, "another instance leads sync");
+                false
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, lock = LOCK_KEY, "failed to try for sync leadership");
+                false
+            }
+        }
+    }
+}
+
+/// A connection holding the sync lock and its backend's process id, or `None` if another
+/// session holds it. The connection is detached from the pool, so dropping it closes the
+/// session and releases the lock rather than handing a locked session to some other query.
+async fn try_lock(pool: &PgPool) -> Result<Option<(PgConnection, i32)>, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    let row = sqlx::query!(
+        r#"SELECT pg_try_advisory_lock($1) AS "locked!", pg_backend_pid() AS "pid!""#,
+        LOCK_KEY
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if row.locked {
+        Ok(Some((conn.detach(), row.pid)))
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn leads_alone_without_postgres() {
+        assert!(LeaderElection::new(None).is_leader().await);
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn another_instance_takes_over_when_the_leader_dies(pool: PgPool) {
+        let mut first = LeaderElection::new(Some(pool.clone()));
+        let mut second = LeaderElection::new(Some(pool.clone()));
+
+        assert!(first.is_leader().await);
+        assert!(!second.is_leader().await);
+        assert!(first.is_leader().await);
+
+        let leader_pid = first.session_pid().unwrap();
+        assert_eq!(
+            sqlx::query_scalar::<_, i32>("SELECT pg_backend_pid()")
+                .fetch_one(first.session().unwrap())
+                .await
+                .unwrap(),
+            leader_pid
+        );
+        sqlx::query("SELECT pg_terminate_backend($1)")
+            .bind(leader_pid)
+            .execute(&pool)
+            .await
+            .unwrap();
+        // Anything the old leader was still doing on its session fails.
+        assert!(sqlx::query("SELECT 1")
+            .execute(first.session().unwrap())
+            .await
+            .is_err());
+
+        // The lock goes once the terminated backend has exited.
+        let mut took_over = false;
+        for _ in 0..50 {
+            if second.is_leader().await {
+                took_over = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(took_over);
+        assert!(!first.is_leader().await);
+        assert!(first.lock.is_none());
+    }
+}
@@ -1,28 +1,62 @@
 pub mod contributions;
 pub mod forges;
+pub mod jobs;
+pub mod leader;
+pub mod readme;
 pub mod registries;
+pub mod workspace;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::interval;
+use tracing::Instrument;
 
-pub use contributions::{ContributionsSync, FetchedContribution};
+use crate::db::CanonicalUrl;
+use crate::snapshot::SnapshotStore;
+use crate::store::{SharedStore, Store};
+
+pub use contributions::{ContributionsSync, FetchedContribution, FetchedUpstreamRepo};
+pub use leader::LeaderElection;
+pub use readme::FetchedReadme;
 pub use registries::{CrateSummary, CratesIoRegistry, NpmPackageSummary, NpmRegistry};
+pub use workspace::WorkspaceMember;
 
 /// Repository data fetched from a forge (before database insertion)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FetchedRepository {
     pub forge: String,
     pub forge_id: String,
     pub name: String,
     pub description: Option<String>,
     pub url: String,
+    pub homepage_url: Option<String>,
     pub language: Option<String>,
     pub stars: i32,
     pub topics: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    /// Last push to the repository, so files fetched since needn't be fetched again
+    #[serde(default)]
+    pub pushed_at: Option<DateTime<Utc>>,
+    /// Path of the README within the repository, for forges that list it with the repository
+    pub readme_path: Option<String>,
+}
+
+/// A published version of a crate, npm package or repository (before database insertion)
+#[derive(Debug, Clone)]
+pub struct FetchedRelease {
+    /// `crate`, `npm` or `repo`, matching the project kinds
+    pub artifact_kind: &'static str,
+    /// Crate or package name, or `owner/name` for repositories
+    pub artifact: String,
+    pub version: String,
+    pub released_at: Option<DateTime<Utc>>,
+    pub yanked: bool,
+    pub notes_url: Option<String>,
 }
 
 #[async_trait]
@@ -30,6 +64,43 @@ pub trait SyncSource: Send + Sync {
     fn name(&self) -> &'static str;
 
     async fn fetch_repositories(&self) -> Result<Vec<FetchedRepository>, SyncError>;
+
+    /// Releases published for a repository. Forges without a releases API return nothing.
+    async fn fetch_releases(
+        &self,
+        _repo: &FetchedRepository,
+    ) -> Result<Vec<FetchedRelease>, SyncError> {
+        Ok(Vec::new())
+    }
+
+    /// The repository's README, or `None` if it has none. Forges without a README API
+    /// return nothing.
+    async fn fetch_readme(
+        &self,
+        _repo: &FetchedRepository,
+    ) -> Result<Option<FetchedReadme>, SyncError> {
+        Ok(None)
+    }
+
+    /// A file on the repository's default branch, or `None` if there is no such file. Forges
+    /// without a contents API return nothing.
+    async fn fetch_file(
+        &self,
+        _repo: &FetchedRepository,
+        _path: &str,
+    ) -> Result<Option<String>, SyncError> {
+        Ok(None)
+    }
+
+    /// Names of the directories directly inside `path` (empty for the root) on the
+    /// repository's default branch. Forges without a contents API return nothing.
+    async fn list_dirs(
+        &self,
+        _repo: &FetchedRepository,
+        _path: &str,
+    ) -> Result<Vec<String>, SyncError> {
+        Ok(Vec::new())
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -50,6 +121,8 @@ pub enum SyncError {
 pub struct SyncConfig {
     pub enabled: bool,
     pub interval_secs: u64,
+    /// Workers claiming queued jobs, when the store is `PostgreSQL`
+    pub workers: usize,
 }
 
 impl Default for SyncConfig {
@@ -57,6 +130,7 @@ impl Default for SyncConfig {
         Self {
             enabled: true,
             interval_secs: 3600,
+            workers: 4,
         }
     }
 }
@@ -71,9 +145,15 @@ impl SyncConfig {
             .and_then(|v| v.parse().ok())
             .unwrap_or(3600);
 
+        let workers = std::env::var("DJV_SYNC_WORKERS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(4);
+
         Self {
             enabled,
             interval_secs,
+            workers,
         }
     }
 }
@@ -96,142 +176,305 @@ impl SyncSources {
     }
 }
 
+/// Sync every source in turn, then refresh the materialised `projects` view from what they
+/// wrote.
+///
 /// # Errors
 /// Returns the first [`SyncError`] from any underlying forge/registry sync.
-#[tracing::instrument(skip(pool, sources))]
-pub async fn run_sync(pool: &PgPool, sources: &SyncSources) -> Result<(), SyncError> {
+#[tracing::instrument(skip(store, sources))]
+pub async fn run_sync(store: &dyn Store, sources: &SyncSources) -> Result<(), SyncError> {
     for source in &sources.forges {
-        sync_forge(pool, source.as_ref()).await?;
+        sync_forge(store, source.as_ref()).await?;
     }
 
     if let Some(ref crates_io) = sources.crates_io {
-        sync_crates(pool, crates_io).await?;
+        sync_crates(store, crates_io).await?;
     }
 
     if let Some(ref npm) = sources.npm {
-        sync_npm(pool, npm).await?;
+        sync_npm(store, npm).await?;
     }
 
     if let Some(ref contributions) = sources.contributions {
-        sync_contributions(pool, contributions).await?;
+        sync_contributions(store, contributions).await?;
     }
 
+    store.refresh_projects().await?;
     Ok(())
 }
 
-#[tracing::instrument(skip(pool, source), fields(source = source.name()))]
-async fn sync_forge(pool: &PgPool, source: &dyn SyncSource) -> Result<(), SyncError> {
+#[tracing::instrument(skip(store, source), fields(source = source.name()))]
+async fn sync_forge(store: &dyn Store, source: &dyn SyncSource) -> Result<(), SyncError> {
+    let repositories = sync_repositories(store, source).await?;
+
+    for (repo, id) in &repositories {
+        if !carry_on(sync_releases(store, source, repo).await, repo, "releases")?
+            || !carry_on(sync_readme(store, source, *id, repo).await, repo, "readme")?
+            || !carry_on(
+                sync_workspace(store, source, *id, repo).await,
+                repo,
+                "workspace",
+            )?
+        {
+            break;
+        }
+    }
+
+    tracing::info!(count = repositories.len(), "forge sync complete");
+    Ok(())
+}
+
+/// Fetch and upsert a forge's repositories, remove those it no longer lists, and link crates
+/// and packages synced before their repository appeared. Returns each repository with its id.
+async fn sync_repositories(
+    store: &dyn Store,
+    source: &dyn SyncSource,
+) -> Result<Vec<(FetchedRepository, uuid::Uuid)>, SyncError> {
     tracing::info!("starting forge sync");
 
     let repositories = source.fetch_repositories().await?;
-    let count = repositories.len();
-    let forge_name = source.name();
-
-    let mut synced_ids = Vec::with_capacity(count);
+    let mut synced = Vec::with_capacity(repositories.len());
     for repo in repositories {
-        let id = upsert_repository(pool, &repo).await?;
-        synced_ids.push(id);
+        let id = upsert_repository(store, &repo).await?;
+        synced.push((repo, id));
     }
 
-    let deleted = crate::db::delete_stale_repositories(pool, forge_name, &synced_ids).await?;
+    let synced_ids: Vec<uuid::Uuid> = synced.iter().map(|&(_, id)| id).collect();
+    let deleted = store
+        .delete_stale_repositories(source.name(), &synced_ids)
+        .await?;
     if deleted > 0 {
         tracing::info!(deleted, "removed stale repositories");
     }
 
-    tracing::info!(count, "forge sync complete");
+    let linked = store.relink_artifacts().await?;
+    if linked > 0 {
+        tracing::info!(linked, "linked artifacts to new repositories");
+    }
+
+    Ok(synced)
+}
+
+/// Whether to go on to the next repository after one of its steps. A rate limit skips the
+/// remaining repositories, a database error ends the sync, and any other failure is logged.
+fn carry_on(
+    result: Result<(), SyncError>,
+    repo: &FetchedRepository,
+    step: &str,
+) -> Result<bool, SyncError> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(SyncError::RateLimited(retry_after)) => {
+            tracing::warn!(
+                retry_after,
+                step,
+                "rate limited, skipping remaining repositories"
+            );
+            Ok(false)
+        }
+        Err(e @ SyncError::Database(_)) => Err(e),
+        Err(e) => {
+            tracing::warn!(repo = %repo.forge_id, step, error = %e, "failed to sync repository");
+            Ok(true)
+        }
+    }
+}
+
+async fn sync_releases(
+    store: &dyn Store,
+    source: &dyn SyncSource,
+    repo: &FetchedRepository,
+) -> Result<(), SyncError> {
+    let releases = source.fetch_releases(repo).await?;
+    upsert_releases(store, &releases).await
+}
+
+async fn sync_readme(
+    store: &dyn Store,
+    source: &dyn SyncSource,
+    repository_id: uuid::Uuid,
+    repo: &FetchedRepository,
+) -> Result<(), SyncError> {
+    if let Some(readme) = source.fetch_readme(repo).await? {
+        store_readme(store, repository_id, &readme).await
+    } else {
+        store.delete_readme(repository_id).await?;
+        Ok(())
+    }
+}
+
+/// Read a repository's workspace, unless nothing has been pushed since it was last read.
+async fn sync_workspace(
+    store: &dyn Store,
+    source: &dyn SyncSource,
+    repository_id: uuid::Uuid,
+    repo: &FetchedRepository,
+) -> Result<(), SyncError> {
+    if repo.pushed_at.is_some()
+        && store.get_workspace_pushed_at(repository_id).await? == repo.pushed_at
+    {
+        tracing::debug!(repo = %repo.forge_id, "workspace unchanged since last push");
+        return Ok(());
+    }
+    let members = workspace::fetch_workspace(source, repo).await?;
+    store_workspace(
+        store,
+        repository_id,
+        repo.pushed_at,
+        &members.unwrap_or_default(),
+    )
+    .await
+}
+
+#[tracing::instrument(skip(store, crates_io))]
+async fn sync_crates(store: &dyn Store, crates_io: &CratesIoRegistry) -> Result<(), SyncError> {
+    let names = upsert_crates(store, crates_io).await?;
+
+    for name in &names {
+        match sync_crate_versions(store, crates_io, name).await {
+            Ok(()) => {}
+            Err(e @ SyncError::Database(_)) => return Err(e),
+            Err(e) => tracing::warn!(name = %name, error = %e, "failed to fetch versions"),
+        }
+    }
+
+    tracing::info!(count = names.len(), "crates.io sync complete");
     Ok(())
 }
 
-#[tracing::instrument(skip(pool, crates_io))]
-async fn sync_crates(pool: &PgPool, crates_io: &CratesIoRegistry) -> Result<(), SyncError> {
+/// Fetch and upsert the user's crates, linked to their repositories where those have synced.
+/// Returns the crates' names.
+async fn upsert_crates(
+    store: &dyn Store,
+    crates_io: &CratesIoRegistry,
+) -> Result<Vec<String>, SyncError> {
     tracing::info!("starting crates.io sync");
 
     let crates = crates_io.fetch_crates().await?;
-    let count = crates.len();
 
     // Batch lookup all repository URLs at once to avoid N+1 queries
-    let repo_urls: Vec<String> = crates
+    let repo_urls: Vec<Option<CanonicalUrl>> = crates
         .iter()
-        .filter_map(|k| k.repository_url.clone())
+        .map(|k| k.repository_url.as_deref().and_then(CanonicalUrl::parse))
         .collect();
+    let known: Vec<CanonicalUrl> = repo_urls.iter().flatten().cloned().collect();
 
-    let repo_map = crate::db::get_repositories_by_urls(pool, &repo_urls).await?;
+    let repo_map = store.get_repositories_by_urls(&known).await?;
 
-    for krate in crates {
-        let repository_id = krate
-            .repository_url
+    let mut names = Vec::with_capacity(crates.len());
+    for (krate, repository_url) in crates.into_iter().zip(repo_urls) {
+        let repository_id = repository_url
             .as_ref()
-            .and_then(|url| repo_map.get(url).copied());
+            .and_then(|url| repo_map.get(url.as_str()).copied());
 
-        crate::db::upsert_crate(
-            pool,
-            &crate::db::NewCrate {
+        store
+            .upsert_crate(&crate::db::NewCrate {
                 name: &krate.name,
                 description: krate.description.as_deref(),
                 repository_id,
+                repository_url: repository_url.as_ref(),
                 crates_io_url: &krate.crates_io_url,
                 documentation_url: krate.documentation_url.as_deref(),
+                homepage_url: krate.homepage_url.as_deref(),
                 downloads: krate.downloads,
                 version: krate.version.as_deref(),
                 keywords: &krate.keywords,
                 categories: &krate.categories,
-            },
-        )
-        .await?;
+                created_at: krate.created_at,
+            })
+            .await?;
 
         tracing::debug!(name = %krate.name, "upserted crate");
+        names.push(krate.name);
+    }
+
+    Ok(names)
+}
+
+async fn sync_crate_versions(
+    store: &dyn Store,
+    crates_io: &CratesIoRegistry,
+    name: &str,
+) -> Result<(), SyncError> {
+    let releases = crates_io.fetch_versions(name).await?;
+    upsert_releases(store, &releases).await
+}
+
+#[tracing::instrument(skip(store, npm))]
+async fn sync_npm(store: &dyn Store, npm: &NpmRegistry) -> Result<(), SyncError> {
+    let names = upsert_npm_packages(store, npm).await?;
+
+    for name in &names {
+        match sync_npm_versions(store, npm, name).await {
+            Ok(()) => {}
+            Err(e @ SyncError::Database(_)) => return Err(e),
+            Err(e) => tracing::warn!(name = %name, error = %e, "failed to fetch versions"),
+        }
     }
 
-    tracing::info!(count, "crates.io sync complete");
+    tracing::info!(count = names.len(), "npm sync complete");
     Ok(())
 }
 
-#[tracing::instrument(skip(pool, npm))]
-async fn sync_npm(pool: &PgPool, npm: &NpmRegistry) -> Result<(), SyncError> {
+/// Fetch and upsert the user's npm packages, linked to their repositories where those have
+/// synced. Returns the packages' names.
+async fn upsert_npm_packages(
+    store: &dyn Store,
+    npm: &NpmRegistry,
+) -> Result<Vec<String>, SyncError> {
     tracing::info!("starting npm sync");
 
     let packages = npm.fetch_packages().await?;
-    let count = packages.len();
 
     // Batch lookup all repository URLs at once to avoid N+1 queries
-    let repo_urls: Vec<String> = packages
+    let repo_urls: Vec<Option<CanonicalUrl>> = packages
         .iter()
-        .filter_map(|p| p.repository_url.clone())
+        .map(|p| p.repository_url.as_deref().and_then(CanonicalUrl::parse))
         .collect();
+    let known: Vec<CanonicalUrl> = repo_urls.iter().flatten().cloned().collect();
 
-    let repo_map = crate::db::get_repositories_by_urls(pool, &repo_urls).await?;
+    let repo_map = store.get_repositories_by_urls(&known).await?;
 
-    for pkg in packages {
-        let repository_id = pkg
-            .repository_url
+    let mut names = Vec::with_capacity(packages.len());
+    for (pkg, repository_url) in packages.into_iter().zip(repo_urls) {
+        let repository_id = repository_url
             .as_ref()
-            .and_then(|url| repo_map.get(url).copied());
+            .and_then(|url| repo_map.get(url.as_str()).copied());
 
-        crate::db::upsert_npm_package(
-            pool,
-            &crate::db::NewNpmPackage {
+        store
+            .upsert_npm_package(&crate::db::NewNpmPackage {
                 name: &pkg.name,
                 scope: pkg.scope.as_deref(),
                 description: pkg.description.as_deref(),
                 repository_id,
+                repository_url: repository_url.as_ref(),
                 npm_url: &pkg.npm_url,
+                homepage_url: pkg.homepage_url.as_deref(),
                 downloads_weekly: pkg.downloads_weekly,
                 version: pkg.version.as_deref(),
                 keywords: &pkg.keywords,
-            },
-        )
-        .await?;
+            })
+            .await?;
 
         tracing::debug!(name = %pkg.name, "upserted npm package");
+        names.push(pkg.name);
     }
 
-    tracing::info!(count, "npm sync complete");
-    Ok(())
+    Ok(names)
 }
 
-#[tracing::instrument(skip(pool, contributions_sync))]
+async fn sync_npm_versions(
+    store: &dyn Store,
+    npm: &NpmRegistry,
+    name: &str,
+) -> Result<(), SyncError> {
+    let releases = npm.fetch_versions(name).await?;
+    upsert_releases(store, &releases).await
+}
+
+#[tracing::instrument(skip(store, contributions_sync))]
 async fn sync_contributions(
-    pool: &PgPool,
+    store: &dyn Store,
     contributions_sync: &ContributionsSync,
 ) -> Result<(), SyncError> {
     tracing::info!("starting contributions sync");
@@ -239,10 +482,9 @@ async fn sync_contributions(
     let contributions = contributions_sync.fetch_contributions().await?;
     let count = contributions.len();
 
-    for contrib in contributions {
-        crate::db::upsert_contribution(
-            pool,
-            &crate::db::NewContribution {
+    for contrib in &contributions {
+        store
+            .upsert_contribution(&crate::db::NewContribution {
                 forge: &contrib.forge,
                 repo_owner: &contrib.repo_owner,
                 repo_name: &contrib.repo_name,
@@ -251,43 +493,160 @@ async fn sync_contributions(
                 title: contrib.title.as_deref(),
                 url: &contrib.url,
                 merged_at: contrib.merged_at,
-            },
-        )
-        .await?;
+                additions: contrib.additions,
+                deletions: contrib.deletions,
+                changed_files: contrib.changed_files,
+                labels: &contrib.labels,
+            })
+            .await?;
 
         tracing::debug!(url = %contrib.url, "upserted contribution");
     }
 
+    // Upstream metadata is decoration; a failure here shouldn't discard the contributions.
+    match contributions_sync
+        .fetch_upstream_repos(&contributions)
+        .await
+    {
+        Ok(upstream_repos) => {
+            for repo in &upstream_repos {
+                store
+                    .upsert_upstream_repo(&crate::db::NewUpstreamRepo {
+                        forge: &repo.forge,
+                        owner: &repo.owner,
+                        name: &repo.name,
+                        url: &repo.url,
+                        description: repo.description.as_deref(),
+                        language: repo.language.as_deref(),
+                        stars: repo.stars,
+                    })
+                    .await?;
+            }
+        }
+        Err(e) => tracing::warn!(error = %e, "failed to fetch upstream repositories"),
+    }
+
     tracing::info!(count, "contributions sync complete");
     Ok(())
 }
 
-#[tracing::instrument(skip(pool, repo), fields(repo.name = %repo.name, repo.forge = %repo.forge))]
+#[tracing::instrument(skip(store, repo), fields(repo.name = %repo.name, repo.forge = %repo.forge))]
 async fn upsert_repository(
-    pool: &PgPool,
+    store: &dyn Store,
     repo: &FetchedRepository,
 ) -> Result<uuid::Uuid, SyncError> {
-    let id = crate::db::upsert_repository(
-        pool,
-        &crate::db::NewRepository {
+    let id = store
+        .upsert_repository(&crate::db::NewRepository {
             forge: &repo.forge,
             forge_id: &repo.forge_id,
             name: &repo.name,
             description: repo.description.as_deref(),
             url: &repo.url,
+            homepage_url: repo.homepage_url.as_deref(),
             language: repo.language.as_deref(),
             stars: repo.stars,
             topics: &repo.topics,
+            created_at: repo.created_at,
             updated_at: repo.updated_at,
-        },
-    )
-    .await?;
+        })
+        .await?;
 
     tracing::debug!("upserted repository");
     Ok(id)
 }
 
-pub fn spawn_sync_task(pool: PgPool, sources: SyncSources, config: &SyncConfig) {
+/// Replace the crates recorded for a repository's workspace, as of its `pushed_at`; none for a
+/// repository without one.
+async fn store_workspace(
+    store: &dyn Store,
+    repository_id: uuid::Uuid,
+    pushed_at: Option<DateTime<Utc>>,
+    members: &[WorkspaceMember],
+) -> Result<(), SyncError> {
+    let members: Vec<crate::db::NewWorkspaceMember<'_>> = members
+        .iter()
+        .map(|m| crate::db::NewWorkspaceMember {
+            crate_name: &m.crate_name,
+            path: &m.path,
+        })
+        .collect();
+    store
+        .set_workspace_members(repository_id, pushed_at, &members)
+        .await?;
+    Ok(())
+}
+
+/// Keep what a successful run left in the store as the snapshot to serve if it goes away.
+async fn save_snapshot(store: &dyn Store, snapshots: &SnapshotStore) {
+    if let Err(e) = snapshots.save(store).await {
+        tracing::warn!(error = %e, "failed to save snapshot");
+    }
+}
+
+/// Record a finished sync run and tell every instance it's over, so they stop serving cached
+/// responses. Even a failed run may have written some rows.
+async fn record_run(store: &dyn Store, result: &Result<(), SyncError>) {
+    let error = result.as_ref().err().map(ToString::to_string);
+    if let Err(e) = store.record_sync_run(error.as_deref()).await {
+        tracing::warn!(error = %e, "failed to record sync run");
+    }
+}
+
+/// Render and store a README, unless the stored copy was rendered from the same content.
+#[tracing::instrument(skip(store, readme))]
+async fn store_readme(
+    store: &dyn Store,
+    repository_id: uuid::Uuid,
+    readme: &FetchedReadme,
+) -> Result<(), SyncError> {
+    let content_hash = readme.content_hash();
+    if store.get_readme_hash(repository_id).await?.as_deref() == Some(&content_hash) {
+        tracing::debug!("README unchanged");
+        return Ok(());
+    }
+
+    let html = readme.render();
+    store
+        .upsert_readme(&crate::db::NewReadme {
+            repository_id,
+            content_hash: &content_hash,
+            html: &html,
+        })
+        .await?;
+
+    tracing::debug!("rendered README");
+    Ok(())
+}
+
+async fn upsert_releases(store: &dyn Store, releases: &[FetchedRelease]) -> Result<(), SyncError> {
+    for release in releases {
+        store
+            .upsert_release(&crate::db::NewRelease {
+                artifact_kind: release.artifact_kind,
+                artifact: &release.artifact,
+                version: &release.version,
+                released_at: release.released_at,
+                yanked: release.yanked,
+                notes_url: release.notes_url.as_deref(),
+            })
+            .await?;
+    }
+
+    tracing::debug!(count = releases.len(), "upserted releases");
+    Ok(())
+}
+
+/// Sync every `config.interval_secs` for as long as this instance leads; see [`leader`].
+///
+/// With a `PostgreSQL` `pool`, a run is queued as [`jobs`] that this and every other instance
+/// work through. Without one, the store is this instance's alone and a run happens inline.
+pub fn spawn_sync_task(
+    store: SharedStore,
+    sources: SyncSources,
+    config: &SyncConfig,
+    snapshots: SnapshotStore,
+    pool: Option<PgPool>,
+) {
     if !config.enabled {
         tracing::info!("sync disabled");
         return;
@@ -298,21 +657,45 @@ pub fn spawn_sync_task(pool: PgPool, sources: SyncSources, config: &SyncConfig)
         return;
     }
 
+    let sources = Arc::new(sources);
+    if let Some(ref pool) = pool {
+        jobs::spawn_workers(pool.clone(), store.clone(), sources.clone(), config.workers);
+    }
+
     let interval_secs = config.interval_secs;
     tokio::spawn(async move {
+        let mut election = LeaderElection::new(pool);
+        // The first tick completes immediately, so the leader syncs on startup.
         let mut ticker = interval(Duration::from_secs(interval_secs));
 
-        // Run immediately on startup
-        if let Err(e) = run_sync(&pool, &sources).await {
-            tracing::error!(error = %e, "initial sync failed");
-        }
-
         loop {
             ticker.tick().await;
+            election.wait_for_leadership().await;
+            // A new leader syncs at once, as the old one may have died mid-run, and then
+            // waits a full interval.
+            ticker.reset();
+
+            let span = tracing::info_span!(
+                "sync_run",
+                sync.leader = true,
+                sync.leader.session_pid = election.session_pid(),
+            );
+            if let Some(session) = election.session() {
+                // Queued on the session holding the lock, so a leader that lost it between
+                // the check and here queues nothing.
+                if let Err(e) = jobs::start_run(session, &sources).instrument(span).await {
+                    tracing::error!(error = %e, "failed to queue sync run");
+                }
+                continue;
+            }
 
-            if let Err(e) = run_sync(&pool, &sources).await {
+            let result = run_sync(store.as_ref(), &sources).instrument(span).await;
+            if let Err(ref e) = result {
                 tracing::error!(error = %e, "sync failed");
+            } else {
+                save_snapshot(store.as_ref(), &snapshots).await;
             }
+            record_run(store.as_ref(), &result).await;
         }
     });
 
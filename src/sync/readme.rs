@@ -0,0 +1,285 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use pulldown_cmark::{html, Event, Options, Parser, Tag, TagEnd};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::sync::SyncError;
+
+/// Bump whenever rendering changes, so stored READMEs are re-rendered on the next sync.
+const RENDER_VERSION: u32 = 2;
+
+const HEADINGS: [&str; 6] = ["h1", "h2", "h3", "h4", "h5", "h6"];
+
+/// Prefixed to every `id` a README renders, as on GitHub, so a heading can't take the id of
+/// an element on the page around it
+const ID_PREFIX: &str = "user-content-";
+
+/// A repository's README as fetched from its forge (before rendering)
+#[derive(Debug, Clone)]
+pub struct FetchedReadme {
+    pub markdown: String,
+    /// Root of the repository's file browser, where relative links point
+    pub blob_root: Url,
+    /// Root of the repository's raw files, where relative images point
+    pub raw_root: Url,
+    /// Directory holding the README relative to the roots: empty or ending in `/`
+    pub dir: String,
+}
+
+impl FetchedReadme {
+    /// # Errors
+    /// Returns [`SyncError::Other`] if either root isn't an absolute URL.
+    pub fn new(
+        markdown: String,
+        blob_root: &str,
+        raw_root: &str,
+        path: &str,
+    ) -> Result<Self, SyncError> {
+        let parse = |root: &str| {
+            Url::parse(root).map_err(|e| SyncError::Other(format!("bad README root {root}: {e}")))
+        };
+        let dir = path
+            .rsplit_once('/')
+            .map_or_else(String::new, |(dir, _)| format!("{dir}/"));
+
+        Ok(Self {
+            markdown,
+            blob_root: parse(blob_root)?,
+            raw_root: parse(raw_root)?,
+            dir,
+        })
+    }
+
+    /// Hex SHA-256 over the Markdown and everything else that shapes the rendered HTML.
+    #[must_use]
+    pub fn content_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            RENDER_VERSION.to_string().as_str(),
+            self.blob_root.as_str(),
+            self.raw_root.as_str(),
+            &self.dir,
+            &self.markdown,
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Render to HTML that is safe to embed: anything outside ammonia's allow-list is
+    /// stripped, relative links and images point back at the forge, and headings get
+    /// anchors. Ids get [`ID_PREFIX`], and links to fragments within the README follow them.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let parser = Parser::new_ext(
+            &self.markdown,
+            Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH,
+        );
+        let mut unsafe_html = String::with_capacity(self.markdown.len() * 3 / 2);
+        html::push_html(&mut unsafe_html, with_heading_anchors(parser).into_iter());
+
+        let (blob_root, raw_root, dir) = (
+            self.blob_root.clone(),
+            self.raw_root.clone(),
+            self.dir.clone(),
+        );
+        let mut sanitizer = ammonia::Builder::default();
+        for heading in HEADINGS {
+            sanitizer.add_tag_attributes(heading, &["id"]);
+        }
+        sanitizer
+            .add_allowed_classes("a", &["anchor"])
+            .id_prefix(Some(ID_PREFIX))
+            .attribute_filter(move |element, attribute, value| {
+                if let ("a", "href") = (element, attribute) {
+                    if let Some(fragment) = value.strip_prefix('#').filter(|f| !f.is_empty()) {
+                        return Some(Cow::Owned(format!("#{ID_PREFIX}{fragment}")));
+                    }
+                }
+                let root = match (element, attribute) {
+                    ("img", "src") => &raw_root,
+                    (_, "href" | "src") => &blob_root,
+                    _ => return Some(Cow::Borrowed(value)),
+                };
+                Some(resolve(root, &dir, value).map_or(Cow::Borrowed(value), Cow::Owned))
+            });
+        sanitizer.clean(&unsafe_html).to_string()
+    }
+}
+
+/// Resolve a relative `target` against the README's directory, or its repository root when
+/// the path starts with `/`. Absolute URLs and fragments are left alone (`None`).
+fn resolve(root: &Url, dir: &str, target: &str) -> Option<String> {
+    if target.is_empty()
+        || target.starts_with('#')
+        || target.starts_with("//")
+        || Url::parse(target).is_ok()
+    {
+        return None;
+    }
+    let path = target
+        .strip_prefix('/')
+        .map_or_else(|| format!("{dir}{target}"), str::to_owned);
+    root.join(&path).ok().map(String::from)
+}
+
+/// Give each heading a GitHub-style `id` and a trailing self-link.
+fn with_heading_anchors<'a>(events: impl Iterator<Item = Event<'a>>) -> Vec<Event<'a>> {
+    let mut out = Vec::new();
+    let mut seen = HashMap::new();
+    // Index of the open heading's start event, and its text so far
+    let mut heading: Option<(usize, String)> = None;
+
+    for event in events {
+        match &event {
+            Event::Start(Tag::Heading { .. }) => heading = Some((out.len(), String::new())),
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, heading_text)) = heading.as_mut() {
+                    heading_text.push_str(text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((start, text)) = heading.take() {
+                    let slug = unique_slug(&text, &mut seen);
+                    if let (false, Some(Event::Start(Tag::Heading { id, .. }))) =
+                        (slug.is_empty(), out.get_mut(start))
+                    {
+                        *id = Some(slug.clone().into());
+                        out.push(Event::InlineHtml(
+                            format!(r##"<a class="anchor" href="#{slug}">#</a>"##).into(),
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+        out.push(event);
+    }
+    out
+}
+
+/// Lowercase, spaces to hyphens, punctuation dropped; repeats get `-1`, `-2`, ... as on
+/// GitHub, so in-README links to headings keep working.
+fn unique_slug(text: &str, seen: &mut HashMap<String, usize>) -> String {
+    let base: String = text
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect();
+    if base.is_empty() {
+        return base;
+    }
+
+    let count = seen.entry(base.clone()).or_insert(0);
+    let slug = if *count == 0 {
+        base
+    } else {
+        format!("{base}-{count}")
+    };
+    *count += 1;
+    slug
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn readme(markdown: &str, path: &str) -> FetchedReadme {
+        FetchedReadme::new(
+            markdown.to_owned(),
+            "https://github.com/user/repo/blob/HEAD/",
+            "https://raw.githubusercontent.com/user/repo/HEAD/",
+            path,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn strips_scripts_and_event_handlers() {
+        let html = readme(
+            "# Title\n\n<script>alert(1)</script>\n\n<img src=\"x.png\" onerror=\"alert(1)\">\n\n[x](javascript:alert(1))",
+            "README.md",
+        )
+        .render();
+
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("javascript:"));
+    }
+
+    #[test]
+    fn points_relative_links_and_images_at_the_forge() {
+        let html = readme(
+            "[guide](guide.md) [root](/LICENSE) [up](../x.md) [site](https://example.com) [top](#title)\n\n![logo](img/logo.png)\n\n<img src=\"badge.svg\">",
+            "docs/README.md",
+        )
+        .render();
+
+        assert!(html.contains(r#"href="https://github.com/user/repo/blob/HEAD/docs/guide.md""#));
+        assert!(html.contains(r#"href="https://github.com/user/repo/blob/HEAD/LICENSE""#));
+        assert!(html.contains(r#"href="https://github.com/user/repo/blob/HEAD/x.md""#));
+        assert!(html.contains(r#"href="https://example.com""#));
+        assert!(html.contains(r##"href="#user-content-title""##));
+        assert!(html.contains(
+            r#"src="https://raw.githubusercontent.com/user/repo/HEAD/docs/img/logo.png""#
+        ));
+        assert!(html
+            .contains(r#"src="https://raw.githubusercontent.com/user/repo/HEAD/docs/badge.svg""#));
+    }
+
+    #[test]
+    fn anchors_headings_with_unique_slugs() {
+        let html = readme(
+            "# Getting `started`!\n\n## Usage\n\n## Usage\n\n## ✨",
+            "README.md",
+        )
+        .render();
+
+        assert!(html.contains(r#"<h1 id="user-content-getting-started">"#));
+        assert!(html.contains(r##"<a class="anchor" href="#user-content-getting-started""##));
+        assert!(html.contains(r#"<h2 id="user-content-usage">"#));
+        assert!(html.contains(r#"<h2 id="user-content-usage-1">"#));
+        assert!(html.contains("<h2>✨</h2>"));
+    }
+
+    #[test]
+    fn prefixes_ids_that_could_collide_with_the_page() {
+        let html = readme(
+            "# Main\n\n<h2 id=\"projects\">Raw</h2>\n\n[back](#main)",
+            "README.md",
+        )
+        .render();
+
+        assert!(html.contains(r#"<h1 id="user-content-main">"#));
+        assert!(html.contains(r#"<h2 id="user-content-projects">"#));
+        assert!(html.contains(r##"href="#user-content-main""##));
+        assert!(!html.contains(r#"id="main""#));
+        assert!(!html.contains(r#"id="projects""#));
+    }
+
+    #[test]
+    fn hash_changes_with_content_and_location() {
+        let base = readme("# A", "README.md");
+
+        assert_eq!(
+            base.content_hash(),
+            readme("# A", "README.md").content_hash()
+        );
+        assert_ne!(
+            base.content_hash(),
+            readme("# B", "README.md").content_hash()
+        );
+        assert_ne!(
+            base.content_hash(),
+            readme("# A", "docs/README.md").content_hash()
+        );
+    }
+}
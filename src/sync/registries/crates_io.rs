@@ -1,7 +1,8 @@
+use chrono::{DateTime, Utc};
 use reqwest::header::USER_AGENT;
 use serde::Deserialize;
 
-use crate::sync::SyncError;
+use crate::sync::{FetchedRelease, SyncError};
 
 const CRATES_IO_API_BASE: &str = "https://crates.io/api/v1";
 
@@ -17,10 +18,12 @@ pub struct CrateSummary {
     pub repository_url: Option<String>,
     pub crates_io_url: String,
     pub documentation_url: Option<String>,
+    pub homepage_url: Option<String>,
     pub downloads: i32,
     pub version: Option<String>,
     pub keywords: Vec<String>,
     pub categories: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
 }
 
 impl CratesIoRegistry {
@@ -87,10 +90,12 @@ impl CratesIoRegistry {
                 repository_url: c.repository,
                 crates_io_url: format!("https://crates.io/crates/{}", c.name),
                 documentation_url: c.documentation,
+                homepage_url: c.homepage,
                 downloads: i32::try_from(c.downloads).unwrap_or(i32::MAX),
                 version: c.newest_version,
                 keywords: Vec::new(),
                 categories: Vec::new(),
+                created_at: c.created_at,
             }));
 
             if response.meta.next_page.is_none() {
@@ -102,6 +107,38 @@ impl CratesIoRegistry {
         tracing::info!(count = all_crates.len(), "fetched all crates");
         Ok(all_crates)
     }
+
+    /// # Errors
+    /// Returns a [`SyncError`] for any HTTP or deserialisation failure.
+    #[tracing::instrument(skip(self))]
+    pub async fn fetch_versions(&self, name: &str) -> Result<Vec<FetchedRelease>, SyncError> {
+        let base = format!("{CRATES_IO_API_BASE}/crates/{name}/versions");
+        let mut url = format!("{base}?per_page=100");
+        let mut releases = Vec::new();
+
+        loop {
+            let response: VersionsResponse = self
+                .client
+                .get(&url)
+                .header(USER_AGENT, "djv-sync/1.0 (https://djv.sh)")
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            releases.extend(response.versions.into_iter().map(|v| v.into_release(name)));
+
+            // `next_page` is a query string relative to the versions endpoint.
+            match response.meta.and_then(|m| m.next_page) {
+                Some(next) => url = format!("{base}{next}"),
+                None => break,
+            }
+        }
+
+        tracing::debug!(count = releases.len(), "fetched crate versions");
+        Ok(releases)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -126,8 +163,10 @@ struct CrateInfo {
     description: Option<String>,
     repository: Option<String>,
     documentation: Option<String>,
+    homepage: Option<String>,
     downloads: u64,
     newest_version: Option<String>,
+    created_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -135,6 +174,35 @@ struct Meta {
     next_page: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct VersionsResponse {
+    versions: Vec<VersionInfo>,
+    meta: Option<Meta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionInfo {
+    num: String,
+    created_at: Option<DateTime<Utc>>,
+    yanked: bool,
+}
+
+impl VersionInfo {
+    fn into_release(self, crate_name: &str) -> FetchedRelease {
+        FetchedRelease {
+            artifact_kind: "crate",
+            artifact: crate_name.to_string(),
+            notes_url: Some(format!(
+                "https://crates.io/crates/{crate_name}/{}",
+                self.num
+            )),
+            version: self.num,
+            released_at: self.created_at,
+            yanked: self.yanked,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -186,8 +254,10 @@ mod tests {
             description: Some("A test crate".to_string()),
             repository: Some("https://github.com/user/test-crate".to_string()),
             documentation: Some("https://docs.rs/test-crate".to_string()),
+            homepage: None,
             downloads: 5000,
             newest_version: Some("2.0.0".to_string()),
+            created_at: None,
         };
 
         let fetched = CrateSummary {
@@ -196,10 +266,12 @@ mod tests {
             repository_url: crate_info.repository,
             crates_io_url: format!("https://crates.io/crates/{}", crate_info.name),
             documentation_url: crate_info.documentation,
+            homepage_url: crate_info.homepage,
             downloads: crate_info.downloads as i32,
             version: crate_info.newest_version,
             keywords: Vec::new(),
             categories: Vec::new(),
+            created_at: crate_info.created_at,
         };
 
         assert_eq!(fetched.name, "test-crate");
@@ -237,4 +309,32 @@ mod tests {
         let registry = CratesIoRegistry::new("testuser".to_string());
         assert_eq!(registry.username, "testuser");
     }
+
+    #[test]
+    fn parses_versions_response() {
+        let json = r#"{
+            "versions": [
+                {"num": "1.1.0", "created_at": "2024-06-01T09:00:00.000000+00:00", "yanked": false},
+                {"num": "1.0.1", "created_at": "2024-02-01T09:00:00.000000+00:00", "yanked": true}
+            ],
+            "meta": {"total": 2, "next_page": null}
+        }"#;
+
+        let response: VersionsResponse = serde_json::from_str(json).unwrap();
+        let releases: Vec<FetchedRelease> = response
+            .versions
+            .into_iter()
+            .map(|v| v.into_release("my-crate"))
+            .collect();
+
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0].artifact_kind, "crate");
+        assert_eq!(releases[0].version, "1.1.0");
+        assert!(releases[0].released_at.is_some());
+        assert!(releases[1].yanked);
+        assert_eq!(
+            releases[1].notes_url.as_deref(),
+            Some("https://crates.io/crates/my-crate/1.0.1")
+        );
+    }
 }
@@ -1,7 +1,10 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
 use reqwest::header::USER_AGENT;
 use serde::Deserialize;
 
-use crate::sync::SyncError;
+use crate::sync::{FetchedRelease, SyncError};
 
 const NPM_REGISTRY_API: &str = "https://registry.npmjs.org";
 const NPM_DOWNLOADS_API: &str = "https://api.npmjs.org/downloads/point/last-week";
@@ -18,6 +21,7 @@ pub struct NpmPackageSummary {
     pub description: Option<String>,
     pub repository_url: Option<String>,
     pub npm_url: String,
+    pub homepage_url: Option<String>,
     pub downloads_weekly: i32,
     pub version: Option<String>,
     pub keywords: Vec<String>,
@@ -98,6 +102,7 @@ impl NpmRegistry {
                 description: pkg.description.clone(),
                 repository_url,
                 npm_url: format!("https://www.npmjs.com/package/{}", pkg.name),
+                homepage_url: pkg.links.as_ref().and_then(|l| l.homepage.clone()),
                 downloads_weekly: downloads,
                 version: pkg.version.clone(),
                 keywords: pkg.keywords.clone().unwrap_or_default(),
@@ -123,6 +128,31 @@ impl NpmRegistry {
 
         Ok(i32::try_from(response.downloads).unwrap_or(i32::MAX))
     }
+
+    /// Every published version of a package, dated from the packument's `time` map.
+    /// Deprecated versions are recorded as yanked.
+    ///
+    /// # Errors
+    /// Returns a [`SyncError`] for any HTTP or deserialisation failure.
+    #[tracing::instrument(skip(self))]
+    pub async fn fetch_versions(
+        &self,
+        package_name: &str,
+    ) -> Result<Vec<FetchedRelease>, SyncError> {
+        let url = format!("{NPM_REGISTRY_API}/{package_name}");
+
+        let packument: Packument = self
+            .client
+            .get(&url)
+            .header(USER_AGENT, "djv-sync/1.0 (https://djv.sh)")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(packument.into_releases(package_name))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -148,7 +178,6 @@ struct PackageInfo {
 struct PackageLinks {
     #[allow(dead_code)]
     npm: Option<String>,
-    #[allow(dead_code)]
     homepage: Option<String>,
     repository: Option<String>,
 }
@@ -158,6 +187,47 @@ struct DownloadsResponse {
     downloads: u64,
 }
 
+#[derive(Debug, Deserialize)]
+struct Packument {
+    /// Version → publish time, plus `created`/`modified` (and `unpublished`, whose value is
+    /// an object rather than a timestamp)
+    #[serde(default)]
+    time: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    versions: HashMap<String, PackumentVersion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PackumentVersion {
+    deprecated: Option<String>,
+}
+
+impl Packument {
+    fn into_releases(self, package_name: &str) -> Vec<FetchedRelease> {
+        let versions = self.versions;
+        self.time
+            .into_iter()
+            .filter(|(version, _)| !matches!(version.as_str(), "created" | "modified"))
+            .filter_map(|(version, time)| {
+                let released_at = time.as_str()?.parse::<DateTime<Utc>>().ok()?;
+                Some((version, released_at))
+            })
+            .map(|(version, released_at)| FetchedRelease {
+                artifact_kind: "npm",
+                artifact: package_name.to_string(),
+                yanked: versions
+                    .get(&version)
+                    .is_some_and(|v| v.deprecated.is_some()),
+                notes_url: Some(format!(
+                    "https://www.npmjs.com/package/{package_name}/v/{version}"
+                )),
+                version,
+                released_at: Some(released_at),
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -239,4 +309,35 @@ mod tests {
         let registry = NpmRegistry::new("testuser".to_string());
         assert_eq!(registry.username, "testuser");
     }
+
+    #[test]
+    fn converts_packument_time_to_releases() {
+        let json = r#"{
+            "name": "my-package",
+            "time": {
+                "created": "2023-01-01T00:00:00.000Z",
+                "modified": "2024-05-01T00:00:00.000Z",
+                "1.0.0": "2023-01-01T00:00:00.000Z",
+                "1.1.0": "2024-05-01T00:00:00.000Z"
+            },
+            "versions": {
+                "1.0.0": {"deprecated": "use 1.1.0"},
+                "1.1.0": {}
+            }
+        }"#;
+
+        let packument: Packument = serde_json::from_str(json).unwrap();
+        let mut releases = packument.into_releases("my-package");
+        releases.sort_by(|a, b| a.version.cmp(&b.version));
+
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0].artifact_kind, "npm");
+        assert_eq!(releases[0].version, "1.0.0");
+        assert!(releases[0].yanked);
+        assert!(!releases[1].yanked);
+        assert_eq!(
+            releases[1].notes_url.as_deref(),
+            Some("https://www.npmjs.com/package/my-package/v/1.1.0")
+        );
+    }
 }
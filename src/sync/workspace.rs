@@ -0,0 +1,369 @@
+use serde::Deserialize;
+
+use crate::sync::{FetchedRepository, SyncError, SyncSource};
+
+/// A crate listed in a repository's Cargo workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceMember {
+    pub crate_name: String,
+    /// Directory of the crate within the repository; empty for a root package
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    package: Option<Package>,
+    workspace: Option<Workspace>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Package {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Workspace {
+    #[serde(default)]
+    members: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+}
+
+fn parse_manifest(path: &str, text: &str) -> Result<Manifest, SyncError> {
+    toml::from_str(text).map_err(|e| SyncError::Other(format!("invalid {path}: {e}")))
+}
+
+/// The crates in a repository's Cargo workspace, read from its root `Cargo.toml` and each
+/// member's own, or `None` if the root manifest declares no workspace. Repositories the
+/// forge reports as written in another language are not looked at.
+///
+/// # Errors
+/// Returns the first [`SyncError`] from the forge, or one if the root manifest is invalid.
+#[tracing::instrument(skip(source, repo), fields(repo = %repo.forge_id))]
+pub async fn fetch_workspace(
+    source: &dyn SyncSource,
+    repo: &FetchedRepository,
+) -> Result<Option<Vec<WorkspaceMember>>, SyncError> {
+    if repo.language.as_deref().is_some_and(|l| l != "Rust") {
+        return Ok(None);
+    }
+    let Some(text) = source.fetch_file(repo, "Cargo.toml").await? else {
+        return Ok(None);
+    };
+    let manifest = parse_manifest("Cargo.toml", &text)?;
+    let Some(workspace) = manifest.workspace else {
+        return Ok(None);
+    };
+
+    let mut members: Vec<WorkspaceMember> = manifest
+        .package
+        .map(|p| WorkspaceMember {
+            crate_name: p.name,
+            path: String::new(),
+        })
+        .into_iter()
+        .collect();
+    for dir in member_dirs(source, repo, &workspace).await? {
+        let path = format!("{dir}/Cargo.toml");
+        let Some(text) = source.fetch_file(repo, &path).await? else {
+            continue;
+        };
+        // One broken member shouldn't hide the rest of the workspace.
+        match parse_manifest(&path, &text) {
+            Ok(Manifest {
+                package: Some(package),
+                ..
+            }) => members.push(WorkspaceMember {
+                crate_name: package.name,
+                path: dir,
+            }),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = %e, "skipping workspace member"),
+        }
+    }
+
+    members.sort_by(|a, b| a.crate_name.cmp(&b.crate_name));
+    members.dedup_by(|a, b| a.crate_name == b.crate_name);
+    Ok(Some(members))
+}
+
+/// Member directories, with globs in the last path segment (`crates/*`) expanded through the
+/// forge's directory listing and `exclude` applied.
+async fn member_dirs(
+    source: &dyn SyncSource,
+    repo: &FetchedRepository,
+    workspace: &Workspace,
+) -> Result<Vec<String>, SyncError> {
+    let mut dirs = Vec::new();
+    for pattern in workspace.members.iter().map(|m| normalise(m)) {
+        if !is_glob(pattern) {
+            dirs.push(pattern.to_owned());
+            continue;
+        }
+        let (parent, last) = pattern.rsplit_once('/').unwrap_or(("", pattern));
+        if is_glob(parent) {
+            tracing::debug!(
+                pattern,
+                "skipping member pattern with a glob before its last segment"
+            );
+            continue;
+        }
+        for name in source.list_dirs(repo, parent).await? {
+            if glob_matches(last, &name) {
+                dirs.push(if parent.is_empty() {
+                    name
+                } else {
+                    format!("{parent}/{name}")
+                });
+            }
+        }
+    }
+
+    dirs.retain(|dir| {
+        !dir.is_empty()
+            && !workspace
+                .exclude
+                .iter()
+                .any(|e| glob_matches(normalise(e), dir))
+    });
+    dirs.sort();
+    dirs.dedup();
+    Ok(dirs)
+}
+
+fn normalise(path: &str) -> &str {
+    path.trim_start_matches("./").trim_matches('/')
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Match `name` against a pattern of `*` and `?` wildcards, the part of glob syntax member
+/// lists use in practice.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) =
+        (pattern.chars().collect(), name.chars().collect());
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name position it was tried against
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                // Let the last `*` swallow one more character and try again.
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+
+    /// A forge serving files and directories from memory
+    struct Files(HashMap<&'static str, &'static str>);
+
+    #[async_trait]
+    impl SyncSource for Files {
+        fn name(&self) -> &'static str {
+            "files"
+        }
+
+        async fn fetch_repositories(&self) -> Result<Vec<FetchedRepository>, SyncError> {
+            Ok(Vec::new())
+        }
+
+        async fn fetch_file(
+            &self,
+            _repo: &FetchedRepository,
+            path: &str,
+        ) -> Result<Option<String>, SyncError> {
+            Ok(self.0.get(path).map(|s| (*s).to_owned()))
+        }
+
+        async fn list_dirs(
+            &self,
+            _repo: &FetchedRepository,
+            path: &str,
+        ) -> Result<Vec<String>, SyncError> {
+            let prefix = if path.is_empty() {
+                String::new()
+            } else {
+                format!("{path}/")
+            };
+            let mut dirs: Vec<String> = self
+                .0
+                .keys()
+                .filter_map(|file| file.strip_prefix(&prefix)?.split_once('/'))
+                .map(|(dir, _)| dir.to_owned())
+                .collect();
+            dirs.sort();
+            dirs.dedup();
+            Ok(dirs)
+        }
+    }
+
+    fn repo(language: Option<&str>) -> FetchedRepository {
+        FetchedRepository {
+            forge: "github".to_owned(),
+            forge_id: "user/tools".to_owned(),
+            name: "tools".to_owned(),
+            description: None,
+            url: "https://github.com/user/tools".to_owned(),
+            homepage_url: None,
+            language: language.map(str::to_owned),
+            stars: 0,
+            topics: Vec::new(),
+            created_at: None,
+            updated_at: None,
+            pushed_at: None,
+            readme_path: None,
+        }
+    }
+
+    fn member(crate_name: &str, path: &str) -> WorkspaceMember {
+        WorkspaceMember {
+            crate_name: crate_name.to_owned(),
+            path: path.to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn expands_member_globs_and_reads_crate_names() {
+        let files = Files(HashMap::from([
+            (
+                "Cargo.toml",
+                "[package]\nname = \"tools\"\n\n[workspace]\nmembers = [\"crates/*\", \"./xtask/\"]\nexclude = [\"crates/old-*\"]\n",
+            ),
+            ("crates/core/Cargo.toml", "[package]\nname = \"tools-core\"\n"),
+            ("crates/cli/Cargo.toml", "[package]\nname = \"tools-cli\"\n"),
+            ("crates/old-cli/Cargo.toml", "[package]\nname = \"tools-old\"\n"),
+            ("xtask/Cargo.toml", "[package]\nname = \"xtask\"\n"),
+        ]));
+
+        let members = fetch_workspace(&files, &repo(Some("Rust")))
+            .await
+            .unwrap()
+            .expect("should find a workspace");
+
+        assert_eq!(
+            members,
+            [
+                member("tools", ""),
+                member("tools-cli", "crates/cli"),
+                member("tools-core", "crates/core"),
+                member("xtask", "xtask"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_repositories_without_a_workspace() {
+        let single = Files(HashMap::from([(
+            "Cargo.toml",
+            "[package]\nname = \"single\"\n",
+        )]));
+        assert_eq!(fetch_workspace(&single, &repo(None)).await.unwrap(), None);
+
+        let empty = Files(HashMap::new());
+        assert_eq!(fetch_workspace(&empty, &repo(None)).await.unwrap(), None);
+
+        let workspace = Files(HashMap::from([(
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"a\"]\n",
+        )]));
+        assert_eq!(
+            fetch_workspace(&workspace, &repo(Some("TypeScript")))
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn tolerates_broken_members_but_not_a_broken_root() {
+        let files = Files(HashMap::from([
+            (
+                "Cargo.toml",
+                "[workspace]\nmembers = [\"good\", \"bad\", \"gone\"]\n",
+            ),
+            ("good/Cargo.toml", "[package]\nname = \"good\"\n"),
+            ("bad/Cargo.toml", "[package\nname = "),
+        ]));
+        assert_eq!(
+            fetch_workspace(&files, &repo(None)).await.unwrap(),
+            Some(vec![member("good", "good")])
+        );
+
+        let broken = Files(HashMap::from([("Cargo.toml", "[workspace")]));
+        assert!(fetch_workspace(&broken, &repo(None)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rereads_a_workspace_only_after_a_push() {
+        use crate::store::{MemoryStore, Store};
+        use chrono::TimeZone;
+
+        let store = MemoryStore::default();
+        let id = uuid::Uuid::new_v4();
+        let mut repo = repo(Some("Rust"));
+        repo.pushed_at = Some(
+            chrono::Utc
+                .with_ymd_and_hms(2024, 1, 15, 10, 30, 0)
+                .unwrap(),
+        );
+        let files = Files(HashMap::from([(
+            "Cargo.toml",
+            "[package]\nname = \"tools\"\n\n[workspace]\n",
+        )]));
+        // A root manifest that no longer parses, to tell whether it's fetched again
+        let broken = Files(HashMap::from([("Cargo.toml", "[workspace")]));
+
+        crate::sync::sync_workspace(&store, &files, id, &repo)
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_workspace_pushed_at(id).await.unwrap(),
+            repo.pushed_at
+        );
+
+        crate::sync::sync_workspace(&store, &broken, id, &repo)
+            .await
+            .expect("nothing pushed since, so nothing should be fetched");
+
+        repo.pushed_at = Some(chrono::Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap());
+        assert!(crate::sync::sync_workspace(&store, &broken, id, &repo)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn matches_wildcards() {
+        assert!(glob_matches("*", "core"));
+        assert!(glob_matches("tools-*", "tools-cli"));
+        assert!(glob_matches("*-cli", "tools-cli"));
+        assert!(glob_matches("t?ols*cli", "tools-cli"));
+        assert!(glob_matches("a*b*c", "axxbyybc"));
+        assert!(!glob_matches("tools-*", "other"));
+        assert!(!glob_matches("?", ""));
+        assert!(!glob_matches("a*c", "abcd"));
+    }
+}